@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: Copyright (c) 2018-2024 slowtec GmbH <post@slowtec.de>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Derive macros for [modbus-core](https://docs.rs/modbus-core).
+//!
+//! Enable the `derive` feature of `modbus-core` instead of
+//! depending on this crate directly.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitInt, Result, Type};
+
+/// Implement `RegisterBlock` for a struct with named fields.
+///
+/// See the documentation of the `RegisterBlock` trait for the
+/// attributes of the fields.
+#[proc_macro_derive(RegisterBlock, attributes(register))]
+pub fn derive_register_block(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    register_block(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The mapping of a field to its registers.
+struct Register {
+    ident: Ident,
+    /// The type of the field.
+    field_ty: Type,
+    /// The type of the registers, if different from the field.
+    ty: Option<Type>,
+    offset: Option<u16>,
+    word_order: Option<Ident>,
+}
+
+impl Register {
+    fn parse(field: &syn::Field) -> Result<Self> {
+        let mut register = Self {
+            ident: field
+                .ident
+                .clone()
+                .ok_or_else(|| Error::new_spanned(field, "expected a named field"))?,
+            field_ty: field.ty.clone(),
+            ty: None,
+            offset: None,
+            word_order: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("register")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("offset") {
+                    register.offset = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else if meta.path.is_ident("word_order") {
+                    register.word_order = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("ty") {
+                    register.ty = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `offset`, `word_order` or `ty`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(register)
+    }
+}
+
+fn register_block(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "generic register blocks are not supported",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(Span::call_site(), "expected a struct"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(&data.fields, "expected named fields"));
+    };
+    let registers = fields
+        .named
+        .iter()
+        .map(Register::parse)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut words = Vec::new();
+    let mut decode = Vec::new();
+    let mut encode = Vec::new();
+    // The offset of a field without an explicit offset
+    let mut next = quote!(0);
+    for register in &registers {
+        let Register {
+            ident, field_ty, ..
+        } = register;
+        let ty = register.ty.as_ref().unwrap_or(field_ty);
+        let offset = register.offset.map_or(next, |offset| quote!(#offset));
+        let word_order = register
+            .word_order
+            .clone()
+            .unwrap_or_else(|| Ident::new("HighFirst", Span::call_site()));
+        let cell = quote! {
+            ::modbus_core::RegisterCell::<#ty>::holding((#offset) as u16)
+                .with_word_order(::modbus_core::WordOrder::#word_order)
+        };
+        let end = quote! {
+            (#offset) as usize + <#ty as ::modbus_core::FromRegisters>::WORDS
+        };
+        words.push(end.clone());
+        if register.ty.is_some() {
+            decode.push(quote! {
+                #ident: ::core::convert::From::from(#cell.decode_from(0, data)?)
+            });
+            encode.push(quote! {
+                let value: #ty = ::core::convert::TryFrom::try_from(self.#ident.clone()).ok()?;
+                #cell.encode_into(0, &value, buf)?;
+            });
+        } else {
+            decode.push(quote! {
+                #ident: #cell.decode_from(0, data)?
+            });
+            encode.push(quote! {
+                #cell.encode_into(0, &self.#ident, buf)?;
+            });
+        }
+        next = end;
+    }
+
+    Ok(quote! {
+        impl ::modbus_core::RegisterBlock for #name {
+            const WORDS: usize = {
+                let mut words = 0;
+                #(
+                    if #words > words {
+                        words = #words;
+                    }
+                )*
+                words
+            };
+
+            fn decode(data: &::modbus_core::Data<'_>) -> ::core::option::Option<Self> {
+                ::core::option::Option::Some(Self {
+                    #(#decode,)*
+                })
+            }
+
+            fn encode<'b>(
+                &self,
+                buf: &'b mut [u8],
+            ) -> ::core::option::Option<::modbus_core::Data<'b>> {
+                let buf = buf.get_mut(..<Self as ::modbus_core::RegisterBlock>::WORDS * 2)?;
+                buf.fill(0);
+                #(#encode)*
+                ::modbus_core::Data::from_bytes(buf).ok()
+            }
+        }
+    })
+}
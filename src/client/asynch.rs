@@ -0,0 +1,198 @@
+//! Asynchronous transactions on top of [`embedded_io_async`].
+
+use super::{io::*, *};
+use core::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+};
+use embedded_io_async::{Read, Write};
+
+/// Send `request` and wait for the response.
+///
+/// This is the asynchronous counterpart of [`super::blocking::transact`].
+/// It waits forever, see [`transact_with_timeout`] to give up early.
+pub async fn transact<'b, T>(
+    io: &mut T,
+    hdr: impl Into<Header>,
+    request: Request<'_>,
+    buf: &'b mut [u8],
+) -> Result<Response<'b>, IoError<T::Error>>
+where
+    T: Read + Write + ?Sized,
+{
+    let mut transaction = ClientTransaction::new(hdr, request);
+    let len = encode_request(&mut transaction, buf)?;
+    io.write_all(&buf[..len]).await.map_err(IoError::Io)?;
+    io.flush().await.map_err(IoError::Io)?;
+    let len = receive(io, &transaction, buf).await?;
+    finish(&mut transaction, &buf[..len])
+}
+
+/// Send `request` and wait for the response until `timeout` completes.
+///
+/// `timeout` is any future of the application's executor,
+/// e.g. an `embassy_time::Timer`.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::future::Future;
+/// use modbus_core::{client::asynch::transact_with_timeout, rtu, Request, Response};
+///
+/// async fn read_temperature<T>(uart: &mut T, deadline: impl Future<Output = ()>) -> Option<u16>
+/// where
+///     T: embedded_io_async::Read + embedded_io_async::Write,
+/// {
+///     let buf = &mut [0; 256];
+///     let request = Request::ReadInputRegisters(0x10, 1);
+///     let hdr = rtu::Header { slave: 1 };
+///     match transact_with_timeout(uart, hdr, request, buf, deadline).await.ok()? {
+///         Response::ReadInputRegisters(words) => words.get(0),
+///         _ => None,
+///     }
+/// }
+/// ```
+pub async fn transact_with_timeout<'b, T, F>(
+    io: &mut T,
+    hdr: impl Into<Header>,
+    request: Request<'_>,
+    buf: &'b mut [u8],
+    timeout: F,
+) -> Result<Response<'b>, IoError<T::Error>>
+where
+    T: Read + Write + ?Sized,
+    F: Future<Output = ()>,
+{
+    let mut transaction = pin!(transact(io, hdr, request, buf));
+    let mut timeout = pin!(timeout);
+    poll_fn(|cx| {
+        if let Poll::Ready(result) = transaction.as_mut().poll(cx) {
+            return Poll::Ready(result);
+        }
+        timeout.as_mut().poll(cx).map(|()| Err(IoError::Timeout))
+    })
+    .await
+}
+
+/// Send an RTU broadcast `request` that is not answered.
+pub async fn broadcast<T>(
+    io: &mut T,
+    request: rtu::BroadcastRequest<'_>,
+    buf: &mut [u8],
+) -> Result<(), IoError<T::Error>>
+where
+    T: Write + ?Sized,
+{
+    let rtu::Unanswered { len } = rtu::client::encode_broadcast(request, buf)?;
+    io.write_all(&buf[..len]).await.map_err(IoError::Io)?;
+    io.flush().await.map_err(IoError::Io)
+}
+
+/// Read into `buf` until it contains the complete response.
+async fn receive<T>(
+    io: &mut T,
+    transaction: &ClientTransaction<'_>,
+    buf: &mut [u8],
+) -> Result<usize, IoError<T::Error>>
+where
+    T: Read + ?Sized,
+{
+    let mut len = 0;
+    loop {
+        if len == buf.len() {
+            return Err(TransactionError::Decode(DecodeError::BufferSize).into());
+        }
+        match io.read(&mut buf[len..]).await.map_err(IoError::Io)? {
+            0 => return Err(IoError::UnexpectedEof),
+            n => len += n,
+        }
+        if is_complete(transaction, buf, &mut len)? {
+            return Ok(len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{
+        future::pending,
+        ptr,
+        task::{Context, RawWaker, RawWakerVTable, Waker},
+    };
+    use embedded_io_async::{ErrorKind, ErrorType};
+
+    /// Poll `future` until it completes.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        #[allow(unsafe_code)]
+        let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// A connection that returns the response in small chunks
+    /// or never answers if there is no response.
+    struct Mock<'a> {
+        rx: &'a [u8],
+        tx_len: usize,
+    }
+
+    impl ErrorType for Mock<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for Mock<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if self.rx.is_empty() {
+                pending::<()>().await;
+            }
+            let n = buf.len().min(self.rx.len()).min(3);
+            buf[..n].copy_from_slice(&self.rx[..n]);
+            self.rx = &self.rx[n..];
+            Ok(n)
+        }
+    }
+
+    impl Write for Mock<'_> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.tx_len += buf.len();
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn rtu_transaction() {
+        let mut io = Mock {
+            rx: &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE],
+            tx_len: 0,
+        };
+        let buf = &mut [0; 256];
+        let request = Request::WriteSingleRegister(0x2222, 0xABCD);
+        let rsp = block_on(transact(&mut io, rtu::Header { slave: 0x12 }, request, buf));
+        assert_eq!(rsp, Ok(Response::WriteSingleRegister(0x2222, 0xABCD)));
+        assert_eq!(io.tx_len, 8);
+    }
+
+    #[test]
+    fn timeout() {
+        let mut io = Mock { rx: &[], tx_len: 0 };
+        let buf = &mut [0; 256];
+        let request = Request::ReadHoldingRegisters(0, 1);
+        let hdr = rtu::Header { slave: 0x12 };
+        let rsp = block_on(transact_with_timeout(&mut io, hdr, request, buf, async {}));
+        assert_eq!(rsp, Err(IoError::Timeout));
+        assert_eq!(io.tx_len, 8);
+    }
+}
@@ -0,0 +1,203 @@
+//! Blocking transactions on top of [`embedded_io`].
+
+use super::{io::*, *};
+use embedded_io::{Read, Write};
+
+/// Send `request` and wait for the response.
+///
+/// The request is encoded into `buf` which then receives the response.
+/// Timeouts have to be implemented by `io`, e.g. by a read that fails
+/// after some time.
+///
+/// # Example
+///
+/// ```no_run
+/// use modbus_core::{client::blocking::transact, rtu, Request, Response};
+///
+/// fn read_temperature<T>(uart: &mut T) -> Option<u16>
+/// where
+///     T: embedded_io::Read + embedded_io::Write,
+/// {
+///     let buf = &mut [0; 256];
+///     let request = Request::ReadInputRegisters(0x10, 1);
+///     match transact(uart, rtu::Header { slave: 1 }, request, buf).ok()? {
+///         Response::ReadInputRegisters(words) => words.get(0),
+///         _ => None,
+///     }
+/// }
+/// ```
+pub fn transact<'b, T>(
+    io: &mut T,
+    hdr: impl Into<Header>,
+    request: Request<'_>,
+    buf: &'b mut [u8],
+) -> Result<Response<'b>, IoError<T::Error>>
+where
+    T: Read + Write + ?Sized,
+{
+    let mut transaction = ClientTransaction::new(hdr, request);
+    let len = encode_request(&mut transaction, buf)?;
+    io.write_all(&buf[..len]).map_err(IoError::Io)?;
+    io.flush().map_err(IoError::Io)?;
+    let len = receive(io, &transaction, buf)?;
+    finish(&mut transaction, &buf[..len])
+}
+
+/// Send an RTU broadcast `request` that is not answered.
+pub fn broadcast<T>(
+    io: &mut T,
+    request: rtu::BroadcastRequest<'_>,
+    buf: &mut [u8],
+) -> Result<(), IoError<T::Error>>
+where
+    T: Write + ?Sized,
+{
+    let rtu::Unanswered { len } = rtu::client::encode_broadcast(request, buf)?;
+    io.write_all(&buf[..len]).map_err(IoError::Io)?;
+    io.flush().map_err(IoError::Io)
+}
+
+/// Read into `buf` until it contains the complete response.
+fn receive<T>(
+    io: &mut T,
+    transaction: &ClientTransaction<'_>,
+    buf: &mut [u8],
+) -> Result<usize, IoError<T::Error>>
+where
+    T: Read + ?Sized,
+{
+    let mut len = 0;
+    loop {
+        if len == buf.len() {
+            return Err(TransactionError::Decode(DecodeError::BufferSize).into());
+        }
+        match io.read(&mut buf[len..]).map_err(IoError::Io)? {
+            0 => return Err(IoError::UnexpectedEof),
+            n => len += n,
+        }
+        if is_complete(transaction, buf, &mut len)? {
+            return Ok(len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_io::{ErrorKind, ErrorType};
+
+    /// A connection that returns the response in small chunks.
+    struct Mock<'a> {
+        rx: &'a [u8],
+        tx: [u8; 16],
+        tx_len: usize,
+    }
+
+    impl<'a> Mock<'a> {
+        const fn new(rx: &'a [u8]) -> Self {
+            Self {
+                rx,
+                tx: [0; 16],
+                tx_len: 0,
+            }
+        }
+    }
+
+    impl ErrorType for Mock<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for Mock<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let n = buf.len().min(self.rx.len()).min(3);
+            buf[..n].copy_from_slice(&self.rx[..n]);
+            self.rx = &self.rx[n..];
+            Ok(n)
+        }
+    }
+
+    impl Write for Mock<'_> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.tx[self.tx_len..self.tx_len + buf.len()].copy_from_slice(buf);
+            self.tx_len += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rtu_transaction() {
+        let mut io = Mock::new(&[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]);
+        let buf = &mut [0; 256];
+        let request = Request::WriteSingleRegister(0x2222, 0xABCD);
+        let rsp = transact(&mut io, rtu::Header { slave: 0x12 }, request, buf).unwrap();
+        assert_eq!(rsp, Response::WriteSingleRegister(0x2222, 0xABCD));
+        assert_eq!(
+            &io.tx[..io.tx_len],
+            &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]
+        );
+    }
+
+    #[test]
+    fn tcp_exception() {
+        let mut io = Mock::new(&[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02]);
+        let buf = &mut [0; 256];
+        let hdr = tcp::Header {
+            transaction_id: 7,
+            unit_id: 1,
+        };
+        let request = Request::ReadHoldingRegisters(0x10, 2);
+        assert_eq!(
+            transact(&mut io, hdr, request, buf),
+            Err(IoError::Transaction(TransactionError::Exception(
+                RejectedRequest::new(&request, Exception::IllegalDataAddress)
+            )))
+        );
+    }
+
+    #[test]
+    fn skip_late_tcp_responses() {
+        let mut io = Mock::new(&[
+            0x00, 0x06, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x00, 0x01, // late
+            0x00, 0x07, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x00, 0x02,
+        ]);
+        let buf = &mut [0; 256];
+        let hdr = tcp::Header {
+            transaction_id: 7,
+            unit_id: 1,
+        };
+        let request = Request::WriteSingleRegister(0x10, 2);
+        assert_eq!(
+            transact(&mut io, hdr, request, buf),
+            Ok(Response::WriteSingleRegister(0x10, 2))
+        );
+    }
+
+    #[test]
+    fn closed_connection() {
+        let mut io = Mock::new(&[0x12, 0x06, 0x22]);
+        let buf = &mut [0; 256];
+        let request = Request::WriteSingleRegister(0x2222, 0xABCD);
+        assert_eq!(
+            transact(&mut io, rtu::Header { slave: 0x12 }, request, buf),
+            Err(IoError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn broadcasts_are_not_answered() {
+        let mut io = Mock::new(&[]);
+        let buf = &mut [0; 256];
+        let request = Request::WriteSingleRegister(0x2222, 0xABCD);
+        assert_eq!(
+            transact(&mut io, rtu::Header { slave: 0 }, request, buf),
+            Err(IoError::NoResponse)
+        );
+        assert_eq!(io.tx_len, 0);
+        broadcast(&mut io, request.try_into().unwrap(), buf).unwrap();
+        assert_eq!(io.tx_len, 8);
+    }
+}
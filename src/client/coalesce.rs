@@ -0,0 +1,214 @@
+use super::*;
+
+/// Collects scattered register writes and merges them into few requests.
+///
+/// Writes to contiguous addresses are sent with a single
+/// [`Request::WriteMultipleRegisters`] of up to 123 registers,
+/// a lone register with [`Request::WriteSingleRegister`].
+/// Up to `N` distinct addresses are buffered, a later write to
+/// the same address replaces the pending value.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{client::WriteCoalescer, Request};
+///
+/// let mut writes = WriteCoalescer::<8>::new();
+/// writes.write(0x0012, 1).unwrap();
+/// writes.write(0x0010, 2).unwrap();
+/// writes.write(0x0011, 3).unwrap();
+/// writes.write(0x0020, 4).unwrap();
+///
+/// let buf = &mut [0; 246];
+/// let Some(Request::WriteMultipleRegisters(address, data)) = writes.next_request(buf) else {
+///     unreachable!();
+/// };
+/// assert_eq!(address, 0x0010);
+/// assert_eq!(data.len(), 3);
+/// assert_eq!(
+///     writes.next_request(buf),
+///     Some(Request::WriteSingleRegister(0x0020, 4))
+/// );
+/// assert!(writes.next_request(buf).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WriteCoalescer<const N: usize> {
+    /// Pending writes, sorted by address.
+    writes: [(Address, Word); N],
+    len: usize,
+}
+
+impl<const N: usize> Default for WriteCoalescer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> WriteCoalescer<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            writes: [(0, 0); N],
+            len: 0,
+        }
+    }
+
+    /// The number of pending register writes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Discard all pending writes.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Write `value` into the register at `address`.
+    ///
+    /// Fails with [`Error::BufferSize`] if `N` other
+    /// addresses are already pending.
+    pub fn write(&mut self, address: Address, value: Word) -> Result<(), Error> {
+        match self.writes[..self.len].binary_search_by_key(&address, |(a, _)| *a) {
+            Ok(idx) => self.writes[idx].1 = value,
+            Err(idx) => {
+                if self.len == N {
+                    return Err(Error::BufferSize);
+                }
+                self.writes.copy_within(idx..self.len, idx + 1);
+                self.writes[idx] = (address, value);
+                self.len += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write `values` into the registers starting at `address`.
+    ///
+    /// Fails without any changes if the values do
+    /// not fit into the remaining capacity.
+    pub fn write_all(&mut self, address: Address, values: &[Word]) -> Result<(), Error> {
+        if values.len() > usize::from(Address::MAX - address) + 1 {
+            return Err(Error::BufferSize);
+        }
+        let pending = (address..=Address::MAX)
+            .take(values.len())
+            .filter(|a| {
+                self.writes[..self.len]
+                    .binary_search_by_key(a, |(a, _)| *a)
+                    .is_ok()
+            })
+            .count();
+        if self.len + values.len() - pending > N {
+            return Err(Error::BufferSize);
+        }
+        for (address, value) in (address..=Address::MAX).zip(values) {
+            self.write(address, *value)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the next run of contiguous writes and return their request.
+    ///
+    /// The values of a [`Request::WriteMultipleRegisters`] are encoded into
+    /// `buf`, a run is split if `buf` cannot hold all of its registers.
+    /// Returns `None` if no writes are pending.
+    pub fn next_request<'b>(&mut self, buf: &'b mut [u8]) -> Option<Request<'b>> {
+        let &(address, value) = self.writes[..self.len].first()?;
+        let limit = MAX_WRITE_REGISTERS.min(buf.len() / 2);
+        let run = self.writes[..self.len]
+            .iter()
+            .zip(address..=Address::MAX)
+            .take(limit)
+            .take_while(|((a, _), expected)| a == expected)
+            .count()
+            .max(1);
+        let request = if run == 1 {
+            Request::WriteSingleRegister(address, value)
+        } else {
+            for (chunk, (_, value)) in buf.chunks_exact_mut(2).zip(&self.writes[..run]) {
+                write_u16(chunk, *value);
+            }
+            let data = Data {
+                data: &buf[..run * 2],
+                quantity: run,
+            };
+            Request::WriteMultipleRegisters(address, data)
+        };
+        self.writes.copy_within(run..self.len, 0);
+        self.len -= run;
+        Some(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_pending_values() {
+        let mut writes = WriteCoalescer::<2>::new();
+        assert_eq!(writes.write(5, 1), Ok(()));
+        assert_eq!(writes.write(5, 2), Ok(()));
+        assert_eq!(writes.write(7, 3), Ok(()));
+        assert_eq!(writes.len(), 2);
+        assert_eq!(writes.write(6, 4), Err(Error::BufferSize));
+        assert_eq!(
+            writes.next_request(&mut []),
+            Some(Request::WriteSingleRegister(5, 2))
+        );
+        assert_eq!(
+            writes.next_request(&mut []),
+            Some(Request::WriteSingleRegister(7, 3))
+        );
+        assert!(writes.is_empty());
+    }
+
+    #[test]
+    fn split_long_runs() {
+        let mut writes = WriteCoalescer::<130>::new();
+        let values = [0xABCD; 124];
+        assert_eq!(writes.write_all(0xFF00, &values), Ok(()));
+        assert_eq!(writes.write_all(0xFFFE, &[1, 2, 3]), Err(Error::BufferSize));
+        assert_eq!(writes.write_all(0xFF00, &[0; 7]), Ok(()));
+        assert_eq!(writes.write_all(0x0000, &[0; 7]), Err(Error::BufferSize));
+        assert_eq!(writes.len(), 124);
+
+        let buf = &mut [0; 300];
+        let Some(Request::WriteMultipleRegisters(0xFF00, data)) = writes.next_request(buf) else {
+            panic!("unexpected request");
+        };
+        assert_eq!(data.len(), 123);
+        assert_eq!(data.get(6), Some(0));
+        assert_eq!(data.get(7), Some(0xABCD));
+        assert_eq!(
+            writes.next_request(buf),
+            Some(Request::WriteSingleRegister(0xFF7B, 0xABCD))
+        );
+        assert_eq!(writes.next_request(buf), None);
+
+        writes.write_all(0xFFFE, &[1, 2]).unwrap();
+        assert!(writes.next_request(buf).is_some());
+    }
+
+    #[test]
+    fn limit_runs_to_the_buffer() {
+        let mut writes = WriteCoalescer::<4>::new();
+        writes.write_all(0x0010, &[1, 2, 3]).unwrap();
+        let buf = &mut [0; 5];
+        let Some(Request::WriteMultipleRegisters(0x0010, data)) = writes.next_request(buf) else {
+            panic!("unexpected request");
+        };
+        assert_eq!(data.len(), 2);
+        assert_eq!(
+            writes.next_request(buf),
+            Some(Request::WriteSingleRegister(0x0012, 3))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use super::*;
+
+/// A register whose value has changed, see [`ChangeDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Change {
+    pub address: Address,
+    /// The last reported value or `None` if the
+    /// register is reported for the first time.
+    pub previous: Option<Word>,
+    pub value: Word,
+}
+
+/// Reports the registers of a polling loop that have changed.
+///
+/// The last reported value of each register is kept in a snapshot
+/// that is provided by the caller. A register is only reported if it
+/// differs by more than its deadband from the snapshot, so small
+/// fluctuations of measured values are not forwarded, e.g. to an
+/// MQTT broker.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{client::ChangeDetector, Data};
+///
+/// let snapshot = &mut [None; 2];
+/// let mut detector = ChangeDetector::new(0x0100, snapshot).with_deadbands(&[0, 5]);
+///
+/// let buf = &mut [0; 4];
+/// let data = Data::from_words(&[1, 100], buf).unwrap();
+/// assert_eq!(detector.update(&data).count(), 2);
+///
+/// let data = Data::from_words(&[2, 104], buf).unwrap();
+/// let mut changes = detector.update(&data);
+/// let change = changes.next().unwrap();
+/// assert_eq!(change.address, 0x0100);
+/// assert_eq!(change.previous, Some(1));
+/// assert_eq!(change.value, 2);
+/// assert!(changes.next().is_none());
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChangeDetector<'s> {
+    start: Address,
+    snapshot: &'s mut [Option<Word>],
+    deadbands: &'s [Word],
+}
+
+impl<'s> ChangeDetector<'s> {
+    /// Detect changes of the registers starting at `start`.
+    ///
+    /// The length of `snapshot` is the number of registers.
+    /// Registers with a snapshot of `None` are reported
+    /// with the next update.
+    #[must_use]
+    pub fn new(start: Address, snapshot: &'s mut [Option<Word>]) -> Self {
+        Self {
+            start,
+            snapshot,
+            deadbands: &[],
+        }
+    }
+
+    /// Set the deadband of each register.
+    ///
+    /// Registers without a deadband are reported on every change.
+    #[must_use]
+    pub const fn with_deadbands(mut self, deadbands: &'s [Word]) -> Self {
+        self.deadbands = deadbands;
+        self
+    }
+
+    /// Report all registers with the next update.
+    pub fn reset(&mut self) {
+        self.snapshot.fill(None);
+    }
+
+    /// Compare `data`, read starting at the first register,
+    /// against the snapshot.
+    ///
+    /// The snapshot of a register is updated when it is reported
+    /// by the returned iterator.
+    pub fn update<'a, 'd>(&'a mut self, data: &Data<'d>) -> Changes<'a, 'd, 's> {
+        Changes {
+            detector: self,
+            data: *data,
+            idx: 0,
+        }
+    }
+}
+
+/// The changes of an update, see [`ChangeDetector::update`].
+#[derive(Debug)]
+pub struct Changes<'a, 'd, 's> {
+    detector: &'a mut ChangeDetector<'s>,
+    data: Data<'d>,
+    idx: usize,
+}
+
+impl Iterator for Changes<'_, '_, '_> {
+    type Item = Change;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ChangeDetector {
+            start,
+            snapshot,
+            deadbands,
+        } = &mut *self.detector;
+        while let (Some(previous), Some(value)) =
+            (snapshot.get_mut(self.idx), self.data.get(self.idx))
+        {
+            let idx = self.idx;
+            self.idx += 1;
+            let deadband = deadbands.get(idx).copied().unwrap_or(0);
+            if matches!(*previous, Some(last) if last.abs_diff(value) <= deadband) {
+                continue;
+            }
+            let change = Change {
+                address: start.wrapping_add(idx as Address),
+                previous: previous.replace(value),
+                value,
+            };
+            return Some(change);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_values_within_the_deadband() {
+        let snapshot = &mut [None; 3];
+        let mut detector = ChangeDetector::new(10, snapshot).with_deadbands(&[2]);
+        let buf = &mut [0; 8];
+
+        let data = Data::from_words(&[100, 200, 300, 400], buf).unwrap();
+        assert_eq!(detector.update(&data).count(), 3);
+
+        let data = Data::from_words(&[98, 200, 300], buf).unwrap();
+        assert_eq!(detector.update(&data).count(), 0);
+
+        // Drifting values are compared against the last reported value
+        let data = Data::from_words(&[97, 201, 300], buf).unwrap();
+        let changes = &mut [None; 2];
+        for (change, slot) in detector.update(&data).zip(changes.iter_mut()) {
+            *slot = Some(change);
+        }
+        assert_eq!(
+            changes,
+            &[
+                Some(Change {
+                    address: 10,
+                    previous: Some(100),
+                    value: 97,
+                }),
+                Some(Change {
+                    address: 11,
+                    previous: Some(200),
+                    value: 201,
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn report_remaining_changes_later() {
+        let snapshot = &mut [None; 2];
+        let mut detector = ChangeDetector::new(0, snapshot);
+        let buf = &mut [0; 4];
+        let data = Data::from_words(&[1, 2], buf).unwrap();
+        assert_eq!(detector.update(&data).next().unwrap().address, 0);
+        assert_eq!(detector.update(&data).next().unwrap().address, 1);
+        assert!(detector.update(&data).next().is_none());
+        detector.reset();
+        assert_eq!(detector.update(&data).count(), 2);
+    }
+}
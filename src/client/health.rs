@@ -0,0 +1,256 @@
+use super::*;
+
+/// The outcome of a transaction, see [`LinkHealth::record`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Outcome {
+    /// The device answered the request.
+    Success,
+    /// The device rejected the request with an exception.
+    Exception(Exception),
+    /// No response has been received in time.
+    Timeout,
+    /// The response is invalid or does not answer the request.
+    Error(TransactionError),
+}
+
+impl Outcome {
+    /// Classify the reply of a transaction.
+    ///
+    /// `None` means that the transaction has timed out.
+    #[must_use]
+    pub const fn classify(reply: Option<Result<ResponsePdu<'_>, TransactionError>>) -> Self {
+        match reply {
+            Some(Ok(ResponsePdu(Ok(_)))) => Self::Success,
+            Some(Ok(ResponsePdu(Err(ExceptionResponse { exception, .. })))) => {
+                Self::Exception(exception)
+            }
+            Some(Err(err)) => Self::from_error(err),
+            None => Self::Timeout,
+        }
+    }
+
+    const fn from_error(err: TransactionError) -> Self {
+        match err {
+            TransactionError::Exception(RejectedRequest { exception, .. }) => {
+                Self::Exception(exception)
+            }
+            err => Self::Error(err),
+        }
+    }
+
+    /// The device has answered, possibly with an exception.
+    #[must_use]
+    pub const fn is_response(&self) -> bool {
+        matches!(self, Self::Success | Self::Exception(_))
+    }
+}
+
+impl From<TransactionError> for Outcome {
+    fn from(err: TransactionError) -> Self {
+        Self::from_error(err)
+    }
+}
+
+/// The health of the link to a single slave or unit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceHealth {
+    pub successes: u32,
+    pub exceptions: u32,
+    pub timeouts: u32,
+    /// Invalid or mismatching responses.
+    pub errors: u32,
+    /// Timeouts and errors since the last response.
+    pub failure_streak: u32,
+    /// The last outcome other than [`Outcome::Success`].
+    pub last_error: Option<Outcome>,
+}
+
+impl DeviceHealth {
+    /// The number of recorded transactions.
+    #[must_use]
+    pub const fn transactions(&self) -> u64 {
+        self.successes as u64 + self.exceptions as u64 + self.timeouts as u64 + self.errors as u64
+    }
+
+    /// The share of transactions that have been answered, including
+    /// exceptions, or `None` if no transaction has been recorded.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn availability(&self) -> Option<f32> {
+        let answered = u64::from(self.successes) + u64::from(self.exceptions);
+        match self.transactions() {
+            0 => None,
+            total => Some(answered as f32 / total as f32),
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome) {
+        let count = match outcome {
+            Outcome::Success => &mut self.successes,
+            Outcome::Exception(_) => &mut self.exceptions,
+            Outcome::Timeout => &mut self.timeouts,
+            Outcome::Error(_) => &mut self.errors,
+        };
+        *count = count.saturating_add(1);
+        if outcome.is_response() {
+            self.failure_streak = 0;
+        } else {
+            self.failure_streak = self.failure_streak.saturating_add(1);
+        }
+        if outcome != Outcome::Success {
+            self.last_error = Some(outcome);
+        }
+    }
+}
+
+/// Tracks the [`DeviceHealth`] of up to `N` slaves or units.
+///
+/// Exceptions count as answers of the device, so only timeouts
+/// and errors extend the failure streak. A master may e.g. stop
+/// polling a device after a number of consecutive failures.
+/// Stale responses (see [`TransactionError::is_stale`]) are ignored.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::client::{LinkHealth, Outcome};
+///
+/// let mut health = LinkHealth::<8>::new();
+/// health.record(0x12, Outcome::Success).unwrap();
+/// health.record(0x12, Outcome::Timeout).unwrap();
+/// let device = health.record(0x12, Outcome::Timeout).unwrap();
+/// assert_eq!(device.failure_streak, 2);
+/// assert_eq!(device.last_error, Some(Outcome::Timeout));
+/// assert_eq!(device.availability(), Some(1.0 / 3.0));
+/// assert!(health.get(0x13).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkHealth<const N: usize> {
+    devices: [Option<(u8, DeviceHealth)>; N],
+}
+
+impl<const N: usize> Default for LinkHealth<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> LinkHealth<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { devices: [None; N] }
+    }
+
+    /// Record the outcome of a transaction with the slave or unit `id`.
+    ///
+    /// Fails with [`Error::BufferSize`] if `id` is new and
+    /// `N` other devices are already tracked.
+    pub fn record(&mut self, id: u8, outcome: Outcome) -> Result<&DeviceHealth, Error> {
+        let idx = self
+            .position(id)
+            .or_else(|| self.devices.iter().position(Option::is_none))
+            .ok_or(Error::BufferSize)?;
+        let (_, health) = self.devices[idx].get_or_insert((id, DeviceHealth::default()));
+        if !matches!(outcome, Outcome::Error(err) if err.is_stale()) {
+            health.record(outcome);
+        }
+        Ok(health)
+    }
+
+    /// The health of the slave or unit `id`.
+    #[must_use]
+    pub fn get(&self, id: u8) -> Option<&DeviceHealth> {
+        self.devices
+            .iter()
+            .flatten()
+            .find(|(i, _)| *i == id)
+            .map(|(_, health)| health)
+    }
+
+    /// Stop tracking the slave or unit `id`.
+    pub fn remove(&mut self, id: u8) -> Option<DeviceHealth> {
+        let idx = self.position(id)?;
+        self.devices[idx].take().map(|(_, health)| health)
+    }
+
+    /// All tracked devices with their id.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &DeviceHealth)> {
+        self.devices
+            .iter()
+            .flatten()
+            .map(|(id, health)| (*id, health))
+    }
+
+    /// Stop tracking all devices.
+    pub fn reset(&mut self) {
+        self.devices = [None; N];
+    }
+
+    fn position(&self, id: u8) -> Option<usize> {
+        self.devices
+            .iter()
+            .position(|device| matches!(device, Some((i, _)) if *i == id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_outcomes() {
+        let mut health = LinkHealth::<1>::new();
+        let err = TransactionError::SlaveId {
+            expected: 1,
+            received: 2,
+        };
+        health.record(1, Outcome::Timeout).unwrap();
+        health.record(1, Outcome::from(err)).unwrap();
+        assert_eq!(health.get(1).unwrap().failure_streak, 2);
+        let device = health
+            .record(1, Outcome::Exception(Exception::ServerDeviceBusy))
+            .unwrap();
+        assert_eq!(
+            *device,
+            DeviceHealth {
+                successes: 0,
+                exceptions: 1,
+                timeouts: 1,
+                errors: 1,
+                failure_streak: 0,
+                last_error: Some(Outcome::Exception(Exception::ServerDeviceBusy)),
+            }
+        );
+        assert_eq!(device.transactions(), 3);
+
+        // Stale responses are not counted
+        health
+            .record(1, Outcome::Error(TransactionError::Late(3)))
+            .unwrap();
+        assert_eq!(health.get(1).unwrap().transactions(), 3);
+
+        assert_eq!(health.record(2, Outcome::Success), Err(Error::BufferSize));
+        assert!(health.remove(1).is_some());
+        assert!(health.record(2, Outcome::Success).is_ok());
+        assert_eq!(health.iter().count(), 1);
+        health.reset();
+        assert_eq!(health.iter().count(), 0);
+    }
+
+    #[test]
+    fn classify_replies() {
+        assert_eq!(Outcome::classify(None), Outcome::Timeout);
+        let rsp = ResponsePdu(Ok(Response::WriteSingleRegister(0, 0)));
+        assert_eq!(Outcome::classify(Some(Ok(rsp))), Outcome::Success);
+        let rejected =
+            RejectedRequest::new(&Request::ReadCoils(0, 1), Exception::IllegalDataAddress);
+        assert_eq!(
+            Outcome::classify(Some(Err(TransactionError::Exception(rejected)))),
+            Outcome::Exception(Exception::IllegalDataAddress)
+        );
+        assert_eq!(DeviceHealth::default().availability(), None);
+    }
+}
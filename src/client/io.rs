@@ -0,0 +1,102 @@
+use super::*;
+use crate::{DecoderType, FrameStatus};
+
+/// Errors of a transaction that performs the I/O itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IoError<E> {
+    /// Reading or writing failed.
+    Io(E),
+    /// The request could not be encoded.
+    Encode(EncodeError),
+    /// The response is invalid or does not answer the request.
+    Transaction(TransactionError),
+    /// The connection has been closed before the response was complete.
+    UnexpectedEof,
+    /// The request is an RTU broadcast that is never answered.
+    NoResponse,
+    /// No response has been received in time.
+    Timeout,
+}
+
+impl<E> From<EncodeError> for IoError<E> {
+    fn from(err: EncodeError) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl<E> From<TransactionError> for IoError<E> {
+    fn from(err: TransactionError) -> Self {
+        Self::Transaction(err)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for IoError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {err:?}"),
+            Self::Encode(err) => write!(f, "{err}"),
+            Self::Transaction(err) => write!(f, "{err}"),
+            Self::UnexpectedEof => write!(f, "Connection closed before the response was complete"),
+            Self::NoResponse => write!(f, "Broadcast requests are never answered"),
+            Self::Timeout => write!(f, "No response received in time"),
+        }
+    }
+}
+
+/// Encode the request of `transaction` that must be answered.
+pub(crate) fn encode_request<E>(
+    transaction: &mut ClientTransaction<'_>,
+    buf: &mut [u8],
+) -> Result<usize, IoError<E>> {
+    if let Header::Rtu(rtu::Header {
+        slave: rtu::BROADCAST,
+    }) = transaction.header()
+    {
+        return Err(IoError::NoResponse);
+    }
+    Ok(transaction.encode(buf)?)
+}
+
+/// Check whether the first `len` bytes of `buf` contain the complete response.
+///
+/// Stale responses are dropped from the start of `buf` and
+/// `len` is reduced accordingly.
+pub(crate) fn is_complete(
+    transaction: &ClientTransaction<'_>,
+    buf: &mut [u8],
+    len: &mut usize,
+) -> Result<bool, TransactionError> {
+    while *len > 0 {
+        let mut probe = *transaction;
+        match probe.decode(&buf[..*len]) {
+            Ok(rsp) => return Ok(rsp.is_some()),
+            Err(err) if err.is_stale() => {
+                let FrameStatus::Complete(stale) =
+                    tcp::frame_status(DecoderType::Response, &buf[..*len])
+                else {
+                    return Err(err);
+                };
+                buf.copy_within(stale..*len, 0);
+                *len -= stale;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(false)
+}
+
+/// Take the response out of the complete bytes `received`.
+pub(crate) fn finish<'b, E>(
+    transaction: &mut ClientTransaction<'_>,
+    received: &'b [u8],
+) -> Result<Response<'b>, IoError<E>> {
+    match transaction.decode(received)? {
+        Some(ResponsePdu(Ok(rsp))) => Ok(rsp),
+        Some(ResponsePdu(Err(ExceptionResponse { exception, .. }))) => Err(
+            TransactionError::Exception(RejectedRequest::new(&transaction.request(), exception))
+                .into(),
+        ),
+        None => Err(IoError::UnexpectedEof),
+    }
+}
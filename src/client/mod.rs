@@ -0,0 +1,59 @@
+//! Modbus client (master) request/response handling.
+//!
+//! Nothing in this module performs any I/O: the application sends
+//! the encoded requests, feeds back the received bytes and signals
+//! timeouts on its own.
+//!
+//! The typed operations like [`read_holding_registers`] return the
+//! request to send together with a decoder for the expected response.
+//!
+//! With the `embedded-io` feature the `blocking` module performs
+//! whole transactions on any [`embedded_io`] stream and with the
+//! `embedded-io-async` feature the `asynch` module does the same
+//! on any [`embedded_io_async`] stream.
+
+use crate::{error::*, frame::*, rtu, tcp};
+use core::fmt;
+
+#[cfg(feature = "embedded-io-async")]
+pub mod asynch;
+#[cfg(feature = "embedded-io")]
+pub mod blocking;
+mod coalesce;
+mod deadband;
+mod health;
+#[cfg(any(feature = "embedded-io", feature = "embedded-io-async"))]
+mod io;
+mod ops;
+mod rejected;
+mod retry;
+mod scan;
+mod schedule;
+mod transaction;
+
+#[cfg(any(feature = "embedded-io", feature = "embedded-io-async"))]
+pub use self::io::IoError;
+pub use self::{
+    coalesce::*, deadband::*, health::*, ops::*, rejected::*, retry::*, scan::*, schedule::*,
+    transaction::*,
+};
+
+/// The transport specific header of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Header {
+    Rtu(rtu::Header),
+    Tcp(tcp::Header),
+}
+
+impl From<rtu::Header> for Header {
+    fn from(hdr: rtu::Header) -> Self {
+        Self::Rtu(hdr)
+    }
+}
+
+impl From<tcp::Header> for Header {
+    fn from(hdr: tcp::Header) -> Self {
+        Self::Tcp(hdr)
+    }
+}
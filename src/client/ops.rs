@@ -0,0 +1,364 @@
+use super::*;
+
+/// Decoder of [`read_coils`] and [`read_discrete_inputs`] responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadCoilsDecoder {
+    function: FunctionCode,
+    address: Address,
+    quantity: Quantity,
+}
+
+impl ReadCoilsDecoder {
+    /// Extract the requested coils from `pdu`.
+    pub fn decode<'b>(&self, pdu: &ResponsePdu<'b>) -> Result<Coils<'b>, TransactionError> {
+        let summary = RequestSummary {
+            function: self.function,
+            address: Some(self.address),
+            quantity: Some(self.quantity),
+        };
+        let coils = match expect_response(pdu, summary)? {
+            Response::ReadCoils(coils) | Response::ReadDiscreteInputs(coils) => coils,
+            rsp => return Err(unexpected(self.function, rsp)),
+        };
+        let quantity = usize::from(self.quantity);
+        check_byte_count(packed_coils_len(quantity), coils.data.len())
+            .map_err(TransactionError::Mismatch)?;
+        Ok(Coils {
+            data: coils.data,
+            quantity,
+        })
+    }
+}
+
+/// Decoder of register read responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadRegistersDecoder {
+    function: FunctionCode,
+    address: Address,
+    quantity: Quantity,
+}
+
+impl ReadRegistersDecoder {
+    /// Extract the requested registers from `pdu`.
+    pub fn decode<'b>(&self, pdu: &ResponsePdu<'b>) -> Result<Data<'b>, TransactionError> {
+        let summary = RequestSummary {
+            function: self.function,
+            address: Some(self.address),
+            quantity: Some(self.quantity),
+        };
+        let words = match expect_response(pdu, summary)? {
+            Response::ReadInputRegisters(words)
+            | Response::ReadHoldingRegisters(words)
+            | Response::ReadWriteMultipleRegisters(words) => words,
+            rsp => return Err(unexpected(self.function, rsp)),
+        };
+        check_byte_count(usize::from(self.quantity) * 2, words.data.len())
+            .map_err(TransactionError::Mismatch)?;
+        Ok(words)
+    }
+}
+
+/// The echoed field of a write response besides the address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum Echo {
+    Quantity(Quantity),
+    Value(Word),
+}
+
+/// Decoder of write responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WriteDecoder {
+    function: FunctionCode,
+    address: Address,
+    echo: Echo,
+}
+
+impl WriteDecoder {
+    /// Check that `pdu` confirms the write.
+    pub fn decode(&self, pdu: &ResponsePdu<'_>) -> Result<(), TransactionError> {
+        let summary = RequestSummary {
+            function: self.function,
+            address: Some(self.address),
+            quantity: match self.echo {
+                Echo::Quantity(quantity) => Some(quantity),
+                Echo::Value(_) => None,
+            },
+        };
+        let (address, echo) = match expect_response(pdu, summary)? {
+            Response::WriteSingleCoil(address, coil) => {
+                (address, Echo::Value(bool_to_u16_coil(coil)))
+            }
+            Response::WriteMultipleCoils(address, quantity)
+            | Response::WriteMultipleRegisters(address, quantity) => {
+                (address, Echo::Quantity(quantity))
+            }
+            Response::WriteSingleRegister(address, word) => (address, Echo::Value(word)),
+            rsp => return Err(unexpected(self.function, rsp)),
+        };
+        check_address(self.address, address).map_err(TransactionError::Mismatch)?;
+        match (self.echo, echo) {
+            (Echo::Quantity(expected), Echo::Quantity(received)) => {
+                check_quantity(expected, received)
+            }
+            (Echo::Value(expected), Echo::Value(received)) => check_value(expected, received),
+            _ => Ok(()),
+        }
+        .map_err(TransactionError::Mismatch)
+    }
+}
+
+/// Read `quantity` coils starting at `address`.
+#[must_use]
+pub const fn read_coils(
+    address: Address,
+    quantity: Quantity,
+) -> (Request<'static>, ReadCoilsDecoder) {
+    let decoder = ReadCoilsDecoder {
+        function: FunctionCode::ReadCoils,
+        address,
+        quantity,
+    };
+    (Request::ReadCoils(address, quantity), decoder)
+}
+
+/// Read `quantity` discrete inputs starting at `address`.
+#[must_use]
+pub const fn read_discrete_inputs(
+    address: Address,
+    quantity: Quantity,
+) -> (Request<'static>, ReadCoilsDecoder) {
+    let decoder = ReadCoilsDecoder {
+        function: FunctionCode::ReadDiscreteInputs,
+        address,
+        quantity,
+    };
+    (Request::ReadDiscreteInputs(address, quantity), decoder)
+}
+
+/// Read `quantity` input registers starting at `address`.
+#[must_use]
+pub const fn read_input_registers(
+    address: Address,
+    quantity: Quantity,
+) -> (Request<'static>, ReadRegistersDecoder) {
+    let decoder = ReadRegistersDecoder {
+        function: FunctionCode::ReadInputRegisters,
+        address,
+        quantity,
+    };
+    (Request::ReadInputRegisters(address, quantity), decoder)
+}
+
+/// Read `quantity` holding registers starting at `address`.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{client::read_holding_registers, ResponsePdu};
+///
+/// let (request, decoder) = read_holding_registers(0x100, 2);
+/// // ... send `request` and receive the response PDU
+/// let bytes: &[u8] = &[0x03, 0x04, 0x12, 0x34, 0x56, 0x78];
+/// let data = decoder.decode(&ResponsePdu::try_from(bytes).unwrap()).unwrap();
+/// assert_eq!(data.get(1), Some(0x5678));
+/// ```
+#[must_use]
+pub const fn read_holding_registers(
+    address: Address,
+    quantity: Quantity,
+) -> (Request<'static>, ReadRegistersDecoder) {
+    let decoder = ReadRegistersDecoder {
+        function: FunctionCode::ReadHoldingRegisters,
+        address,
+        quantity,
+    };
+    (Request::ReadHoldingRegisters(address, quantity), decoder)
+}
+
+/// Write `words` starting at `write_address` and read `quantity`
+/// holding registers starting at `read_address` afterwards.
+#[must_use]
+pub const fn read_write_multiple_registers(
+    read_address: Address,
+    quantity: Quantity,
+    write_address: Address,
+    words: Data<'_>,
+) -> (Request<'_>, ReadRegistersDecoder) {
+    let decoder = ReadRegistersDecoder {
+        function: FunctionCode::ReadWriteMultipleRegisters,
+        address: read_address,
+        quantity,
+    };
+    let request = Request::ReadWriteMultipleRegisters(read_address, quantity, write_address, words);
+    (request, decoder)
+}
+
+/// Write a single coil.
+#[must_use]
+pub const fn write_single_coil(address: Address, coil: Coil) -> (Request<'static>, WriteDecoder) {
+    let decoder = WriteDecoder {
+        function: FunctionCode::WriteSingleCoil,
+        address,
+        echo: Echo::Value(bool_to_u16_coil(coil)),
+    };
+    (Request::WriteSingleCoil(address, coil), decoder)
+}
+
+/// Write multiple coils starting at `address`.
+#[must_use]
+pub const fn write_multiple_coils(
+    address: Address,
+    coils: Coils<'_>,
+) -> (Request<'_>, WriteDecoder) {
+    let decoder = WriteDecoder {
+        function: FunctionCode::WriteMultipleCoils,
+        address,
+        echo: Echo::Quantity(coils.len() as Quantity),
+    };
+    (Request::WriteMultipleCoils(address, coils), decoder)
+}
+
+/// Write a single holding register.
+#[must_use]
+pub const fn write_single_register(
+    address: Address,
+    word: Word,
+) -> (Request<'static>, WriteDecoder) {
+    let decoder = WriteDecoder {
+        function: FunctionCode::WriteSingleRegister,
+        address,
+        echo: Echo::Value(word),
+    };
+    (Request::WriteSingleRegister(address, word), decoder)
+}
+
+/// Write multiple holding registers starting at `address`.
+#[must_use]
+pub const fn write_multiple_registers(
+    address: Address,
+    words: Data<'_>,
+) -> (Request<'_>, WriteDecoder) {
+    let decoder = WriteDecoder {
+        function: FunctionCode::WriteMultipleRegisters,
+        address,
+        echo: Echo::Quantity(words.len() as Quantity),
+    };
+    (Request::WriteMultipleRegisters(address, words), decoder)
+}
+
+/// Get the response to the summarized request out of `pdu`.
+fn expect_response<'b>(
+    pdu: &ResponsePdu<'b>,
+    request: RequestSummary,
+) -> Result<Response<'b>, TransactionError> {
+    let expected = request.function;
+    let received = match pdu.0 {
+        Ok(rsp) => FunctionCode::from(rsp),
+        Err(ExceptionResponse { function, .. }) => function,
+    };
+    if received != expected {
+        return Err(TransactionError::FunctionCode { expected, received });
+    }
+    pdu.0.map_err(|ExceptionResponse { exception, .. }| {
+        TransactionError::Exception(RejectedRequest { request, exception })
+    })
+}
+
+/// A response with the right function code but an unexpected variant,
+/// e.g. a [`Response::Custom`].
+fn unexpected(expected: FunctionCode, rsp: Response<'_>) -> TransactionError {
+    TransactionError::FunctionCode {
+        expected,
+        received: FunctionCode::from(rsp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pdu(bytes: &[u8]) -> ResponsePdu<'_> {
+        ResponsePdu::try_from(bytes).unwrap()
+    }
+
+    #[test]
+    fn read_exact_coils() {
+        let (request, decoder) = read_coils(0x10, 3);
+        assert_eq!(request, Request::ReadCoils(0x10, 3));
+        let coils = decoder.decode(&pdu(&[0x01, 0x01, 0b101])).unwrap();
+        assert_eq!(coils.len(), 3);
+        assert_eq!(coils.get(2), Some(true));
+        assert_eq!(
+            decoder.decode(&pdu(&[0x01, 0x02, 0b101, 0x00])).err(),
+            Some(TransactionError::Mismatch(MismatchError::ByteCount {
+                expected: 1,
+                received: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn read_registers() {
+        let (request, decoder) = read_input_registers(0x10, 1);
+        assert_eq!(request, Request::ReadInputRegisters(0x10, 1));
+        let data = decoder.decode(&pdu(&[0x04, 0x02, 0xAB, 0xCD])).unwrap();
+        assert_eq!(data.get(0), Some(0xABCD));
+        assert_eq!(
+            decoder.decode(&pdu(&[0x03, 0x02, 0xAB, 0xCD])).err(),
+            Some(TransactionError::FunctionCode {
+                expected: FunctionCode::ReadInputRegisters,
+                received: FunctionCode::ReadHoldingRegisters,
+            })
+        );
+    }
+
+    #[test]
+    fn exception_response() {
+        let (_, decoder) = read_holding_registers(0, 1);
+        assert_eq!(
+            decoder.decode(&pdu(&[0x83, 0x02])).err(),
+            Some(TransactionError::Exception(RejectedRequest::new(
+                &Request::ReadHoldingRegisters(0, 1),
+                Exception::IllegalDataAddress
+            )))
+        );
+    }
+
+    #[test]
+    fn write_echo() {
+        let (_, decoder) = write_single_register(0x22, 0xABCD);
+        assert!(decoder
+            .decode(&pdu(&[0x06, 0x00, 0x22, 0xAB, 0xCD]))
+            .is_ok());
+        assert_eq!(
+            decoder.decode(&pdu(&[0x06, 0x00, 0x22, 0xAB, 0xCE])).err(),
+            Some(TransactionError::Mismatch(MismatchError::Value {
+                expected: 0xABCD,
+                received: 0xABCE
+            }))
+        );
+        let words = Data {
+            data: &[0x00, 0x01, 0x00, 0x02],
+            quantity: 2,
+        };
+        let (_, decoder) = write_multiple_registers(0x22, words);
+        assert_eq!(
+            decoder.decode(&pdu(&[0x10, 0x00, 0x23, 0x00, 0x02])).err(),
+            Some(TransactionError::Mismatch(MismatchError::Address {
+                expected: 0x22,
+                received: 0x23
+            }))
+        );
+        assert_eq!(
+            decoder.decode(&pdu(&[0x10, 0x00, 0x22, 0x00, 0x01])).err(),
+            Some(TransactionError::Mismatch(MismatchError::Quantity {
+                expected: 2,
+                received: 1
+            }))
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use super::*;
+
+/// The function and the addressed range of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestSummary {
+    pub function: FunctionCode,
+    /// The (first read) address, if any.
+    pub address: Option<Address>,
+    /// The number of (read) coils or registers, if any.
+    pub quantity: Option<Quantity>,
+}
+
+impl From<&Request<'_>> for RequestSummary {
+    fn from(request: &Request<'_>) -> Self {
+        Self {
+            function: request.function_code(),
+            address: request.address(),
+            quantity: request.quantity(),
+        }
+    }
+}
+
+impl fmt::Display for RequestSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.function)?;
+        if let Some(address) = self.address {
+            write!(f, "@0x{address:0>4X}")?;
+        }
+        if let Some(quantity) = self.quantity {
+            write!(f, "[{quantity}]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RequestSummary {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", self.function);
+        if let Some(address) = self.address {
+            defmt::write!(f, "@0x{=u16:04X}", address);
+        }
+        if let Some(quantity) = self.quantity {
+            defmt::write!(f, "[{=u16}]", quantity);
+        }
+    }
+}
+
+/// A request that has been answered with an exception.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{client::RejectedRequest, Exception, Request};
+///
+/// let rejected = RejectedRequest::new(
+///     &Request::WriteSingleRegister(0x2001, 7),
+///     Exception::IllegalDataAddress,
+/// );
+/// assert_eq!(
+///     rejected.to_string(),
+///     "WriteSingleRegister@0x2001 rejected: IllegalDataAddress"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedRequest {
+    pub request: RequestSummary,
+    pub exception: Exception,
+}
+
+impl RejectedRequest {
+    /// Attach the context of `request` to `exception`.
+    #[must_use]
+    pub fn new(request: &Request<'_>, exception: Exception) -> Self {
+        Self {
+            request: RequestSummary::from(request),
+            exception,
+        }
+    }
+}
+
+impl fmt::Display for RejectedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} rejected: {:?}", self.request, self.exception)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RejectedRequest {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{} rejected: {}", self.request, self.exception);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn summary_of_read_requests() {
+        let summary = RequestSummary::from(&Request::ReadHoldingRegisters(0x100, 10));
+        assert_eq!(summary.address, Some(0x100));
+        assert_eq!(summary.quantity, Some(10));
+        assert_eq!(
+            RejectedRequest::new(
+                &Request::ReadHoldingRegisters(0x100, 10),
+                Exception::IllegalDataValue
+            )
+            .to_string(),
+            "ReadHoldingRegisters@0x0100[10] rejected: IllegalDataValue"
+        );
+    }
+
+    #[test]
+    fn summary_without_address() {
+        let summary = RequestSummary::from(&Request::Custom(RawPdu {
+            function: FunctionCode::Custom(0x42),
+            data: &[],
+        }));
+        assert_eq!(summary.to_string(), "Custom(66)");
+    }
+}
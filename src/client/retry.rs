@@ -0,0 +1,131 @@
+/// A duration in ticks of the application's clock.
+///
+/// The resolution is up to the application, e.g. milliseconds
+/// or the period of a timer interrupt.
+pub type Ticks = u32;
+
+/// What to do after a request has timed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RetryDecision {
+    /// Encode and send the request again.
+    ResendNow,
+    /// Wait for the given number of ticks before resending the request.
+    Wait(Ticks),
+    /// All attempts are exhausted.
+    GiveUp,
+}
+
+/// How often and when a timed out request is sent again.
+///
+/// The backoff starts with the initial number of ticks and doubles
+/// with every further retry until it reaches the maximum.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::client::{RetryDecision, RetryPolicy};
+///
+/// let policy = RetryPolicy::new(3).with_backoff(100, 150);
+/// assert_eq!(policy.decide(1), RetryDecision::Wait(100));
+/// assert_eq!(policy.decide(2), RetryDecision::Wait(150));
+/// assert_eq!(policy.decide(3), RetryDecision::GiveUp);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    max_attempts: u8,
+    initial_backoff: Ticks,
+    max_backoff: Ticks,
+}
+
+impl RetryPolicy {
+    /// Send a request at most `max_attempts` times without waiting in between.
+    #[must_use]
+    pub const fn new(max_attempts: u8) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff: 0,
+            max_backoff: 0,
+        }
+    }
+
+    /// Wait before resending a request.
+    #[must_use]
+    pub const fn with_backoff(self, initial: Ticks, max: Ticks) -> Self {
+        Self {
+            initial_backoff: initial,
+            max_backoff: max,
+            ..self
+        }
+    }
+
+    /// The maximum number of times a request is sent.
+    #[must_use]
+    pub const fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+
+    /// The number of ticks to wait after the `attempts`-th attempt failed.
+    #[must_use]
+    pub const fn backoff(&self, attempts: u8) -> Ticks {
+        let shift = attempts.saturating_sub(1) as u32;
+        let backoff = if shift < Ticks::BITS {
+            self.initial_backoff.saturating_mul(1 << shift)
+        } else {
+            Ticks::MAX
+        };
+        if backoff > self.max_backoff {
+            self.max_backoff
+        } else {
+            backoff
+        }
+    }
+
+    /// Decide what to do after `attempts` attempts have timed out.
+    #[must_use]
+    pub const fn decide(&self, attempts: u8) -> RetryDecision {
+        if attempts >= self.max_attempts {
+            return RetryDecision::GiveUp;
+        }
+        match self.backoff(attempts) {
+            0 => RetryDecision::ResendNow,
+            ticks => RetryDecision::Wait(ticks),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Send a request only once.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_retries_by_default() {
+        assert_eq!(RetryPolicy::default().decide(1), RetryDecision::GiveUp);
+    }
+
+    #[test]
+    fn resend_without_backoff() {
+        let policy = RetryPolicy::new(2);
+        assert_eq!(policy.decide(1), RetryDecision::ResendNow);
+        assert_eq!(policy.decide(2), RetryDecision::GiveUp);
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy::new(255).with_backoff(10, 1000);
+        assert_eq!(policy.backoff(1), 10);
+        assert_eq!(policy.backoff(2), 20);
+        assert_eq!(policy.backoff(3), 40);
+        assert_eq!(policy.backoff(7), 640);
+        assert_eq!(policy.backoff(8), 1000);
+        assert_eq!(policy.backoff(200), 1000);
+    }
+}
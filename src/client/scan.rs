@@ -0,0 +1,189 @@
+use super::*;
+use core::ops::RangeInclusive;
+
+/// The request that is sent to every TCP unit.
+const TCP_PROBE: Request<'static> = Request::ReadHoldingRegisters(0, 1);
+
+/// The result of probing a single slave or unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProbeResult {
+    /// A device answered the probe.
+    Present,
+    /// A device answered the probe with an exception,
+    /// e.g. because it does not support the probe request.
+    Rejected(Exception),
+    /// No device answered in time.
+    Absent,
+    /// The reply could not be decoded or belongs to another device,
+    /// e.g. due to a collision on the bus.
+    Garbled(TransactionError),
+}
+
+impl ProbeResult {
+    /// Classify the reply of a probe.
+    ///
+    /// `None` means that the probe has timed out.
+    #[must_use]
+    pub const fn classify(reply: Option<Result<ResponsePdu<'_>, TransactionError>>) -> Self {
+        match reply {
+            Some(Ok(ResponsePdu(Ok(_)))) => Self::Present,
+            Some(Ok(ResponsePdu(Err(ExceptionResponse { exception, .. })))) => {
+                Self::Rejected(exception)
+            }
+            Some(Err(err)) => Self::Garbled(err),
+            None => Self::Absent,
+        }
+    }
+
+    /// A device answered the probe.
+    #[must_use]
+    pub const fn is_present(&self) -> bool {
+        matches!(self, Self::Present | Self::Rejected(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Transport {
+    #[cfg(feature = "rtu")]
+    Rtu,
+    Tcp(tcp::client::TransactionIdGen),
+}
+
+/// Generator of the probe transactions of a bus scan.
+///
+/// RTU slaves are probed with a `ReportServerId` request and TCP units
+/// with reading a single holding register. The application runs each
+/// transaction and passes the outcome to [`ProbeResult::classify`].
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::client::{BusScan, ProbeResult};
+///
+/// let buf = &mut [0; 256];
+/// for mut probe in BusScan::tcp(1..=3) {
+///     let len = probe.encode(buf).unwrap();
+///     // ... send `&buf[..len]` and wait for the reply
+///     let reply = None; // timed out
+///     if ProbeResult::classify(reply).is_present() {
+///         println!("Found unit {:?}", probe.header());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusScan {
+    ids: RangeInclusive<u8>,
+    transport: Transport,
+}
+
+impl BusScan {
+    /// Scan the RTU `slaves`.
+    ///
+    /// The broadcast address `0` is skipped because it is never answered.
+    #[cfg(feature = "rtu")]
+    #[must_use]
+    pub fn rtu(slaves: RangeInclusive<rtu::SlaveId>) -> Self {
+        let (first, last) = slaves.into_inner();
+        Self {
+            ids: first.max(1)..=last,
+            transport: Transport::Rtu,
+        }
+    }
+
+    /// Scan the TCP `units`.
+    #[must_use]
+    pub const fn tcp(units: RangeInclusive<tcp::UnitId>) -> Self {
+        Self {
+            ids: units,
+            transport: Transport::Tcp(tcp::client::TransactionIdGen::new()),
+        }
+    }
+}
+
+impl Iterator for BusScan {
+    type Item = ClientTransaction<'static>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.ids.next()?;
+        let transaction = match &mut self.transport {
+            #[cfg(feature = "rtu")]
+            Transport::Rtu => {
+                ClientTransaction::new(rtu::Header { slave: id }, Request::ReportServerId)
+            }
+            Transport::Tcp(ids) => {
+                let hdr = tcp::Header {
+                    transaction_id: ids.next_id(),
+                    unit_id: id,
+                };
+                ClientTransaction::new(hdr, TCP_PROBE)
+            }
+        };
+        Some(transaction)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "rtu")]
+    fn skip_rtu_broadcast() {
+        let mut scan = BusScan::rtu(0..=2);
+        let probe = scan.next().unwrap();
+        assert_eq!(probe.header(), Header::Rtu(rtu::Header { slave: 1 }));
+        assert_eq!(probe.request(), Request::ReportServerId);
+        assert_eq!(scan.count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "rtu")]
+    fn rtu_probe() {
+        let mut probe = BusScan::rtu(0x12..=0x12).next().unwrap();
+        let buf = &mut [0; 8];
+        let len = probe.encode(buf).unwrap();
+        assert_eq!(&buf[..len], &[0x12, 0x11, 0xCD, 0x1C]);
+        let rsp = &[0x12, 0x91, 0x01, 0x7D, 0x95];
+        let reply = probe.decode(rsp).transpose();
+        assert_eq!(
+            ProbeResult::classify(reply),
+            ProbeResult::Rejected(Exception::IllegalFunction)
+        );
+    }
+
+    #[test]
+    fn tcp_probes() {
+        let mut scan = BusScan::tcp(5..=6);
+        let first = scan.next().unwrap();
+        let second = scan.next().unwrap();
+        assert_eq!(scan.next(), None);
+        assert_eq!(
+            second.header(),
+            Header::Tcp(tcp::Header {
+                transaction_id: 1,
+                unit_id: 6
+            })
+        );
+        assert_eq!(first.request(), TCP_PROBE);
+    }
+
+    #[test]
+    fn classify_replies() {
+        assert_eq!(ProbeResult::classify(None), ProbeResult::Absent);
+        let rsp = ResponsePdu(Ok(Response::ReadHoldingRegisters(Data {
+            data: &[0, 0],
+            quantity: 1,
+        })));
+        assert!(ProbeResult::classify(Some(Ok(rsp))).is_present());
+        let err = TransactionError::Decode(DecodeError::Crc(0, 1));
+        assert_eq!(
+            ProbeResult::classify(Some(Err(err))),
+            ProbeResult::Garbled(err)
+        );
+    }
+}
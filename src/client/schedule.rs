@@ -0,0 +1,254 @@
+use super::*;
+use core::cmp::Reverse;
+
+/// A request that is sent periodically, see [`PollScheduler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PollItem<'r> {
+    pub hdr: Header,
+    pub request: Request<'r>,
+    /// The number of ticks between two polls.
+    pub interval: Ticks,
+    /// Items with a higher priority are sent first if several are due.
+    pub priority: u8,
+}
+
+impl<'r> PollItem<'r> {
+    #[must_use]
+    pub fn new(hdr: impl Into<Header>, request: Request<'r>, interval: Ticks) -> Self {
+        Self {
+            hdr: hdr.into(),
+            request,
+            interval,
+            priority: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Create the transaction that polls the item.
+    #[must_use]
+    pub fn transaction(&self) -> ClientTransaction<'r> {
+        ClientTransaction::new(self.hdr, self.request)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Entry<'r> {
+    item: PollItem<'r>,
+    /// The tick at which the item is due or
+    /// `None` if it has never been polled.
+    due: Option<Ticks>,
+}
+
+/// Decides which of up to `N` periodic requests is due.
+///
+/// The scheduler does not perform any I/O and has no clock.
+/// The application passes the current tick of its clock, which
+/// may wrap around, and runs the returned request, e.g. with a
+/// [`ClientTransaction`], before asking for the next one.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     client::{PollItem, PollScheduler},
+///     rtu, Request,
+/// };
+///
+/// let hdr = rtu::Header { slave: 1 };
+/// let mut scheduler = PollScheduler::<4>::new();
+/// let fast = scheduler
+///     .add(PollItem::new(hdr, Request::ReadInputRegisters(0, 2), 100).with_priority(1))
+///     .unwrap();
+/// let slow = scheduler
+///     .add(PollItem::new(hdr, Request::ReadHoldingRegisters(0x10, 8), 1000))
+///     .unwrap();
+///
+/// // Everything is due initially, the higher priority first
+/// assert_eq!(scheduler.next_due(0).unwrap().0, fast);
+/// assert_eq!(scheduler.next_due(0).unwrap().0, slow);
+/// assert!(scheduler.next_due(50).is_none());
+/// assert_eq!(scheduler.ticks_until_due(50), Some(50));
+///
+/// let (_, item) = scheduler.next_due(100).unwrap();
+/// let mut transaction = item.transaction();
+/// // ... encode, send and decode the transaction
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PollScheduler<'r, const N: usize> {
+    entries: [Option<Entry<'r>>; N],
+}
+
+impl<const N: usize> Default for PollScheduler<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'r, const N: usize> PollScheduler<'r, N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    /// Add `item`, which is due immediately, and return its index.
+    ///
+    /// Fails with [`Error::BufferSize`] if the table is full.
+    pub fn add(&mut self, item: PollItem<'r>) -> Result<usize, Error> {
+        let (idx, entry) = self
+            .entries
+            .iter_mut()
+            .enumerate()
+            .find(|(_, entry)| entry.is_none())
+            .ok_or(Error::BufferSize)?;
+        *entry = Some(Entry { item, due: None });
+        Ok(idx)
+    }
+
+    /// Remove the item at `idx`.
+    pub fn remove(&mut self, idx: usize) -> Option<PollItem<'r>> {
+        self.entries.get_mut(idx)?.take().map(|entry| entry.item)
+    }
+
+    /// The item at `idx`.
+    #[must_use]
+    pub fn get(&self, idx: usize) -> Option<&PollItem<'r>> {
+        self.entries.get(idx)?.as_ref().map(|entry| &entry.item)
+    }
+
+    /// Poll all items again, starting with the next call of [`Self::next_due`].
+    pub fn reset(&mut self) {
+        for entry in self.entries.iter_mut().flatten() {
+            entry.due = None;
+        }
+    }
+
+    /// Return the next item that is due at the tick `now` together
+    /// with its index and schedule its next poll.
+    ///
+    /// Of several due items the one with the highest priority is
+    /// returned, then the one that is overdue the longest.
+    pub fn next_due(&mut self, now: Ticks) -> Option<(usize, PollItem<'r>)> {
+        let (idx, _, _) = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                let entry = entry.as_ref()?;
+                Some((idx, entry.item.priority, overdue(entry.due, now)?))
+            })
+            .min_by_key(|&(_, priority, overdue)| (Reverse(priority), Reverse(overdue)))?;
+        let entry = self.entries[idx].as_mut()?;
+        let interval = entry.item.interval;
+        // Keep the cadence unless the item has missed a whole interval
+        let due = match entry.due {
+            Some(due) if now.wrapping_sub(due) < interval => due.wrapping_add(interval),
+            _ => now.wrapping_add(interval),
+        };
+        entry.due = Some(due);
+        Some((idx, entry.item))
+    }
+
+    /// The number of ticks until the next item is due at the tick `now`.
+    ///
+    /// Returns `Some(0)` if an item is already due and `None`
+    /// if the table is empty.
+    #[must_use]
+    pub fn ticks_until_due(&self, now: Ticks) -> Option<Ticks> {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|entry| match entry.due {
+                Some(due) if overdue(Some(due), now).is_none() => due.wrapping_sub(now),
+                _ => 0,
+            })
+            .min()
+    }
+}
+
+/// The number of ticks that `due` has passed at `now` or
+/// `None` if it is still in the future.
+///
+/// Ticks in the past and in the future are told apart
+/// by the half of the range of the wrapping clock.
+const fn overdue(due: Option<Ticks>, now: Ticks) -> Option<Ticks> {
+    let Some(due) = due else {
+        return Some(Ticks::MAX);
+    };
+    let elapsed = now.wrapping_sub(due);
+    if elapsed <= Ticks::MAX / 2 {
+        Some(elapsed)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDR: tcp::Header = tcp::Header {
+        transaction_id: 0,
+        unit_id: 1,
+    };
+
+    #[test]
+    fn keep_the_cadence() {
+        let mut scheduler = PollScheduler::<1>::new();
+        let item = PollItem::new(HDR, Request::ReadHoldingRegisters(0, 1), 10);
+        assert_eq!(scheduler.add(item), Ok(0));
+        assert_eq!(scheduler.add(item), Err(Error::BufferSize));
+
+        assert_eq!(scheduler.next_due(3), Some((0, item)));
+        assert_eq!(scheduler.next_due(12), None);
+        // 2 ticks late
+        assert!(scheduler.next_due(15).is_some());
+        assert_eq!(scheduler.ticks_until_due(15), Some(8));
+        // More than an interval late
+        assert!(scheduler.next_due(40).is_some());
+        assert_eq!(scheduler.ticks_until_due(40), Some(10));
+
+        scheduler.reset();
+        assert_eq!(scheduler.ticks_until_due(41), Some(0));
+        assert_eq!(scheduler.remove(0), Some(item));
+        assert_eq!(scheduler.ticks_until_due(41), None);
+    }
+
+    #[test]
+    fn prefer_high_priorities_and_long_waits() {
+        let mut scheduler = PollScheduler::<3>::new();
+        let low = PollItem::new(HDR, Request::ReadHoldingRegisters(0, 1), 10);
+        let high = PollItem::new(HDR, Request::ReadInputRegisters(0, 1), 30).with_priority(5);
+        assert_eq!(scheduler.add(low), Ok(0));
+        assert_eq!(scheduler.add(low), Ok(1));
+        assert_eq!(scheduler.add(high), Ok(2));
+
+        assert_eq!(scheduler.next_due(0).unwrap().0, 2);
+        assert_eq!(scheduler.next_due(0).unwrap().0, 0);
+        assert_eq!(scheduler.next_due(5).unwrap().0, 1);
+        assert_eq!(scheduler.next_due(5), None);
+        // Both low items are due, the first one has waited longer
+        assert_eq!(scheduler.next_due(30).unwrap().0, 2);
+        assert_eq!(scheduler.next_due(30).unwrap().0, 0);
+        assert_eq!(scheduler.next_due(30).unwrap().0, 1);
+        assert_eq!(scheduler.get(1), Some(&low));
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut scheduler = PollScheduler::<1>::new();
+        let item = PollItem::new(HDR, Request::ReadHoldingRegisters(0, 1), 10);
+        scheduler.add(item).unwrap();
+        assert!(scheduler.next_due(Ticks::MAX - 5).is_some());
+        assert_eq!(scheduler.next_due(Ticks::MAX), None);
+        assert_eq!(scheduler.ticks_until_due(Ticks::MAX), Some(5));
+        assert!(scheduler.next_due(4).is_some());
+    }
+}
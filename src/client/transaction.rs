@@ -0,0 +1,549 @@
+use super::*;
+
+/// The state of a [`ClientTransaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransactionState {
+    /// The request has not been sent yet.
+    Ready,
+    /// The request has been sent and the response is pending.
+    AwaitingResponse,
+    /// The matching response has been received.
+    Completed,
+    /// No response has been received in time.
+    TimedOut,
+}
+
+/// Errors of a [`ClientTransaction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransactionError {
+    /// The received bytes could not be decoded.
+    Decode(DecodeError),
+    /// The response belongs to a different function.
+    FunctionCode {
+        expected: FunctionCode,
+        received: FunctionCode,
+    },
+    /// The response has been sent by a different RTU slave.
+    SlaveId {
+        expected: rtu::SlaveId,
+        received: rtu::SlaveId,
+    },
+    /// The response has been sent by a different TCP unit.
+    UnitId {
+        expected: tcp::UnitId,
+        received: tcp::UnitId,
+    },
+    /// The response belongs to a different TCP transaction.
+    TransactionId {
+        expected: tcp::TransactionId,
+        received: tcp::TransactionId,
+    },
+    /// The response does not match the request (see [`Response::validate_against`]).
+    Mismatch(MismatchError),
+    /// The server answered with an exception.
+    Exception(RejectedRequest),
+    /// No response is expected in the current state.
+    State(TransactionState),
+    /// The TCP response has already been received before.
+    Duplicate(tcp::TransactionId),
+    /// The TCP response belongs to a transaction that has timed out
+    /// or to an earlier one.
+    Late(tcp::TransactionId),
+}
+
+impl TransactionError {
+    /// The response is a stale one that has to be dropped.
+    ///
+    /// A stale response does not indicate a broken connection
+    /// and the transaction keeps waiting for its response.
+    #[must_use]
+    pub const fn is_stale(&self) -> bool {
+        matches!(self, Self::Duplicate(_) | Self::Late(_))
+    }
+}
+
+impl From<DecodeError> for TransactionError {
+    fn from(err: DecodeError) -> Self {
+        Self::Decode(err)
+    }
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "Invalid response: {err}"),
+            Self::FunctionCode { expected, received } => write!(
+                f,
+                "Function code mismatch: expected = 0x{:0>2X}, received = 0x{:0>2X}",
+                expected.value(),
+                received.value()
+            ),
+            Self::SlaveId { expected, received } => write!(
+                f,
+                "Slave ID mismatch: expected = {expected}, received = {received}"
+            ),
+            Self::UnitId { expected, received } => write!(
+                f,
+                "Unit ID mismatch: expected = {expected}, received = {received}"
+            ),
+            Self::TransactionId { expected, received } => write!(
+                f,
+                "Transaction ID mismatch: expected = {expected}, received = {received}"
+            ),
+            Self::Mismatch(err) => write!(f, "{err}"),
+            Self::Exception(err) => write!(f, "{err}"),
+            Self::State(state) => write!(f, "Unexpected response in state {state:?}"),
+            Self::Duplicate(id) => write!(f, "Duplicate response: transaction ID = {id}"),
+            Self::Late(id) => write!(f, "Late response: transaction ID = {id}"),
+        }
+    }
+}
+
+/// A single request/response cycle of a client.
+///
+/// The transaction encodes the request and checks that the received
+/// response actually belongs to it. A mismatching response is rejected
+/// with an error but the transaction keeps waiting for the matching one.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{client::ClientTransaction, rtu::Header, Request};
+///
+/// let mut transaction =
+///     ClientTransaction::new(Header { slave: 0x12 }, Request::ReadHoldingRegisters(0x100, 2));
+/// let buf = &mut [0; 256];
+/// let len = transaction.encode(buf).unwrap();
+/// assert_eq!(len, 8);
+/// // ... send `&buf[..len]` and feed the received bytes into `transaction.decode()`.
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClientTransaction<'r> {
+    hdr: Header,
+    request: Request<'r>,
+    state: TransactionState,
+    retry: RetryPolicy,
+    attempts: u8,
+    backoff: Ticks,
+}
+
+impl<'r> ClientTransaction<'r> {
+    /// Create a transaction for `request`.
+    #[must_use]
+    pub fn new(hdr: impl Into<Header>, request: Request<'r>) -> Self {
+        Self {
+            hdr: hdr.into(),
+            request,
+            state: TransactionState::Ready,
+            retry: RetryPolicy::default(),
+            attempts: 0,
+            backoff: 0,
+        }
+    }
+
+    /// Create a transaction that broadcasts `request` to all RTU slaves.
+    ///
+    /// The transaction is completed as soon as the request is encoded.
+    #[must_use]
+    pub fn broadcast(request: rtu::BroadcastRequest<'r>) -> Self {
+        let hdr = rtu::Header {
+            slave: rtu::BROADCAST,
+        };
+        Self::new(hdr, request.request())
+    }
+
+    /// Resend the request after a timeout according to `retry`.
+    #[must_use]
+    pub const fn with_retry_policy(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// The header of the request.
+    #[must_use]
+    pub const fn header(&self) -> Header {
+        self.hdr
+    }
+
+    /// The request of this transaction.
+    #[must_use]
+    pub const fn request(&self) -> Request<'r> {
+        self.request
+    }
+
+    /// The current state.
+    #[must_use]
+    pub const fn state(&self) -> TransactionState {
+        self.state
+    }
+
+    /// How often the request has been encoded.
+    #[must_use]
+    pub const fn attempts(&self) -> u8 {
+        self.attempts
+    }
+
+    /// Encode the request ADU that has to be sent.
+    ///
+    /// The request may be encoded again after a timeout to resend it.
+    /// RTU broadcast requests (slave `0`) are never answered and
+    /// complete the transaction immediately.
+    pub fn encode(&mut self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let pdu = RequestPdu(self.request);
+        let len = match self.hdr {
+            Header::Rtu(hdr) => rtu::client::encode_request(rtu::RequestAdu { hdr, pdu }, buf)?,
+            Header::Tcp(hdr) => tcp::client::encode_request(tcp::RequestAdu { hdr, pdu }, buf)?,
+        };
+        self.attempts = self.attempts.saturating_add(1);
+        self.state = match self.hdr {
+            Header::Rtu(rtu::Header {
+                slave: rtu::BROADCAST,
+            }) => TransactionState::Completed,
+            _ => TransactionState::AwaitingResponse,
+        };
+        Ok(len)
+    }
+
+    /// Decode the received bytes.
+    ///
+    /// Returns `None` if the response is incomplete.
+    /// On success the transaction is completed.
+    ///
+    /// TCP responses that arrive after the transaction has been completed
+    /// or timed out are rejected as [`TransactionError::Duplicate`] or
+    /// [`TransactionError::Late`] (see [`TransactionError::is_stale`]).
+    /// Assuming ascending transaction IDs, e.g. from a
+    /// [`TransactionIdGen`](tcp::client::TransactionIdGen), responses
+    /// to earlier transactions are rejected as late, too.
+    pub fn decode<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> Result<Option<ResponsePdu<'b>>, TransactionError> {
+        match (self.state, self.hdr) {
+            (TransactionState::AwaitingResponse, _)
+            | (TransactionState::Completed | TransactionState::TimedOut, Header::Tcp(_)) => {}
+            (state, _) => return Err(TransactionError::State(state)),
+        }
+        let pdu = match self.hdr {
+            Header::Rtu(expected) => {
+                let Some(rtu::ResponseAdu { hdr, pdu }) = rtu::client::decode_response(buf)? else {
+                    return Ok(None);
+                };
+                if hdr.slave != expected.slave {
+                    return Err(TransactionError::SlaveId {
+                        expected: expected.slave,
+                        received: hdr.slave,
+                    });
+                }
+                pdu
+            }
+            Header::Tcp(expected) => {
+                let Some(tcp::ResponseAdu { hdr, pdu }) = tcp::client::decode_response(buf)? else {
+                    return Ok(None);
+                };
+                self.check_transaction_id(expected.transaction_id, hdr.transaction_id)?;
+                if hdr.unit_id != expected.unit_id {
+                    return Err(TransactionError::UnitId {
+                        expected: expected.unit_id,
+                        received: hdr.unit_id,
+                    });
+                }
+                pdu
+            }
+        };
+        let expected = FunctionCode::from(self.request);
+        let received = match pdu {
+            ResponsePdu(Ok(rsp)) => FunctionCode::from(rsp),
+            ResponsePdu(Err(ExceptionResponse { function, .. })) => function,
+        };
+        if received != expected {
+            return Err(TransactionError::FunctionCode { expected, received });
+        }
+        let pdu = match pdu {
+            ResponsePdu(Ok(rsp)) => {
+                rsp.validate_against(&self.request)
+                    .map_err(TransactionError::Mismatch)?;
+                ResponsePdu(Ok(rsp.with_request_quantity(&self.request)?))
+            }
+            pdu => pdu,
+        };
+        self.state = TransactionState::Completed;
+        Ok(Some(pdu))
+    }
+
+    /// Check that a TCP response answers the pending request.
+    const fn check_transaction_id(
+        &self,
+        expected: tcp::TransactionId,
+        received: tcp::TransactionId,
+    ) -> Result<(), TransactionError> {
+        // IDs that are less than half the range behind are earlier ones
+        let earlier = matches!(expected.wrapping_sub(received), 1..=0x7FFF);
+        match self.state {
+            TransactionState::Completed if received == expected => {
+                Err(TransactionError::Duplicate(received))
+            }
+            TransactionState::TimedOut if received == expected => {
+                Err(TransactionError::Late(received))
+            }
+            _ if earlier => Err(TransactionError::Late(received)),
+            TransactionState::AwaitingResponse if received == expected => Ok(()),
+            TransactionState::AwaitingResponse => {
+                Err(TransactionError::TransactionId { expected, received })
+            }
+            state => Err(TransactionError::State(state)),
+        }
+    }
+
+    /// Signal that no response has been received in time.
+    ///
+    /// Returns what to do next according to the [`RetryPolicy`]
+    /// or `None` if no response is awaited.
+    pub fn timeout(&mut self) -> Option<RetryDecision> {
+        if self.state != TransactionState::AwaitingResponse {
+            return None;
+        }
+        self.state = TransactionState::TimedOut;
+        self.backoff = self.retry.backoff(self.attempts);
+        Some(self.retry.decide(self.attempts))
+    }
+
+    /// Let `elapsed` ticks pass after a timeout.
+    ///
+    /// Returns [`RetryDecision::ResendNow`] once the backoff is over
+    /// or `None` if the transaction has not timed out.
+    pub fn poll(&mut self, elapsed: Ticks) -> Option<RetryDecision> {
+        if self.state != TransactionState::TimedOut {
+            return None;
+        }
+        if self.attempts >= self.retry.max_attempts() {
+            return Some(RetryDecision::GiveUp);
+        }
+        self.backoff = self.backoff.saturating_sub(elapsed);
+        let decision = match self.backoff {
+            0 => RetryDecision::ResendNow,
+            ticks => RetryDecision::Wait(ticks),
+        };
+        Some(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtu_transaction() {
+        let mut transaction = ClientTransaction::new(
+            rtu::Header { slave: 0x12 },
+            Request::WriteSingleRegister(0x2222, 0xABCD),
+        );
+        assert_eq!(transaction.state(), TransactionState::Ready);
+        assert_eq!(
+            transaction.decode(&[]).err(),
+            Some(TransactionError::State(TransactionState::Ready))
+        );
+
+        let buf = &mut [0; 16];
+        let len = transaction.encode(buf).unwrap();
+        assert_eq!(
+            &buf[..len],
+            &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]
+        );
+        assert_eq!(transaction.state(), TransactionState::AwaitingResponse);
+
+        assert_eq!(transaction.decode(&buf[..4]).unwrap(), None);
+        let rsp = transaction.decode(&buf[..len]).unwrap();
+        assert_eq!(
+            rsp,
+            Some(ResponsePdu(Ok(Response::WriteSingleRegister(
+                0x2222, 0xABCD
+            ))))
+        );
+        assert_eq!(transaction.state(), TransactionState::Completed);
+    }
+
+    #[test]
+    fn rtu_wrong_slave() {
+        let mut transaction = ClientTransaction::new(
+            rtu::Header { slave: 0x11 },
+            Request::WriteSingleRegister(0x2222, 0xABCD),
+        );
+        transaction.encode(&mut [0; 16]).unwrap();
+        let rsp = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::SlaveId {
+                expected: 0x11,
+                received: 0x12
+            })
+        );
+        assert_eq!(transaction.state(), TransactionState::AwaitingResponse);
+    }
+
+    #[test]
+    fn rtu_broadcast() {
+        let mut transaction =
+            ClientTransaction::new(rtu::Header { slave: 0 }, Request::WriteSingleRegister(0, 1));
+        transaction.encode(&mut [0; 16]).unwrap();
+        assert_eq!(transaction.state(), TransactionState::Completed);
+
+        let request = Request::WriteSingleCoil(0, true).try_into().unwrap();
+        let mut broadcast = ClientTransaction::broadcast(request);
+        assert_eq!(broadcast.state(), TransactionState::Ready);
+        broadcast.encode(&mut [0; 16]).unwrap();
+        assert_eq!(broadcast.state(), TransactionState::Completed);
+    }
+
+    fn tcp_transaction(request: Request<'_>) -> ClientTransaction<'_> {
+        let hdr = tcp::Header {
+            transaction_id: 7,
+            unit_id: 1,
+        };
+        let mut transaction = ClientTransaction::new(hdr, request);
+        transaction.encode(&mut [0; 32]).unwrap();
+        transaction
+    }
+
+    #[test]
+    fn tcp_exception_response() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 1));
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).unwrap(),
+            Some(ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            })))
+        );
+    }
+
+    #[test]
+    fn tcp_mismatch() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 1));
+        let rsp = &[0x00, 0x08, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::TransactionId {
+                expected: 7,
+                received: 8
+            })
+        );
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x02, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::UnitId {
+                expected: 1,
+                received: 2
+            })
+        );
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x84, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::FunctionCode {
+                expected: FunctionCode::ReadHoldingRegisters,
+                received: FunctionCode::ReadInputRegisters,
+            })
+        );
+        assert_eq!(transaction.state(), TransactionState::AwaitingResponse);
+    }
+
+    #[test]
+    fn tcp_byte_count_mismatch() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 2));
+        let rsp = &[
+            0x00, 0x07, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34,
+        ];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::Mismatch(MismatchError::ByteCount {
+                expected: 4,
+                received: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn tcp_exact_coil_count() {
+        let mut transaction = tcp_transaction(Request::ReadCoils(0, 3));
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x04, 0x01, 0x01, 0x01, 0xFF];
+        let Some(ResponsePdu(Ok(Response::ReadCoils(coils)))) = transaction.decode(rsp).unwrap()
+        else {
+            panic!("unexpected response");
+        };
+        assert_eq!(coils.len(), 3);
+    }
+
+    #[test]
+    fn retry_after_backoff() {
+        let mut transaction = ClientTransaction::new(
+            rtu::Header { slave: 0x12 },
+            Request::ReadHoldingRegisters(0, 1),
+        )
+        .with_retry_policy(RetryPolicy::new(2).with_backoff(10, 10));
+        assert_eq!(transaction.timeout(), None);
+        assert_eq!(transaction.poll(1), None);
+
+        let buf = &mut [0; 16];
+        transaction.encode(buf).unwrap();
+        assert_eq!(transaction.timeout(), Some(RetryDecision::Wait(10)));
+        assert_eq!(transaction.poll(4), Some(RetryDecision::Wait(6)));
+        assert_eq!(transaction.poll(6), Some(RetryDecision::ResendNow));
+
+        transaction.encode(buf).unwrap();
+        assert_eq!(transaction.attempts(), 2);
+        assert_eq!(transaction.poll(1), None);
+        assert_eq!(transaction.timeout(), Some(RetryDecision::GiveUp));
+        assert_eq!(transaction.poll(100), Some(RetryDecision::GiveUp));
+    }
+
+    #[test]
+    fn tcp_timeout() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 1));
+        transaction.timeout();
+        assert_eq!(transaction.state(), TransactionState::TimedOut);
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::Late(7))
+        );
+        transaction.encode(&mut [0; 32]).unwrap();
+        assert!(transaction.decode(rsp).unwrap().is_some());
+    }
+
+    #[test]
+    fn tcp_duplicate_response() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 1));
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert!(transaction.decode(rsp).unwrap().is_some());
+        let err = transaction.decode(rsp).unwrap_err();
+        assert_eq!(err, TransactionError::Duplicate(7));
+        assert!(err.is_stale());
+        // Responses that are not stale are still rejected
+        let rsp = &[0x00, 0x09, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::State(TransactionState::Completed))
+        );
+    }
+
+    #[test]
+    fn tcp_response_of_an_earlier_transaction() {
+        let mut transaction = tcp_transaction(Request::ReadHoldingRegisters(0, 1));
+        let rsp = &[0x00, 0x06, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::Late(6))
+        );
+        let rsp = &[0xFF, 0xFF, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert_eq!(
+            transaction.decode(rsp).err(),
+            Some(TransactionError::Late(0xFFFF))
+        );
+        assert_eq!(transaction.state(), TransactionState::AwaitingResponse);
+        let rsp = &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        assert!(transaction.decode(rsp).unwrap().is_some());
+    }
+}
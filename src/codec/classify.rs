@@ -0,0 +1,183 @@
+use super::*;
+use core::cmp::Ordering;
+
+/// The direction that a PDU has been attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PduKind {
+    Request,
+    Response,
+    /// The PDU is a valid request as well as a valid response,
+    /// e.g. the echo of `WriteSingleRegister`.
+    Ambiguous,
+}
+
+/// How reliable a [`Classification`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Confidence {
+    /// Nothing rules out the other direction.
+    Low,
+    /// The PDU fits both layouts, but violates the specification as the other direction.
+    Medium,
+    /// The PDU only fits the layout of one direction.
+    High,
+}
+
+/// The outcome of [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Classification {
+    pub kind: PduKind,
+    pub confidence: Confidence,
+}
+
+/// Guess whether `pdu` has been sent by a client or a server.
+///
+/// The length of the PDU is compared with the layout of the request and
+/// the response of its function code. If it fits both, the one that passes
+/// the checks of [`DecodeOptions::strict`] wins. PDUs of unknown function
+/// codes are [ambiguous](PduKind::Ambiguous).
+///
+/// Returns `None` if the PDU neither is a request nor a response,
+/// e.g. because it has been truncated.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{classify, Confidence, PduKind};
+///
+/// // Read 1 holding register
+/// let guess = classify(&[0x03, 0x00, 0x10, 0x00, 0x01]).unwrap();
+/// assert_eq!(guess.kind, PduKind::Request);
+/// assert_eq!(guess.confidence, Confidence::High);
+///
+/// // Write single register or its echo
+/// let guess = classify(&[0x06, 0x00, 0x10, 0x12, 0x34]).unwrap();
+/// assert_eq!(guess.kind, PduKind::Ambiguous);
+/// ```
+#[must_use]
+pub fn classify(pdu: &[u8]) -> Option<Classification> {
+    pdu.first()?;
+    let request_len = request_len(pdu);
+    let response_len = response_len(pdu);
+    if request_len.is_none() && response_len.is_none() {
+        return Some(Classification {
+            kind: PduKind::Ambiguous,
+            confidence: Confidence::Low,
+        });
+    }
+
+    let strict = DecodeOptions::strict();
+    let request = match request_len {
+        Some(len) if len == pdu.len() => {
+            1 + u8::from(Request::try_from_with_options(pdu, &strict).is_ok())
+        }
+        _ => 0,
+    };
+    let response = match response_len {
+        Some(len) if len == pdu.len() => {
+            1 + u8::from(ResponsePdu::try_from_with_options(pdu, &strict).is_ok())
+        }
+        _ => 0,
+    };
+    let (kind, loser) = match request.cmp(&response) {
+        Ordering::Greater => (PduKind::Request, response),
+        Ordering::Less => (PduKind::Response, request),
+        Ordering::Equal if request == 0 => return None,
+        Ordering::Equal => {
+            return Some(Classification {
+                kind: PduKind::Ambiguous,
+                confidence: Confidence::Low,
+            });
+        }
+    };
+    let confidence = if loser == 0 {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    };
+    Some(Classification { kind, confidence })
+}
+
+/// The length of a request PDU as announced by its fields.
+fn request_len(pdu: &[u8]) -> Option<usize> {
+    let byte_count = |offset| pdu.get(offset).map(|&count| usize::from(count));
+    match pdu[0] {
+        0x01..=0x06 | 0x08 => Some(5),
+        0x07 | 0x0B | 0x0C | 0x11 => Some(1),
+        0x0F | 0x10 => byte_count(5).map(|count| 6 + count),
+        0x14 | 0x15 => byte_count(1).map(|count| 2 + count),
+        0x16 => Some(7),
+        0x17 => byte_count(9).map(|count| 10 + count),
+        0x18 => Some(3),
+        _ => None,
+    }
+}
+
+/// The length of a response PDU as announced by its fields.
+fn response_len(pdu: &[u8]) -> Option<usize> {
+    match pdu[0] {
+        0x01..=0x04 | 0x0C | 0x11 | 0x14 | 0x15 | 0x17 => {
+            pdu.get(1).map(|&count| 2 + usize::from(count))
+        }
+        0x05 | 0x06 | 0x08 | 0x0B | 0x0F | 0x10 => Some(5),
+        0x07 | 0x80..=0xFF => Some(2),
+        0x16 => Some(7),
+        0x18 => pdu.get(1..3).map(|count| 3 + usize::from(read_u16(count))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn guess(kind: PduKind, confidence: Confidence) -> Classification {
+        Classification { kind, confidence }
+    }
+
+    #[test]
+    fn by_layout() {
+        assert_eq!(
+            classify(&[0x03, 0x04, 0x12, 0x34, 0x56, 0x78]),
+            Some(guess(PduKind::Response, Confidence::High))
+        );
+        assert_eq!(
+            classify(&[0x10, 0x00, 0x10, 0x00, 0x01, 0x02, 0x12, 0x34]),
+            Some(guess(PduKind::Request, Confidence::High))
+        );
+        assert_eq!(
+            classify(&[0x83, 0x02]),
+            Some(guess(PduKind::Response, Confidence::High))
+        );
+        assert_eq!(classify(&[0x03, 0x04, 0x12]), None);
+        assert_eq!(classify(&[]), None);
+    }
+
+    #[test]
+    fn by_plausibility() {
+        // A request for 3 registers or a response with an odd byte count
+        assert_eq!(
+            classify(&[0x03, 0x03, 0x00, 0x00, 0x03]),
+            Some(guess(PduKind::Request, Confidence::Medium))
+        );
+        // A request for 0 coils or a response with 24 coils
+        assert_eq!(
+            classify(&[0x01, 0x03, 0x00, 0x00, 0x00]),
+            Some(guess(PduKind::Response, Confidence::Medium))
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert_eq!(
+            classify(&[0x05, 0x00, 0x10, 0xFF, 0x00]),
+            Some(guess(PduKind::Ambiguous, Confidence::Low))
+        );
+        assert_eq!(
+            classify(&[0x42, 0x01, 0x02]),
+            Some(guess(PduKind::Ambiguous, Confidence::Low))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use super::*;
+
+/// How the PDU length of a function is determined.
+///
+/// Describes the layouts of vendor-specific functions (see [`CustomFunction`])
+/// as well as those of the standard function codes
+/// (see [`FunctionDescriptor::request_layout`]).
+#[derive(Debug, Clone, Copy, Eq)]
+pub enum PduLen {
+    /// A fixed number of bytes, including the function code.
+    Fixed(usize),
+    /// `base` bytes plus the value of the byte at `offset` of the PDU,
+    /// e.g. `ByteCount { offset: 1, base: 2 }` for a byte count
+    /// that directly follows the function code.
+    ByteCount { offset: usize, base: usize },
+    /// Like [`ByteCount`](Self::ByteCount), but with a big-endian
+    /// 16-bit byte count, e.g. of a `ReadFifoQueue` response.
+    ByteCountU16 { offset: usize, base: usize },
+    /// Calculated from the bytes of the PDU that have been received so far.
+    /// The callback returns `Ok(None)` as long as more bytes are needed
+    /// and fails if the PDU cannot be delimited at all.
+    Callback(fn(&[u8]) -> Result<Option<usize>>),
+}
+
+impl PduLen {
+    /// The length of `pdu`, or `None` if more bytes are needed to tell.
+    ///
+    /// Only a [`Callback`](Self::Callback) may fail.
+    pub fn pdu_len(self, pdu: &[u8]) -> Result<Option<usize>> {
+        let len = match self {
+            Self::Fixed(len) => Some(len),
+            Self::ByteCount { offset, base } => pdu.get(offset).map(|cnt| base + usize::from(*cnt)),
+            Self::ByteCountU16 { offset, base } => pdu
+                .get(offset..offset + 2)
+                .map(|cnt| base + usize::from(read_u16(cnt))),
+            Self::Callback(f) => return f(pdu),
+        };
+        Ok(len)
+    }
+}
+
+impl PartialEq for PduLen {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Fixed(a), Self::Fixed(b)) => a == b,
+            (
+                Self::ByteCount { offset, base },
+                Self::ByteCount {
+                    offset: other_offset,
+                    base: other_base,
+                },
+            )
+            | (
+                Self::ByteCountU16 { offset, base },
+                Self::ByteCountU16 {
+                    offset: other_offset,
+                    base: other_base,
+                },
+            ) => offset == other_offset && base == other_base,
+            // Callbacks are identified by their address
+            (Self::Callback(a), Self::Callback(b)) => *a as usize == *b as usize,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PduLen {
+    #[allow(clippy::match_same_arms)] // only the format strings differ
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Self::Fixed(len) => defmt::write!(f, "Fixed({})", len),
+            Self::ByteCount { offset, base } => {
+                defmt::write!(f, "ByteCount {{ offset: {}, base: {} }}", offset, base);
+            }
+            Self::ByteCountU16 { offset, base } => {
+                defmt::write!(f, "ByteCountU16 {{ offset: {}, base: {} }}", offset, base);
+            }
+            Self::Callback(_) => defmt::write!(f, "Callback"),
+        }
+    }
+}
+
+/// The frame lengths of a vendor-specific function.
+///
+/// The framing layer cannot tell where a frame with an unknown function
+/// code ends. A table of custom functions, e.g. passed to
+/// [`rtu::Decoder::with_custom_functions`], fills this gap.
+/// Exception responses of a registered function are framed as well.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu, CustomFunction, DecodeStatus, DecoderType, PduLen};
+///
+/// const CUSTOM: &[CustomFunction] = &[CustomFunction {
+///     fn_code: 0x41,
+///     request: PduLen::Fixed(3),
+///     response: PduLen::ByteCount { offset: 1, base: 2 },
+/// }];
+///
+/// let mut decoder = rtu::Decoder::new(DecoderType::Request).with_custom_functions(CUSTOM);
+/// let rx = [0x12, 0x41, 0xAB, 0xCD, 0xEB, 0xED];
+/// let DecodeStatus::Complete((frame, _)) = decoder.decode_status(&rx).unwrap() else {
+///     unreachable!();
+/// };
+/// assert_eq!(frame.pdu, &[0x41, 0xAB, 0xCD]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CustomFunction {
+    pub fn_code: u8,
+    pub request: PduLen,
+    pub response: PduLen,
+}
+
+/// The length of a PDU with a function code that is unknown to the
+/// framing layer according to `custom`.
+pub(crate) fn custom_pdu_len(
+    custom: &[CustomFunction],
+    decoder_type: DecoderType,
+    pdu: &[u8],
+) -> Result<Option<usize>> {
+    let Some(&fn_code) = pdu.first() else {
+        return Ok(None);
+    };
+    let Some(function) = custom.iter().find(|f| f.fn_code == fn_code & 0x7F) else {
+        return Err(DecodeError::FnCode(fn_code));
+    };
+    let len = match decoder_type {
+        DecoderType::Response if fn_code & 0x80 != 0 => Some(2),
+        DecoderType::Response => function.response.pdu_len(pdu)?,
+        DecoderType::Request if fn_code & 0x80 != 0 => return Err(DecodeError::FnCode(fn_code)),
+        DecoderType::Request => function.request.pdu_len(pdu)?,
+    };
+    match len {
+        Some(len) if len == 0 || len > MAX_PDU_LEN => Err(DecodeError::FnCode(fn_code)),
+        len => Ok(len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUSTOM: &[CustomFunction] = &[
+        CustomFunction {
+            fn_code: 0x41,
+            request: PduLen::Fixed(3),
+            response: PduLen::ByteCount { offset: 1, base: 2 },
+        },
+        CustomFunction {
+            fn_code: 0x64,
+            request: PduLen::Callback(|pdu| Ok(pdu.get(1).map(|len| usize::from(*len) * 2))),
+            response: PduLen::Fixed(300),
+        },
+    ];
+
+    #[test]
+    fn pdu_len_of_layouts() {
+        let pdu = &[0x18, 0x00, 0x06, 0x00, 0x02];
+        assert_eq!(PduLen::Fixed(3).pdu_len(pdu), Ok(Some(3)));
+        assert_eq!(
+            PduLen::ByteCount { offset: 2, base: 3 }.pdu_len(pdu),
+            Ok(Some(9))
+        );
+        assert_eq!(
+            PduLen::ByteCountU16 { offset: 1, base: 3 }.pdu_len(pdu),
+            Ok(Some(9))
+        );
+        assert_eq!(
+            PduLen::ByteCountU16 { offset: 1, base: 3 }.pdu_len(&pdu[..2]),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn registered_functions() {
+        use DecoderType::{Request, Response};
+
+        assert_eq!(custom_pdu_len(CUSTOM, Request, &[0x41]), Ok(Some(3)));
+        assert_eq!(custom_pdu_len(CUSTOM, Response, &[0x41]), Ok(None));
+        assert_eq!(custom_pdu_len(CUSTOM, Response, &[0x41, 0x04]), Ok(Some(6)));
+        assert_eq!(custom_pdu_len(CUSTOM, Response, &[0xC1]), Ok(Some(2)));
+        assert_eq!(custom_pdu_len(CUSTOM, Request, &[0x64, 0x05]), Ok(Some(10)));
+        assert_eq!(custom_pdu_len(CUSTOM, Request, &[]), Ok(None));
+    }
+
+    #[test]
+    fn reject_unknown_functions_and_lengths() {
+        use DecoderType::{Request, Response};
+
+        assert_eq!(
+            custom_pdu_len(CUSTOM, Request, &[0x42]),
+            Err(DecodeError::FnCode(0x42))
+        );
+        assert_eq!(
+            custom_pdu_len(CUSTOM, Request, &[0xC1]),
+            Err(DecodeError::FnCode(0xC1))
+        );
+        assert_eq!(
+            custom_pdu_len(CUSTOM, Response, &[0x64]),
+            Err(DecodeError::FnCode(0x64))
+        );
+        assert_eq!(
+            custom_pdu_len(CUSTOM, Request, &[0x64, 0x00]),
+            Err(DecodeError::FnCode(0x64))
+        );
+    }
+}
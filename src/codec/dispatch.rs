@@ -0,0 +1,631 @@
+// All parsers share the signature of the dispatch table.
+#![allow(clippy::unnecessary_wraps)]
+
+use super::*;
+
+/// The layout of the PDUs of a function code and how they are decoded.
+///
+/// [`Request::try_from`] and [`Response::try_from`] dispatch to the
+/// descriptor of the function code, so code that needs to know about
+/// the PDUs of a function, e.g. to delimit or validate them, can rely
+/// on the same descriptors.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{DecodeOptions, FunctionCode, FunctionDescriptor, PduLen, Request};
+///
+/// let descriptor = FunctionDescriptor::of(FunctionCode::ReadHoldingRegisters).unwrap();
+/// assert_eq!(descriptor.min_request_len(), 5);
+/// assert_eq!(descriptor.request_layout(), PduLen::Fixed(5));
+/// assert_eq!(
+///     descriptor.response_layout(),
+///     PduLen::ByteCount { offset: 1, base: 2 }
+/// );
+/// assert!(!descriptor.is_serial_line_only());
+///
+/// let pdu = &[0x03, 0x00, 0x10, 0x00, 0x02];
+/// assert_eq!(
+///     descriptor.decode_request(pdu, &DecodeOptions::new()),
+///     Ok(Request::ReadHoldingRegisters(0x10, 2))
+/// );
+/// assert!(FunctionDescriptor::of(FunctionCode::Custom(0x41)).is_none());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionDescriptor {
+    pub(crate) function: FunctionCode,
+    pub(crate) min_request_len: usize,
+    pub(crate) min_response_len: usize,
+    pub(crate) request_layout: PduLen,
+    pub(crate) response_layout: PduLen,
+    pub(crate) parse_request: for<'r> fn(&'r [u8], DecodeOptions) -> Result<Request<'r>>,
+    pub(crate) parse_response: for<'r> fn(&'r [u8], DecodeOptions) -> Result<Response<'r>>,
+}
+
+impl FunctionDescriptor {
+    /// The descriptors of all function codes that are decoded by this crate.
+    pub const ALL: &'static [Self] = &[
+        Self::new(F::ReadCoils, 5, 2, read_coils_req, read_coils_rsp).layouts(Fixed(5), BYTE_COUNT),
+        Self::new(
+            F::ReadDiscreteInputs,
+            5,
+            2,
+            read_inputs_req,
+            read_inputs_rsp,
+        )
+        .layouts(Fixed(5), BYTE_COUNT),
+        Self::new(
+            F::ReadHoldingRegisters,
+            5,
+            2,
+            read_holding_req,
+            read_holding_rsp,
+        )
+        .layouts(Fixed(5), BYTE_COUNT),
+        Self::new(
+            F::ReadInputRegisters,
+            5,
+            2,
+            read_input_regs_req,
+            read_input_regs_rsp,
+        )
+        .layouts(Fixed(5), BYTE_COUNT),
+        Self::new(F::WriteSingleCoil, 5, 5, write_coil_req, write_coil_rsp)
+            .layouts(Fixed(5), Fixed(5)),
+        Self::new(F::WriteSingleRegister, 5, 5, write_reg_req, write_reg_rsp)
+            .layouts(Fixed(5), Fixed(5)),
+        #[cfg(feature = "rtu")]
+        Self::new(
+            F::ReadExceptionStatus,
+            1,
+            2,
+            exception_status_req,
+            exception_status_rsp,
+        )
+        .layouts(Fixed(1), Fixed(2)),
+        // Without a byte count only a single data word can be delimited
+        #[cfg(feature = "rtu")]
+        Self::new(F::Diagnostics, 5, 5, diagnostics_req, diagnostics_rsp),
+        #[cfg(feature = "rtu")]
+        Self::new(
+            F::GetCommEventCounter,
+            1,
+            5,
+            event_counter_req,
+            event_counter_rsp,
+        )
+        .layouts(Fixed(1), Fixed(5)),
+        #[cfg(feature = "rtu")]
+        Self::new(F::GetCommEventLog, 1, 8, event_log_req, event_log_rsp)
+            .layouts(Fixed(1), BYTE_COUNT),
+        Self::new(
+            F::WriteMultipleCoils,
+            6,
+            5,
+            write_coils_req,
+            write_coils_rsp,
+        )
+        .layouts(ByteCount { offset: 5, base: 6 }, Fixed(5)),
+        Self::new(
+            F::WriteMultipleRegisters,
+            6,
+            5,
+            write_regs_req,
+            write_regs_rsp,
+        )
+        .layouts(ByteCount { offset: 5, base: 6 }, Fixed(5)),
+        #[cfg(feature = "rtu")]
+        Self::new(F::ReportServerId, 1, 1, server_id_req, custom_rsp).layouts(Fixed(1), BYTE_COUNT),
+        Self::new(F::ReadFileRecord, 2, 2, read_file_req, read_file_rsp)
+            .layouts(BYTE_COUNT, BYTE_COUNT),
+        Self::new(F::WriteFileRecord, 2, 2, write_file_req, write_file_rsp)
+            .layouts(BYTE_COUNT, BYTE_COUNT),
+        Self::new(F::MaskWriteRegister, 7, 7, custom_req, custom_rsp),
+        Self::new(
+            F::ReadWriteMultipleRegisters,
+            10,
+            2,
+            read_write_req,
+            read_write_rsp,
+        )
+        .layouts(
+            ByteCount {
+                offset: 9,
+                base: 10,
+            },
+            BYTE_COUNT,
+        ),
+        Self::new(F::ReadFifoQueue, 3, 5, fifo_req, fifo_rsp)
+            .layouts(Fixed(3), ByteCountU16 { offset: 1, base: 3 }),
+        // Encapsulated Interface Transport
+        Self::new(F::Custom(0x2B), 2, 2, custom_req, custom_rsp).layouts(
+            Callback(mei_request_pdu_len),
+            Callback(mei_response_pdu_len),
+        ),
+    ];
+
+    const fn new(
+        function: FunctionCode,
+        min_request_len: usize,
+        min_response_len: usize,
+        parse_request: for<'r> fn(&'r [u8], DecodeOptions) -> Result<Request<'r>>,
+        parse_response: for<'r> fn(&'r [u8], DecodeOptions) -> Result<Response<'r>>,
+    ) -> Self {
+        Self {
+            function,
+            min_request_len,
+            min_response_len,
+            request_layout: Fixed(min_request_len),
+            response_layout: Fixed(min_response_len),
+            parse_request,
+            parse_response,
+        }
+    }
+
+    const fn layouts(self, request_layout: PduLen, response_layout: PduLen) -> Self {
+        Self {
+            request_layout,
+            response_layout,
+            ..self
+        }
+    }
+
+    /// The descriptor of `function`.
+    ///
+    /// Returns `None` for function codes that are unknown to the
+    /// framing layer, i.e. that can only be delimited with a
+    /// [`CustomFunction`]. The PDUs of a few known function codes,
+    /// e.g. of `MaskWriteRegister`, are decoded as `Custom` as well.
+    #[must_use]
+    pub const fn of(function: FunctionCode) -> Option<&'static Self> {
+        let code = function.value();
+        if code >= 0x80 {
+            return None;
+        }
+        TABLE[code as usize]
+    }
+
+    #[must_use]
+    pub const fn function(&self) -> FunctionCode {
+        self.function
+    }
+
+    /// The length of the shortest request PDU, including the function code.
+    #[must_use]
+    pub const fn min_request_len(&self) -> usize {
+        self.min_request_len
+    }
+
+    /// The length of the shortest response PDU, including the function code.
+    #[must_use]
+    pub const fn min_response_len(&self) -> usize {
+        self.min_response_len
+    }
+
+    /// How the framing layer determines the length of a request PDU.
+    #[must_use]
+    pub const fn request_layout(&self) -> PduLen {
+        self.request_layout
+    }
+
+    /// How the framing layer determines the length of a response PDU.
+    ///
+    /// Exception responses always consist of two bytes.
+    #[must_use]
+    pub const fn response_layout(&self) -> PduLen {
+        self.response_layout
+    }
+
+    /// Returns `true` if the function is only defined for serial lines.
+    #[must_use]
+    pub const fn is_serial_line_only(&self) -> bool {
+        self.function.is_serial_line_only()
+    }
+
+    /// Decode a request PDU of this function with the checks of `options`.
+    pub fn decode_request<'r>(
+        &self,
+        bytes: &'r [u8],
+        options: &DecodeOptions,
+    ) -> Result<Request<'r>> {
+        self.check_pdu(DecoderType::Request, bytes, self.min_request_len)?;
+        let req = (self.parse_request)(bytes, *options)?;
+        options.check_request(&req)?;
+        Ok(req)
+    }
+
+    /// Decode a response PDU of this function with the checks of `options`.
+    pub fn decode_response<'r>(
+        &self,
+        bytes: &'r [u8],
+        options: &DecodeOptions,
+    ) -> Result<Response<'r>> {
+        self.check_pdu(DecoderType::Response, bytes, self.min_response_len)?;
+        let rsp = (self.parse_response)(bytes, *options)?;
+        options.check_response(&rsp)?;
+        Ok(rsp)
+    }
+
+    fn check_pdu(&self, decoder: DecoderType, bytes: &[u8], min_len: usize) -> Result<()> {
+        let fn_code = read_fn_code(decoder, bytes)?;
+        if fn_code != self.function.value() {
+            return Err(DecodeError::FnCode(fn_code));
+        }
+        if bytes.len() < min_len {
+            return Err(truncated(decoder, bytes, bytes.len()));
+        }
+        Ok(())
+    }
+}
+
+/// The descriptors of [`FunctionDescriptor::ALL`] indexed by their function code.
+const TABLE: [Option<&FunctionDescriptor>; 0x80] = {
+    let mut table = [None; 0x80];
+    let mut i = 0;
+    while i < FunctionDescriptor::ALL.len() {
+        let descriptor = &FunctionDescriptor::ALL[i];
+        table[descriptor.function.value() as usize] = Some(descriptor);
+        i += 1;
+    }
+    table
+};
+
+/// The length of the `pdu` of a request or response according to the
+/// layout of its function code, or `None` if more bytes are needed.
+///
+/// Exception responses always consist of two bytes.
+/// Fails with [`DecodeError::FnCode`] if the function code has no descriptor.
+pub(crate) fn framed_pdu_len(decoder_type: DecoderType, pdu: &[u8]) -> Result<Option<usize>> {
+    let Some(&fn_code) = pdu.first() else {
+        return Ok(None);
+    };
+    let is_exception = fn_code & 0x80 != 0;
+    let descriptor = match FunctionDescriptor::of(FunctionCode::new(fn_code & 0x7F)) {
+        Some(descriptor) if !(is_exception && decoder_type == DecoderType::Request) => descriptor,
+        _ => return Err(DecodeError::FnCode(fn_code)),
+    };
+    match decoder_type {
+        DecoderType::Response if is_exception => Ok(Some(2)),
+        DecoderType::Response => descriptor.response_layout.pdu_len(pdu),
+        DecoderType::Request => descriptor.request_layout.pdu_len(pdu),
+    }
+}
+
+use FunctionCode as F;
+use PduLen::{ByteCount, ByteCountU16, Callback, Fixed};
+
+/// A byte count that directly follows the function code.
+const BYTE_COUNT: PduLen = ByteCount { offset: 1, base: 2 };
+
+/// The address and the 16-bit value that follow the function code.
+fn address_and_value(bytes: &[u8]) -> (Address, u16) {
+    (read_u16(&bytes[1..3]), read_u16(&bytes[3..5]))
+}
+
+/// The `byte_count` bytes that start at `offset`.
+fn counted_bytes(bytes: &[u8], offset: usize, byte_count: u8) -> Result<&[u8]> {
+    bytes
+        .get(offset..offset + byte_count as usize)
+        .ok_or(DecodeError::ByteCount(byte_count))
+}
+
+fn read_coils_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Request::ReadCoils(address, quantity))
+}
+
+fn read_inputs_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Request::ReadDiscreteInputs(address, quantity))
+}
+
+fn read_holding_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Request::ReadHoldingRegisters(address, quantity))
+}
+
+fn read_input_regs_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Request::ReadInputRegisters(address, quantity))
+}
+
+fn write_coil_req(bytes: &[u8], options: DecodeOptions) -> Result<Request<'_>> {
+    let (address, value) = address_and_value(bytes);
+    Ok(Request::WriteSingleCoil(address, options.coil(value)?))
+}
+
+fn write_reg_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, word) = address_and_value(bytes);
+    Ok(Request::WriteSingleRegister(address, word))
+}
+
+fn write_coils_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    let data = counted_bytes(bytes, 6, bytes[5])?;
+    let coils = Coils {
+        data,
+        quantity: quantity.into(),
+    };
+    Ok(Request::WriteMultipleCoils(address, coils))
+}
+
+fn write_regs_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    let data = counted_bytes(bytes, 6, bytes[5])?;
+    let words = Data {
+        data,
+        quantity: quantity.into(),
+    };
+    Ok(Request::WriteMultipleRegisters(address, words))
+}
+
+fn read_write_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (read_address, read_quantity) = address_and_value(bytes);
+    let write_address = read_u16(&bytes[5..7]);
+    let write_quantity = read_u16(&bytes[7..9]);
+    let data = counted_bytes(bytes, 10, bytes[9])?;
+    let words = Data {
+        data,
+        quantity: write_quantity.into(),
+    };
+    Ok(Request::ReadWriteMultipleRegisters(
+        read_address,
+        read_quantity,
+        write_address,
+        words,
+    ))
+}
+
+fn fifo_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::ReadFifoQueue(read_u16(&bytes[1..3])))
+}
+
+fn read_file_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let data = counted_bytes(bytes, 2, bytes[1])?;
+    Ok(Request::ReadFileRecord(FileRecordRequests::parse(data)?))
+}
+
+fn write_file_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let data = counted_bytes(bytes, 2, bytes[1])?;
+    Ok(Request::WriteFileRecord(FileRecords::parse(data)?))
+}
+
+#[cfg(feature = "rtu")]
+const fn exception_status_req(_: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::ReadExceptionStatus)
+}
+
+#[cfg(feature = "rtu")]
+fn diagnostics_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    let (sub_function, words) = decode_diagnostics(bytes)?;
+    Ok(Request::Diagnostics(sub_function, words))
+}
+
+#[cfg(feature = "rtu")]
+const fn event_counter_req(_: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::GetCommEventCounter)
+}
+
+#[cfg(feature = "rtu")]
+const fn event_log_req(_: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::GetCommEventLog)
+}
+
+#[cfg(feature = "rtu")]
+const fn server_id_req(_: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::ReportServerId)
+}
+
+/// The packed coils that follow the byte count.
+fn packed_coils(bytes: &[u8]) -> Result<Coils<'_>> {
+    let byte_count = bytes[1] as usize;
+    let data = bytes
+        .get(2..2 + byte_count)
+        .ok_or_else(|| truncated(DecoderType::Response, bytes, 2))?;
+    // Here we have not information about the exact requested quantity
+    // therefore we just assume that the whole byte is meant.
+    Ok(Coils {
+        data,
+        quantity: byte_count * 8,
+    })
+}
+
+/// The registers that follow the byte count.
+fn counted_words(bytes: &[u8]) -> Result<Data<'_>> {
+    let byte_count = bytes[1] as usize;
+    let data = bytes
+        .get(2..2 + byte_count)
+        .ok_or_else(|| truncated(DecoderType::Response, bytes, 2))?;
+    Ok(Data {
+        data,
+        quantity: byte_count / 2,
+    })
+}
+
+fn read_coils_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    packed_coils(bytes).map(Response::ReadCoils)
+}
+
+fn read_inputs_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    packed_coils(bytes).map(Response::ReadDiscreteInputs)
+}
+
+fn read_holding_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    counted_words(bytes).map(Response::ReadHoldingRegisters)
+}
+
+fn read_input_regs_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    counted_words(bytes).map(Response::ReadInputRegisters)
+}
+
+fn read_write_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    counted_words(bytes).map(Response::ReadWriteMultipleRegisters)
+}
+
+fn write_coil_rsp(bytes: &[u8], options: DecodeOptions) -> Result<Response<'_>> {
+    let (address, value) = address_and_value(bytes);
+    Ok(Response::WriteSingleCoil(address, options.coil(value)?))
+}
+
+fn write_reg_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let (address, word) = address_and_value(bytes);
+    Ok(Response::WriteSingleRegister(address, word))
+}
+
+fn write_coils_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Response::WriteMultipleCoils(address, quantity))
+}
+
+fn write_regs_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let (address, quantity) = address_and_value(bytes);
+    Ok(Response::WriteMultipleRegisters(address, quantity))
+}
+
+fn fifo_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let byte_count = read_u16(&bytes[1..3]) as usize;
+    let fifo_count = read_u16(&bytes[3..5]) as usize;
+    let expected = checked_words_len(2, fifo_count)?;
+    if byte_count != expected {
+        return Err(DecodeError::LengthMismatch(byte_count, expected));
+    }
+    if checked_len(3, byte_count)? > bytes.len() {
+        return Err(truncated(DecoderType::Response, bytes, 5));
+    }
+    Ok(Response::ReadFifoQueue(Data {
+        data: &bytes[5..3 + byte_count],
+        quantity: fifo_count,
+    }))
+}
+
+fn read_file_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let data = counted_bytes(bytes, 2, bytes[1])?;
+    Ok(Response::ReadFileRecord(FileRecordData::parse(data)?))
+}
+
+fn write_file_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let data = counted_bytes(bytes, 2, bytes[1])?;
+    Ok(Response::WriteFileRecord(FileRecords::parse(data)?))
+}
+
+#[cfg(feature = "rtu")]
+fn exception_status_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    Ok(Response::ReadExceptionStatus(bytes[1]))
+}
+
+#[cfg(feature = "rtu")]
+fn diagnostics_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let (sub_function, words) = decode_diagnostics(bytes)?;
+    DiagnosticsPayload::new(sub_function, words)?;
+    Ok(Response::Diagnostics(sub_function, words))
+}
+
+#[cfg(feature = "rtu")]
+fn event_counter_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    Ok(Response::GetCommEventCounter(
+        read_u16(&bytes[1..3]),
+        read_u16(&bytes[3..5]),
+    ))
+}
+
+#[cfg(feature = "rtu")]
+fn event_log_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    let byte_count = bytes[1];
+    if byte_count < 6 || usize::from(byte_count) > 6 + MAX_COMM_EVENTS {
+        return Err(DecodeError::ByteCount(byte_count));
+    }
+    let data = counted_bytes(bytes, 2, byte_count)?;
+    Ok(Response::GetCommEventLog(
+        read_u16(&data[0..2]),
+        read_u16(&data[2..4]),
+        read_u16(&data[4..6]),
+        &data[6..],
+    ))
+}
+
+/// Requests that are not decoded yet.
+fn custom_req(bytes: &[u8], _: DecodeOptions) -> Result<Request<'_>> {
+    Ok(Request::Custom(RawPdu {
+        function: FunctionCode::new(bytes[0]),
+        data: &bytes[1..],
+    }))
+}
+
+/// Responses that are not decoded yet.
+fn custom_rsp(bytes: &[u8], _: DecodeOptions) -> Result<Response<'_>> {
+    Ok(Response::Custom(RawPdu {
+        function: FunctionCode::new(bytes[0]),
+        data: &bytes[1..],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_matches_descriptors() {
+        for descriptor in FunctionDescriptor::ALL {
+            let found = FunctionDescriptor::of(descriptor.function()).unwrap();
+            assert_eq!(found.function(), descriptor.function());
+            assert!(found.min_request_len() >= 1);
+            assert!(found.min_response_len() >= 1);
+        }
+        assert!(FunctionDescriptor::of(FunctionCode::Custom(0x41)).is_none());
+        assert!(FunctionDescriptor::of(FunctionCode::new(0x83)).is_none());
+    }
+
+    #[test]
+    fn framed_lengths() {
+        use DecoderType::{Request, Response};
+
+        assert_eq!(framed_pdu_len(Request, &[]), Ok(None));
+        assert_eq!(framed_pdu_len(Request, &[0x03]), Ok(Some(5)));
+        assert_eq!(framed_pdu_len(Response, &[0x03]), Ok(None));
+        assert_eq!(framed_pdu_len(Response, &[0x03, 0x04]), Ok(Some(6)));
+        assert_eq!(framed_pdu_len(Request, &[0x16]), Ok(Some(7)));
+        assert_eq!(framed_pdu_len(Response, &[0x83]), Ok(Some(2)));
+        assert_eq!(
+            framed_pdu_len(Request, &[0x83]),
+            Err(DecodeError::FnCode(0x83))
+        );
+        assert_eq!(
+            framed_pdu_len(Response, &[0x41]),
+            Err(DecodeError::FnCode(0x41))
+        );
+        assert_eq!(
+            framed_pdu_len(Response, &[0xC1]),
+            Err(DecodeError::FnCode(0xC1))
+        );
+    }
+
+    #[test]
+    fn serial_line_only_functions() {
+        let serial = FunctionDescriptor::ALL
+            .iter()
+            .filter(|descriptor| descriptor.is_serial_line_only())
+            .count();
+        assert_eq!(serial, if cfg!(feature = "rtu") { 5 } else { 0 });
+        assert!(!FunctionDescriptor::of(FunctionCode::ReadFifoQueue)
+            .unwrap()
+            .is_serial_line_only());
+    }
+
+    #[test]
+    fn reject_pdus_of_other_functions() {
+        let descriptor = FunctionDescriptor::of(FunctionCode::WriteSingleRegister).unwrap();
+        let options = DecodeOptions::new();
+        assert_eq!(
+            descriptor.decode_request(&[0x05, 0x00, 0x01, 0xFF, 0x00], &options),
+            Err(DecodeError::FnCode(0x05))
+        );
+        assert!(matches!(
+            descriptor.decode_response(&[0x06, 0x00, 0x01, 0xFF], &options),
+            Err(DecodeError::Truncated(_))
+        ));
+        assert_eq!(
+            descriptor.decode_response(&[0x06, 0x00, 0x01, 0xFF, 0x00], &options),
+            Ok(Response::WriteSingleRegister(0x0001, 0xFF00))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use super::*;
+use core::fmt;
+
+/// Render an encoded ADU as annotated hex.
+///
+/// The header fields, the function code, the payload and (for RTU) the CRC
+/// are printed separately. Frames that are too short to contain a header
+/// are printed as raw bytes.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::DisplayAdu;
+///
+/// let adu = [0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+/// assert_eq!(
+///     format!("{}", DisplayAdu::rtu(&adu)),
+///     "slave: 0x12, fn: 0x06, data: [22 22 AB CD], crc: 0x9FBE (ok)"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayAdu<'a> {
+    transport: Transport,
+    adu: &'a [u8],
+}
+
+impl<'a> DisplayAdu<'a> {
+    /// Render the bytes of an RTU frame.
+    #[must_use]
+    pub const fn rtu(adu: &'a [u8]) -> Self {
+        Self {
+            transport: Transport::Rtu,
+            adu,
+        }
+    }
+
+    /// Render the bytes of a TCP frame.
+    #[must_use]
+    pub const fn tcp(adu: &'a [u8]) -> Self {
+        Self {
+            transport: Transport::Tcp,
+            adu,
+        }
+    }
+
+    /// Split the ADU into its fields.
+    fn fields(&self) -> Option<Fields<'a>> {
+        let adu = self.adu;
+        match self.transport {
+            Transport::Rtu => {
+                if adu.len() < 4 {
+                    return None;
+                }
+                let (frame, crc) = adu.split_at(adu.len() - 2);
+                Some(Fields {
+                    header: Header::Rtu { slave: frame[0] },
+                    fn_code: frame[1],
+                    data: &frame[2..],
+                    crc: Some((read_u16(crc), rtu::crc16(frame))),
+                    trailing: &[],
+                })
+            }
+            Transport::Tcp => {
+                if adu.len() < 8 {
+                    return None;
+                }
+                let length = read_u16(&adu[4..6]);
+                let end = (6 + usize::from(length)).clamp(8, adu.len());
+                Some(Fields {
+                    header: Header::Tcp {
+                        transaction_id: read_u16(&adu[0..2]),
+                        protocol_id: read_u16(&adu[2..4]),
+                        length,
+                        unit_id: adu[6],
+                    },
+                    fn_code: adu[7],
+                    data: &adu[8..end],
+                    crc: None,
+                    trailing: &adu[end..],
+                })
+            }
+        }
+    }
+}
+
+enum Header {
+    Rtu {
+        slave: u8,
+    },
+    Tcp {
+        transaction_id: u16,
+        protocol_id: u16,
+        length: u16,
+        unit_id: u8,
+    },
+}
+
+struct Fields<'a> {
+    header: Header,
+    fn_code: u8,
+    data: &'a [u8],
+    /// The received and the calculated CRC.
+    crc: Option<(u16, u16)>,
+    /// Bytes beyond the length of a TCP frame.
+    trailing: &'a [u8],
+}
+
+/// Space separated hex bytes.
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{byte:02X}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl fmt::Display for DisplayAdu<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(fields) = self.fields() else {
+            return write!(f, "incomplete: {}", Hex(self.adu));
+        };
+        match fields.header {
+            Header::Rtu { slave } => write!(f, "slave: 0x{slave:02X}, ")?,
+            Header::Tcp {
+                transaction_id,
+                protocol_id,
+                length,
+                unit_id,
+            } => write!(
+                f,
+                "transaction: 0x{transaction_id:04X}, protocol: 0x{protocol_id:04X}, \
+                 length: {length}, unit: 0x{unit_id:02X}, "
+            )?,
+        }
+        write!(
+            f,
+            "fn: 0x{:02X}, data: {}",
+            fields.fn_code,
+            Hex(fields.data)
+        )?;
+        if let Some((received, calculated)) = fields.crc {
+            if received == calculated {
+                write!(f, ", crc: 0x{received:04X} (ok)")?;
+            } else {
+                write!(f, ", crc: 0x{received:04X} (expected 0x{calculated:04X})")?;
+            }
+        }
+        if !fields.trailing.is_empty() {
+            write!(f, ", trailing: {}", Hex(fields.trailing))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DisplayAdu<'_> {
+    fn format(&self, f: defmt::Formatter) {
+        let Some(fields) = self.fields() else {
+            defmt::write!(f, "incomplete: {=[u8]:02X}", self.adu);
+            return;
+        };
+        match fields.header {
+            Header::Rtu { slave } => defmt::write!(f, "slave: {=u8:#04X}, ", slave),
+            Header::Tcp {
+                transaction_id,
+                protocol_id,
+                length,
+                unit_id,
+            } => defmt::write!(
+                f,
+                "transaction: {=u16:#06X}, protocol: {=u16:#06X}, length: {=u16}, unit: {=u8:#04X}, ",
+                transaction_id,
+                protocol_id,
+                length,
+                unit_id
+            ),
+        }
+        defmt::write!(
+            f,
+            "fn: {=u8:#04X}, data: {=[u8]:02X}",
+            fields.fn_code,
+            fields.data
+        );
+        if let Some((received, calculated)) = fields.crc {
+            if received == calculated {
+                defmt::write!(f, ", crc: {=u16:#06X} (ok)", received);
+            } else {
+                defmt::write!(
+                    f,
+                    ", crc: {=u16:#06X} (expected {=u16:#06X})",
+                    received,
+                    calculated
+                );
+            }
+        }
+        if !fields.trailing.is_empty() {
+            defmt::write!(f, ", trailing: {=[u8]:02X}", fields.trailing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    /// Render `adu` into `buf`.
+    fn render<'b>(adu: DisplayAdu<'_>, buf: &'b mut [u8]) -> &'b str {
+        struct Writer<'b>(&'b mut [u8], usize);
+
+        impl fmt::Write for Writer<'_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let end = self.1 + s.len();
+                self.0.get_mut(self.1..end).ok_or(fmt::Error)?;
+                self.0[self.1..end].copy_from_slice(s.as_bytes());
+                self.1 = end;
+                Ok(())
+            }
+        }
+
+        let mut writer = Writer(buf, 0);
+        write!(writer, "{adu}").unwrap();
+        let len = writer.1;
+        core::str::from_utf8(&buf[..len]).unwrap()
+    }
+
+    #[test]
+    fn rtu_with_invalid_crc() {
+        let buf = &mut [0; 128];
+        assert_eq!(
+            render(DisplayAdu::rtu(&[0x01, 0x83, 0x02, 0x00, 0x00]), buf),
+            "slave: 0x01, fn: 0x83, data: [02], crc: 0x0000 (expected 0xC0F1)"
+        );
+    }
+
+    #[test]
+    fn tcp() {
+        let buf = &mut [0; 128];
+        let adu = [0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02, 0xFF];
+        assert_eq!(
+            render(DisplayAdu::tcp(&adu), buf),
+            "transaction: 0x0007, protocol: 0x0000, length: 3, unit: 0x01, \
+             fn: 0x83, data: [02], trailing: [FF]"
+        );
+        assert_eq!(
+            render(DisplayAdu::tcp(&adu[..5]), buf),
+            "incomplete: [00 07 00 00 00]"
+        );
+    }
+}
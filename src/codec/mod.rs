@@ -1,234 +1,469 @@
 use crate::{error::*, frame::*};
-use byteorder::{BigEndian, ByteOrder};
-
+use core::mem::MaybeUninit;
+
+mod classify;
+mod custom;
+mod dispatch;
+mod display;
+mod options;
+mod quirks;
 pub mod rtu;
+mod stats;
 pub mod tcp;
 
+pub use self::{classify::*, custom::*, dispatch::*, display::*, options::*, quirks::*, stats::*};
+
 /// The type of decoding
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DecoderType {
     Request,
     Response,
 }
 
-type Result<T> = core::result::Result<T, Error>;
+/// The outcome of decoding a frame from a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeStatus<T> {
+    /// A complete frame has been decoded.
+    Complete(T),
+    /// More bytes have to be received.
+    Incomplete {
+        /// The number of missing bytes if the length of the frame is already known.
+        needed: Option<usize>,
+    },
+}
+
+impl<T> DecodeStatus<T> {
+    /// Get the decoded frame, if any.
+    pub fn complete(self) -> Option<T> {
+        match self {
+            Self::Complete(frame) => Some(frame),
+            Self::Incomplete { .. } => None,
+        }
+    }
+}
+
+/// Whether a buffer starts with a complete frame, see e.g. [`rtu::frame_status`].
+///
+/// In contrast to [`DecodeStatus`] the frame is not decoded,
+/// i.e. neither the checksum nor the PDU have been verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameStatus {
+    /// The frame is complete and has the given length in bytes.
+    Complete(usize),
+    /// More bytes have to be received.
+    ///
+    /// Contains the number of missing bytes if the length
+    /// of the frame is already known.
+    NeedMore(Option<usize>),
+    /// The buffer does not start with a valid frame.
+    Invalid(DecodeError),
+}
+
+impl FrameStatus {
+    /// Check `len` received bytes against the expected `frame_len`.
+    const fn of_len(frame_len: usize, len: usize) -> Self {
+        if len >= frame_len {
+            Self::Complete(frame_len)
+        } else {
+            Self::NeedMore(Some(frame_len - len))
+        }
+    }
+}
+
+/// The transport of an ADU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Transport {
+    Rtu,
+    Tcp,
+}
+
+/// Read the function code of the PDU `bytes`.
+const fn read_fn_code(decoder: DecoderType, bytes: &[u8]) -> Result<u8> {
+    if bytes.is_empty() {
+        return Err(truncated(decoder, bytes, 0));
+    }
+    Ok(bytes[0])
+}
+
+/// The PDU `bytes` end before the field at `offset`.
+const fn truncated(decoder: DecoderType, bytes: &[u8], offset: usize) -> DecodeError {
+    let fn_code = if bytes.is_empty() {
+        None
+    } else {
+        Some(bytes[0])
+    };
+    DecodeError::Truncated(DecodeContext {
+        decoder,
+        transport: None,
+        fn_code,
+        offset,
+    })
+}
+
+/// The length of `fixed` bytes followed by `len` bytes
+/// that are announced by a field of the frame.
+///
+/// Such fields are controlled by the peer, so the sum might
+/// not fit into `usize`, e.g. on 16-bit targets.
+const fn checked_len(fixed: usize, len: usize) -> Result<usize> {
+    match fixed.checked_add(len) {
+        Some(len) => Ok(len),
+        None => Err(DecodeError::LengthOverflow),
+    }
+}
+
+/// The length of `fixed` bytes followed by `quantity` words.
+pub(crate) const fn checked_words_len(fixed: usize, quantity: usize) -> Result<usize> {
+    match quantity.checked_mul(2) {
+        Some(len) => checked_len(fixed, len),
+        None => Err(DecodeError::LengthOverflow),
+    }
+}
+
+type Result<T, E = DecodeError> = core::result::Result<T, E>;
 
 impl TryFrom<u8> for Exception {
-    type Error = Error;
+    type Error = DecodeError;
 
     fn try_from(code: u8) -> Result<Self> {
-        let ex = match code {
-            0x01 => Self::IllegalFunction,
-            0x02 => Self::IllegalDataAddress,
-            0x03 => Self::IllegalDataValue,
-            0x04 => Self::ServerDeviceFailure,
-            0x05 => Self::Acknowledge,
-            0x06 => Self::ServerDeviceBusy,
-            0x08 => Self::MemoryParityError,
-            0x0A => Self::GatewayPathUnavailable,
-            0x0B => Self::GatewayTargetDevice,
-            _ => {
-                return Err(Error::ExceptionCode(code));
-            }
-        };
-        Ok(ex)
+        match Self::new(code) {
+            Self::Custom(code) => Err(DecodeError::ExceptionCode(code)),
+            ex => Ok(ex),
+        }
     }
 }
 
+/// The function code must be below `0x80`, otherwise
+/// its highest bit is lost. [`Encode`] rejects such exceptions.
 impl From<ExceptionResponse> for [u8; 2] {
     fn from(ex: ExceptionResponse) -> [u8; 2] {
-        let data = &mut [0; 2];
-        let fn_code: u8 = ex.function.value();
-        debug_assert!(fn_code < 0x80);
-        data[0] = fn_code + 0x80;
-        data[1] = ex.exception as u8;
-        *data
+        [ex.function.value() | 0x80, ex.exception.value()]
     }
 }
 
 impl TryFrom<&[u8]> for ExceptionResponse {
-    type Error = Error;
+    type Error = DecodeError;
 
     fn try_from(bytes: &[u8]) -> Result<Self> {
-        if bytes.is_empty() {
-            return Err(Error::BufferSize);
-        }
-        let fn_err_code = bytes[0];
+        Self::try_from_with_options(bytes, &DecodeOptions::new())
+    }
+}
+
+impl ExceptionResponse {
+    /// Decode an exception PDU with the checks of `options`.
+    pub fn try_from_with_options(bytes: &[u8], options: &DecodeOptions) -> Result<Self> {
+        let fn_err_code = read_fn_code(DecoderType::Response, bytes)?;
         if fn_err_code < 0x80 {
-            return Err(Error::ExceptionFnCode(fn_err_code));
+            return Err(DecodeError::ExceptionFnCode(fn_err_code));
+        }
+        if bytes.len() < 2 {
+            return Err(truncated(DecoderType::Response, bytes, 1));
         }
         let function = FunctionCode::new(fn_err_code - 0x80);
-        let exception = Exception::try_from(bytes[1])?;
-        Ok(ExceptionResponse {
-            function,
-            exception,
-        })
+        let exception = if options.accept_custom_exception_codes {
+            Exception::new(bytes[1])
+        } else {
+            Exception::try_from(bytes[1])?
+        };
+        Ok(Self::new(function, exception))
     }
 }
 
 impl<'r> TryFrom<&'r [u8]> for Request<'r> {
-    type Error = Error;
+    type Error = DecodeError;
 
     fn try_from(bytes: &'r [u8]) -> Result<Self> {
-        use FunctionCode as F;
+        Self::try_from_with_options(bytes, &DecodeOptions::new())
+    }
+}
 
-        if bytes.is_empty() {
-            return Err(Error::BufferSize);
+impl<'r> Request<'r> {
+    /// Decode a request PDU with the checks of `options`.
+    pub fn try_from_with_options(bytes: &'r [u8], options: &DecodeOptions) -> Result<Self> {
+        let req = Self::decode(bytes, *options)?;
+        options.check_request(&req)?;
+        Ok(req)
+    }
+
+    fn decode(bytes: &'r [u8], options: DecodeOptions) -> Result<Self> {
+        let fn_code = read_fn_code(DecoderType::Request, bytes)?;
+        let Some(descriptor) = FunctionDescriptor::of(FunctionCode::new(fn_code)) else {
+            if fn_code >= 0x80 {
+                return Err(DecodeError::FnCode(fn_code));
+            }
+            return Ok(Self::Custom(RawPdu {
+                function: FunctionCode::Custom(fn_code),
+                data: &bytes[1..],
+            }));
+        };
+        if bytes.len() < descriptor.min_request_len {
+            return Err(truncated(DecoderType::Request, bytes, bytes.len()));
         }
+        (descriptor.parse_request)(bytes, options)
+    }
+}
 
-        let fn_code = bytes[0];
+impl<'r> TryFrom<&'r [u8]> for Response<'r> {
+    type Error = DecodeError;
 
-        if bytes.len() < min_request_pdu_len(FunctionCode::new(fn_code)) {
-            return Err(Error::BufferSize);
+    fn try_from(bytes: &'r [u8]) -> Result<Self> {
+        Self::try_from_with_options(bytes, &DecodeOptions::new())
+    }
+}
+
+impl<'r> Response<'r> {
+    /// Decode a response PDU with the checks of `options`.
+    pub fn try_from_with_options(bytes: &'r [u8], options: &DecodeOptions) -> Result<Self> {
+        let rsp = Self::decode(bytes, *options)?;
+        options.check_response(&rsp)?;
+        Ok(rsp)
+    }
+
+    fn decode(bytes: &'r [u8], options: DecodeOptions) -> Result<Self> {
+        let fn_code = read_fn_code(DecoderType::Response, bytes)?;
+        let Some(descriptor) = FunctionDescriptor::of(FunctionCode::new(fn_code)) else {
+            return Ok(Self::Custom(RawPdu {
+                function: FunctionCode::new(fn_code),
+                data: &bytes[1..],
+            }));
+        };
+        if bytes.len() < descriptor.min_response_len {
+            return Err(truncated(DecoderType::Response, bytes, bytes.len()));
         }
+        (descriptor.parse_response)(bytes, options)
+    }
 
-        let req = match FunctionCode::new(fn_code) {
-            F::ReadCoils
-            | F::ReadDiscreteInputs
-            | F::ReadInputRegisters
-            | F::ReadHoldingRegisters
-            | F::WriteSingleRegister => {
-                let addr = BigEndian::read_u16(&bytes[1..3]);
-                let quantity = BigEndian::read_u16(&bytes[3..5]);
+    /// Decode the response to `request`.
+    ///
+    /// In contrast to [`Response::try_from`] the number of coils of
+    /// `ReadCoils` and `ReadDiscreteInputs` responses is taken from the
+    /// request, so the padding bits of the last byte are not reported as coils.
+    pub fn try_from_with_request(bytes: &'r [u8], request: &Request<'_>) -> Result<Self> {
+        Self::try_from(bytes)?.with_request_quantity(request)
+    }
 
-                match FunctionCode::new(fn_code) {
-                    F::ReadCoils => Self::ReadCoils(addr, quantity),
-                    F::ReadDiscreteInputs => Self::ReadDiscreteInputs(addr, quantity),
-                    F::ReadInputRegisters => Self::ReadInputRegisters(addr, quantity),
-                    F::ReadHoldingRegisters => Self::ReadHoldingRegisters(addr, quantity),
-                    F::WriteSingleRegister => Self::WriteSingleRegister(addr, quantity),
-                    _ => unreachable!(),
-                }
-            }
-            F::WriteSingleCoil => Self::WriteSingleCoil(
-                BigEndian::read_u16(&bytes[1..3]),
-                u16_coil_to_bool(BigEndian::read_u16(&bytes[3..5]))?,
-            ),
-            F::WriteMultipleCoils => {
-                let address = BigEndian::read_u16(&bytes[1..3]);
-                let quantity = BigEndian::read_u16(&bytes[3..5]) as usize;
-                let byte_count = bytes[5];
-                if bytes.len() < (6 + byte_count as usize) {
-                    return Err(Error::ByteCount(byte_count));
-                }
-                let data = &bytes[6..];
-                let coils = Coils { data, quantity };
-                Self::WriteMultipleCoils(address, coils)
-            }
-            F::WriteMultipleRegisters => {
-                let address = BigEndian::read_u16(&bytes[1..3]);
-                let quantity = BigEndian::read_u16(&bytes[3..5]) as usize;
-                let byte_count = bytes[5];
-                if bytes.len() < (6 + byte_count as usize) {
-                    return Err(Error::ByteCount(byte_count));
-                }
-                let data = Data {
-                    quantity,
-                    data: &bytes[6..6 + byte_count as usize],
-                };
-                Self::WriteMultipleRegisters(address, data)
+    /// Replace the guessed number of coils with the requested one.
+    pub(crate) fn with_request_quantity(self, request: &Request<'_>) -> Result<Self> {
+        let rsp = match (self, *request) {
+            (Self::ReadCoils(coils), Request::ReadCoils(_, quantity)) => {
+                Self::ReadCoils(exact_coils(coils, quantity)?)
             }
-            F::ReadWriteMultipleRegisters => {
-                let read_address = BigEndian::read_u16(&bytes[1..3]);
-                let read_quantity = BigEndian::read_u16(&bytes[3..5]);
-                let write_address = BigEndian::read_u16(&bytes[5..7]);
-                let write_quantity = BigEndian::read_u16(&bytes[7..9]) as usize;
-                let write_count = bytes[9];
-                if bytes.len() < (10 + write_count as usize) {
-                    return Err(Error::ByteCount(write_count));
-                }
-                let data = Data {
-                    quantity: write_quantity,
-                    data: &bytes[10..10 + write_count as usize],
-                };
-                Self::ReadWriteMultipleRegisters(read_address, read_quantity, write_address, data)
+            (Self::ReadDiscreteInputs(coils), Request::ReadDiscreteInputs(_, quantity)) => {
+                Self::ReadDiscreteInputs(exact_coils(coils, quantity)?)
             }
-            _ => match fn_code {
-                fn_code if fn_code < 0x80 => {
-                    Self::Custom(FunctionCode::Custom(fn_code), &bytes[1..])
-                }
-                _ => return Err(Error::FnCode(fn_code)),
-            },
+            (rsp, _) => rsp,
         };
-        Ok(req)
+        Ok(rsp)
     }
 }
 
-impl<'r> TryFrom<&'r [u8]> for Response<'r> {
-    type Error = Error;
+fn exact_coils(coils: Coils<'_>, quantity: Quantity) -> Result<Coils<'_>> {
+    let quantity = usize::from(quantity);
+    if coils.data.len() != packed_coils_len(quantity) {
+        return Err(DecodeError::ByteCount(coils.data.len() as u8));
+    }
+    Ok(Coils {
+        data: coils.data,
+        quantity,
+    })
+}
+
+impl<'r> TryFrom<&'r [u8]> for ResponsePdu<'r> {
+    type Error = DecodeError;
 
     fn try_from(bytes: &'r [u8]) -> Result<Self> {
-        use FunctionCode as F;
-        if bytes.is_empty() {
-            return Err(Error::BufferSize);
-        }
-        let fn_code = bytes[0];
-        if bytes.len() < min_response_pdu_len(FunctionCode::new(fn_code)) {
-            return Err(Error::BufferSize);
-        }
-        let rsp = match FunctionCode::new(fn_code) {
-            F::ReadCoils | FunctionCode::ReadDiscreteInputs => {
-                let byte_count = bytes[1] as usize;
-                if byte_count + 2 > bytes.len() {
-                    return Err(Error::BufferSize);
-                }
-                let data = &bytes[2..byte_count + 2];
-                // Here we have not information about the exact requested quantity
-                // therefore we just assume that the whole byte is meant.
-                let quantity = byte_count * 8;
-
-                match FunctionCode::new(fn_code) {
-                    FunctionCode::ReadCoils => Self::ReadCoils(Coils { data, quantity }),
-                    FunctionCode::ReadDiscreteInputs => {
-                        Self::ReadDiscreteInputs(Coils { data, quantity })
-                    }
-                    _ => unreachable!(),
-                }
-            }
-            F::WriteSingleCoil => Self::WriteSingleCoil(BigEndian::read_u16(&bytes[1..])),
-
-            F::WriteMultipleCoils | F::WriteSingleRegister | F::WriteMultipleRegisters => {
-                let addr = BigEndian::read_u16(&bytes[1..]);
-                let payload = BigEndian::read_u16(&bytes[3..]);
-                match FunctionCode::new(fn_code) {
-                    F::WriteMultipleCoils => Self::WriteMultipleCoils(addr, payload),
-                    F::WriteSingleRegister => Self::WriteSingleRegister(addr, payload),
-                    F::WriteMultipleRegisters => Self::WriteMultipleRegisters(addr, payload),
-                    _ => unreachable!(),
-                }
-            }
-            F::ReadInputRegisters | F::ReadHoldingRegisters | F::ReadWriteMultipleRegisters => {
-                let byte_count = bytes[1] as usize;
-                let quantity = byte_count / 2;
-                if byte_count + 2 > bytes.len() {
-                    return Err(Error::BufferSize);
-                }
-                let data = &bytes[2..2 + byte_count];
-                let data = Data { data, quantity };
-
-                match FunctionCode::new(fn_code) {
-                    F::ReadInputRegisters => Self::ReadInputRegisters(data),
-                    F::ReadHoldingRegisters => Self::ReadHoldingRegisters(data),
-                    F::ReadWriteMultipleRegisters => Self::ReadWriteMultipleRegisters(data),
-                    _ => unreachable!(),
-                }
-            }
-            _ => Self::Custom(FunctionCode::new(fn_code), &bytes[1..]),
+        Self::try_from_with_options(bytes, &DecodeOptions::new())
+    }
+}
+
+impl<'r> ResponsePdu<'r> {
+    /// Decode a response or exception PDU with the checks of `options`.
+    pub fn try_from_with_options(bytes: &'r [u8], options: &DecodeOptions) -> Result<Self> {
+        let fn_code = read_fn_code(DecoderType::Response, bytes)?;
+        let rsp = if fn_code >= 0x80 {
+            Err(ExceptionResponse::try_from_with_options(bytes, options)?)
+        } else {
+            Ok(Response::try_from_with_options(bytes, options)?)
         };
-        Ok(rsp)
+        Ok(Self(rsp))
+    }
+}
+
+/// Initialize the first `len` bytes of `buf` and let `encode` write into them.
+///
+/// Only the bytes of the frame are initialized, not the whole buffer.
+#[allow(unsafe_code)]
+fn encode_uninit(
+    buf: &mut [MaybeUninit<u8>],
+    len: usize,
+    encode: impl FnOnce(&mut [u8]) -> Result<usize, EncodeError>,
+) -> Result<&[u8], EncodeError> {
+    let buf = buf.get_mut(..len).ok_or(EncodeError::BufferSize)?;
+    for byte in buf.iter_mut() {
+        byte.write(0);
+    }
+    // SAFETY: All bytes have been initialized and `MaybeUninit<u8>`
+    // has the same layout as `u8`.
+    let buf = unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) };
+    let len = encode(buf)?;
+    Ok(&buf[..len])
+}
+
+/// Encode the PDU of a `WriteMultipleRegisters` request with
+/// the values of `words` to the start of `buf`.
+fn encode_write_multiple_registers_pdu(
+    address: Address,
+    words: impl ExactSizeIterator<Item = Word>,
+    buf: &mut [u8],
+) -> Result<usize, EncodeError> {
+    let quantity = words.len();
+    if !(1..=MAX_WRITE_REGISTERS).contains(&quantity) {
+        return Err(EncodeError::Quantity(
+            u16::try_from(quantity).unwrap_or(u16::MAX),
+        ));
+    }
+    let len = 6 + quantity * 2;
+    let buf = buf.get_mut(..len).ok_or(EncodeError::BufferSize)?;
+    buf[0] = FunctionCode::WriteMultipleRegisters.value();
+    write_u16(&mut buf[1..], address);
+    write_u16(&mut buf[3..], quantity as u16);
+    buf[5] = (quantity * 2) as u8;
+    let mut written = 0;
+    for (word, bytes) in words.zip(buf[6..].chunks_exact_mut(2)) {
+        write_u16(bytes, word);
+        written += 1;
+    }
+    if written != quantity {
+        // The iterator did not yield as many words as it claimed.
+        return Err(EncodeError::Quantity(written as u16));
+    }
+    Ok(len)
+}
+
+/// An encoded ADU whose payload is not copied.
+///
+/// The `head` (and for RTU the CRC in `tail`) is encoded into a small
+/// buffer while the `payload` is borrowed from the request, e.g. from
+/// the register image of the application. The segments have to be
+/// transmitted in the order `head`, `payload`, `tail`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segments<'p, 'b> {
+    /// The header and all PDU fields in front of the payload.
+    pub head: &'b [u8],
+    /// The payload of the request.
+    pub payload: &'p [u8],
+    /// The bytes that follow the payload.
+    pub tail: &'b [u8],
+}
+
+impl Segments<'_, '_> {
+    /// Total number of bytes of the ADU.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.head.len() + self.payload.len() + self.tail.len()
+    }
+
+    /// Returns `true` if there are no bytes to transmit.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All segments in the order of transmission, e.g. for vectored I/O.
+    #[must_use]
+    pub const fn as_slices(&self) -> [&[u8]; 3] {
+        [self.head, self.payload, self.tail]
     }
 }
 
 /// Encode a struct into a buffer.
 pub trait Encode {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize>;
+    /// Encode into `buf` and return the number of bytes written.
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError>;
+
+    /// Number of bytes that [`encode`](Self::encode) writes,
+    /// e.g. to size the buffer in advance.
+    fn encoded_len(&self) -> usize;
+
+    /// Encode into `buf` and return the written bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use modbus_core::{Encode, Request};
+    ///
+    /// let req = Request::ReadHoldingRegisters(0x10, 2);
+    /// let buf = &mut [0; 8];
+    /// assert_eq!(req.encoded_len(), 5);
+    /// assert_eq!(req.encode_checked(buf).unwrap(), &[0x03, 0x00, 0x10, 0x00, 0x02]);
+    /// assert!(req.encode_checked(&mut buf[..4]).is_err());
+    /// ```
+    fn encode_checked<'b>(&self, buf: &'b mut [u8]) -> Result<&'b [u8], EncodeError> {
+        let buf = buf
+            .get_mut(..self.encoded_len())
+            .ok_or(EncodeError::BufferSize)?;
+        let len = self.encode(buf)?;
+        Ok(&buf[..len])
+    }
+}
+
+impl Encode for Request<'_> {
+    fn encoded_len(&self) -> usize {
+        self.pdu_len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let payload = self.payload()?;
+        let pdu_len = self.pdu_len();
+        let buf = buf.get_mut(..pdu_len).ok_or(EncodeError::BufferSize)?;
+        let head_len = self.encode_head(buf);
+        buf[head_len..].copy_from_slice(payload);
+        Ok(pdu_len)
+    }
 }
 
-impl<'r> Encode for Request<'r> {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize> {
-        if buf.len() < self.pdu_len() {
-            return Err(Error::BufferSize);
+impl<'r> Request<'r> {
+    /// The bytes at the end of the PDU that are borrowed from the request.
+    ///
+    /// Fails if the request cannot be encoded, i.e. if it exceeds
+    /// a PDU or if its quantity does not match the payload.
+    pub(crate) fn payload(&self) -> Result<&'r [u8], EncodeError> {
+        if self.pdu_len() > MAX_PDU_LEN {
+            // Only possible with values that have not been checked,
+            // e.g. of `RequestOwned`.
+            return Err(EncodeError::BufferSize);
         }
+        let payload = match *self {
+            Self::WriteMultipleCoils(_, coils) => coils.packed()?,
+            Self::WriteMultipleRegisters(_, words)
+            | Self::ReadWriteMultipleRegisters(_, _, _, words) => {
+                if words.data.len() != words.len() * 2 {
+                    return Err(EncodeError::LengthMismatch(
+                        words.len() * 2,
+                        words.data.len(),
+                    ));
+                }
+                words.data
+            }
+            Self::ReadFileRecord(FileRecordRequests { data })
+            | Self::WriteFileRecord(FileRecords { data })
+            | Self::Custom(RawPdu { data, .. }) => data,
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(_, words) => words.data,
+            _ => &[],
+        };
+        Ok(payload)
+    }
+
+    /// Encode all fields in front of the payload and return their length.
+    ///
+    /// `buf` must be large enough for these fields and
+    /// [`payload`](Self::payload) must have succeeded.
+    pub(crate) fn encode_head(&self, buf: &mut [u8]) -> usize {
         buf[0] = FunctionCode::from(*self).value();
         match self {
             Self::ReadCoils(address, payload)
@@ -236,107 +471,195 @@ impl<'r> Encode for Request<'r> {
             | Self::ReadInputRegisters(address, payload)
             | Self::ReadHoldingRegisters(address, payload)
             | Self::WriteSingleRegister(address, payload) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
-                BigEndian::write_u16(&mut buf[3..], *payload);
+                write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[3..], *payload);
             }
             Self::WriteSingleCoil(address, state) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
-                BigEndian::write_u16(&mut buf[3..], bool_to_u16_coil(*state));
+                write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[3..], bool_to_u16_coil(*state));
             }
             Self::WriteMultipleCoils(address, coils) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[1..], *address);
                 let len = coils.len();
-                BigEndian::write_u16(&mut buf[3..], len as u16);
+                write_u16(&mut buf[3..], len as u16);
                 buf[5] = coils.packed_len() as u8;
-                coils.copy_to(&mut buf[6..]);
             }
             Self::WriteMultipleRegisters(address, words) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[1..], *address);
                 let len = words.len();
-                BigEndian::write_u16(&mut buf[3..], len as u16);
+                write_u16(&mut buf[3..], len as u16);
                 buf[5] = len as u8 * 2;
-                for (idx, byte) in words.data.iter().enumerate() {
-                    buf[idx + 6] = *byte;
-                }
             }
             Self::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => {
-                BigEndian::write_u16(&mut buf[1..], *read_address);
-                BigEndian::write_u16(&mut buf[3..], *quantity);
-                BigEndian::write_u16(&mut buf[5..], *write_address);
+                write_u16(&mut buf[1..], *read_address);
+                write_u16(&mut buf[3..], *quantity);
+                write_u16(&mut buf[5..], *write_address);
                 let n = words.len();
-                BigEndian::write_u16(&mut buf[7..], n as u16);
+                write_u16(&mut buf[7..], n as u16);
                 buf[9] = n as u8 * 2;
-                for (idx, byte) in words.data.iter().enumerate() {
-                    buf[idx + 10] = *byte;
-                }
             }
-            Self::Custom(_, custom_data) => {
-                custom_data.iter().enumerate().for_each(|(idx, d)| {
-                    buf[idx + 1] = *d;
-                });
+            Self::ReadFifoQueue(address) => {
+                write_u16(&mut buf[1..], *address);
             }
+            Self::ReadFileRecord(FileRecordRequests { data })
+            | Self::WriteFileRecord(FileRecords { data }) => {
+                buf[1] = data.len() as u8;
+            }
+            Self::Custom(_) => {}
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus
+            | Self::GetCommEventCounter
+            | Self::GetCommEventLog
+            | Self::ReportServerId => {}
             #[cfg(feature = "rtu")]
-            _ => panic!(),
+            Self::Diagnostics(sub_function, _) => {
+                write_u16(&mut buf[1..], *sub_function);
+            }
         }
-        Ok(self.pdu_len())
+        self.pdu_len() - self.payload().map_or(0, <[u8]>::len)
     }
 }
 
-impl<'r> Encode for Response<'r> {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize> {
-        if buf.len() < self.pdu_len() {
-            return Err(Error::BufferSize);
+impl Encode for Response<'_> {
+    fn encoded_len(&self) -> usize {
+        self.pdu_len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let payload = self.payload()?;
+        let pdu_len = self.pdu_len();
+        let buf = buf.get_mut(..pdu_len).ok_or(EncodeError::BufferSize)?;
+        let head_len = self.encode_head(buf);
+        buf[head_len..].copy_from_slice(payload);
+        Ok(pdu_len)
+    }
+}
+
+impl<'r> Response<'r> {
+    /// The bytes at the end of the PDU that are borrowed from the response.
+    ///
+    /// Fails if the response cannot be encoded, i.e. if it exceeds
+    /// a PDU or if its quantity does not match the payload.
+    pub(crate) fn payload(&self) -> Result<&'r [u8], EncodeError> {
+        if self.pdu_len() > MAX_PDU_LEN {
+            // Only possible with values that have not been checked,
+            // e.g. of `ResponseOwned`.
+            return Err(EncodeError::BufferSize);
         }
+        let payload = match *self {
+            Self::ReadCoils(coils) | Self::ReadDiscreteInputs(coils) => coils.packed()?,
+            Self::ReadInputRegisters(words)
+            | Self::ReadHoldingRegisters(words)
+            | Self::ReadWriteMultipleRegisters(words)
+            | Self::ReadFifoQueue(words) => words.packed()?,
+            Self::ReadFileRecord(FileRecordData { data })
+            | Self::WriteFileRecord(FileRecords { data })
+            | Self::Custom(RawPdu { data, .. }) => data,
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(_, words) => words.data,
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog(_, _, _, events) => events,
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId(_, _) => {
+                return Err(EncodeError::FnCode(FunctionCode::from(*self).value()));
+            }
+            _ => &[],
+        };
+        Ok(payload)
+    }
 
+    /// Encode all fields in front of the payload and return their length.
+    ///
+    /// `buf` must be large enough for these fields and
+    /// [`payload`](Self::payload) must have succeeded.
+    pub(crate) fn encode_head(&self, buf: &mut [u8]) -> usize {
         buf[0] = FunctionCode::from(*self).value();
         match self {
             Self::ReadCoils(coils) | Self::ReadDiscreteInputs(coils) => {
                 buf[1] = coils.packed_len() as u8;
-                coils.copy_to(&mut buf[2..]);
+                2
             }
             Self::ReadInputRegisters(registers)
             | Self::ReadHoldingRegisters(registers)
             | Self::ReadWriteMultipleRegisters(registers) => {
                 buf[1] = (registers.len() * 2) as u8;
-                registers.copy_to(&mut buf[2..]);
+                2
+            }
+            Self::ReadFifoQueue(registers) => {
+                write_u16(&mut buf[1..], (2 + registers.len() * 2) as u16);
+                write_u16(&mut buf[3..], registers.len() as u16);
+                5
             }
-            Self::WriteSingleCoil(address) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
+            Self::ReadFileRecord(FileRecordData { data })
+            | Self::WriteFileRecord(FileRecords { data }) => {
+                buf[1] = data.len() as u8;
+                2
+            }
+            Self::WriteSingleCoil(address, state) => {
+                write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[3..], bool_to_u16_coil(*state));
+                5
             }
             Self::WriteMultipleCoils(address, payload)
             | Self::WriteMultipleRegisters(address, payload)
             | Self::WriteSingleRegister(address, payload) => {
-                BigEndian::write_u16(&mut buf[1..], *address);
-                BigEndian::write_u16(&mut buf[3..], *payload);
-            }
-            Self::Custom(_, custom_data) => {
-                for (idx, d) in custom_data.iter().enumerate() {
-                    buf[idx + 1] = *d;
-                }
+                write_u16(&mut buf[1..], *address);
+                write_u16(&mut buf[3..], *payload);
+                5
             }
+            Self::Custom(_) => 1,
+            #[cfg(feature = "rtu")]
             Self::ReadExceptionStatus(error_code) => {
                 buf[1] = *error_code;
+                2
+            }
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(sub_function, _) => {
+                write_u16(&mut buf[1..], *sub_function);
+                3
+            }
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter(status, event_count) => {
+                write_u16(&mut buf[1..], *status);
+                write_u16(&mut buf[3..], *event_count);
+                5
             }
             #[cfg(feature = "rtu")]
-            _ => {
-                // TODO:
-                unimplemented!()
+            Self::GetCommEventLog(status, event_count, message_count, events) => {
+                buf[1] = (6 + events.len()) as u8;
+                write_u16(&mut buf[2..], *status);
+                write_u16(&mut buf[4..], *event_count);
+                write_u16(&mut buf[6..], *message_count);
+                8
             }
+            // Rejected by `payload`, because it cannot be encoded yet.
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId(_, _) => 1,
         }
-        Ok(self.pdu_len())
     }
 }
 
-impl<'r> Encode for RequestPdu<'r> {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize> {
+impl Encode for RequestPdu<'_> {
+    fn encoded_len(&self) -> usize {
+        self.0.pdu_len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
         self.0.encode(buf)
     }
 }
 
-impl<'r> Encode for ResponsePdu<'r> {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize> {
+impl Encode for ResponsePdu<'_> {
+    fn encoded_len(&self) -> usize {
+        match self.0 {
+            Ok(rsp) => rsp.pdu_len(),
+            Err(ex) => ex.encoded_len(),
+        }
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
         if buf.is_empty() {
-            return Err(Error::BufferSize);
+            return Err(EncodeError::BufferSize);
         }
         match self.0 {
             Ok(res) => res.encode(buf),
@@ -346,44 +669,84 @@ impl<'r> Encode for ResponsePdu<'r> {
 }
 
 impl Encode for ExceptionResponse {
-    fn encode(&self, buf: &mut [u8]) -> Result<usize> {
-        if buf.is_empty() {
-            return Err(Error::BufferSize);
-        }
-        let [code, ex]: [u8; 2] = (*self).into();
-        buf[0] = code;
-        buf[1] = ex;
+    fn encoded_len(&self) -> usize {
+        2
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let head = self.head()?;
+        buf.get_mut(..2)
+            .ok_or(EncodeError::BufferSize)?
+            .copy_from_slice(&head);
         Ok(2)
     }
 }
 
-const fn min_request_pdu_len(fn_code: FunctionCode) -> usize {
-    use FunctionCode as F;
-    match fn_code {
-        F::ReadCoils
-        | F::ReadDiscreteInputs
-        | F::ReadInputRegisters
-        | F::WriteSingleCoil
-        | F::ReadHoldingRegisters
-        | F::WriteSingleRegister => 5,
-        F::WriteMultipleCoils | F::WriteMultipleRegisters => 6,
-        F::ReadWriteMultipleRegisters => 10,
-        _ => 1,
+impl ExceptionResponse {
+    /// The error code and the exception code.
+    ///
+    /// Fails if the function code is already an error code.
+    pub(crate) fn head(self) -> Result<[u8; 2], EncodeError> {
+        let fn_code = self.function.value();
+        if fn_code >= 0x80 {
+            return Err(EncodeError::FnCode(fn_code));
+        }
+        Ok(self.into())
+    }
+}
+
+/// Decode the sub-function code and the data of a diagnostics PDU.
+#[cfg(feature = "rtu")]
+fn decode_diagnostics(bytes: &[u8]) -> Result<(SubFunctionCode, Data<'_>)> {
+    let sub_function = read_u16(&bytes[1..3]);
+    // The framing layer only delimits PDUs with a single data word
+    let data = &bytes[3..];
+    if data.len() != 2 {
+        return Err(DecodeError::ByteCount(data.len() as u8));
     }
+    let words = Data { data, quantity: 1 };
+    Ok((sub_function, words))
 }
 
-const fn min_response_pdu_len(fn_code: FunctionCode) -> usize {
-    use FunctionCode as F;
-    match fn_code {
-        F::ReadCoils
-        | F::ReadDiscreteInputs
-        | F::ReadInputRegisters
-        | F::ReadHoldingRegisters
-        | F::ReadWriteMultipleRegisters => 2,
-        F::WriteSingleCoil => 3,
-        F::WriteMultipleCoils | F::WriteSingleRegister | F::WriteMultipleRegisters => 5,
-        _ => 1,
+/// The MEI type of device identification requests and responses.
+const MEI_READ_DEVICE_ID: u8 = 0x0E;
+
+/// The length of a MEI (`0x2B`) request `pdu`.
+///
+/// Only device identification can be delimited.
+fn mei_request_pdu_len(pdu: &[u8]) -> Result<Option<usize>> {
+    match pdu.get(1) {
+        None => Ok(None),
+        // MEI type, read device ID code and object ID
+        Some(&MEI_READ_DEVICE_ID) => Ok(Some(4)),
+        Some(_) => Err(DecodeError::FnCode(pdu[0])),
+    }
+}
+
+/// The length of a MEI (`0x2B`) response `pdu`.
+///
+/// Only device identification can be delimited.
+/// Its length depends on the lengths of the objects.
+fn mei_response_pdu_len(pdu: &[u8]) -> Result<Option<usize>> {
+    match pdu.get(1) {
+        None => return Ok(None),
+        Some(&MEI_READ_DEVICE_ID) => {}
+        Some(_) => return Err(DecodeError::FnCode(pdu[0])),
+    }
+    // MEI type, read device ID code, conformity level,
+    // more follows, next object ID and number of objects
+    let Some(&objects) = pdu.get(6) else {
+        return Ok(None);
+    };
+    let mut len = 7;
+    for _ in 0..objects {
+        // object ID and object length
+        let Some(&object_len) = pdu.get(len + 1) else {
+            return Ok(None);
+        };
+        len = checked_len(len + 2, object_len.into())?;
     }
+    Ok(Some(len))
 }
 
 #[cfg(test)]
@@ -417,10 +780,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reject_lengths_that_overflow() {
+        assert_eq!(checked_len(7, 0xFFFF), Ok(0x1_0006));
+        assert_eq!(checked_len(3, usize::MAX), Err(DecodeError::LengthOverflow));
+        assert_eq!(checked_words_len(2, 3), Ok(8));
+        assert_eq!(
+            checked_words_len(0, usize::MAX / 2 + 1),
+            Err(DecodeError::LengthOverflow)
+        );
+    }
+
     #[test]
     fn test_min_request_pdu_len() {
         use FunctionCode::*;
 
+        let min_request_pdu_len =
+            |f| FunctionDescriptor::of(f).map_or(1, FunctionDescriptor::min_request_len);
+
         assert_eq!(min_request_pdu_len(ReadCoils), 5);
         assert_eq!(min_request_pdu_len(ReadDiscreteInputs), 5);
         assert_eq!(min_request_pdu_len(ReadInputRegisters), 5);
@@ -430,21 +807,26 @@ mod tests {
         assert_eq!(min_request_pdu_len(WriteMultipleCoils), 6);
         assert_eq!(min_request_pdu_len(WriteMultipleRegisters), 6);
         assert_eq!(min_request_pdu_len(ReadWriteMultipleRegisters), 10);
+        assert_eq!(min_request_pdu_len(ReadFifoQueue), 3);
     }
 
     #[test]
     fn test_min_response_pdu_len() {
         use FunctionCode::*;
 
+        let min_response_pdu_len =
+            |f| FunctionDescriptor::of(f).map_or(1, FunctionDescriptor::min_response_len);
+
         assert_eq!(min_response_pdu_len(ReadCoils), 2);
         assert_eq!(min_response_pdu_len(ReadDiscreteInputs), 2);
         assert_eq!(min_response_pdu_len(ReadInputRegisters), 2);
-        assert_eq!(min_response_pdu_len(WriteSingleCoil), 3);
+        assert_eq!(min_response_pdu_len(WriteSingleCoil), 5);
         assert_eq!(min_response_pdu_len(ReadHoldingRegisters), 2);
         assert_eq!(min_response_pdu_len(WriteSingleRegister), 5);
         assert_eq!(min_response_pdu_len(WriteMultipleCoils), 5);
         assert_eq!(min_response_pdu_len(WriteMultipleRegisters), 5);
         assert_eq!(min_response_pdu_len(ReadWriteMultipleRegisters), 2);
+        assert_eq!(min_response_pdu_len(ReadFifoQueue), 5);
     }
 
     mod serialize_requests {
@@ -612,12 +994,87 @@ mod tests {
             assert_eq!(bytes[13], 0x12);
         }
 
+        #[test]
+        fn read_fifo_queue() {
+            let bytes = &mut [0; 3];
+            Request::ReadFifoQueue(0x04DE).encode(bytes).unwrap();
+            assert_eq!(bytes, &[0x18, 0x04, 0xDE]);
+        }
+
+        #[test]
+        fn read_file_record() {
+            let buf = &mut [0; 7];
+            let requests = FileRecordRequests::from_requests(
+                &[FileRecordRequest {
+                    file_number: 4,
+                    record_number: 1,
+                    record_length: 2,
+                }],
+                buf,
+            )
+            .unwrap();
+            let bytes = &mut [0; 9];
+            assert_eq!(Request::ReadFileRecord(requests).encode(bytes).unwrap(), 9);
+            assert_eq!(
+                bytes,
+                &[0x14, 0x07, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02]
+            );
+        }
+
+        #[test]
+        fn write_file_record() {
+            let records = FileRecords {
+                data: &[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF],
+            };
+            let bytes = &mut [0; 11];
+            assert_eq!(Request::WriteFileRecord(records).encode(bytes).unwrap(), 11);
+            assert_eq!(
+                bytes,
+                &[0x15, 0x09, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF]
+            );
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn read_exception_status() {
+            let bytes = &mut [0; 1];
+            assert_eq!(Request::ReadExceptionStatus.encode(bytes).unwrap(), 1);
+            assert_eq!(bytes, &[0x07]);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn requests_without_data() {
+            let bytes = &mut [0; 1];
+            assert_eq!(Request::GetCommEventCounter.encode(bytes).unwrap(), 1);
+            assert_eq!(bytes, &[0x0B]);
+            assert_eq!(Request::GetCommEventLog.encode(bytes).unwrap(), 1);
+            assert_eq!(bytes, &[0x0C]);
+            assert_eq!(Request::ReportServerId.encode(bytes).unwrap(), 1);
+            assert_eq!(bytes, &[0x11]);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn diagnostics() {
+            let bytes = &mut [0; 5];
+            let words = Data {
+                data: &[0xA5, 0x37],
+                quantity: 1,
+            };
+            Request::Diagnostics(0x00, words).encode(bytes).unwrap();
+            assert_eq!(bytes, &[0x08, 0x00, 0x00, 0xA5, 0x37]);
+        }
+
         #[test]
         fn custom() {
             let bytes = &mut [0; 5];
-            Request::Custom(FunctionCode::Custom(0x55), &[0xCC, 0x88, 0xAA, 0xFF])
-                .encode(bytes)
-                .unwrap();
+            Request::Custom(RawPdu {
+                function: FunctionCode::Custom(0x55),
+                data: &[0xCC, 0x88, 0xAA, 0xFF],
+            })
+            .encode(bytes)
+            .unwrap();
             assert_eq!(bytes[0], 0x55);
             assert_eq!(bytes[1], 0xCC);
             assert_eq!(bytes[2], 0x88);
@@ -728,7 +1185,7 @@ mod tests {
                 assert_eq!(data.get(1), Some(0xEF12));
             } else {
                 unreachable!()
-            };
+            }
         }
 
         #[test]
@@ -754,7 +1211,97 @@ mod tests {
                 assert_eq!(data.get(1), Some(0xEF12));
             } else {
                 unreachable!()
+            }
+        }
+
+        #[test]
+        fn read_fifo_queue() {
+            let bytes: &[u8] = &[0x18, 0x04];
+            assert!(Request::try_from(bytes).is_err());
+            let bytes: &[u8] = &[0x18, 0x04, 0xDE];
+            let req = Request::try_from(bytes).unwrap();
+            assert_eq!(req, Request::ReadFifoQueue(0x04DE));
+        }
+
+        #[test]
+        fn read_file_record() {
+            let bytes: &[u8] = &[0x14, 0x07, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02];
+            let req = Request::try_from(bytes).unwrap();
+            let Request::ReadFileRecord(requests) = req else {
+                panic!("unexpected request: {req:?}");
             };
+            assert_eq!(
+                requests.iter().next(),
+                Some(FileRecordRequest {
+                    file_number: 4,
+                    record_number: 1,
+                    record_length: 2,
+                })
+            );
+            let broken_bytes: &[u8] = &[0x14, 0x07, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00];
+            assert!(Request::try_from(broken_bytes).is_err());
+            let broken_bytes: &[u8] = &[0x14, 0x07, 0x05, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02];
+            assert_eq!(
+                Request::try_from(broken_bytes).err(),
+                Some(DecodeError::ReferenceType(0x05))
+            );
+        }
+
+        #[test]
+        fn write_file_record() {
+            let bytes: &[u8] = &[
+                0x15, 0x09, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF,
+            ];
+            let req = Request::try_from(bytes).unwrap();
+            let Request::WriteFileRecord(records) = req else {
+                panic!("unexpected request: {req:?}");
+            };
+            let record = records.iter().next().unwrap();
+            assert_eq!(record.file_number, 4);
+            assert_eq!(record.record_number, 7);
+            assert_eq!(record.data.get(0), Some(0x06AF));
+            let broken_bytes: &[u8] = &[
+                0x15, 0x09, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x02, 0x06, 0xAF,
+            ];
+            assert!(Request::try_from(broken_bytes).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn read_exception_status() {
+            let bytes: &[u8] = &[0x07];
+            let req = Request::try_from(bytes).unwrap();
+            assert_eq!(req, Request::ReadExceptionStatus);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn requests_without_data() {
+            let bytes: &[u8] = &[0x0B];
+            assert_eq!(Request::try_from(bytes), Ok(Request::GetCommEventCounter));
+            let bytes: &[u8] = &[0x0C];
+            assert_eq!(Request::try_from(bytes), Ok(Request::GetCommEventLog));
+            let bytes: &[u8] = &[0x11];
+            assert_eq!(Request::try_from(bytes), Ok(Request::ReportServerId));
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn diagnostics() {
+            let bytes: &[u8] = &[0x08, 0x00, 0x00, 0xA5, 0x37];
+            let req = Request::try_from(bytes).unwrap();
+            assert_eq!(
+                req,
+                Request::Diagnostics(
+                    0x00,
+                    Data {
+                        data: &[0xA5, 0x37],
+                        quantity: 1
+                    }
+                )
+            );
+            let broken_bytes: &[u8] = &[0x08, 0x00, 0x00, 0xA5];
+            assert!(Request::try_from(broken_bytes).is_err());
         }
 
         #[test]
@@ -763,7 +1310,10 @@ mod tests {
             let req = Request::try_from(bytes).unwrap();
             assert_eq!(
                 req,
-                Request::Custom(FunctionCode::Custom(0x55), &[0xCC, 0x88, 0xAA, 0xFF])
+                Request::Custom(RawPdu {
+                    function: FunctionCode::Custom(0x55),
+                    data: &[0xCC, 0x88, 0xAA, 0xFF],
+                })
             );
         }
     }
@@ -786,6 +1336,22 @@ mod tests {
             assert_eq!(bytes[2], 0b_0000_1001);
         }
 
+        #[test]
+        fn encoded_len() {
+            let ex = ExceptionResponse::new(FunctionCode::ReadCoils, Exception::IllegalFunction);
+            let pdu = ResponsePdu(Err(ex));
+            assert_eq!(pdu.encoded_len(), 2);
+            assert_eq!(pdu.encode(&mut [0]), Err(EncodeError::BufferSize));
+            assert_eq!(pdu.encode_checked(&mut [0; 4]).unwrap(), &[0x81, 0x01]);
+
+            let pdu = ResponsePdu(Ok(Response::WriteSingleRegister(0x10, 0xABCD)));
+            assert_eq!(pdu.encoded_len(), 5);
+            assert_eq!(
+                pdu.encode_checked(&mut [0; 4]),
+                Err(EncodeError::BufferSize)
+            );
+        }
+
         #[test]
         fn read_discrete_inputs() {
             let buff: &mut [u8] = &mut [0];
@@ -801,12 +1367,14 @@ mod tests {
 
         #[test]
         fn write_single_coil() {
-            let res = Response::WriteSingleCoil(0x33);
-            let bytes = &mut [0, 0, 0];
+            let res = Response::WriteSingleCoil(0x33, true);
+            let bytes = &mut [0, 0, 0, 0, 0];
             res.encode(bytes).unwrap();
             assert_eq!(bytes[0], 5);
             assert_eq!(bytes[1], 0x00);
             assert_eq!(bytes[2], 0x33);
+            assert_eq!(bytes[3], 0xFF);
+            assert_eq!(bytes[4], 0x00);
         }
 
         #[test]
@@ -891,9 +1459,80 @@ mod tests {
             assert_eq!(bytes[3], 0x34);
         }
 
+        #[test]
+        fn read_fifo_queue() {
+            let buf: &mut [u8] = &mut [0; 4];
+            let res = Response::ReadFifoQueue(Data::from_words(&[0x01B8, 0x1284], buf).unwrap());
+            let bytes = &mut [0; 9];
+            assert_eq!(res.encode(bytes).unwrap(), 9);
+            assert_eq!(
+                bytes,
+                &[0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]
+            );
+        }
+
+        #[test]
+        fn read_file_record() {
+            let words = &mut [0; 4];
+            let record = Data::from_words(&[0x0DFE, 0x0020], words).unwrap();
+            let buf = &mut [0; 6];
+            let res =
+                Response::ReadFileRecord(FileRecordData::from_records(&[record], buf).unwrap());
+            let bytes = &mut [0; 8];
+            assert_eq!(res.encode(bytes).unwrap(), 8);
+            assert_eq!(bytes, &[0x14, 0x06, 0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20]);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn read_exception_status() {
+            let bytes = &mut [0; 2];
+            assert_eq!(
+                Response::ReadExceptionStatus(0x6D).encode(bytes).unwrap(),
+                2
+            );
+            assert_eq!(bytes, &[0x07, 0x6D]);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn get_comm_event_counter() {
+            let bytes = &mut [0; 5];
+            let rsp = Response::GetCommEventCounter(0xFFFF, 0x0108);
+            assert_eq!(rsp.encode(bytes).unwrap(), 5);
+            assert_eq!(bytes, &[0x0B, 0xFF, 0xFF, 0x01, 0x08]);
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn get_comm_event_log() {
+            let bytes = &mut [0; 10];
+            let rsp = Response::GetCommEventLog(0x0000, 0x0108, 0x0121, &[0x20, 0x00]);
+            assert_eq!(rsp.encode(bytes).unwrap(), 10);
+            assert_eq!(
+                bytes,
+                &[0x0C, 0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20, 0x00]
+            );
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn diagnostics() {
+            let bytes = &mut [0; 5];
+            let words = Data {
+                data: &[0x00, 0x2A],
+                quantity: 1,
+            };
+            Response::Diagnostics(0x0B, words).encode(bytes).unwrap();
+            assert_eq!(bytes, &[0x08, 0x00, 0x0B, 0x00, 0x2A]);
+        }
+
         #[test]
         fn custom() {
-            let res = Response::Custom(FunctionCode::Custom(0x55), &[0xCC, 0x88, 0xAA, 0xFF]);
+            let res = Response::Custom(RawPdu {
+                function: FunctionCode::Custom(0x55),
+                data: &[0xCC, 0x88, 0xAA, 0xFF],
+            });
             let bytes = &mut [0; 5];
             res.encode(bytes).unwrap();
             assert_eq!(bytes[0], 0x55);
@@ -907,6 +1546,36 @@ mod tests {
     mod deserialize_responses {
         use super::*;
 
+        #[test]
+        fn truncated_exception_response() {
+            let bytes: &[u8] = &[0x83];
+            let err = ResponsePdu::try_from(bytes).err().unwrap();
+            assert_eq!(
+                err,
+                DecodeError::Truncated(DecodeContext {
+                    decoder: DecoderType::Response,
+                    transport: None,
+                    fn_code: Some(0x83),
+                    offset: 1,
+                })
+            );
+        }
+
+        #[test]
+        fn truncated_error_in_adu() {
+            let bytes: &[u8] = &[0x03, 0x04, 0x12];
+            let err = Response::try_from(bytes).err().unwrap();
+            assert_eq!(
+                err.in_adu(Transport::Tcp, 7),
+                DecodeError::Truncated(DecodeContext {
+                    decoder: DecoderType::Response,
+                    transport: Some(Transport::Tcp),
+                    fn_code: Some(0x03),
+                    offset: 9,
+                })
+            );
+        }
+
         #[test]
         fn read_coils() {
             let bytes: &[u8] = &[1, 1, 0b_0000_1001];
@@ -933,6 +1602,31 @@ mod tests {
             );
         }
 
+        #[test]
+        fn read_coils_with_request() {
+            let bytes: &[u8] = &[1, 2, 0b_0000_1001, 0b_0000_0001];
+            let rsp = Response::try_from_with_request(bytes, &Request::ReadCoils(0, 9)).unwrap();
+            assert_eq!(
+                rsp,
+                Response::ReadCoils(Coils {
+                    quantity: 9,
+                    data: &[0b_0000_1001, 0b_0000_0001]
+                })
+            );
+            let rsp = Response::try_from_with_request(bytes, &Request::ReadCoils(0, 8));
+            assert_eq!(rsp, Err(DecodeError::ByteCount(2)));
+            let bytes: &[u8] = &[2, 1, 0b_0000_1001];
+            let rsp =
+                Response::try_from_with_request(bytes, &Request::ReadDiscreteInputs(0, 4)).unwrap();
+            assert_eq!(
+                rsp,
+                Response::ReadDiscreteInputs(Coils {
+                    quantity: 4,
+                    data: &[0b_0000_1001]
+                })
+            );
+        }
+
         #[test]
         fn read_coils_with_invalid_byte_count() {
             let bytes: &[u8] = &[1, 2, 0x6];
@@ -954,11 +1648,11 @@ mod tests {
 
         #[test]
         fn write_single_coil() {
-            let bytes: &[u8] = &[5, 0x00, 0x33];
+            let bytes: &[u8] = &[5, 0x00, 0x33, 0x00, 0x00];
             let rsp = Response::try_from(bytes).unwrap();
-            assert_eq!(rsp, Response::WriteSingleCoil(0x33));
+            assert_eq!(rsp, Response::WriteSingleCoil(0x33, false));
 
-            let broken_bytes: &[u8] = &[5, 0x00];
+            let broken_bytes: &[u8] = &[5, 0x00, 0x33];
             assert!(Response::try_from(broken_bytes).is_err());
         }
 
@@ -1030,17 +1724,291 @@ mod tests {
             assert!(Response::try_from(broken_bytes).is_err());
         }
 
+        #[test]
+        fn read_fifo_queue() {
+            let bytes: &[u8] = &[0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84];
+            let rsp = Response::try_from(bytes).unwrap();
+            assert_eq!(
+                rsp,
+                Response::ReadFifoQueue(Data {
+                    quantity: 2,
+                    data: &[0x01, 0xB8, 0x12, 0x84]
+                })
+            );
+            let broken_bytes: &[u8] = &[0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12];
+            assert!(Response::try_from(broken_bytes).is_err());
+            let broken_bytes: &[u8] = &[0x18, 0x00, 0x06, 0x00, 0x03, 0x01, 0xB8, 0x12, 0x84];
+            assert!(Response::try_from(broken_bytes).is_err());
+        }
+
+        #[test]
+        fn read_file_record() {
+            let bytes: &[u8] = &[0x14, 0x06, 0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20];
+            let rsp = Response::try_from(bytes).unwrap();
+            let Response::ReadFileRecord(records) = rsp else {
+                panic!("unexpected response: {rsp:?}");
+            };
+            let record = records.iter().next().unwrap();
+            assert_eq!(record.len(), 2);
+            assert_eq!(record.get(0), Some(0x0DFE));
+            assert_eq!(record.get(1), Some(0x0020));
+            let broken_bytes: &[u8] = &[0x14, 0x06, 0x05, 0x06, 0x0D, 0xFE, 0x00];
+            assert!(Response::try_from(broken_bytes).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn read_exception_status() {
+            let bytes: &[u8] = &[0x07, 0x6D];
+            let rsp = Response::try_from(bytes).unwrap();
+            assert_eq!(rsp, Response::ReadExceptionStatus(0x6D));
+            let broken_bytes: &[u8] = &[0x07];
+            assert!(Response::try_from(broken_bytes).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn get_comm_event_counter() {
+            let bytes: &[u8] = &[0x0B, 0xFF, 0xFF, 0x01, 0x08];
+            let rsp = Response::try_from(bytes).unwrap();
+            assert_eq!(rsp, Response::GetCommEventCounter(0xFFFF, 0x0108));
+            let broken_bytes: &[u8] = &[0x0B, 0xFF, 0xFF, 0x01];
+            assert!(Response::try_from(broken_bytes).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn get_comm_event_log() {
+            let bytes: &[u8] = &[0x0C, 0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20, 0x00];
+            let rsp = Response::try_from(bytes).unwrap();
+            assert_eq!(
+                rsp,
+                Response::GetCommEventLog(0x0000, 0x0108, 0x0121, &[0x20, 0x00])
+            );
+            // The event `0x20` of the example of the specification is not defined
+            assert_eq!(
+                Response::try_from_with_options(bytes, &DecodeOptions::strict()),
+                Err(DecodeError::CommEvent(0x20))
+            );
+            let bytes: &[u8] = &[0x0C, 0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x60, 0x00];
+            let Ok(Response::GetCommEventLog(_, _, _, events)) =
+                Response::try_from_with_options(bytes, &DecodeOptions::strict())
+            else {
+                panic!("invalid event log");
+            };
+            let events: [_; 2] = [events[0].try_into(), events[1].try_into()];
+            assert_eq!(
+                events,
+                [
+                    Ok(CommEvent::Send(SendEvent {
+                        listen_only: true,
+                        ..Default::default()
+                    })),
+                    Ok(CommEvent::Restart)
+                ]
+            );
+
+            let short_byte_count: &[u8] = &[0x0C, 0x05, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21];
+            assert_eq!(
+                Response::try_from(short_byte_count),
+                Err(DecodeError::ByteCount(0x05))
+            );
+            let truncated: &[u8] = &[0x0C, 0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20];
+            assert!(Response::try_from(truncated).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "rtu")]
+        fn diagnostics() {
+            let bytes: &[u8] = &[0x08, 0x00, 0x0B, 0x00, 0x2A];
+            let rsp = Response::try_from(bytes).unwrap();
+            assert_eq!(
+                rsp,
+                Response::Diagnostics(
+                    0x0B,
+                    Data {
+                        data: &[0x00, 0x2A],
+                        quantity: 1
+                    }
+                )
+            );
+            // Only a single data word can be delimited
+            let broken_bytes: &[u8] = &[0x08, 0x00, 0x0B];
+            assert!(matches!(
+                Response::try_from(broken_bytes),
+                Err(DecodeError::Truncated(_))
+            ));
+            let broken_bytes: &[u8] = &[0x08, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x2B];
+            assert_eq!(
+                Response::try_from(broken_bytes),
+                Err(DecodeError::ByteCount(4))
+            );
+        }
+
         #[test]
         fn custom() {
             let bytes: &[u8] = &[0x55, 0xCC, 0x88, 0xAA, 0xFF];
             let rsp = Response::try_from(bytes).unwrap();
             assert_eq!(
                 rsp,
-                Response::Custom(FunctionCode::Custom(0x55), &[0xCC, 0x88, 0xAA, 0xFF])
+                Response::Custom(RawPdu {
+                    function: FunctionCode::Custom(0x55),
+                    data: &[0xCC, 0x88, 0xAA, 0xFF],
+                })
             );
             let bytes: &[u8] = &[0x66];
             let rsp = Response::try_from(bytes).unwrap();
-            assert_eq!(rsp, Response::Custom(FunctionCode::Custom(0x66), &[]));
+            assert_eq!(
+                rsp,
+                Response::Custom(RawPdu {
+                    function: FunctionCode::Custom(0x66),
+                    data: &[],
+                })
+            );
+        }
+    }
+
+    /// Decoding and encoding must fail with an error instead of a panic,
+    /// whatever the input is.
+    mod no_panic {
+        use super::*;
+
+        /// Pseudo random bytes.
+        fn noise(seed: u32, buf: &mut [u8]) {
+            let mut state = seed.wrapping_mul(0x9E37_79B9) | 1;
+            for byte in buf {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                *byte = state as u8;
+            }
+        }
+
+        fn decode_pdu(pdu: &[u8]) {
+            let _ = Request::try_from(pdu);
+            let _ = Response::try_from(pdu);
+            let _ = Response::try_from_with_request(pdu, &Request::ReadCoils(0, 2000));
+            let _ = ExceptionResponse::try_from(pdu);
+            for options in [DecodeOptions::default(), DecodeOptions::lenient()] {
+                let _ = Request::try_from_with_options(pdu, &options);
+                let _ = ResponsePdu::try_from_with_options(pdu, &options);
+            }
+        }
+
+        fn decode_adu(transport: Transport, adu: &[u8]) {
+            for decoder in [DecoderType::Request, DecoderType::Response] {
+                let _ = match transport {
+                    Transport::Rtu => rtu::decode(decoder, adu).map(|_| ()),
+                    Transport::Tcp => tcp::decode(decoder, adu).map(|_| ()),
+                };
+            }
+            match transport {
+                Transport::Rtu => {
+                    let _ = rtu::request_pdu_len(adu);
+                    let _ = rtu::response_pdu_len(adu);
+                    let _ = rtu::server::decode_request(adu);
+                    let _ = rtu::client::decode_response(adu);
+                }
+                Transport::Tcp => {
+                    let _ = tcp::request_pdu_len(adu);
+                    let _ = tcp::response_pdu_len(adu);
+                    let _ = tcp::server::decode_request(adu);
+                    let _ = tcp::client::decode_response(adu);
+                }
+            }
+        }
+
+        #[test]
+        fn decode_garbage() {
+            let pdu = &mut [0; 40];
+            let adu = &mut [0; MAX_TCP_ADU_LEN];
+            for fn_code in 0..=u8::MAX {
+                for seed in 0..8 {
+                    noise(seed, pdu);
+                    pdu[0] = fn_code;
+                    for len in 0..=pdu.len() {
+                        let pdu = &pdu[..len];
+                        decode_pdu(pdu);
+
+                        adu[0] = 0x01;
+                        adu[1..=len].copy_from_slice(pdu);
+                        let crc = rtu::crc16(&adu[..=len]);
+                        write_u16(&mut adu[len + 1..], crc);
+                        decode_adu(Transport::Rtu, &adu[..len + 3]);
+                        decode_adu(Transport::Rtu, &adu[..=len]);
+
+                        write_u16(&mut adu[0..], 0x1234);
+                        write_u16(&mut adu[2..], 0);
+                        write_u16(&mut adu[4..], len as u16 + 1);
+                        adu[6] = 0x01;
+                        adu[7..7 + len].copy_from_slice(pdu);
+                        decode_adu(Transport::Tcp, &adu[..7 + len]);
+                        decode_adu(Transport::Tcp, &adu[..7 + len / 2]);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn reject_exceptions_of_error_codes() {
+            let ex = ExceptionResponse {
+                function: FunctionCode::new(0x83),
+                exception: Exception::IllegalDataAddress,
+            };
+            assert_eq!(ex.encode(&mut [0; 2]), Err(EncodeError::FnCode(0x83)));
+            let adu = rtu::ResponseAdu {
+                hdr: rtu::Header { slave: 0x01 },
+                pdu: ResponsePdu(Err(ex)),
+            };
+            assert_eq!(
+                rtu::server::encode_response_to_sink(adu, |_| unreachable!()),
+                Err(EncodeError::FnCode(0x83))
+            );
+        }
+
+        #[test]
+        fn reject_payloads_shorter_than_their_quantity() {
+            let buf = &mut [0; MAX_PDU_LEN];
+            let coils = Coils {
+                data: &[0xFF],
+                quantity: 100,
+            };
+            assert_eq!(
+                Request::WriteMultipleCoils(0, coils).encode(buf),
+                Err(EncodeError::LengthMismatch(13, 1))
+            );
+            assert_eq!(
+                Response::ReadCoils(coils).encode(buf),
+                Err(EncodeError::LengthMismatch(13, 1))
+            );
+            let words = Data {
+                data: &[0x12, 0x34],
+                quantity: 200,
+            };
+            assert_eq!(
+                Request::WriteMultipleRegisters(0, words).encode(buf),
+                Err(EncodeError::LengthMismatch(400, 2))
+            );
+            assert_eq!(
+                Response::ReadFifoQueue(words).encode(buf),
+                Err(EncodeError::BufferSize)
+            );
+            let adu = rtu::RequestAdu {
+                hdr: rtu::Header { slave: 0x01 },
+                pdu: RequestPdu(Request::WriteMultipleCoils(0, coils)),
+            };
+            assert_eq!(
+                rtu::client::encode_request_segments(adu, buf),
+                Err(EncodeError::LengthMismatch(13, 1))
+            );
+            assert!(rtu::client::encode_request_to_sink(adu, |_| unreachable!()).is_err());
+        }
+
+        #[cfg(feature = "rtu")]
+        #[test]
+        fn reject_responses_that_cannot_be_encoded() {
+            let rsp = Response::ReportServerId(&[0x01], true);
+            assert_eq!(rsp.encode(&mut [0; 5]), Err(EncodeError::FnCode(0x11)));
         }
     }
 }
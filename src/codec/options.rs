@@ -0,0 +1,358 @@
+use super::*;
+
+/// How strictly frames are checked while decoding.
+///
+/// The [default](DecodeOptions::new) reproduces the behavior of the plain
+/// `TryFrom` implementations. [`DecodeOptions::strict`] rejects everything
+/// that violates the specification, whereas [`DecodeOptions::lenient`]
+/// tolerates the quirks of common devices.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{DecodeError, DecodeOptions, Request};
+///
+/// let bytes: &[u8] = &[0x05, 0x00, 0x10, 0x00, 0x01];
+/// assert_eq!(Request::try_from(bytes), Err(DecodeError::CoilValue(0x0001)));
+/// assert_eq!(
+///     Request::try_from_with_options(bytes, &DecodeOptions::lenient()),
+///     Ok(Request::WriteSingleCoil(0x10, true))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(clippy::struct_excessive_bools)]
+pub struct DecodeOptions {
+    /// Reject quantities outside the limits of the specification
+    /// with [`DecodeError::Quantity`] and byte counts of write requests
+    /// that do not match the quantity with [`DecodeError::QuantityBytesMismatch`].
+    pub strict_quantities: bool,
+    /// Reject unknown function codes with [`DecodeError::FnCode`]
+    /// instead of decoding them as `Custom`.
+    pub reject_custom_function_codes: bool,
+    /// Decode any non-zero coil value as `true` instead of
+    /// failing with [`DecodeError::CoilValue`].
+    pub lenient_coil_values: bool,
+    /// Accept register data with an odd number of bytes.
+    /// The trailing byte is ignored.
+    pub accept_odd_byte_counts: bool,
+    /// Decode unknown exception codes as [`Exception::Custom`] instead of
+    /// failing with [`DecodeError::ExceptionCode`].
+    pub accept_custom_exception_codes: bool,
+    /// Reject packed coils of write requests with [`DecodeError::CoilPadding`]
+    /// if the unused bits of the last byte are not zero.
+    pub reject_coil_padding: bool,
+    /// Reject events of `GetCommEventLog` responses that are not defined
+    /// by the specification with [`DecodeError::CommEvent`].
+    pub reject_undefined_comm_events: bool,
+}
+
+impl DecodeOptions {
+    /// The checks of the plain `TryFrom` implementations.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strict_quantities: false,
+            reject_custom_function_codes: false,
+            lenient_coil_values: false,
+            accept_odd_byte_counts: true,
+            accept_custom_exception_codes: false,
+            reject_coil_padding: false,
+            reject_undefined_comm_events: false,
+        }
+    }
+
+    /// Reject all frames that violate the specification.
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            strict_quantities: true,
+            reject_custom_function_codes: true,
+            lenient_coil_values: false,
+            accept_odd_byte_counts: false,
+            accept_custom_exception_codes: false,
+            reject_coil_padding: true,
+            reject_undefined_comm_events: true,
+        }
+    }
+
+    /// Accept frames of devices that do not follow the specification exactly.
+    #[must_use]
+    pub const fn lenient() -> Self {
+        Self {
+            strict_quantities: false,
+            reject_custom_function_codes: false,
+            lenient_coil_values: true,
+            accept_odd_byte_counts: true,
+            accept_custom_exception_codes: true,
+            reject_coil_padding: false,
+            reject_undefined_comm_events: false,
+        }
+    }
+
+    /// Convert the raw value of a single coil.
+    pub(crate) const fn coil(self, value: u16) -> Result<bool> {
+        if self.lenient_coil_values {
+            return Ok(value != 0);
+        }
+        u16_coil_to_bool(value)
+    }
+
+    /// Apply the checks that do not affect the decoding itself.
+    pub(crate) fn check_request(self, req: &Request<'_>) -> Result<()> {
+        use Request as R;
+
+        match *req {
+            R::ReadCoils(_, quantity) | R::ReadDiscreteInputs(_, quantity) => {
+                self.check_quantity(quantity.into(), MAX_READ_COILS)
+            }
+            R::ReadInputRegisters(_, quantity) | R::ReadHoldingRegisters(_, quantity) => {
+                self.check_quantity(quantity.into(), MAX_READ_REGISTERS)
+            }
+            R::WriteMultipleCoils(_, coils) => {
+                self.check_quantity(coils.len(), MAX_WRITE_COILS)?;
+                self.check_byte_count(coils.len(), coils.packed_len(), coils.data.len())?;
+                self.check_coil_padding(coils)
+            }
+            R::WriteMultipleRegisters(_, words) => {
+                self.check_quantity(words.len(), MAX_WRITE_REGISTERS)?;
+                self.check_word_bytes(words)?;
+                self.check_byte_count(words.len(), words.len() * 2, words.data.len())
+            }
+            R::ReadWriteMultipleRegisters(_, quantity, _, words) => {
+                self.check_quantity(quantity.into(), MAX_READ_REGISTERS)?;
+                self.check_quantity(words.len(), MAX_READ_WRITE_REGISTERS)?;
+                self.check_word_bytes(words)?;
+                self.check_byte_count(words.len(), words.len() * 2, words.data.len())
+            }
+            R::Custom(pdu) => self.check_function(pdu.function),
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply the checks that do not affect the decoding itself.
+    pub(crate) fn check_response(self, rsp: &Response<'_>) -> Result<()> {
+        use Response as R;
+
+        match *rsp {
+            R::ReadCoils(coils) | R::ReadDiscreteInputs(coils) => {
+                self.check_quantity(coils.len(), packed_coils_len(MAX_READ_COILS) * 8)
+            }
+            R::ReadInputRegisters(words)
+            | R::ReadHoldingRegisters(words)
+            | R::ReadWriteMultipleRegisters(words) => {
+                self.check_quantity(words.len(), MAX_READ_REGISTERS)?;
+                self.check_word_bytes(words)
+            }
+            R::WriteMultipleCoils(_, quantity) => {
+                self.check_quantity(quantity.into(), MAX_WRITE_COILS)
+            }
+            R::WriteMultipleRegisters(_, quantity) => {
+                self.check_quantity(quantity.into(), MAX_WRITE_REGISTERS)
+            }
+            #[cfg(feature = "rtu")]
+            R::GetCommEventLog(_, _, _, events) => self.check_comm_events(events),
+            R::Custom(pdu) => self.check_function(pdu.function),
+            _ => Ok(()),
+        }
+    }
+
+    const fn check_quantity(self, quantity: usize, max: usize) -> Result<()> {
+        if self.strict_quantities && (quantity == 0 || quantity > max) {
+            return Err(DecodeError::Quantity(quantity as u16));
+        }
+        Ok(())
+    }
+
+    const fn check_byte_count(self, quantity: usize, expected: usize, actual: usize) -> Result<()> {
+        if self.strict_quantities && expected != actual {
+            return Err(DecodeError::QuantityBytesMismatch(
+                quantity as u16,
+                actual as u8,
+            ));
+        }
+        Ok(())
+    }
+
+    const fn check_function(self, function: FunctionCode) -> Result<()> {
+        if self.reject_custom_function_codes {
+            return Err(DecodeError::FnCode(function.value()));
+        }
+        Ok(())
+    }
+
+    const fn check_coil_padding(self, coils: Coils<'_>) -> Result<()> {
+        if self.reject_coil_padding && !coils.has_clean_padding() {
+            if let Some(byte) = coils.last_byte() {
+                return Err(DecodeError::CoilPadding(byte));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rtu")]
+    fn check_comm_events(self, events: &[u8]) -> Result<()> {
+        if self.reject_undefined_comm_events {
+            for event in events {
+                CommEvent::try_from(*event)?;
+            }
+        }
+        Ok(())
+    }
+
+    const fn check_word_bytes(self, words: Data<'_>) -> Result<()> {
+        if !self.accept_odd_byte_counts && words.data.len() % 2 != 0 {
+            return Err(DecodeError::ByteCount(words.data.len() as u8));
+        }
+        Ok(())
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_keeps_plain_decoding() {
+        let bytes: &[u8] = &[0x03, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::default()),
+            Request::try_from(bytes)
+        );
+        let bytes: &[u8] = &[0x03, 0x03, 0x12, 0x34, 0x56];
+        assert_eq!(
+            Response::try_from_with_options(bytes, &DecodeOptions::default()),
+            Response::try_from(bytes)
+        );
+    }
+
+    #[test]
+    fn strict_quantities() {
+        let strict = DecodeOptions::strict();
+        let bytes: &[u8] = &[0x01, 0x00, 0x00, 0x07, 0xD0];
+        assert!(Request::try_from_with_options(bytes, &strict).is_ok());
+        let bytes: &[u8] = &[0x01, 0x00, 0x00, 0x07, 0xD1];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &strict),
+            Err(DecodeError::Quantity(2001))
+        );
+        let bytes: &[u8] = &[0x03, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &strict),
+            Err(DecodeError::Quantity(0))
+        );
+        let bytes: &[u8] = &[0x10, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(
+            Response::try_from_with_options(bytes, &strict),
+            Err(DecodeError::Quantity(0))
+        );
+    }
+
+    #[test]
+    fn byte_count_mismatch() {
+        let strict = DecodeOptions::strict();
+        let bytes: &[u8] = &[0x0F, 0x00, 0x00, 0x00, 0x09, 0x01, 0xFF];
+        assert!(Request::try_from(bytes).is_ok());
+        assert_eq!(
+            Request::try_from_with_options(bytes, &strict),
+            Err(DecodeError::QuantityBytesMismatch(9, 1))
+        );
+        let bytes: &[u8] = &[0x0F, 0x00, 0x00, 0x00, 0x09, 0x02, 0xFF, 0x01];
+        assert!(Request::try_from_with_options(bytes, &strict).is_ok());
+        let bytes: &[u8] = &[0x10, 0x00, 0x00, 0x00, 0x02, 0x02, 0x12, 0x34];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &strict),
+            Err(DecodeError::QuantityBytesMismatch(2, 2))
+        );
+        let bytes: &[u8] = &[
+            0x17, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x12, 0x34, 0x56, 0x78,
+        ];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &strict),
+            Err(DecodeError::QuantityBytesMismatch(1, 4))
+        );
+    }
+
+    #[test]
+    fn coil_padding() {
+        let bytes: &[u8] = &[0x0F, 0x00, 0x00, 0x00, 0x09, 0x02, 0xFF, 0x03];
+        assert!(Request::try_from(bytes).is_ok());
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::CoilPadding(0x03))
+        );
+    }
+
+    #[test]
+    fn custom_function_codes() {
+        let bytes: &[u8] = &[0x42, 0x01];
+        assert!(Request::try_from_with_options(bytes, &DecodeOptions::new()).is_ok());
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::FnCode(0x42))
+        );
+        assert_eq!(
+            ResponsePdu::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::FnCode(0x42))
+        );
+    }
+
+    #[test]
+    fn coil_values() {
+        let bytes: &[u8] = &[0x05, 0x00, 0x01, 0x12, 0x34];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::CoilValue(0x1234))
+        );
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::lenient()),
+            Ok(Request::WriteSingleCoil(0x01, true))
+        );
+        let bytes: &[u8] = &[0x05, 0x00, 0x01, 0x00, 0x01];
+        assert_eq!(
+            Response::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::CoilValue(0x0001))
+        );
+        assert_eq!(
+            Response::try_from_with_options(bytes, &DecodeOptions::lenient()),
+            Ok(Response::WriteSingleCoil(0x01, true))
+        );
+    }
+
+    #[test]
+    fn custom_exception_codes() {
+        let bytes: &[u8] = &[0x83, 0x42];
+        assert_eq!(
+            ResponsePdu::try_from_with_options(bytes, &DecodeOptions::new()),
+            Err(DecodeError::ExceptionCode(0x42))
+        );
+        assert_eq!(
+            ResponsePdu::try_from_with_options(bytes, &DecodeOptions::lenient()),
+            Ok(ResponsePdu(Err(ExceptionResponse::new(
+                FunctionCode::ReadHoldingRegisters,
+                Exception::Custom(0x42)
+            ))))
+        );
+    }
+
+    #[test]
+    fn odd_byte_counts() {
+        let bytes: &[u8] = &[0x04, 0x03, 0x12, 0x34, 0x56];
+        assert!(Response::try_from_with_options(bytes, &DecodeOptions::lenient()).is_ok());
+        assert_eq!(
+            Response::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::ByteCount(3))
+        );
+        let bytes: &[u8] = &[0x10, 0x00, 0x00, 0x00, 0x01, 0x03, 0x12, 0x34, 0x56];
+        assert_eq!(
+            Request::try_from_with_options(bytes, &DecodeOptions::strict()),
+            Err(DecodeError::ByteCount(3))
+        );
+    }
+}
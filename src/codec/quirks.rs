@@ -0,0 +1,165 @@
+use super::*;
+
+/// Tolerated violations of the specification by the devices of a vendor.
+///
+/// In contrast to [`DecodeOptions`] the quirks also affect the framing,
+/// so they are applied by the client functions `decode_response_with_quirks`
+/// of [`rtu::client`] and [`tcp::client`].
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu, FunctionCode, QuirkProfile, Request, RequestPdu};
+///
+/// // The device reports the number of registers instead of bytes.
+/// let quirks = QuirkProfile::new().ignore_byte_count(FunctionCode::ReadHoldingRegisters);
+/// let request = rtu::RequestAdu {
+///     hdr: rtu::Header { slave: 0x01 },
+///     pdu: RequestPdu(Request::ReadHoldingRegisters(0x00, 2)),
+/// };
+/// let rx = [0x01, 0x03, 0x02, 0x12, 0x34, 0x56, 0x78, 0x09, 0x07];
+/// assert!(rtu::client::decode_response(&rx).ok().flatten().is_none());
+/// let (rsp, location) = rtu::client::decode_response_with_quirks(request, &rx, &quirks)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(location.size, rx.len());
+/// let data = rsp.pdu.ok().unwrap().data().unwrap();
+/// assert_eq!(data.get(1), Some(0x5678));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuirkProfile {
+    /// Bit set of the function codes with an unreliable byte count.
+    byte_counts: u128,
+    pub(crate) trailing_bytes: u8,
+    echoed_requests: bool,
+}
+
+impl QuirkProfile {
+    /// A profile without any quirks.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            byte_counts: 0,
+            trailing_bytes: 0,
+            echoed_requests: false,
+        }
+    }
+
+    /// Accept up to `count` unexpected bytes between the PDU
+    /// of an RTU response and the CRC.
+    #[must_use]
+    pub const fn trailing_bytes(mut self, count: u8) -> Self {
+        self.trailing_bytes = count;
+        self
+    }
+
+    /// Skip a copy of the request in front of the response, e.g. the local
+    /// echo of an RS-485 transceiver or a device that repeats the request.
+    #[must_use]
+    pub const fn accept_echoed_requests(mut self) -> Self {
+        self.echoed_requests = true;
+        self
+    }
+
+    /// Ignore the byte count of responses to `function`.
+    ///
+    /// The data extend to the end of the PDU instead. This applies to
+    /// responses that read coils, discrete inputs or registers.
+    #[must_use]
+    pub const fn ignore_byte_count(mut self, function: FunctionCode) -> Self {
+        let fn_code = function.value();
+        if fn_code < 0x80 {
+            self.byte_counts |= 1 << fn_code;
+        }
+        self
+    }
+
+    /// Returns `true` if the byte count of responses with `fn_code` is ignored.
+    #[must_use]
+    pub const fn ignores_byte_count(&self, fn_code: u8) -> bool {
+        fn_code < 0x80 && self.byte_counts & (1 << fn_code) != 0
+    }
+
+    /// The number of bytes of `buf` that repeat the request `echo`,
+    /// or `None` if the echo has not been received completely.
+    pub(crate) fn skip_echo(&self, buf: &[u8], echo: &[u8]) -> Option<usize> {
+        if !self.echoed_requests {
+            return Some(0);
+        }
+        if buf.starts_with(echo) {
+            return Some(echo.len());
+        }
+        if echo.starts_with(buf) {
+            return None;
+        }
+        Some(0)
+    }
+}
+
+impl<'r> ResponsePdu<'r> {
+    /// Decode a response PDU of a device with `quirks`.
+    pub fn try_from_with_quirks(bytes: &'r [u8], quirks: &QuirkProfile) -> Result<Self> {
+        use FunctionCode as F;
+
+        let fn_code = read_fn_code(DecoderType::Response, bytes)?;
+        if !quirks.ignores_byte_count(fn_code) {
+            return Self::try_from(bytes);
+        }
+        if bytes.len() < 2 {
+            return Err(truncated(DecoderType::Response, bytes, 1));
+        }
+        let data = &bytes[2..];
+        let coils = Coils {
+            data,
+            quantity: data.len() * 8,
+        };
+        let words = Data {
+            data,
+            quantity: data.len() / 2,
+        };
+        let rsp = match FunctionCode::new(fn_code) {
+            F::ReadCoils => Response::ReadCoils(coils),
+            F::ReadDiscreteInputs => Response::ReadDiscreteInputs(coils),
+            F::ReadHoldingRegisters => Response::ReadHoldingRegisters(words),
+            F::ReadInputRegisters => Response::ReadInputRegisters(words),
+            F::ReadWriteMultipleRegisters => Response::ReadWriteMultipleRegisters(words),
+            _ => return Self::try_from(bytes),
+        };
+        Ok(Self(Ok(rsp)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignore_byte_counts() {
+        let quirks = QuirkProfile::new().ignore_byte_count(FunctionCode::ReadInputRegisters);
+        assert!(quirks.ignores_byte_count(0x04));
+        assert!(!quirks.ignores_byte_count(0x03));
+        assert!(!quirks.ignores_byte_count(0x84));
+
+        let bytes: &[u8] = &[0x04, 0x01, 0x12, 0x34];
+        let rsp = ResponsePdu::try_from_with_quirks(bytes, &quirks).unwrap();
+        assert_eq!(rsp.ok().unwrap().data().unwrap().len(), 1);
+        let bytes: &[u8] = &[0x84, 0x02];
+        let rsp = ResponsePdu::try_from_with_quirks(bytes, &quirks).unwrap();
+        assert!(rsp.is_exception());
+        let bytes: &[u8] = &[0x03, 0x01, 0x12, 0x34];
+        let rsp = ResponsePdu::try_from_with_quirks(bytes, &quirks).unwrap();
+        assert!(rsp.ok().unwrap().data().unwrap().is_empty());
+    }
+
+    #[test]
+    fn skip_echoed_requests() {
+        let echo = &[0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A];
+        let quirks = QuirkProfile::new();
+        assert_eq!(quirks.skip_echo(echo, echo), Some(0));
+        let quirks = QuirkProfile::new().accept_echoed_requests();
+        assert_eq!(quirks.skip_echo(echo, echo), Some(8));
+        assert_eq!(quirks.skip_echo(&echo[..3], echo), None);
+        assert_eq!(quirks.skip_echo(&[0x01, 0x83], echo), Some(0));
+    }
+}
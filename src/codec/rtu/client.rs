@@ -0,0 +1,589 @@
+//! Modbus RTU client (master) specific functions.
+use super::*;
+
+/// Encode an RTU request into a buffer that does not have to be initialized.
+///
+/// Returns the bytes of the encoded ADU, e.g. to hand them
+/// to a network stack that provides uninitialized transmit buffers.
+pub fn encode_request_into_uninit<'b>(
+    adu: RequestAdu,
+    buf: &'b mut [MaybeUninit<u8>],
+) -> Result<&'b [u8], EncodeError> {
+    encode_uninit(buf, adu.adu_len(), |buf| encode_request(adu, buf))
+}
+
+/// Encode an RTU request without copying its payload.
+///
+/// `buf` only has to hold the header, the PDU fields in front
+/// of the payload and the CRC.
+pub fn encode_request_segments<'r, 'b>(
+    adu: RequestAdu<'r>,
+    buf: &'b mut [u8],
+) -> Result<Segments<'r, 'b>, EncodeError> {
+    let RequestAdu { hdr, pdu } = adu;
+    let payload = pdu.0.payload()?;
+    let head_len = 1 + pdu.0.pdu_len() - payload.len();
+    if buf.len() < head_len + 2 {
+        return Err(EncodeError::BufferSize);
+    }
+    buf[0] = hdr.slave;
+    pdu.0.encode_head(&mut buf[1..]);
+    let crc = crc16_of(&[&buf[..head_len], payload]);
+    write_u16(&mut buf[head_len..], crc);
+    let (head, tail) = buf.split_at(head_len);
+    Ok(Segments {
+        head,
+        payload,
+        tail: &tail[..2],
+    })
+}
+
+/// Encode an RTU request into `sink` without buffering the whole frame.
+///
+/// The frame is passed in chunks of at most [`SINK_CHUNK_LEN`] bytes
+/// and the CRC is calculated on the fly, e.g. to fill the small transmit
+/// FIFO of a UART without a scratch buffer for the largest frame.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     rtu::{client::encode_request_to_sink, Header, RequestAdu},
+///     Request, RequestPdu,
+/// };
+///
+/// let adu = RequestAdu {
+///     hdr: Header { slave: 0x01 },
+///     pdu: RequestPdu(Request::ReadHoldingRegisters(0x0000, 1)),
+/// };
+/// let mut tx = [0; 8];
+/// let mut len = 0;
+/// encode_request_to_sink(adu, |chunk| {
+///     tx[len..len + chunk.len()].copy_from_slice(chunk);
+///     len += chunk.len();
+/// })?;
+/// assert_eq!(tx, [0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A]);
+/// # Ok::<(), modbus_core::EncodeError>(())
+/// ```
+///
+/// Nothing is passed to `sink` if the request cannot be encoded.
+pub fn encode_request_to_sink(adu: RequestAdu, sink: impl FnMut(&[u8])) -> Result<(), EncodeError> {
+    let RequestAdu { hdr, pdu } = adu;
+    let payload = pdu.0.payload()?;
+    let mut head = [0; SINK_CHUNK_LEN];
+    let head_len = pdu.0.encode_head(&mut head);
+    encode_to_sink(hdr.slave, &head[..head_len], payload, sink);
+    Ok(())
+}
+
+/// Encode an RTU request.
+pub fn encode_request(adu: RequestAdu, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let RequestAdu { hdr, pdu } = adu;
+    if buf.len() < 2 {
+        return Err(EncodeError::BufferSize);
+    }
+    let len = pdu.encode(&mut buf[1..])?;
+    if buf.len() < len + 3 {
+        return Err(EncodeError::BufferSize);
+    }
+    buf[0] = hdr.slave;
+    let crc = crc16(&buf[0..=len]);
+    write_u16(&mut buf[len + 1..], crc);
+    Ok(len + 3)
+}
+
+/// Encode a `WriteMultipleRegisters` request with the values of `words`.
+///
+/// The words are written directly into `buf`, so no [`Data`]
+/// buffer is needed.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::rtu::{client::encode_write_multiple_registers, Header};
+///
+/// let setpoints = [0x000A, 0x0102];
+/// let buf = &mut [0; 16];
+/// let len = encode_write_multiple_registers(Header { slave: 1 }, 0x0001, setpoints.into_iter(), buf)
+///     .unwrap();
+/// assert_eq!(
+///     &buf[..len],
+///     &[0x01, 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02, 0x92, 0x30]
+/// );
+/// ```
+pub fn encode_write_multiple_registers(
+    hdr: Header,
+    address: Address,
+    words: impl ExactSizeIterator<Item = Word>,
+    buf: &mut [u8],
+) -> Result<usize, EncodeError> {
+    if buf.is_empty() {
+        return Err(EncodeError::BufferSize);
+    }
+    let len = encode_write_multiple_registers_pdu(address, words, &mut buf[1..])?;
+    if buf.len() < len + 3 {
+        return Err(EncodeError::BufferSize);
+    }
+    buf[0] = hdr.slave;
+    let crc = crc16(&buf[0..=len]);
+    write_u16(&mut buf[len + 1..], crc);
+    Ok(len + 3)
+}
+
+/// Encode a request that is broadcast to all slaves.
+///
+/// The returned marker signals that no response must be awaited.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu::client::encode_broadcast, Request};
+///
+/// let request = Request::WriteSingleRegister(0x0001, 0x0003).try_into().unwrap();
+/// let buf = &mut [0; 8];
+/// let sent = encode_broadcast(request, buf).unwrap();
+/// assert_eq!(&buf[..sent.len], &[0x00, 0x06, 0x00, 0x01, 0x00, 0x03, 0x99, 0xDA]);
+/// ```
+pub fn encode_broadcast(
+    request: BroadcastRequest<'_>,
+    buf: &mut [u8],
+) -> Result<Unanswered, EncodeError> {
+    let len = encode_request(request.into(), buf)?;
+    Ok(Unanswered { len })
+}
+
+/// Decode an RTU response.
+pub fn decode_response(buf: &[u8]) -> Result<Option<ResponseAdu<'_>>> {
+    Ok(decode_next_response(buf)?.map(|decoded| decoded.value))
+}
+
+/// Decode the next RTU response and the bytes that follow it, see [`decode_next`].
+pub fn decode_next_response(buf: &[u8]) -> Result<Option<Decoded<'_, ResponseAdu<'_>>>> {
+    let Some(decoded) = decode_next(DecoderType::Response, buf)? else {
+        return Ok(None);
+    };
+    #[cfg(feature = "tracing")]
+    let frame = decoded.value;
+    // Decoding of the PDU should are unlikely to fail due
+    // to transmission errors, because the frame's bytes
+    // have already been verified with the CRC.
+    decoded
+        .try_map(DecodedFrame::try_into_response_adu)
+        .map(Some)
+        .map_err(|err| {
+            // Unrecoverable error
+            log::error!("Failed to decode response PDU: {err}");
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                slave = frame.slave,
+                error = %err,
+                "Failed to decode response PDU"
+            );
+            err
+        })
+}
+
+/// Decode an RTU response of the slave `expected`.
+///
+/// Responses of other slaves are rejected with [`DecodeError::ServerId`].
+/// On a multi-drop bus they occur e.g. if a slow device answers
+/// a request of a previous master.
+pub fn decode_response_from(expected: SlaveId, buf: &[u8]) -> Result<Option<ResponseAdu<'_>>> {
+    let Some(adu) = decode_response(buf)? else {
+        return Ok(None);
+    };
+    if adu.hdr.slave != expected {
+        return Err(DecodeError::ServerId(expected, adu.hdr.slave));
+    }
+    Ok(Some(adu))
+}
+
+/// Decode the next RTU response of the slave `expected` and skip
+/// the complete responses of other slaves in front of it.
+///
+/// In contrast to [`decode_response_from`] responses of other slaves
+/// are not rejected, e.g. on a diagnostics tap of a bus that is shared
+/// by several masters. Returns the response, if it has been received,
+/// and the number of bytes that have been consumed, including the
+/// skipped frames. The consumed bytes can be discarded.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::rtu::client::decode_next_response_from;
+///
+/// let rx = [
+///     0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B, // slave 0x11
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, // slave 0x12
+/// ];
+/// let (rsp, consumed) = decode_next_response_from(0x12, &rx).unwrap();
+/// assert_eq!(rsp.unwrap().hdr.slave, 0x12);
+/// assert_eq!(consumed, 16);
+///
+/// let (rsp, consumed) = decode_next_response_from(0x12, &rx[..8]).unwrap();
+/// assert!(rsp.is_none());
+/// assert_eq!(consumed, 8);
+/// ```
+pub fn decode_next_response_from(
+    expected: SlaveId,
+    buf: &[u8],
+) -> Result<(Option<ResponseAdu<'_>>, usize)> {
+    let mut rest = buf;
+    while let Some(decoded) = decode_next(DecoderType::Response, rest)? {
+        rest = decoded.rest;
+        if decoded.value.slave != expected {
+            log::debug!(
+                "Skipped response of slave {} while waiting for slave {expected}",
+                decoded.value.slave
+            );
+            continue;
+        }
+        let rsp = decoded.value.try_into_response_adu()?;
+        return Ok((Some(rsp), buf.len() - rest.len()));
+    }
+    Ok((None, buf.len() - rest.len()))
+}
+
+/// Decode the RTU response to `request` of a device with `quirks`.
+///
+/// Frames whose length cannot be taken from the byte count are delimited
+/// by searching for a valid CRC. Returns the location of the response
+/// within `buf`, which is preceded by the echoed request, if any.
+pub fn decode_response_with_quirks<'b>(
+    request: RequestAdu<'_>,
+    buf: &'b [u8],
+    quirks: &QuirkProfile,
+) -> Result<Option<(ResponseAdu<'b>, FrameLocation)>> {
+    let mut echo = [0; MAX_FRAME_LEN];
+    let echo_len = encode_request(request, &mut echo).map_err(|_| DecodeError::BufferSize)?;
+    let Some(start) = quirks.skip_echo(buf, &echo[..echo_len]) else {
+        return Ok(None);
+    };
+    let raw_frame = &buf[start..];
+    let Some(size) = quirky_frame_len(raw_frame, quirks)? else {
+        return Ok(None);
+    };
+    let Some(frame) = extract_frame(raw_frame, size - 3)? else {
+        return Ok(None);
+    };
+    let pdu = ResponsePdu::try_from_with_quirks(frame.pdu, quirks)
+        .map_err(|err| err.in_adu(Transport::Rtu, 1))?;
+    let adu = ResponseAdu {
+        hdr: frame.header(),
+        pdu,
+    };
+    Ok(Some((adu, FrameLocation { start, size })))
+}
+
+/// The length of the response frame at the start of `buf`.
+fn quirky_frame_len(buf: &[u8], quirks: &QuirkProfile) -> Result<Option<usize>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let crc_matches = |len: usize| read_u16(&buf[len - 2..len]) == crc16(&buf[..len - 2]);
+    if quirks.ignores_byte_count(buf[1]) {
+        let max_len = buf.len().min(MAX_FRAME_LEN);
+        if let Some(len) = (5..=max_len).find(|len| crc_matches(*len)) {
+            return Ok(Some(len));
+        }
+        if max_len < MAX_FRAME_LEN {
+            return Ok(None);
+        }
+        return Err(DecodeError::Crc(
+            read_u16(&buf[max_len - 2..max_len]),
+            crc16(&buf[..max_len - 2]),
+        ));
+    }
+    let Some(pdu_len) = response_pdu_len(buf)? else {
+        return Ok(None);
+    };
+    let len = checked_len(3, pdu_len)?;
+    for extra in 0..=usize::from(quirks.trailing_bytes) {
+        let len = len.saturating_add(extra);
+        if buf.len() < len {
+            return Ok(None);
+        }
+        if crc_matches(len) {
+            return Ok(Some(len));
+        }
+    }
+    Ok(Some(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Claims to yield more words than it does.
+    struct ShortIter(u16);
+
+    impl Iterator for ShortIter {
+        type Item = Word;
+
+        fn next(&mut self) -> Option<Word> {
+            self.0 = self.0.checked_sub(1)?;
+            Some(self.0)
+        }
+    }
+
+    impl ExactSizeIterator for ShortIter {
+        fn len(&self) -> usize {
+            usize::from(self.0) + 1
+        }
+    }
+
+    #[test]
+    fn encode_write_multiple_registers_from_iter() {
+        let hdr = Header { slave: 0x12 };
+        let words = [0xABCD, 0xEF01, 0x2345];
+        let expected = &mut [0; 32];
+        let data_buf = &mut [0; 6];
+        let adu = RequestAdu {
+            hdr,
+            pdu: RequestPdu(Request::WriteMultipleRegisters(
+                0x1000,
+                Data::from_words(&words, data_buf).unwrap(),
+            )),
+        };
+        let expected_len = encode_request(adu, expected).unwrap();
+
+        let buf = &mut [0; 32];
+        let len = encode_write_multiple_registers(hdr, 0x1000, words.into_iter(), buf).unwrap();
+        assert_eq!(&buf[..len], &expected[..expected_len]);
+
+        assert_eq!(
+            encode_write_multiple_registers(hdr, 0, words.into_iter(), &mut buf[..len - 1]),
+            Err(EncodeError::BufferSize)
+        );
+        assert_eq!(
+            encode_write_multiple_registers(hdr, 0, core::iter::empty(), buf),
+            Err(EncodeError::Quantity(0))
+        );
+        assert_eq!(
+            encode_write_multiple_registers(hdr, 0, core::iter::repeat(0).take(124), &mut [0; 256]),
+            Err(EncodeError::Quantity(124))
+        );
+        assert_eq!(
+            encode_write_multiple_registers(hdr, 0, ShortIter(2), buf),
+            Err(EncodeError::Quantity(2))
+        );
+    }
+
+    #[test]
+    fn encode_write_single_register_request() {
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+        };
+        let buf = &mut [0; 100];
+        let len = encode_request(adu, buf).unwrap();
+        assert_eq!(len, 8);
+        assert_eq!(
+            &buf[..len],
+            &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]
+        );
+    }
+
+    #[test]
+    fn encode_request_adu() {
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+        };
+        assert_eq!(adu.adu_len(), 8);
+        assert_eq!(adu.encoded_len(), 8);
+        assert_eq!(request_adu_len(&Request::ReadCoils(0, 8)), 8);
+        let rsp = Response::ReadHoldingRegisters(Data {
+            data: &[0; 4],
+            quantity: 2,
+        });
+        assert_eq!(response_adu_len(&rsp), 9);
+        assert_eq!(
+            adu.encode_checked(&mut [0; 100]).unwrap(),
+            &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]
+        );
+    }
+
+    #[test]
+    fn encode_request_into_uninit_buffer() {
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+        };
+        let buf = &mut [MaybeUninit::uninit(); 100];
+        assert_eq!(
+            encode_request_into_uninit(adu, buf),
+            Ok(&[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE][..])
+        );
+        let buf = &mut [MaybeUninit::uninit(); 7];
+        assert_eq!(
+            encode_request_into_uninit(adu, buf),
+            Err(EncodeError::BufferSize)
+        );
+    }
+
+    #[test]
+    fn encode_request_without_copying_the_payload() {
+        let words = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteMultipleRegisters(0x10, words)),
+        };
+        let buf = &mut [0; 9];
+        let segments = encode_request_segments(adu, buf).unwrap();
+        assert_eq!(segments.head, &[0x12, 0x10, 0x00, 0x10, 0x00, 0x02, 0x04]);
+        assert_eq!(segments.payload, words.data);
+        let expected = &mut [0; 13];
+        assert_eq!(encode_request(adu, expected), Ok(13));
+        assert_eq!(segments.tail, &expected[11..]);
+        assert_eq!(segments.len(), 13);
+        assert_eq!(
+            encode_request_segments(adu, &mut [0; 8]),
+            Err(EncodeError::BufferSize)
+        );
+    }
+
+    #[test]
+    fn encode_request_to_sink_in_chunks() {
+        let data = &[0xAB; 40];
+        let words = Data { data, quantity: 20 };
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteMultipleRegisters(0x10, words)),
+        };
+        let tx = &mut [0; 100];
+        let mut len = 0;
+        let mut chunks = 0;
+        encode_request_to_sink(adu, |chunk| {
+            assert!(chunk.len() <= SINK_CHUNK_LEN);
+            tx[len..len + chunk.len()].copy_from_slice(chunk);
+            len += chunk.len();
+            chunks += 1;
+        })
+        .unwrap();
+        let expected = &mut [0; 100];
+        assert_eq!(encode_request(adu, expected), Ok(len));
+        assert_eq!(tx[..len], expected[..len]);
+        assert_eq!(chunks, 4);
+    }
+
+    #[test]
+    fn decode_write_single_register_response() {
+        let buf = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+        let adu = decode_response(buf).unwrap().unwrap();
+        assert_eq!(adu.hdr.slave, 0x12);
+        assert_eq!(
+            adu.pdu,
+            ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD)))
+        );
+    }
+
+    #[test]
+    fn decode_response_of_another_slave() {
+        let buf = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+        assert!(decode_response_from(0x12, buf).unwrap().is_some());
+        assert_eq!(
+            decode_response_from(0x11, buf).err(),
+            Some(DecodeError::ServerId(0x11, 0x12))
+        );
+        assert_eq!(decode_response_from(0x11, &buf[..4]).unwrap(), None);
+    }
+
+    #[test]
+    fn skip_responses_of_other_slaves() {
+        let buf = &mut [0; 32];
+        let mut len = 0;
+        for slave in [0x11, 0x13, 0x12] {
+            let adu = ResponseAdu {
+                hdr: Header { slave },
+                pdu: ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD))),
+            };
+            len += server::encode_response(adu, &mut buf[len..]).unwrap();
+        }
+        let (rsp, consumed) = decode_next_response_from(0x12, &buf[..len]).unwrap();
+        assert_eq!(rsp.unwrap().hdr.slave, 0x12);
+        assert_eq!(consumed, len);
+
+        // The responses of other slaves are consumed
+        assert_eq!(
+            decode_next_response_from(0x14, &buf[..len]).unwrap(),
+            (None, len)
+        );
+        assert_eq!(
+            decode_next_response_from(0x12, &buf[..20]).unwrap(),
+            (None, 16)
+        );
+        assert_eq!(
+            decode_next_response_from(0x12, &buf[..4]).unwrap(),
+            (None, 0)
+        );
+        // Garbage in front of the response
+        buf[0] ^= 0xFF;
+        let (rsp, consumed) = decode_next_response_from(0x12, &buf[..len]).unwrap();
+        assert_eq!(rsp.unwrap().hdr.slave, 0x12);
+        assert_eq!(consumed, len);
+    }
+
+    #[test]
+    fn decode_partly_received_response() {
+        let buf = &[0x12, 0x06, 0x22, 0x22];
+        assert_eq!(decode_response(buf).unwrap(), None);
+    }
+
+    #[test]
+    fn decode_response_with_echo_and_trailing_bytes() {
+        let quirks = QuirkProfile::new()
+            .trailing_bytes(1)
+            .accept_echoed_requests();
+        let request = RequestAdu {
+            hdr: Header { slave: 0x01 },
+            pdu: RequestPdu(Request::ReadHoldingRegisters(0x00, 1)),
+        };
+        let buf = &[
+            0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A, // echo
+            0x01, 0x03, 0x02, 0x12, 0x34, 0xB5, 0x33,
+        ];
+        assert_eq!(
+            decode_response_with_quirks(request, &buf[..5], &quirks),
+            Ok(None)
+        );
+        assert_eq!(
+            decode_response_with_quirks(request, &buf[..10], &quirks),
+            Ok(None)
+        );
+        let (rsp, location) = decode_response_with_quirks(request, buf, &quirks)
+            .unwrap()
+            .unwrap();
+        assert_eq!(location, FrameLocation { start: 8, size: 7 });
+        assert_eq!(rsp.pdu.ok().unwrap().data().unwrap().get(0), Some(0x1234));
+
+        let request = RequestAdu {
+            hdr: Header { slave: 0x01 },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x01, 0x03)),
+        };
+        let buf = &[0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0xFF, 0x4A, 0xEA];
+        let (rsp, location) = decode_response_with_quirks(request, buf, &quirks)
+            .unwrap()
+            .unwrap();
+        assert_eq!(location.size, 9);
+        assert_eq!(
+            rsp.pdu,
+            ResponsePdu(Ok(Response::WriteSingleRegister(0x01, 0x03)))
+        );
+        assert!(decode_response_with_quirks(request, buf, &QuirkProfile::new()).is_err());
+    }
+
+    #[test]
+    fn request_buffer_too_small() {
+        let adu = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+        };
+        let buf = &mut [0; 7];
+        let res = encode_request(adu, buf).err().unwrap();
+        assert_eq!(res, EncodeError::BufferSize);
+    }
+}
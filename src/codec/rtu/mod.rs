@@ -1,24 +1,55 @@
 //! Modbus RTU
 
 use super::*;
-use byteorder::{BigEndian, ByteOrder};
 
+pub mod client;
+pub mod monitor;
+pub mod recovery;
+pub mod replay;
 pub mod server;
 pub use crate::frame::rtu::*;
 
 // [MODBUS over Serial Line Specification and Implementation Guide V1.02](http://modbus.org/docs/Modbus_over_serial_line_V1_02.pdf), page 13
 // "The maximum size of a MODBUS RTU frame is 256 bytes."
-const MAX_FRAME_LEN: usize = 256;
+const MAX_FRAME_LEN: usize = MAX_RTU_ADU_LEN;
 
 /// An extracted RTU PDU frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DecodedFrame<'a> {
     pub slave: SlaveId,
     pub pdu: &'a [u8],
 }
 
+impl<'a> DecodedFrame<'a> {
+    /// The header of the frame.
+    #[must_use]
+    pub const fn header(&self) -> Header {
+        Header { slave: self.slave }
+    }
+
+    /// Decode the PDU of a request frame.
+    pub fn try_into_request_adu(self) -> Result<RequestAdu<'a>> {
+        let pdu = Request::try_from(self.pdu).map_err(|err| err.in_adu(Transport::Rtu, 1))?;
+        Ok(RequestAdu {
+            hdr: self.header(),
+            pdu: RequestPdu(pdu),
+        })
+    }
+
+    /// Decode the PDU of a response frame.
+    pub fn try_into_response_adu(self) -> Result<ResponseAdu<'a>> {
+        let pdu = ResponsePdu::try_from(self.pdu).map_err(|err| err.in_adu(Transport::Rtu, 1))?;
+        Ok(ResponseAdu {
+            hdr: self.header(),
+            pdu,
+        })
+    }
+}
+
 /// The location of all bytes that belong to the frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FrameLocation {
     /// The index where the frame starts
     pub start: usize,
@@ -26,50 +57,259 @@ pub struct FrameLocation {
     pub size: usize,
 }
 
+/// A decoded frame or ADU and the bytes that follow it.
+///
+/// Decoding can continue with [`rest`](Self::rest)
+/// without recomputing the offset of the next frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Decoded<'a, T> {
+    pub value: T,
+    /// The location of the frame within the decoded buffer
+    pub location: FrameLocation,
+    /// The bytes after the frame
+    pub rest: &'a [u8],
+}
+
+impl<'a, T> Decoded<'a, T> {
+    /// Attach the bytes of `buf` that follow `location` to `value`.
+    fn new(value: T, location: FrameLocation, buf: &'a [u8]) -> Self {
+        let rest = buf.get(location.start + location.size..).unwrap_or(&[]);
+        Self {
+            value,
+            location,
+            rest,
+        }
+    }
+
+    /// Convert the value, e.g. to decode the PDU of a frame.
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<Decoded<'a, U>> {
+        Ok(Decoded {
+            value: f(self.value)?,
+            location: self.location,
+            rest: self.rest,
+        })
+    }
+}
+
 /// Decode RTU PDU frames from a buffer.
 pub fn decode(
     decoder_type: DecoderType,
     buf: &[u8],
-) -> Result<Option<(DecodedFrame, FrameLocation)>> {
+) -> Result<Option<(DecodedFrame<'_>, FrameLocation)>> {
+    decode_status(decoder_type, buf).map(DecodeStatus::complete)
+}
+
+/// Decode the next RTU PDU frame from a buffer.
+///
+/// In contrast to [`decode`] the bytes after the frame are returned,
+/// so that a loop can continue with them. An empty buffer contains
+/// no frame.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu, DecoderType};
+///
+/// let mut rx: &[u8] = &[
+///     0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B,
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE,
+///     0x12, 0x06,
+/// ];
+/// let mut slaves = Vec::new();
+/// while let Some(frame) = rtu::decode_next(DecoderType::Response, rx)? {
+///     slaves.push(frame.value.slave);
+///     rx = frame.rest;
+/// }
+/// assert_eq!(slaves, [0x11, 0x12]);
+/// assert_eq!(rx, [0x12, 0x06]);
+/// # Ok::<(), modbus_core::DecodeError>(())
+/// ```
+pub fn decode_next(
+    decoder_type: DecoderType,
+    buf: &[u8],
+) -> Result<Option<Decoded<'_, DecodedFrame<'_>>>> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    let frame = decode(decoder_type, buf)?;
+    Ok(frame.map(|(frame, location)| Decoded::new(frame, location, buf)))
+}
+
+/// Decode RTU PDU frames from a buffer.
+///
+/// In contrast to [`decode`] the number of missing bytes
+/// of an incomplete frame is reported if it is already known.
+pub fn decode_status(
+    decoder_type: DecoderType,
+    buf: &[u8],
+) -> Result<DecodeStatus<(DecodedFrame<'_>, FrameLocation)>> {
+    scan(decoder_type, buf, &[], &mut 0, &mut ())
+}
+
+/// Decode RTU PDU frames from a buffer, see [`decode_status`].
+///
+/// Frames with a function code that is unknown to the framing layer
+/// are delimited according to `custom`.
+pub fn decode_status_with_custom<'b>(
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    custom: &[CustomFunction],
+) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
+    scan(decoder_type, buf, custom, &mut 0, &mut ())
+}
+
+/// Check whether `buf` starts with a complete RTU frame without decoding it.
+///
+/// This is cheaper than [`decode_status`], e.g. to size the next
+/// transfer of a receiver. The CRC is not verified and leading
+/// bytes that do not belong to a frame are not skipped.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu, DecoderType, FrameStatus};
+///
+/// let rx = [0x12, 0x03, 0x04, 0x89];
+/// assert_eq!(
+///     rtu::frame_status(DecoderType::Response, &rx),
+///     FrameStatus::NeedMore(Some(5))
+/// );
+/// ```
+#[must_use]
+pub fn frame_status(decoder_type: DecoderType, buf: &[u8]) -> FrameStatus {
+    match pdu_len(decoder_type, buf, &[]).and_then(adu_len) {
+        Ok(Some(adu_len)) => FrameStatus::of_len(adu_len, buf.len()),
+        Ok(None) => FrameStatus::NeedMore(None),
+        Err(err) => FrameStatus::Invalid(err),
+    }
+}
+
+/// Returns `true` if `buf` starts with a complete RTU frame, see [`frame_status`].
+#[must_use]
+pub fn is_frame_complete(decoder_type: DecoderType, buf: &[u8]) -> bool {
+    matches!(frame_status(decoder_type, buf), FrameStatus::Complete(_))
+}
+
+/// The length of the ADU around a PDU of `pdu_len` bytes, if known.
+fn adu_len(pdu_len: Option<usize>) -> Result<Option<usize>> {
+    pdu_len.map(|pdu_len| checked_len(3, pdu_len)).transpose()
+}
+
+/// Extract the PDU length out of the ADU buffer.
+///
+/// Unknown function codes are looked up in `custom`.
+fn pdu_len(
+    decoder_type: DecoderType,
+    adu_buf: &[u8],
+    custom: &[CustomFunction],
+) -> Result<Option<usize>> {
+    match decoder_type {
+        DecoderType::Request => request_pdu_len(adu_buf),
+        DecoderType::Response => response_pdu_len(adu_buf),
+    }
+    .or_else(|err| match err {
+        DecodeError::FnCode(_) => custom_pdu_len(custom, decoder_type, &adu_buf[1..]),
+        err => Err(err),
+    })
+}
+
+/// Decode all complete RTU frames in a buffer.
+///
+/// The locations of the frames are relative to the start of `buf`.
+/// The iteration ends at the first incomplete frame or when no frame
+/// could be found, see [`Frames::rest`].
+#[must_use]
+pub const fn decode_all(decoder_type: DecoderType, buf: &[u8]) -> Frames<'_> {
+    Frames {
+        decoder_type,
+        buf,
+        offset: 0,
+    }
+}
+
+/// Iterator over the frames of a buffer, see [`decode_all`].
+#[derive(Debug, Clone)]
+pub struct Frames<'b> {
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> Frames<'b> {
+    /// The bytes after the last decoded frame, e.g. to be kept
+    /// until more bytes have been received.
+    #[must_use]
+    pub fn rest(&self) -> &'b [u8] {
+        &self.buf[self.offset..]
+    }
+}
+
+impl<'b> Iterator for Frames<'b> {
+    type Item = (DecodedFrame<'b>, FrameLocation);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest();
+        if rest.is_empty() {
+            return None;
+        }
+        let (frame, location) = decode(self.decoder_type, rest).ok().flatten()?;
+        let location = FrameLocation {
+            start: self.offset + location.start,
+            size: location.size,
+        };
+        self.offset = location.start + location.size;
+        Some((frame, location))
+    }
+}
+
+/// Decode a frame after the first `drop_cnt` bytes of `buf`
+/// which are already known to be invalid.
+fn scan<'b>(
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    custom: &[CustomFunction],
+    drop_cnt: &mut usize,
+    observer: &mut impl Observer,
+) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
     use DecoderType::{Request, Response};
-    let mut drop_cnt = 0;
 
     if buf.is_empty() {
-        return Err(Error::BufferSize);
+        return Err(DecodeError::BufferSize);
     }
 
     loop {
         let mut retry = false;
-        if drop_cnt + 1 >= buf.len() {
-            return Ok(None);
+        if *drop_cnt + 1 >= buf.len() {
+            return Ok(DecodeStatus::Incomplete { needed: None });
         }
-        let raw_frame = &buf[drop_cnt..];
-        let res = match decoder_type {
-            Request => request_pdu_len(raw_frame),
-            Response => response_pdu_len(raw_frame),
-        }
-        .and_then(|pdu_len| {
+        let raw_frame = &buf[*drop_cnt..];
+        let res = pdu_len(decoder_type, raw_frame, custom).and_then(|pdu_len| {
             retry = false;
             let Some(pdu_len) = pdu_len else {
                 // Incomplete frame
-                return Ok(None);
+                return Ok(DecodeStatus::Incomplete { needed: None });
+            };
+            let frame_location = FrameLocation {
+                start: *drop_cnt,
+                size: checked_len(3, pdu_len)?, // TODO: use 'const FOO:usize = 3;'
             };
-            extract_frame(raw_frame, pdu_len).map(|x| {
-                x.map(|res| {
-                    let frame_location = FrameLocation {
-                        start: drop_cnt,
-                        size: pdu_len + 3, // TODO: use 'const FOO:usize = 3;'
-                    };
-                    (res, frame_location)
-                })
+            let needed = frame_location.size.saturating_sub(raw_frame.len());
+            extract_frame(raw_frame, pdu_len).map(|x| match x {
+                Some(res) => DecodeStatus::Complete((res, frame_location)),
+                None => DecodeStatus::Incomplete {
+                    needed: Some(needed),
+                },
             })
         })
         .or_else(|err| {
-            if drop_cnt + 1 >= MAX_FRAME_LEN {
+            if *drop_cnt + 1 >= MAX_FRAME_LEN {
                 log::error!(
                     "Giving up to decode frame after dropping {drop_cnt} byte(s): {:X?}",
-                    &buf[0..drop_cnt]
+                    &buf[0..*drop_cnt]
                 );
+                #[cfg(feature = "tracing")]
+                tracing::error!(dropped = *drop_cnt, error = %err, "Giving up to decode RTU frame");
                 return Err(err);
             }
             log::warn!(
@@ -79,33 +319,170 @@ pub fn decode(
                     Response => "response",
                 }
             );
-            drop_cnt += 1;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                decoder = ?decoder_type,
+                dropped = *drop_cnt + 1,
+                error = %err,
+                "Dropped byte to resynchronize RTU frame"
+            );
+            if *drop_cnt == 0 && matches!(err, DecodeError::Crc(..)) {
+                observer.crc_error();
+            }
+            observer.dropped();
+            *drop_cnt += 1;
             retry = true;
-            Ok(None)
+            Ok(DecodeStatus::Incomplete { needed: None })
         });
 
         if !retry {
+            if let Ok(DecodeStatus::Complete((frame, _))) = &res {
+                observer.frame(frame.pdu);
+            }
+            #[cfg(feature = "tracing")]
+            if let Ok(DecodeStatus::Complete((frame, location))) = &res {
+                tracing::trace!(
+                    decoder = ?decoder_type,
+                    slave = frame.slave,
+                    fn_code = frame.pdu.first().copied(),
+                    len = location.size,
+                    dropped = location.start,
+                    "Decoded RTU frame"
+                );
+            }
             return res;
         }
     }
 }
 
+/// A decoder that resumes where the previous call stopped.
+///
+/// [`decode_status`] starts from the beginning of the buffer on every call.
+/// Decoding a buffer full of line noise after every received byte therefore
+/// costs quadratic time. The `Decoder` remembers the leading bytes that it
+/// has already dropped and continues behind them.
+///
+/// Between two calls bytes may only be appended to the buffer.
+/// After a complete frame or an error the decoder starts over,
+/// i.e. the caller is expected to remove the frame or to clear the buffer.
+///
+/// With [`with_stats`](Self::with_stats) the decoder feeds an
+/// [`Observer`] like [`Stats`] with the frames and errors it encounters.
+/// Vendor-specific functions can be registered with
+/// [`with_custom_functions`](Self::with_custom_functions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Decoder<S = ()> {
+    direction: DecoderType,
+    dropped: usize,
+    custom: &'static [CustomFunction],
+    stats: S,
+}
+
+impl Decoder {
+    /// Create a decoder for requests or responses.
+    #[must_use]
+    pub const fn new(decoder_type: DecoderType) -> Self {
+        Self::with_stats(decoder_type, ())
+    }
+}
+
+impl<S: Observer> Decoder<S> {
+    /// Create a decoder that reports to `stats`.
+    #[must_use]
+    pub const fn with_stats(decoder_type: DecoderType, stats: S) -> Self {
+        Self {
+            direction: decoder_type,
+            dropped: 0,
+            custom: &[],
+            stats,
+        }
+    }
+
+    /// Delimit frames of the vendor-specific functions in `custom`.
+    #[must_use]
+    pub const fn with_custom_functions(mut self, custom: &'static [CustomFunction]) -> Self {
+        self.custom = custom;
+        self
+    }
+
+    /// The statistics that have been collected so far.
+    #[must_use]
+    pub const fn stats(&self) -> &S {
+        &self.stats
+    }
+
+    /// The statistics, e.g. to reset them.
+    pub fn stats_mut(&mut self) -> &mut S {
+        &mut self.stats
+    }
+
+    /// The number of leading bytes that do not belong to a frame.
+    #[must_use]
+    pub const fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Start over, e.g. after the dropped bytes have been removed from the buffer.
+    pub fn reset(&mut self) {
+        self.dropped = 0;
+    }
+
+    /// Decode the next frame, see [`decode_status`].
+    pub fn decode_status<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
+        let res = scan(
+            self.direction,
+            buf,
+            self.custom,
+            &mut self.dropped,
+            &mut self.stats,
+        );
+        if !matches!(res, Ok(DecodeStatus::Incomplete { .. })) {
+            self.reset();
+        }
+        res
+    }
+}
+
+impl Encode for RequestAdu<'_> {
+    fn encoded_len(&self) -> usize {
+        self.adu_len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        client::encode_request(*self, buf)
+    }
+}
+
+impl Encode for ResponseAdu<'_> {
+    fn encoded_len(&self) -> usize {
+        self.pdu.encoded_len() + 3
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        server::encode_response(*self, buf)
+    }
+}
+
 /// Extract a PDU frame out of a buffer.
 #[allow(clippy::similar_names)]
-pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame>> {
+pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame<'_>>> {
     if buf.is_empty() {
-        return Err(Error::BufferSize);
+        return Err(DecodeError::BufferSize);
     }
 
-    let adu_len = 1 + pdu_len;
-    if buf.len() >= adu_len + 2 {
+    let adu_len = checked_len(1, pdu_len)?;
+    if buf.len().saturating_sub(adu_len) >= 2 {
         let (adu_buf, buf) = buf.split_at(adu_len);
         let (crc_buf, _) = buf.split_at(2);
         // Read trailing CRC and verify ADU
-        let expected_crc = BigEndian::read_u16(crc_buf);
+        let expected_crc = read_u16(crc_buf);
         let actual_crc = crc16(adu_buf);
         if expected_crc != actual_crc {
-            return Err(Error::Crc(expected_crc, actual_crc));
+            return Err(DecodeError::Crc(expected_crc, actual_crc));
         }
         let (slave_id, pdu_data) = adu_buf.split_at(1);
         let slave_id = slave_id[0];
@@ -118,90 +495,180 @@ pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame>>
     Ok(None)
 }
 
+/// Wrap the raw `pdu` of `slave` into an RTU frame.
+///
+/// Only the slave address and the CRC are added, the PDU is not
+/// checked. This allows proxies and test tools to forward frames
+/// that [`Request`] and [`Response`] cannot represent.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::rtu;
+///
+/// let buf = &mut [0; 8];
+/// let len = rtu::wrap_raw_pdu(0x12, &[0x06, 0x22, 0x22, 0xAB, 0xCD], buf).unwrap();
+/// assert_eq!(buf[..len], [0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]);
+/// ```
+pub fn wrap_raw_pdu(slave: SlaveId, pdu: &[u8], buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let len = pdu.len() + 3;
+    if buf.len() < len {
+        return Err(EncodeError::BufferSize);
+    }
+    buf[0] = slave;
+    buf[1..=pdu.len()].copy_from_slice(pdu);
+    let crc = crc16(&buf[..=pdu.len()]);
+    write_u16(&mut buf[pdu.len() + 1..], crc);
+    Ok(len)
+}
+
+/// Encode a request of 8 bytes at compile time.
+///
+/// This is an implementation detail of [`modbus_rtu_request!`](crate::modbus_rtu_request).
+/// Invalid quantities of read requests fail the const evaluation.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+#[must_use]
+pub const fn fixed_request_adu(slave: SlaveId, fn_code: u8, address: u16, value: u16) -> [u8; 8] {
+    match fn_code {
+        0x01 | 0x02 => assert!(value >= 1 && value <= 2000, "invalid quantity of coils"),
+        0x03 | 0x04 => assert!(value >= 1 && value <= 125, "invalid quantity of registers"),
+        _ => {}
+    }
+    let [address_hi, address_lo] = address.to_be_bytes();
+    let [value_hi, value_lo] = value.to_be_bytes();
+    let mut adu = [
+        slave, fn_code, address_hi, address_lo, value_hi, value_lo, 0, 0,
+    ];
+    let mut crc = 0xFFFF;
+    let mut i = 0;
+    while i < 6 {
+        crc = crc16_update(crc, adu[i]);
+        i += 1;
+    }
+    let [crc_hi, crc_lo] = crc.rotate_right(8).to_be_bytes();
+    adu[6] = crc_hi;
+    adu[7] = crc_lo;
+    adu
+}
+
 /// Calculate the CRC (Cyclic Redundancy Check) sum.
 #[must_use]
 pub fn crc16(data: &[u8]) -> u16 {
-    let mut crc = 0xFFFF;
-    for x in data {
-        crc ^= u16::from(*x);
-        for _ in 0..8 {
-            // if we followed clippy's suggestion to move out the crc >>= 1, the condition may not be met any more
-            // the recommended action therefore makes no sense and it is better to allow this lint
-            #[allow(clippy::branches_sharing_code)]
-            if (crc & 0x0001) != 0 {
-                crc >>= 1;
-                crc ^= 0xA001;
-            } else {
-                crc >>= 1;
-            }
+    crc16_of(&[data])
+}
+
+/// Calculate the CRC sum of consecutive `parts`.
+pub(crate) fn crc16_of(parts: &[&[u8]]) -> u16 {
+    let crc = parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(0xFFFF, |crc, x| crc16_update(crc, *x));
+    crc.rotate_right(8)
+}
+
+/// Add `byte` to the CRC register `crc`.
+///
+/// The CRC sum is the final register with swapped bytes.
+const fn crc16_update(mut crc: u16, byte: u8) -> u16 {
+    crc ^= byte as u16;
+    let mut i = 0;
+    while i < 8 {
+        // if we followed clippy's suggestion to move out the crc >>= 1, the condition may not be met any more
+        // the recommended action therefore makes no sense and it is better to allow this lint
+        #[allow(clippy::branches_sharing_code)]
+        if (crc & 0x0001) != 0 {
+            crc >>= 1;
+            crc ^= 0xA001;
+        } else {
+            crc >>= 1;
         }
+        i += 1;
     }
-    crc << 8 | crc >> 8
+    crc
 }
 
-/// Extract the PDU length out of the ADU request buffer.
-pub const fn request_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
-    if adu_buf.len() < 2 {
-        return Ok(None);
+/// The maximum number of bytes that a sink receives at once.
+///
+/// See [`client::encode_request_to_sink`] and [`server::encode_response_to_sink`].
+pub const SINK_CHUNK_LEN: usize = 16;
+
+/// Passes the bytes of a frame to a sink in chunks of
+/// [`SINK_CHUNK_LEN`] bytes and calculates the CRC on the fly.
+struct ChunkedSink<F> {
+    sink: F,
+    chunk: [u8; SINK_CHUNK_LEN],
+    len: usize,
+    crc: u16,
+}
+
+impl<F> ChunkedSink<F>
+where
+    F: FnMut(&[u8]),
+{
+    const fn new(sink: F) -> Self {
+        Self {
+            sink,
+            chunk: [0; SINK_CHUNK_LEN],
+            len: 0,
+            crc: 0xFFFF,
+        }
     }
-    let fn_code = adu_buf[1];
-    let len = match fn_code {
-        0x01..=0x06 => Some(5),
-        0x07 | 0x0B | 0x0C | 0x11 => Some(1),
-        0x0F | 0x10 => {
-            if adu_buf.len() > 4 {
-                Some(6 + adu_buf[4] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
+
+    fn push(&mut self, byte: u8) {
+        self.chunk[self.len] = byte;
+        self.len += 1;
+        if self.len == SINK_CHUNK_LEN {
+            self.flush();
         }
-        0x16 => Some(7),
-        0x18 => Some(3),
-        0x17 => {
-            if adu_buf.len() > 10 {
-                Some(10 + adu_buf[10] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
+    }
+
+    fn flush(&mut self) {
+        if self.len > 0 {
+            (self.sink)(&self.chunk[..self.len]);
+            self.len = 0;
         }
-        _ => {
-            return Err(Error::FnCode(fn_code));
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.crc = crc16_update(self.crc, byte);
+            self.push(byte);
         }
-    };
-    Ok(len)
+    }
+
+    /// Append the CRC and pass the remaining bytes to the sink.
+    fn finish(mut self) {
+        for byte in self.crc.rotate_right(8).to_be_bytes() {
+            self.push(byte);
+        }
+        self.flush();
+    }
+}
+
+/// Pass the frame of `slave` with the PDU `head` + `payload` to `sink`.
+pub(crate) fn encode_to_sink(slave: u8, head: &[u8], payload: &[u8], sink: impl FnMut(&[u8])) {
+    let mut sink = ChunkedSink::new(sink);
+    sink.write(&[slave]);
+    sink.write(head);
+    sink.write(payload);
+    sink.finish();
+}
+
+/// Extract the PDU length out of the ADU request buffer.
+///
+/// The length is determined by the [layout](FunctionDescriptor::request_layout)
+/// of the function code.
+pub fn request_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
+    framed_pdu_len(DecoderType::Request, adu_buf.get(1..).unwrap_or_default())
 }
 
 /// Extract the PDU length out of the ADU response buffer.
+///
+/// The length is determined by the [layout](FunctionDescriptor::response_layout)
+/// of the function code.
 pub fn response_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
-    if adu_buf.len() < 2 {
-        return Ok(None);
-    }
-    let fn_code = adu_buf[1];
-    let len = match fn_code {
-        0x01..=0x04 | 0x0C | 0x17 => {
-            if adu_buf.len() > 2 {
-                Some(2 + adu_buf[2] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        0x05 | 0x06 | 0x0B | 0x0F | 0x10 => Some(5),
-        0x07 | 0x81..=0xAB => Some(2),
-        0x16 => Some(7),
-        0x18 => {
-            if adu_buf.len() > 3 {
-                Some(3 + BigEndian::read_u16(&adu_buf[2..=3]) as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        _ => return Err(Error::FnCode(fn_code)),
-    };
-    Ok(len)
+    framed_pdu_len(DecoderType::Response, adu_buf.get(1..).unwrap_or_default())
 }
 
 #[cfg(test)]
@@ -240,31 +707,20 @@ mod tests {
         buf[1] = 0x06;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(5));
 
-        buf[1] = 0x07;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
-        // TODO: 0x08
-
-        buf[1] = 0x0B;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
-        buf[1] = 0x0C;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
         buf[1] = 0x0F;
-        buf[4] = 99;
+        buf[6] = 99; // byte count
         assert_eq!(request_pdu_len(buf).unwrap(), Some(105));
 
         buf[1] = 0x10;
-        buf[4] = 99;
+        buf[6] = 99;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(105));
 
-        buf[1] = 0x11;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
-        // TODO: 0x14
+        buf[1] = 0x14;
+        buf[2] = 14; // byte count
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(16));
 
-        // TODO: 0x15
+        buf[1] = 0x15;
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(16));
 
         buf[1] = 0x16;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(7));
@@ -276,7 +732,12 @@ mod tests {
         buf[1] = 0x18;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(3));
 
-        // TODO: 0x2B
+        buf[1] = 0x2B;
+        buf[2] = 0x0E; // read device identification
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(4));
+        buf[2] = 0x0D; // CANopen general reference
+        assert_eq!(request_pdu_len(buf), Err(DecodeError::FnCode(0x2B)));
+        assert_eq!(request_pdu_len(&buf[..2]), Ok(None));
     }
 
     #[test]
@@ -285,10 +746,13 @@ mod tests {
         assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
         let buf = &mut [0x66, 0x00, 99, 0x00];
-        assert_eq!(response_pdu_len(buf).err().unwrap(), Error::FnCode(0));
+        assert_eq!(response_pdu_len(buf).err().unwrap(), DecodeError::FnCode(0));
 
         let buf = &mut [0x66, 0xee, 99, 0x00];
-        assert_eq!(response_pdu_len(buf).err().unwrap(), Error::FnCode(0xee));
+        assert_eq!(
+            response_pdu_len(buf).err().unwrap(),
+            DecodeError::FnCode(0xee)
+        );
 
         buf[1] = 0x01;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
@@ -308,28 +772,17 @@ mod tests {
         buf[1] = 0x06;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
-        buf[1] = 0x07;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(2));
-
-        // TODO: 0x08
-
-        buf[1] = 0x0B;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
-
-        buf[1] = 0x0C;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
-
         buf[1] = 0x0F;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
         buf[1] = 0x10;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
-        // TODO: 0x11
-
-        // TODO: 0x14
+        buf[1] = 0x14;
+        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
-        // TODO: 0x15
+        buf[1] = 0x15;
+        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
         buf[1] = 0x16;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(7));
@@ -342,12 +795,53 @@ mod tests {
         buf[3] = 0x00; // byte count Lo
         assert_eq!(response_pdu_len(buf).unwrap(), Some(259));
 
-        // TODO: 0x2B
-
-        for i in 0x81..0xAB {
-            buf[1] = i;
+        let device_id = &[
+            0x01, 0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x02, // two objects
+            0x00, 0x03, b'A', b'B', b'C', // vendor name
+            0x01, 0x02, b'X', b'Y', // product code
+        ];
+        assert_eq!(response_pdu_len(device_id), Ok(Some(16)));
+        for len in 2..device_id.len() - 2 {
+            assert_eq!(response_pdu_len(&device_id[..len]), Ok(None));
+        }
+        assert_eq!(response_pdu_len(&device_id[..15]), Ok(Some(16)));
+        assert_eq!(
+            response_pdu_len(&[0x01, 0x2B, 0x0D]),
+            Err(DecodeError::FnCode(0x2B))
+        );
+
+        for descriptor in FunctionDescriptor::ALL {
+            buf[1] = descriptor.function().value() | 0x80;
             assert_eq!(response_pdu_len(buf).unwrap(), Some(2));
         }
+        // Exception responses of unknown functions cannot be told apart from garbage
+        buf[1] = 0x89;
+        assert_eq!(response_pdu_len(buf), Err(DecodeError::FnCode(0x89)));
+    }
+
+    #[test]
+    fn serial_line_pdu_lens() {
+        let requests = [(0x07, 1), (0x08, 5), (0x0B, 1), (0x0C, 1), (0x11, 1)];
+        let responses = [(0x07, 2), (0x08, 5), (0x0B, 5), (0x0C, 101), (0x11, 101)];
+        let buf = &mut [0x66, 0x00, 99, 0x00, 0x00, 0x00, 0x00];
+        for (fn_code, len) in requests {
+            buf[1] = fn_code;
+            let expected = if cfg!(feature = "rtu") {
+                Ok(Some(len))
+            } else {
+                Err(DecodeError::FnCode(fn_code))
+            };
+            assert_eq!(request_pdu_len(buf), expected);
+        }
+        for (fn_code, len) in responses {
+            buf[1] = fn_code;
+            let expected = if cfg!(feature = "rtu") {
+                Ok(Some(len))
+            } else {
+                Err(DecodeError::FnCode(fn_code))
+            };
+            assert_eq!(response_pdu_len(buf), expected);
+        }
     }
 
     mod frame_decoder {
@@ -391,6 +885,25 @@ mod tests {
             assert_eq!(pdu.len(), 6);
         }
 
+        #[test]
+        fn decode_rtu_response_needed_bytes() {
+            let buf = &[0x01];
+            assert_eq!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Incomplete { needed: None }
+            );
+            let buf = &[0x01, 0x03, 0x04, 0x89];
+            assert_eq!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Incomplete { needed: Some(5) }
+            );
+            let buf = &[0x01, 0x03, 0x04, 0x89, 0x02, 0x42, 0xC7, 0x00, 0x9D];
+            assert!(matches!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Complete(_)
+            ));
+        }
+
         #[test]
         fn decode_rtu_response_drop_invalid_bytes() {
             let buf = &[
@@ -414,6 +927,127 @@ mod tests {
             assert_eq!(location.size, 9);
         }
 
+        #[test]
+        fn convert_decoded_frames() {
+            let frame = DecodedFrame {
+                slave: 0x12,
+                pdu: &[0x06, 0x22, 0x22, 0xAB, 0xCD],
+            };
+            let adu = frame.try_into_request_adu().unwrap();
+            assert_eq!(adu.hdr, Header { slave: 0x12 });
+            assert_eq!(
+                adu.pdu,
+                RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD))
+            );
+            let adu = frame.try_into_response_adu().unwrap();
+            assert_eq!(
+                adu.pdu,
+                ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD)))
+            );
+            let frame = DecodedFrame {
+                slave: 0x12,
+                pdu: &[0x06, 0x22],
+            };
+            assert!(matches!(
+                frame.try_into_request_adu(),
+                Err(DecodeError::Truncated(DecodeContext {
+                    transport: Some(Transport::Rtu),
+                    ..
+                }))
+            ));
+        }
+
+        #[test]
+        fn check_frame_status() {
+            use DecoderType::{Request, Response};
+
+            let buf = &[0x01, 0x03, 0x04, 0x89, 0x02, 0x42, 0xC7, 0x00, 0x9D, 0x01];
+            assert_eq!(
+                frame_status(Response, &buf[..1]),
+                FrameStatus::NeedMore(None)
+            );
+            assert_eq!(
+                frame_status(Response, &buf[..2]),
+                FrameStatus::NeedMore(None)
+            );
+            assert_eq!(
+                frame_status(Response, &buf[..3]),
+                FrameStatus::NeedMore(Some(6))
+            );
+            assert_eq!(frame_status(Response, buf), FrameStatus::Complete(9));
+            assert!(is_frame_complete(Response, &buf[..9]));
+            assert!(!is_frame_complete(Response, &buf[..8]));
+            assert_eq!(
+                frame_status(Request, &[0x01, 0x66]),
+                FrameStatus::Invalid(DecodeError::FnCode(0x66))
+            );
+        }
+
+        #[test]
+        fn decode_all_frames() {
+            let frame = [0x01, 0x03, 0x04, 0x89, 0x02, 0x42, 0xC7, 0x00, 0x9D];
+            let mut buf = [0; 21];
+            buf[0] = 0x42; // dropped byte
+            buf[1..10].copy_from_slice(&frame);
+            buf[10..19].copy_from_slice(&frame);
+            buf[19..].copy_from_slice(&frame[..2]);
+            let mut frames = decode_all(DecoderType::Response, &buf);
+            let (frame, location) = frames.next().unwrap();
+            assert_eq!(frame.slave, 0x01);
+            assert_eq!(location, FrameLocation { start: 1, size: 9 });
+            let (_, location) = frames.next().unwrap();
+            assert_eq!(location, FrameLocation { start: 10, size: 9 });
+            assert!(frames.next().is_none());
+            assert_eq!(frames.rest(), &[0x01, 0x03]);
+        }
+
+        #[test]
+        fn decode_next_frames() {
+            let frame = [0x01, 0x03, 0x04, 0x89, 0x02, 0x42, 0xC7, 0x00, 0x9D];
+            let mut buf = [0; 20];
+            buf[0] = 0x42; // dropped byte
+            buf[1..10].copy_from_slice(&frame);
+            buf[10..19].copy_from_slice(&frame);
+            buf[19] = 0x01;
+            let decoded = decode_next(DecoderType::Response, &buf).unwrap().unwrap();
+            assert_eq!(decoded.value.slave, 0x01);
+            assert_eq!(decoded.location, FrameLocation { start: 1, size: 9 });
+            assert_eq!(decoded.rest, &buf[10..]);
+            let decoded = decode_next(DecoderType::Response, decoded.rest)
+                .unwrap()
+                .unwrap();
+            assert_eq!(decoded.location, FrameLocation { start: 0, size: 9 });
+            assert_eq!(decoded.rest, &[0x01]);
+            assert_eq!(decode_next(DecoderType::Response, decoded.rest), Ok(None));
+            assert_eq!(decode_next(DecoderType::Response, &[]), Ok(None));
+
+            let adu = client::decode_next_response(&buf[1..]).unwrap().unwrap();
+            assert_eq!(adu.value.hdr.slave, 0x01);
+            assert_eq!(adu.rest, &buf[10..]);
+        }
+
+        #[test]
+        fn decode_device_identification_traffic() {
+            let mut device_id = [
+                0x01, 0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x01, // one object
+                0x00, 0x03, b'A', b'B', b'C', // vendor name
+                0x00, 0x00, // crc
+            ];
+            let crc = crc16(&device_id[..13]);
+            write_u16(&mut device_id[13..], crc);
+            let frame = [0x01, 0x03, 0x04, 0x89, 0x02, 0x42, 0xC7, 0x00, 0x9D];
+            let mut buf = [0; 24];
+            buf[..15].copy_from_slice(&device_id);
+            buf[15..].copy_from_slice(&frame);
+            let mut frames = decode_all(DecoderType::Response, &buf);
+            let (frame, location) = frames.next().unwrap();
+            assert_eq!(frame.pdu, &device_id[1..13]);
+            assert_eq!(location, FrameLocation { start: 0, size: 15 });
+            let (_, location) = frames.next().unwrap();
+            assert_eq!(location, FrameLocation { start: 15, size: 9 });
+            assert!(frames.rest().is_empty());
+        }
+
         #[test]
         fn decode_rtu_response_with_max_drops() {
             let buf = &[0x42; 10];
@@ -431,5 +1065,59 @@ mod tests {
             buf[264] = 0x9D; // crc
             assert!(decode(DecoderType::Response, buf).is_err());
         }
+
+        #[test]
+        fn resume_after_dropped_bytes() {
+            let buf = &[0xFF, 0xFF, 0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+            let mut decoder = Decoder::new(DecoderType::Response);
+            for len in 1..buf.len() {
+                let status = decoder.decode_status(&buf[..len]).unwrap();
+                assert_eq!(
+                    status,
+                    decode_status(DecoderType::Response, &buf[..len]).unwrap()
+                );
+                assert!(status.complete().is_none());
+            }
+            assert_eq!(decoder.dropped(), 2);
+            let (frame, location) = decoder.decode_status(buf).unwrap().complete().unwrap();
+            assert_eq!(frame.slave, 0x12);
+            assert_eq!(location, FrameLocation { start: 2, size: 8 });
+            assert_eq!(decoder.dropped(), 0);
+        }
+
+        #[test]
+        fn collect_stats() {
+            let buf = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBF];
+            let mut decoder = Decoder::with_stats(DecoderType::Response, Stats::new());
+            for len in 1..=buf.len() {
+                let status = decoder.decode_status(&buf[..len]).unwrap();
+                assert!(status.complete().is_none());
+            }
+            let stats = decoder.stats();
+            assert_eq!(stats.crc_errors, 1);
+            assert_eq!(stats.dropped_bytes as usize, decoder.dropped());
+            assert_eq!(stats.frames, 0);
+
+            let buf = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+            decoder.reset();
+            decoder.decode_status(buf).unwrap().complete().unwrap();
+            assert_eq!(decoder.stats().frames, 1);
+            assert_eq!(
+                decoder.stats().function(FunctionCode::WriteSingleRegister),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_unknown_pdu() {
+        let buf = &mut [0; 6];
+        assert_eq!(wrap_raw_pdu(0x01, &[0x64, 0x01, 0x02], buf), Ok(6));
+        assert_eq!(buf[..4], [0x01, 0x64, 0x01, 0x02]);
+        assert_eq!(read_u16(&buf[4..]), crc16(&buf[..4]));
+        assert_eq!(
+            wrap_raw_pdu(0x01, &[0x64, 0x01, 0x02], &mut [0; 5]),
+            Err(EncodeError::BufferSize)
+        );
     }
 }
@@ -0,0 +1,299 @@
+//! Passive decoding of a shared serial line.
+//!
+//! A sniffer on an RS-485 line receives the requests of the client and the
+//! responses of the servers as one byte stream. The [`Monitor`] remembers the
+//! outstanding request of each slave to decide whether a frame is a request
+//! or a response and pairs each response with its request.
+
+use super::*;
+
+/// The outstanding request of a slave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pending {
+    seq: u32,
+    fn_code: u8,
+}
+
+/// How a captured frame relates to the other frames on the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Kind {
+    /// A request of the client.
+    Request {
+        /// The previous request to the same slave that has never been answered.
+        unanswered: Option<u32>,
+    },
+    /// The response to the request with the sequence number `request`.
+    Response { request: u32 },
+    /// A response that does not answer an outstanding request.
+    Orphan,
+}
+
+/// A frame captured on the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capture<'a> {
+    /// The number of frames that have been captured before.
+    pub seq: u32,
+    pub kind: Kind,
+    pub frame: DecodedFrame<'a>,
+}
+
+/// Decodes the interleaved requests and responses of a serial line.
+///
+/// Like [`Decoder`] the caller is expected to remove each captured
+/// frame, including the dropped bytes in front of it, from the buffer.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu::monitor::{Kind, Monitor}, DecodeStatus};
+///
+/// let mut monitor = Monitor::new();
+/// let mut line: &[u8] = &[
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, // request
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, // response
+/// ];
+/// let mut kinds = [Kind::Orphan; 2];
+/// for kind in &mut kinds {
+///     let DecodeStatus::Complete((capture, location)) = monitor.decode_status(line).unwrap() else {
+///         unreachable!();
+///     };
+///     *kind = capture.kind;
+///     line = &line[location.start + location.size..];
+/// }
+/// assert_eq!(kinds, [Kind::Request { unanswered: None }, Kind::Response { request: 0 }]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    pending: [Option<Pending>; 256],
+    seq: u32,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    /// Create a monitor that expects requests from all slaves.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            pending: [None; 256],
+            seq: 0,
+        }
+    }
+
+    /// The type of the next frame of `slave`.
+    #[must_use]
+    pub const fn expected(&self, slave: SlaveId) -> DecoderType {
+        if self.pending[slave as usize].is_some() {
+            DecoderType::Response
+        } else {
+            DecoderType::Request
+        }
+    }
+
+    /// Forget all outstanding requests, e.g. after a gap in the capture.
+    pub fn reset(&mut self) {
+        self.pending = [None; 256];
+    }
+
+    /// Decode the next request or response.
+    ///
+    /// A frame is decoded as the type that is [expected](Self::expected)
+    /// for its slave and as the other type if that fails.
+    /// Bytes that belong to neither are dropped.
+    pub fn decode_status<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> Result<DecodeStatus<(Capture<'b>, FrameLocation)>> {
+        use DecoderType::{Request, Response};
+
+        if buf.is_empty() {
+            return Err(DecodeError::BufferSize);
+        }
+        let mut start = 0;
+        loop {
+            let raw_frame = &buf[start..];
+            if raw_frame.len() < 2 {
+                return Ok(DecodeStatus::Incomplete { needed: None });
+            }
+            let (first, second) = match self.expected(raw_frame[0]) {
+                Request => (Request, Response),
+                Response => (Response, Request),
+            };
+            let decoded = match (try_decode(first, raw_frame), try_decode(second, raw_frame)) {
+                (Ok(Some(decoded)), _) => (first, decoded),
+                (_, Ok(Some(decoded))) => (second, decoded),
+                (Ok(None), _) | (_, Ok(None)) => {
+                    return Ok(DecodeStatus::Incomplete { needed: None });
+                }
+                (Err(err), Err(_)) => {
+                    if start + 1 >= MAX_FRAME_LEN {
+                        log::error!(
+                            "Giving up to decode frame after dropping {start} byte(s): {:X?}",
+                            &buf[..start]
+                        );
+                        return Err(err);
+                    }
+                    start += 1;
+                    continue;
+                }
+            };
+            let (decoder_type, (frame, size)) = decoded;
+            let capture = self.capture(decoder_type, frame);
+            return Ok(DecodeStatus::Complete((
+                capture,
+                FrameLocation { start, size },
+            )));
+        }
+    }
+
+    /// Pair `frame` with the outstanding request of its slave.
+    fn capture<'b>(&mut self, decoder_type: DecoderType, frame: DecodedFrame<'b>) -> Capture<'b> {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+        let fn_code = frame.pdu[0];
+        let pending = &mut self.pending[usize::from(frame.slave)];
+        let kind = match decoder_type {
+            DecoderType::Request => {
+                let unanswered = pending.take().map(|pending| pending.seq);
+                // Broadcasts are never answered
+                if frame.slave != 0 {
+                    *pending = Some(Pending { seq, fn_code });
+                }
+                Kind::Request { unanswered }
+            }
+            DecoderType::Response => match *pending {
+                Some(request) if request.fn_code == fn_code & 0x7F => {
+                    *pending = None;
+                    Kind::Response {
+                        request: request.seq,
+                    }
+                }
+                _ => Kind::Orphan,
+            },
+        };
+        Capture { seq, kind, frame }
+    }
+}
+
+/// Decode a complete frame at the start of `raw_frame`.
+fn try_decode(
+    decoder_type: DecoderType,
+    raw_frame: &[u8],
+) -> Result<Option<(DecodedFrame<'_>, usize)>> {
+    let pdu_len = match decoder_type {
+        DecoderType::Request => request_pdu_len(raw_frame),
+        DecoderType::Response => response_pdu_len(raw_frame),
+    }?;
+    let Some(pdu_len) = pdu_len else {
+        return Ok(None);
+    };
+    let adu_len = checked_len(3, pdu_len)?;
+    Ok(extract_frame(raw_frame, pdu_len)?.map(|frame| (frame, adu_len)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Append `frame` and its CRC to the first `len` bytes of `line`.
+    fn append(line: &mut [u8], len: &mut usize, frame: &[u8]) {
+        let end = *len + frame.len();
+        line[*len..end].copy_from_slice(frame);
+        write_u16(&mut line[end..], crc16(frame));
+        *len = end + 2;
+    }
+
+    fn capture_all<'b>(
+        monitor: &mut Monitor,
+        mut line: &'b [u8],
+        captures: &mut [Option<Capture<'b>>],
+    ) {
+        for capture in captures {
+            let Ok(DecodeStatus::Complete((frame, location))) = monitor.decode_status(line) else {
+                return;
+            };
+            *capture = Some(frame);
+            line = &line[location.start + location.size..];
+        }
+    }
+
+    #[test]
+    fn pair_interleaved_frames() {
+        let line = &mut [0; 64];
+        let len = &mut 0;
+        append(line, len, &[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        append(line, len, &[0x01, 0x03, 0x02, 0x12, 0x34]);
+        append(line, len, &[0x02, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        append(line, len, &[0x02, 0x83, 0x02]);
+
+        let mut monitor = Monitor::new();
+        let captures = &mut [None; 4];
+        capture_all(&mut monitor, &line[..*len], captures);
+        let kinds = captures.map(|capture| capture.unwrap().kind);
+        assert_eq!(
+            kinds,
+            [
+                Kind::Request { unanswered: None },
+                Kind::Response { request: 0 },
+                Kind::Request { unanswered: None },
+                Kind::Response { request: 2 },
+            ]
+        );
+        assert_eq!(monitor.expected(0x02), DecoderType::Request);
+    }
+
+    #[test]
+    fn flag_orphans() {
+        let line = &mut [0; 64];
+        let len = &mut 0;
+        append(line, len, &[0x01, 0x06, 0x00, 0x01, 0x00, 0x02]);
+        append(line, len, &[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        append(line, len, &[0x05, 0x03, 0x02, 0x12, 0x34]);
+
+        let mut monitor = Monitor::new();
+        let captures = &mut [None; 3];
+        capture_all(&mut monitor, &line[..*len], captures);
+        let kinds = captures.map(|capture| capture.unwrap().kind);
+        assert_eq!(
+            kinds,
+            [
+                Kind::Request { unanswered: None },
+                Kind::Request {
+                    unanswered: Some(0)
+                },
+                Kind::Orphan,
+            ]
+        );
+        assert_eq!(monitor.expected(0x01), DecoderType::Response);
+        monitor.reset();
+        assert_eq!(monitor.expected(0x01), DecoderType::Request);
+    }
+
+    #[test]
+    fn skip_noise() {
+        let line = &mut [0; 64];
+        let len = &mut 2;
+        line[..2].copy_from_slice(&[0xFF, 0x00]);
+        append(line, len, &[0x00, 0x06, 0x00, 0x01, 0x00, 0x02]);
+
+        let mut monitor = Monitor::new();
+        let Ok(DecodeStatus::Complete((capture, location))) = monitor.decode_status(&line[..*len])
+        else {
+            panic!("no frame");
+        };
+        assert_eq!(location, FrameLocation { start: 2, size: 8 });
+        assert_eq!(capture.frame.slave, 0);
+        // Broadcasts are not answered
+        assert_eq!(monitor.expected(0x00), DecoderType::Request);
+        assert_eq!(
+            monitor.decode_status(&line[..3]),
+            Ok(DecodeStatus::Incomplete { needed: None })
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Recovery of frames from corrupted captures.
+//!
+//! The regular decoders take the length of a frame from its function code
+//! and byte count. A corrupted byte in these fields misleads them, so they
+//! drop the frame and often the following ones while resynchronizing.
+//! The [`Recovery`] iterator ignores the length fields and searches for
+//! byte ranges that end with a valid CRC instead.
+//!
+//! The search is considerably slower and may report false positives in
+//! line noise, so it is meant for the offline analysis of captures only.
+
+use super::*;
+use crate::slave::Slave;
+
+/// The shortest frame: slave, function code and CRC.
+const MIN_FRAME_LEN: usize = 4;
+
+/// A frame found by [`Recovery`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Recovered<'a> {
+    pub frame: DecodedFrame<'a>,
+    pub location: FrameLocation,
+    /// Whether the PDU looks like a request or a response.
+    pub classification: Classification,
+}
+
+/// Searches a buffer for frames with a valid CRC.
+///
+/// At every position the shortest range with a valid CRC is taken.
+/// Ranges with a reserved slave address or a PDU that neither is
+/// a request nor a response are skipped.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::rtu::recovery::Recovery;
+///
+/// let capture = [
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, // request
+///     0x12, 0x86, 0x02, 0x22, 0xAB, // corrupted response
+///     0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, // request
+/// ];
+/// let mut recovery = Recovery::new(&capture);
+/// assert_eq!(recovery.next().unwrap().location.start, 0);
+/// assert_eq!(recovery.next().unwrap().location.start, 13);
+/// assert!(recovery.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recovery<'b> {
+    buf: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> Recovery<'b> {
+    /// Search `buf` from the start.
+    #[must_use]
+    pub const fn new(buf: &'b [u8]) -> Self {
+        Self { buf, offset: 0 }
+    }
+
+    /// The number of bytes that have been searched.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The shortest frame that starts at `start`.
+    fn frame_at(&self, start: usize) -> Option<Recovered<'b>> {
+        let bytes = &self.buf[start..];
+        if bytes.len() < MIN_FRAME_LEN || Slave::from_raw(bytes[0]).is_reserved() {
+            return None;
+        }
+        let max_len = bytes.len().min(MAX_FRAME_LEN);
+        let mut crc = 0xFFFF;
+        for (i, byte) in bytes[..max_len - 2].iter().enumerate() {
+            crc = crc16_update(crc, *byte);
+            let len = i + 3;
+            if len < MIN_FRAME_LEN || crc.rotate_right(8) != read_u16(&bytes[len - 2..len]) {
+                continue;
+            }
+            let frame = DecodedFrame {
+                slave: bytes[0],
+                pdu: &bytes[1..len - 2],
+            };
+            let Some(classification) = classify(frame.pdu) else {
+                continue;
+            };
+            return Some(Recovered {
+                frame,
+                location: FrameLocation { start, size: len },
+                classification,
+            });
+        }
+        None
+    }
+}
+
+impl<'b> Iterator for Recovery<'b> {
+    type Item = Recovered<'b>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.buf.len() {
+            let start = self.offset;
+            if let Some(recovered) = self.frame_at(start) {
+                self.offset = start + recovered.location.size;
+                return Some(recovered);
+            }
+            self.offset += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recover_frames_behind_corrupted_byte_count() {
+        let capture = &[
+            0x01, 0x03, 0xFA, // truncated response
+            0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A, // request
+            0x01, 0x03, 0x02, 0x12, 0x34, 0xB5, 0x33, // response
+        ];
+        // The regular decoder waits for the 250 announced bytes
+        assert_eq!(decode(DecoderType::Response, capture), Ok(None));
+        let mut recovery = Recovery::new(capture);
+        let request = recovery.next().unwrap();
+        assert_eq!(request.location, FrameLocation { start: 3, size: 8 });
+        assert_eq!(request.classification.kind, PduKind::Request);
+        let response = recovery.next().unwrap();
+        assert_eq!(response.location, FrameLocation { start: 11, size: 7 });
+        assert_eq!(response.frame.pdu, &[0x03, 0x02, 0x12, 0x34]);
+        assert!(recovery.next().is_none());
+        assert_eq!(recovery.offset(), capture.len());
+    }
+
+    #[test]
+    fn skip_reserved_slaves() {
+        let capture = &[0xF8, 0x03, 0x02, 0x12, 0x34, 0xB5, 0x33];
+        assert!(Recovery::new(capture).next().is_none());
+    }
+}
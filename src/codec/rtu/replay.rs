@@ -0,0 +1,264 @@
+//! Offline decoding of recorded serial traffic.
+//!
+//! Captures, e.g. the export of a logic analyzer, list the received bytes
+//! with a timestamp and the direction of the transfer. The [`Replay`]
+//! iterator joins the bytes of each frame and decodes them. As on the line,
+//! a frame ends at a gap in the transmission or when the direction changes.
+
+use super::*;
+
+/// Bytes that have been recorded at `timestamp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Record<'a> {
+    /// The time of the first byte in units of the capture.
+    pub timestamp: u64,
+    /// Whether the bytes have been sent by the client or by a server.
+    pub direction: DecoderType,
+    pub bytes: &'a [u8],
+}
+
+/// A frame decoded by [`Replay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Replayed {
+    /// The time of the record that started the frame.
+    pub timestamp: u64,
+    pub direction: DecoderType,
+    frame: Result<(SlaveId, [u8; MAX_PDU_LEN], usize)>,
+}
+
+impl Replayed {
+    /// The decoded frame or the reason why the bytes could not be decoded.
+    pub fn frame(&self) -> Result<DecodedFrame<'_>> {
+        let (slave, pdu, len) = self.frame.as_ref().map_err(|err| *err)?;
+        Ok(DecodedFrame {
+            slave: *slave,
+            pdu: &pdu[..*len],
+        })
+    }
+}
+
+/// Decodes the frames of a capture.
+///
+/// Records that follow each other within `gap` units of time
+/// and have the same direction are joined to a frame.
+/// Each frame carries the timestamp of its first record.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu::replay::{Record, Replay}, DecoderType};
+///
+/// let capture = [
+///     (0, DecoderType::Request, &[0x12, 0x06, 0x22][..]),
+///     (1, DecoderType::Request, &[0x22, 0xAB, 0xCD, 0x9F, 0xBE][..]),
+///     (9, DecoderType::Response, &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE][..]),
+/// ];
+/// let records = capture.iter().map(|&(timestamp, direction, bytes)| Record {
+///     timestamp,
+///     direction,
+///     bytes,
+/// });
+/// let mut replay = Replay::new(records, 4);
+///
+/// let request = replay.next().unwrap();
+/// assert_eq!(request.timestamp, 0);
+/// assert_eq!(request.frame().unwrap().pdu, &[0x06, 0x22, 0x22, 0xAB, 0xCD]);
+/// let response = replay.next().unwrap();
+/// assert_eq!(response.timestamp, 9);
+/// assert_eq!(response.direction, DecoderType::Response);
+/// assert!(replay.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replay<'a, I> {
+    records: I,
+    gap: u64,
+    /// The record that belongs to the next frame.
+    next: Option<Record<'a>>,
+    chunk: [u8; MAX_FRAME_LEN],
+    len: usize,
+    /// The number of bytes of the chunk that have been decoded.
+    decoded: usize,
+    /// No further records are added to the chunk.
+    closed: bool,
+    timestamp: u64,
+    last: u64,
+    direction: DecoderType,
+}
+
+impl<'a, I> Replay<'a, I>
+where
+    I: Iterator<Item = Record<'a>>,
+{
+    /// Decode `records`, expecting a silent interval of more than `gap`
+    /// units of time between two frames.
+    pub const fn new(records: I, gap: u64) -> Self {
+        Self {
+            records,
+            gap,
+            next: None,
+            chunk: [0; MAX_FRAME_LEN],
+            len: 0,
+            decoded: 0,
+            closed: false,
+            timestamp: 0,
+            last: 0,
+            direction: DecoderType::Request,
+        }
+    }
+
+    /// Decode the next frame of the closed chunk.
+    fn decode_chunk(&mut self) -> Option<Replayed> {
+        let bytes = &self.chunk[self.decoded..self.len];
+        if bytes.is_empty() {
+            return None;
+        }
+        let frame = match decode_status(self.direction, bytes) {
+            Ok(DecodeStatus::Complete((frame, location))) => {
+                self.decoded += location.start + location.size;
+                let mut pdu = [0; MAX_PDU_LEN];
+                pdu[..frame.pdu.len()].copy_from_slice(frame.pdu);
+                Ok((frame.slave, pdu, frame.pdu.len()))
+            }
+            Ok(DecodeStatus::Incomplete { .. }) => {
+                self.decoded = self.len;
+                Err(DecodeError::Truncated(DecodeContext {
+                    decoder: self.direction,
+                    transport: Some(Transport::Rtu),
+                    fn_code: bytes.get(1).copied(),
+                    offset: bytes.len(),
+                }))
+            }
+            Err(err) => {
+                self.decoded = self.len;
+                Err(err)
+            }
+        };
+        Some(Replayed {
+            timestamp: self.timestamp,
+            direction: self.direction,
+            frame,
+        })
+    }
+
+    /// Add `record` to the current chunk unless it starts a new frame.
+    fn append(&mut self, record: Record<'a>) {
+        if self.len > 0
+            && (record.direction != self.direction
+                || record.timestamp.saturating_sub(self.last) > self.gap)
+        {
+            self.next = Some(record);
+            self.closed = true;
+            return;
+        }
+        if self.len == 0 {
+            self.timestamp = record.timestamp;
+            self.direction = record.direction;
+        }
+        self.last = record.timestamp;
+        let free = MAX_FRAME_LEN - self.len;
+        let (bytes, rest) = record.bytes.split_at(record.bytes.len().min(free));
+        self.chunk[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        if !rest.is_empty() {
+            // Longer than any frame
+            self.next = Some(Record {
+                bytes: rest,
+                ..record
+            });
+            self.closed = true;
+        }
+    }
+}
+
+impl<'a, I> Iterator for Replay<'a, I>
+where
+    I: Iterator<Item = Record<'a>>,
+{
+    type Item = Replayed;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.closed {
+                if let Some(replayed) = self.decode_chunk() {
+                    return Some(replayed);
+                }
+                self.closed = false;
+                self.len = 0;
+                self.decoded = 0;
+            }
+            match self.next.take().or_else(|| self.records.next()) {
+                Some(record) => self.append(record),
+                None if self.len > 0 => self.closed = true,
+                None => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, direction: DecoderType, bytes: &[u8]) -> Record<'_> {
+        Record {
+            timestamp,
+            direction,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn split_frames_at_gaps() {
+        let frame = &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+        let records = frame
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| record(i as u64, DecoderType::Request, core::slice::from_ref(byte)))
+            .chain(
+                frame
+                    .iter()
+                    .map(|byte| record(20, DecoderType::Request, core::slice::from_ref(byte))),
+            );
+        let mut replay = Replay::new(records, 2);
+        assert_eq!(replay.next().unwrap().timestamp, 0);
+        let second = replay.next().unwrap();
+        assert_eq!(second.timestamp, 20);
+        assert_eq!(second.frame().unwrap().slave, 0x12);
+        assert!(replay.next().is_none());
+    }
+
+    #[test]
+    fn several_frames_in_one_record() {
+        let bytes = &[
+            0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE, 0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD,
+            0x9F, 0xBE,
+        ];
+        let mut replay = Replay::new([record(5, DecoderType::Response, bytes)].into_iter(), 0);
+        assert!(replay.next().unwrap().frame().is_ok());
+        assert!(replay.next().unwrap().frame().is_ok());
+        assert!(replay.next().is_none());
+    }
+
+    #[test]
+    fn report_truncated_frames() {
+        let records = [
+            record(0, DecoderType::Request, &[0x12, 0x06, 0x22]),
+            record(1, DecoderType::Response, &[0x12, 0x06]),
+        ];
+        let mut replay = Replay::new(records.into_iter(), 10);
+        let request = replay.next().unwrap();
+        assert_eq!(request.direction, DecoderType::Request);
+        assert!(matches!(
+            request.frame(),
+            Err(DecodeError::Truncated(DecodeContext {
+                fn_code: Some(0x06),
+                offset: 3,
+                ..
+            }))
+        ));
+        assert!(replay.next().unwrap().frame().is_err());
+        assert!(replay.next().is_none());
+    }
+}
@@ -2,51 +2,106 @@
 use super::*;
 
 /// Decode an RTU request.
-pub fn decode_request(buf: &[u8]) -> Result<Option<RequestAdu>> {
-    if buf.is_empty() {
+pub fn decode_request(buf: &[u8]) -> Result<Option<RequestAdu<'_>>> {
+    Ok(decode_next_request(buf)?.map(|decoded| decoded.value))
+}
+
+/// Decode the next RTU request and the bytes that follow it, see [`decode_next`].
+pub fn decode_next_request(buf: &[u8]) -> Result<Option<Decoded<'_, RequestAdu<'_>>>> {
+    let Some(decoded) = decode_next(DecoderType::Request, buf)? else {
         return Ok(None);
-    }
-    decode(DecoderType::Request, buf)
-        .and_then(|frame| {
-            let Some((DecodedFrame { slave, pdu }, _frame_pos)) = frame else {
-                return Ok(None);
-            };
-            let hdr = Header { slave };
-            // Decoding of the PDU should are unlikely to fail due
-            // to transmission errors, because the frame's bytes
-            // have already been verified with the CRC.
-            Request::try_from(pdu)
-                .map(RequestPdu)
-                .map(|pdu| Some(RequestAdu { hdr, pdu }))
-                .map_err(|err| {
-                    // Unrecoverable error
-                    log::error!("Failed to decode request PDU: {err}");
-                    err
-                })
-        })
-        .map_err(|_| {
-            // Decoding the transport frame is non-destructive and must
-            // never fail!
-            unreachable!();
+    };
+    #[cfg(feature = "tracing")]
+    let frame = decoded.value;
+    // Decoding of the PDU should are unlikely to fail due
+    // to transmission errors, because the frame's bytes
+    // have already been verified with the CRC.
+    decoded
+        .try_map(DecodedFrame::try_into_request_adu)
+        .map(Some)
+        .map_err(|err| {
+            // Unrecoverable error
+            log::error!("Failed to decode request PDU: {err}");
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                slave = frame.slave,
+                error = %err,
+                "Failed to decode request PDU"
+            );
+            err
         })
 }
 
+/// Encode an RTU response into a buffer that does not have to be initialized.
+///
+/// Returns the bytes of the encoded ADU.
+pub fn encode_response_into_uninit<'b>(
+    adu: ResponseAdu,
+    buf: &'b mut [MaybeUninit<u8>],
+) -> Result<&'b [u8], EncodeError> {
+    encode_uninit(buf, adu.encoded_len(), |buf| encode_response(adu, buf))
+}
+
+/// Encode an RTU response into `sink` without buffering the whole frame.
+///
+/// The frame is passed in chunks of at most [`SINK_CHUNK_LEN`] bytes
+/// and the CRC is calculated on the fly.
+///
+/// Nothing is passed to `sink` if the response cannot be encoded.
+pub fn encode_response_to_sink(
+    adu: ResponseAdu,
+    sink: impl FnMut(&[u8]),
+) -> Result<(), EncodeError> {
+    let ResponseAdu { hdr, pdu } = adu;
+    match pdu.0 {
+        Ok(rsp) => {
+            let payload = rsp.payload()?;
+            let mut head = [0; SINK_CHUNK_LEN];
+            let head_len = rsp.encode_head(&mut head);
+            encode_to_sink(hdr.slave, &head[..head_len], payload, sink);
+        }
+        Err(ex) => {
+            encode_to_sink(hdr.slave, &ex.head()?, &[], sink);
+        }
+    }
+    Ok(())
+}
+
 /// Encode an RTU response.
-pub fn encode_response(adu: ResponseAdu, buf: &mut [u8]) -> Result<usize> {
+pub fn encode_response(adu: ResponseAdu, buf: &mut [u8]) -> Result<usize, EncodeError> {
     let ResponseAdu { hdr, pdu } = adu;
     if buf.len() < 2 {
-        return Err(Error::BufferSize);
+        return Err(EncodeError::BufferSize);
     }
     let len = pdu.encode(&mut buf[1..])?;
     if buf.len() < len + 3 {
-        return Err(Error::BufferSize);
+        return Err(EncodeError::BufferSize);
     }
     buf[0] = hdr.slave;
     let crc = crc16(&buf[0..=len]);
-    BigEndian::write_u16(&mut buf[len + 1..], crc);
+    write_u16(&mut buf[len + 1..], crc);
     Ok(len + 3)
 }
 
+/// Reject a request with [`Exception::IllegalFunction`].
+///
+/// The response echoes the slave ID and the function code
+/// of the request, e.g. to answer all requests that are not
+/// implemented by a minimal server.
+///
+/// Broadcast requests (see [`BROADCAST`]) must not be answered at all.
+#[must_use]
+pub fn unsupported_function_response(request: &RequestAdu<'_>) -> ResponseAdu<'static> {
+    let function = FunctionCode::from(request.pdu.0);
+    ResponseAdu {
+        hdr: request.hdr,
+        pdu: ResponsePdu(Err(ExceptionResponse::new(
+            function,
+            Exception::IllegalFunction,
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +141,12 @@ mod tests {
         assert_eq!(FunctionCode::from(pdu), FunctionCode::WriteSingleRegister);
     }
 
+    #[test]
+    fn decode_invalid_request_pdu() {
+        let buf = &[0x12, 0x05, 0x00, 0x01, 0x12, 0x34, 0x93, 0xDE];
+        assert_eq!(decode_request(buf), Err(DecodeError::CoilValue(0x1234)));
+    }
+
     #[test]
     fn encode_write_single_register_response() {
         let adu = ResponseAdu {
@@ -104,4 +165,52 @@ mod tests {
         assert_eq!(buf[6], 0x9F);
         assert_eq!(buf[7], 0xBE);
     }
+
+    #[test]
+    fn encode_response_to_sink() {
+        let tx = &mut [0; 100];
+        let expected = &mut [0; 100];
+        let words = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        for pdu in [
+            ResponsePdu(Ok(Response::ReadHoldingRegisters(words))),
+            ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD))),
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            })),
+        ] {
+            let adu = ResponseAdu {
+                hdr: Header { slave: 0x12 },
+                pdu,
+            };
+            let mut len = 0;
+            super::encode_response_to_sink(adu, |chunk| {
+                tx[len..len + chunk.len()].copy_from_slice(chunk);
+                len += chunk.len();
+            })
+            .unwrap();
+            assert_eq!(encode_response(adu, expected), Ok(len));
+            assert_eq!(tx[..len], expected[..len]);
+        }
+    }
+
+    #[test]
+    fn reject_unsupported_function() {
+        let request = RequestAdu {
+            hdr: Header { slave: 0x12 },
+            pdu: RequestPdu(Request::ReadFifoQueue(0x1000)),
+        };
+        let response = unsupported_function_response(&request);
+        assert_eq!(response.hdr, request.hdr);
+        assert_eq!(
+            response.pdu,
+            ResponsePdu(Err(ExceptionResponse::new(
+                FunctionCode::ReadFifoQueue,
+                Exception::IllegalFunction
+            )))
+        );
+    }
 }
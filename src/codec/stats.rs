@@ -0,0 +1,287 @@
+use super::*;
+
+/// Receives the events of a [`rtu::Decoder`] or [`tcp::Decoder`].
+///
+/// The unit type `()` ignores all events, so a decoder without
+/// statistics compiles to the same code as before.
+pub trait Observer {
+    /// A complete frame with the PDU `pdu` has been decoded.
+    fn frame(&mut self, pdu: &[u8]);
+
+    /// The frame at the start of the buffer has an invalid CRC.
+    fn crc_error(&mut self);
+
+    /// A byte has been dropped to resynchronize.
+    fn dropped(&mut self);
+}
+
+impl Observer for () {
+    #[inline(always)]
+    fn frame(&mut self, _: &[u8]) {}
+
+    #[inline(always)]
+    fn crc_error(&mut self) {}
+
+    #[inline(always)]
+    fn dropped(&mut self) {}
+}
+
+/// Counters of the traffic that a decoder has seen.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu, DecodeStatus, DecoderType, Exception, FunctionCode, Stats};
+///
+/// let mut decoder = rtu::Decoder::with_stats(DecoderType::Response, Stats::new());
+/// let rx = [0x01, 0x83, 0x02, 0xC0, 0xF1];
+/// let DecodeStatus::Complete(_) = decoder.decode_status(&rx).unwrap() else {
+///     unreachable!();
+/// };
+/// let stats = decoder.stats();
+/// assert_eq!(stats.frames, 1);
+/// assert_eq!(stats.function(FunctionCode::ReadHoldingRegisters), 1);
+/// assert_eq!(stats.exception(Exception::IllegalDataAddress), 1);
+///
+/// let fc3 = stats.exceptions_of(FunctionCode::ReadHoldingRegisters);
+/// assert_eq!(fc3.get(Exception::IllegalDataAddress), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    /// Complete frames, including exception responses.
+    pub frames: u32,
+    /// Frames with an invalid CRC.
+    pub crc_errors: u32,
+    /// Bytes that have been dropped to resynchronize.
+    pub dropped_bytes: u32,
+    /// Exception responses.
+    pub exceptions: u32,
+    fn_codes: [u32; 0x80],
+    exception_codes: [u32; 0x100],
+    fn_exceptions: [ExceptionCounts; 0x80],
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stats {
+    /// Create zeroed counters.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            frames: 0,
+            crc_errors: 0,
+            dropped_bytes: 0,
+            exceptions: 0,
+            fn_codes: [0; 0x80],
+            exception_codes: [0; 0x100],
+            fn_exceptions: [ExceptionCounts::new(); 0x80],
+        }
+    }
+
+    /// The number of frames with `function`, including exception responses.
+    #[must_use]
+    pub const fn function(&self, function: FunctionCode) -> u32 {
+        let fn_code = function.value();
+        if fn_code < 0x80 {
+            self.fn_codes[fn_code as usize]
+        } else {
+            0
+        }
+    }
+
+    /// The number of exception responses with `exception`.
+    #[must_use]
+    pub const fn exception(&self, exception: Exception) -> u32 {
+        self.exception_codes[exception.value() as usize]
+    }
+
+    /// The exception responses to requests with `function`.
+    #[must_use]
+    pub const fn exceptions_of(&self, function: FunctionCode) -> ExceptionCounts {
+        let fn_code = function.value();
+        if fn_code < 0x80 {
+            self.fn_exceptions[fn_code as usize]
+        } else {
+            ExceptionCounts::new()
+        }
+    }
+
+    /// Zero all counters.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Observer for Stats {
+    fn frame(&mut self, pdu: &[u8]) {
+        self.frames = self.frames.saturating_add(1);
+        let Some(&fn_code) = pdu.first() else {
+            return;
+        };
+        let count = &mut self.fn_codes[usize::from(fn_code & 0x7F)];
+        *count = count.saturating_add(1);
+        if fn_code < 0x80 {
+            return;
+        }
+        self.exceptions = self.exceptions.saturating_add(1);
+        if let Some(&code) = pdu.get(1) {
+            let count = &mut self.exception_codes[usize::from(code)];
+            *count = count.saturating_add(1);
+            self.fn_exceptions[usize::from(fn_code & 0x7F)].count(code);
+        }
+    }
+
+    fn crc_error(&mut self) {
+        self.crc_errors = self.crc_errors.saturating_add(1);
+    }
+
+    fn dropped(&mut self) {
+        self.dropped_bytes = self.dropped_bytes.saturating_add(1);
+    }
+}
+
+/// The exceptions that are defined by the specification.
+const STANDARD_EXCEPTIONS: [Exception; 9] = [
+    Exception::IllegalFunction,
+    Exception::IllegalDataAddress,
+    Exception::IllegalDataValue,
+    Exception::ServerDeviceFailure,
+    Exception::Acknowledge,
+    Exception::ServerDeviceBusy,
+    Exception::MemoryParityError,
+    Exception::GatewayPathUnavailable,
+    Exception::GatewayTargetDevice,
+];
+
+/// Counters of the exception responses to a single function code,
+/// see [`Stats::exceptions_of`].
+///
+/// Custom exception codes are counted together.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ExceptionCounts {
+    standard: [u32; STANDARD_EXCEPTIONS.len()],
+    custom: u32,
+}
+
+impl ExceptionCounts {
+    const fn new() -> Self {
+        Self {
+            standard: [0; STANDARD_EXCEPTIONS.len()],
+            custom: 0,
+        }
+    }
+
+    /// The number of responses with `exception`.
+    ///
+    /// For [`Exception::Custom`] this is the number
+    /// of responses with any custom exception code.
+    #[must_use]
+    pub const fn get(&self, exception: Exception) -> u32 {
+        match standard_index(exception.value()) {
+            Some(idx) => self.standard[idx],
+            None => self.custom,
+        }
+    }
+
+    /// The number of responses with a custom exception code.
+    #[must_use]
+    pub const fn custom(&self) -> u32 {
+        self.custom
+    }
+
+    /// The number of all exception responses.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.standard
+            .iter()
+            .fold(self.custom, |total, count| total.saturating_add(*count))
+    }
+
+    /// The standard exceptions that have been responded at least once.
+    pub fn iter(&self) -> impl Iterator<Item = (Exception, u32)> + '_ {
+        STANDARD_EXCEPTIONS
+            .iter()
+            .zip(&self.standard)
+            .filter(|(_, count)| **count > 0)
+            .map(|(exception, count)| (*exception, *count))
+    }
+
+    fn count(&mut self, code: u8) {
+        let count = match standard_index(code) {
+            Some(idx) => &mut self.standard[idx],
+            None => &mut self.custom,
+        };
+        *count = count.saturating_add(1);
+    }
+}
+
+/// The index of the exception `code` in [`STANDARD_EXCEPTIONS`].
+const fn standard_index(code: u8) -> Option<usize> {
+    match code {
+        0x01..=0x06 => Some(code as usize - 1),
+        0x08 => Some(6),
+        0x0A | 0x0B => Some(code as usize - 3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_frames() {
+        let mut stats = Stats::new();
+        stats.frame(&[0x03, 0x02, 0x12, 0x34]);
+        stats.frame(&[0x83, 0x02]);
+        stats.frame(&[0x90, 0x42]);
+        stats.crc_error();
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.exceptions, 2);
+        assert_eq!(stats.crc_errors, 1);
+        assert_eq!(stats.function(FunctionCode::ReadHoldingRegisters), 2);
+        assert_eq!(stats.function(FunctionCode::WriteMultipleRegisters), 1);
+        assert_eq!(stats.exception(Exception::Custom(0x42)), 1);
+        stats.reset();
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test]
+    fn count_exceptions_per_function() {
+        let mut stats = Stats::new();
+        stats.frame(&[0x83, 0x02]);
+        stats.frame(&[0x83, 0x02]);
+        stats.frame(&[0x83, 0x0B]);
+        stats.frame(&[0x83, 0x42]);
+        stats.frame(&[0x84, 0x02]);
+        stats.frame(&[0x03, 0x02, 0x12, 0x34]);
+
+        let fc3 = stats.exceptions_of(FunctionCode::ReadHoldingRegisters);
+        assert_eq!(fc3.get(Exception::IllegalDataAddress), 2);
+        assert_eq!(fc3.get(Exception::GatewayTargetDevice), 1);
+        assert_eq!(fc3.get(Exception::Custom(0x43)), 1);
+        assert_eq!(fc3.total(), 4);
+        let mut iter = fc3.iter();
+        assert_eq!(iter.next(), Some((Exception::IllegalDataAddress, 2)));
+        assert_eq!(iter.next(), Some((Exception::GatewayTargetDevice, 1)));
+        assert_eq!(iter.next(), None);
+
+        let fc4 = stats.exceptions_of(FunctionCode::ReadInputRegisters);
+        assert_eq!(fc4.get(Exception::IllegalDataAddress), 1);
+        assert_eq!(stats.exceptions_of(FunctionCode::Custom(0xC0)).total(), 0);
+        for exception in STANDARD_EXCEPTIONS {
+            assert_eq!(
+                standard_index(exception.value()).map(|idx| STANDARD_EXCEPTIONS[idx]),
+                Some(exception)
+            );
+        }
+        stats.reset();
+        assert_eq!(stats, Stats::default());
+    }
+}
@@ -0,0 +1,442 @@
+//! Modbus TCP client (master) specific functions.
+use super::*;
+
+/// Encode a TCP request into a buffer that does not have to be initialized.
+///
+/// Returns the bytes of the encoded ADU, e.g. to hand them
+/// to a network stack that provides uninitialized transmit buffers.
+pub fn encode_request_into_uninit<'b>(
+    adu: RequestAdu,
+    buf: &'b mut [MaybeUninit<u8>],
+) -> Result<&'b [u8], EncodeError> {
+    encode_uninit(buf, adu.adu_len(), |buf| encode_request(adu, buf))
+}
+
+/// Encode a TCP request without copying its payload.
+///
+/// `buf` only has to hold the MBAP header and the PDU fields
+/// in front of the payload.
+pub fn encode_request_segments<'r, 'b>(
+    adu: RequestAdu<'r>,
+    buf: &'b mut [u8],
+) -> Result<Segments<'r, 'b>, EncodeError> {
+    let RequestAdu { hdr, pdu } = adu;
+    let payload = pdu.0.payload()?;
+    let head_len = 7 + pdu.0.pdu_len() - payload.len();
+    if buf.len() < head_len {
+        return Err(EncodeError::BufferSize);
+    }
+    write_u16(&mut buf[0..2], hdr.transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    write_u16(&mut buf[4..6], (pdu.0.pdu_len() + 1) as u16);
+    buf[6] = hdr.unit_id;
+    pdu.0.encode_head(&mut buf[7..]);
+    Ok(Segments {
+        head: &buf[..head_len],
+        payload,
+        tail: &[],
+    })
+}
+
+/// Encode a TCP request.
+pub fn encode_request(adu: RequestAdu, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let RequestAdu { hdr, pdu } = adu;
+    if buf.len() < 7 {
+        return Err(EncodeError::BufferSize);
+    }
+    write_u16(&mut buf[0..2], hdr.transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    buf[6] = hdr.unit_id;
+    let len = pdu.encode(&mut buf[7..])?;
+    if buf.len() < len + 7 {
+        return Err(EncodeError::BufferSize);
+    }
+    write_u16(&mut buf[4..6], (len + 1) as u16);
+
+    Ok(len + 7)
+}
+
+/// Encode a `WriteMultipleRegisters` request with the values of `words`.
+///
+/// The words are written directly into `buf`, so no [`Data`]
+/// buffer is needed.
+pub fn encode_write_multiple_registers(
+    hdr: Header,
+    address: Address,
+    words: impl ExactSizeIterator<Item = Word>,
+    buf: &mut [u8],
+) -> Result<usize, EncodeError> {
+    if buf.len() < 7 {
+        return Err(EncodeError::BufferSize);
+    }
+    let len = encode_write_multiple_registers_pdu(address, words, &mut buf[7..])?;
+    write_u16(&mut buf[0..2], hdr.transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    write_u16(&mut buf[4..6], (len + 1) as u16);
+    buf[6] = hdr.unit_id;
+    Ok(len + 7)
+}
+
+/// Generator of consecutive transaction IDs.
+///
+/// The IDs wrap around after [`TransactionId::MAX`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransactionIdGen {
+    next: TransactionId,
+}
+
+impl TransactionIdGen {
+    /// Create a generator that starts with `0`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Create a generator that starts with `id`.
+    #[must_use]
+    pub const fn starting_at(id: TransactionId) -> Self {
+        Self { next: id }
+    }
+
+    /// The ID that will be assigned next.
+    #[must_use]
+    pub const fn peek(&self) -> TransactionId {
+        self.next
+    }
+
+    /// Assign the next ID.
+    pub fn next_id(&mut self) -> TransactionId {
+        let id = self.next;
+        self.next = id.wrapping_add(1);
+        id
+    }
+}
+
+/// Encode a TCP request with the next transaction ID of `ids`.
+///
+/// Returns the assigned transaction ID that has to be matched
+/// against the response and the number of encoded bytes.
+/// The ID is only consumed if the request could be encoded.
+pub fn encode_request_auto(
+    ids: &mut TransactionIdGen,
+    request: Request<'_>,
+    unit_id: UnitId,
+    buf: &mut [u8],
+) -> Result<(TransactionId, usize), EncodeError> {
+    let hdr = Header {
+        transaction_id: ids.peek(),
+        unit_id,
+    };
+    let len = encode_request(
+        RequestAdu {
+            hdr,
+            pdu: RequestPdu(request),
+        },
+        buf,
+    )?;
+    Ok((ids.next_id(), len))
+}
+
+/// Decode a TCP response.
+pub fn decode_response(buf: &[u8]) -> Result<Option<ResponseAdu<'_>>> {
+    if buf.is_empty() {
+        return Err(DecodeError::BufferSize);
+    }
+    Ok(decode_next_response(buf)?.map(|decoded| decoded.value))
+}
+
+/// Decode the next TCP response and the bytes that follow it, see [`decode_next`].
+pub fn decode_next_response(buf: &[u8]) -> Result<Option<Decoded<'_, ResponseAdu<'_>>>> {
+    let Some(decoded) = decode_next(DecoderType::Response, buf)? else {
+        return Ok(None);
+    };
+    #[cfg(feature = "tracing")]
+    let frame = decoded.value;
+    // Decoding of the PDU should are unlikely to fail due
+    // to transmission errors, because the frame's bytes
+    // have already been verified at the TCP level.
+    decoded
+        .try_map(DecodedFrame::try_into_response_adu)
+        .map(Some)
+        .map_err(|err| {
+            // Unrecoverable error
+            log::error!("Failed to decode response PDU: {err}");
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                transaction_id = frame.transaction_id,
+                unit_id = frame.unit_id,
+                error = %err,
+                "Failed to decode response PDU"
+            );
+            err
+        })
+}
+
+/// Decode a TCP response of the unit `expected`.
+///
+/// Responses of other units are rejected with [`DecodeError::ServerId`].
+pub fn decode_response_from(expected: UnitId, buf: &[u8]) -> Result<Option<ResponseAdu<'_>>> {
+    let Some(adu) = decode_response(buf)? else {
+        return Ok(None);
+    };
+    if adu.hdr.unit_id != expected {
+        return Err(DecodeError::ServerId(expected, adu.hdr.unit_id));
+    }
+    Ok(Some(adu))
+}
+
+/// Decode the response with `transaction_id` of the unit `unit_id`.
+///
+/// Complete frames with other transaction IDs, e.g. responses to the
+/// requests of other tasks that share the connection, are skipped.
+/// Returns the response, if it has been received, and the number of
+/// bytes that have been consumed, including the skipped frames.
+///
+/// A response with the expected transaction ID from another unit
+/// is rejected with [`DecodeError::ServerId`].
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::tcp::client::decode_response_for;
+///
+/// let buf = &[
+///     0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02, // other task
+///     0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x12, 0x84, 0x02,
+/// ];
+/// let (rsp, consumed) = decode_response_for(2, 0x12, buf).unwrap();
+/// assert_eq!(rsp.unwrap().hdr.transaction_id, 2);
+/// assert_eq!(consumed, buf.len());
+///
+/// let (rsp, consumed) = decode_response_for(3, 0x12, buf).unwrap();
+/// assert!(rsp.is_none());
+/// assert_eq!(consumed, buf.len());
+/// ```
+pub fn decode_response_for(
+    transaction_id: TransactionId,
+    unit_id: UnitId,
+    buf: &[u8],
+) -> Result<(Option<ResponseAdu<'_>>, usize)> {
+    let mut consumed = 0;
+    for (frame, location) in decode_all(DecoderType::Response, buf) {
+        consumed = location.start + location.size;
+        if frame.transaction_id != transaction_id {
+            log::debug!(
+                "Skipping response with transaction ID {}",
+                frame.transaction_id
+            );
+            continue;
+        }
+        if frame.unit_id != unit_id {
+            return Err(DecodeError::ServerId(unit_id, frame.unit_id));
+        }
+        return Ok((Some(frame.try_into_response_adu()?), consumed));
+    }
+    Ok((None, consumed))
+}
+
+/// Decode the TCP response to `request` of a device with `quirks`.
+///
+/// The frame is delimited by the length field of the MBAP header only.
+/// Returns the location of the response within `buf`, which is preceded
+/// by the echoed request, if any.
+pub fn decode_response_with_quirks<'b>(
+    request: RequestAdu<'_>,
+    buf: &'b [u8],
+    quirks: &QuirkProfile,
+) -> Result<Option<(ResponseAdu<'b>, FrameLocation)>> {
+    let mut echo = [0; MAX_TCP_ADU_LEN];
+    let echo_len = encode_request(request, &mut echo).map_err(|_| DecodeError::BufferSize)?;
+    let Some(start) = quirks.skip_echo(buf, &echo[..echo_len]) else {
+        return Ok(None);
+    };
+    let raw_frame = &buf[start..];
+    let size = match frame_status(DecoderType::Response, raw_frame) {
+        FrameStatus::Complete(size) => size,
+        FrameStatus::NeedMore(_) => return Ok(None),
+        FrameStatus::Invalid(err) => return Err(err),
+    };
+    if size < 8 {
+        return Err(DecodeError::LengthMismatch(size - 6, 2));
+    }
+    let frame = DecodedFrame {
+        transaction_id: read_u16(raw_frame),
+        unit_id: raw_frame[6],
+        pdu: &raw_frame[7..size],
+    };
+    let pdu = ResponsePdu::try_from_with_quirks(frame.pdu, quirks)
+        .map_err(|err| err.in_adu(Transport::Tcp, 7))?;
+    let adu = ResponseAdu {
+        hdr: frame.header(),
+        pdu,
+    };
+    Ok(Some((adu, FrameLocation { start, size })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_write_multiple_registers_from_iter() {
+        let hdr = Header {
+            transaction_id: 7,
+            unit_id: 1,
+        };
+        let buf = &mut [0; 17];
+        let len = encode_write_multiple_registers(hdr, 0x0001, [0x000A, 0x0102].into_iter(), buf)
+            .unwrap();
+        assert_eq!(
+            &buf[..len],
+            &[
+                0x00, 0x07, 0x00, 0x00, 0x00, 0x0B, 0x01, // MBAP header
+                0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02
+            ]
+        );
+        assert_eq!(
+            encode_write_multiple_registers(hdr, 0, [1].into_iter(), &mut buf[..9]),
+            Err(EncodeError::BufferSize)
+        );
+    }
+
+    #[test]
+    fn encode_request_without_copying_the_payload() {
+        let adu = RequestAdu {
+            hdr: Header {
+                transaction_id: 7,
+                unit_id: 1,
+            },
+            pdu: RequestPdu(Request::Custom(RawPdu {
+                function: FunctionCode::Custom(0x42),
+                data: &[1, 2, 3],
+            })),
+        };
+        let buf = &mut [0; 8];
+        let segments = encode_request_segments(adu, buf).unwrap();
+        assert_eq!(
+            segments.head,
+            &[0x00, 0x07, 0x00, 0x00, 0x00, 0x05, 0x01, 0x42]
+        );
+        assert_eq!(segments.payload, &[1, 2, 3]);
+        assert!(segments.tail.is_empty());
+    }
+
+    #[test]
+    fn assign_transaction_ids() {
+        let mut ids = TransactionIdGen::starting_at(0xFFFF);
+        let buf = &mut [0; 12];
+        let (id, len) =
+            encode_request_auto(&mut ids, Request::ReadHoldingRegisters(0x10, 1), 0x01, buf)
+                .unwrap();
+        assert_eq!(id, 0xFFFF);
+        assert_eq!(
+            &buf[..len],
+            &[0xFF, 0xFF, 0, 0, 0, 6, 0x01, 0x03, 0, 0x10, 0, 1]
+        );
+        let (id, _) =
+            encode_request_auto(&mut ids, Request::ReadHoldingRegisters(0x10, 1), 0x01, buf)
+                .unwrap();
+        assert_eq!(id, 0);
+    }
+
+    #[test]
+    fn decode_response_of_another_unit() {
+        let buf = &[0x00, 0x2a, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02];
+        assert!(decode_response_from(0x12, buf).unwrap().is_some());
+        assert_eq!(
+            decode_response_from(0x01, buf).err(),
+            Some(DecodeError::ServerId(0x01, 0x12))
+        );
+    }
+
+    #[test]
+    fn keep_transaction_id_on_error() {
+        let mut ids = TransactionIdGen::new();
+        let err = encode_request_auto(&mut ids, Request::ReadCoils(0, 1), 0x01, &mut [0; 8]);
+        assert!(err.is_err());
+        assert_eq!(ids.peek(), 0);
+    }
+
+    #[test]
+    fn decode_exception_response() {
+        let buf = &[
+            0x00, // Transaction id
+            0x2a, // Transaction id
+            0x00, // Protocol id
+            0x00, // Protocol id
+            0x00, // length
+            0x03, // length
+            0x12, // unit id
+            0x83, // exception function code
+            0x02, // exception code
+        ];
+        let adu = decode_response(buf).unwrap().unwrap();
+        assert_eq!(adu.hdr.transaction_id, 42);
+        assert_eq!(
+            adu.pdu,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            }))
+        );
+    }
+
+    #[test]
+    fn decode_response_with_transaction_id() {
+        let buf = &[
+            0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02, // skipped
+            0x00, 0x08, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02, // expected
+            0x00, 0x09, 0x00, 0x00, // incomplete
+        ];
+        let (rsp, consumed) = decode_response_for(8, 0x12, buf).unwrap();
+        assert_eq!(rsp.unwrap().hdr.transaction_id, 8);
+        assert_eq!(consumed, 18);
+        assert_eq!(decode_response_for(9, 0x12, buf), Ok((None, 18)));
+        assert_eq!(decode_response_for(9, 0x12, &buf[..4]), Ok((None, 0)));
+        assert_eq!(
+            decode_response_for(7, 0x01, buf),
+            Err(DecodeError::ServerId(0x01, 0x12))
+        );
+    }
+
+    #[test]
+    fn decode_response_with_wrong_byte_count() {
+        let quirks = QuirkProfile::new().ignore_byte_count(FunctionCode::ReadInputRegisters);
+        let request = RequestAdu {
+            hdr: Header {
+                transaction_id: 3,
+                unit_id: 1,
+            },
+            pdu: RequestPdu(Request::ReadInputRegisters(0x00, 2)),
+        };
+        let buf = &[
+            0x00, 0x03, 0x00, 0x00, 0x00, 0x07, 0x01, 0x04, 0x02, 0x12, 0x34, 0x56, 0x78,
+        ];
+        assert!(decode_response(buf).ok().flatten().is_none());
+        assert_eq!(
+            decode_response_with_quirks(request, &buf[..12], &quirks),
+            Ok(None)
+        );
+        let (rsp, location) = decode_response_with_quirks(request, buf, &quirks)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp.hdr, request.hdr);
+        assert_eq!(rsp.pdu.ok().unwrap().data().unwrap().len(), 2);
+        assert_eq!(location, FrameLocation { start: 0, size: 13 });
+    }
+
+    #[test]
+    fn request_buffer_too_small() {
+        let adu = RequestAdu {
+            hdr: Header {
+                transaction_id: 42,
+                unit_id: 0x12,
+            },
+            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+        };
+        let buf = &mut [0; 11];
+        let res = encode_request(adu, buf).err().unwrap();
+        assert_eq!(res, EncodeError::BufferSize);
+    }
+}
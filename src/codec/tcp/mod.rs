@@ -1,8 +1,8 @@
 //! Modbus RTU
 
 use super::*;
-use byteorder::{BigEndian, ByteOrder};
 
+pub mod client;
 pub mod server;
 pub use crate::frame::tcp::*;
 
@@ -12,14 +12,45 @@ const MAX_FRAME_LEN: usize = 256;
 
 /// An extracted TCP PDU frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DecodedFrame<'a> {
     pub transaction_id: TransactionId,
     pub unit_id: UnitId,
     pub pdu: &'a [u8],
 }
 
+impl<'a> DecodedFrame<'a> {
+    /// The header of the frame.
+    #[must_use]
+    pub const fn header(&self) -> Header {
+        Header {
+            transaction_id: self.transaction_id,
+            unit_id: self.unit_id,
+        }
+    }
+
+    /// Decode the PDU of a request frame.
+    pub fn try_into_request_adu(self) -> Result<RequestAdu<'a>> {
+        let pdu = Request::try_from(self.pdu).map_err(|err| err.in_adu(Transport::Tcp, 7))?;
+        Ok(RequestAdu {
+            hdr: self.header(),
+            pdu: RequestPdu(pdu),
+        })
+    }
+
+    /// Decode the PDU of a response frame.
+    pub fn try_into_response_adu(self) -> Result<ResponseAdu<'a>> {
+        let pdu = ResponsePdu::try_from(self.pdu).map_err(|err| err.in_adu(Transport::Tcp, 7))?;
+        Ok(ResponseAdu {
+            hdr: self.header(),
+            pdu,
+        })
+    }
+}
+
 /// The location of all bytes that belong to the frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FrameLocation {
     /// The index where the frame starts
     pub start: usize,
@@ -27,92 +58,482 @@ pub struct FrameLocation {
     pub size: usize,
 }
 
+/// A decoded frame or ADU and the bytes that follow it.
+///
+/// Decoding can continue with [`rest`](Self::rest)
+/// without recomputing the offset of the next frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Decoded<'a, T> {
+    pub value: T,
+    /// The location of the frame within the decoded buffer
+    pub location: FrameLocation,
+    /// The bytes after the frame
+    pub rest: &'a [u8],
+}
+
+impl<'a, T> Decoded<'a, T> {
+    /// Attach the bytes of `buf` that follow `location` to `value`.
+    fn new(value: T, location: FrameLocation, buf: &'a [u8]) -> Self {
+        let rest = buf.get(location.start + location.size..).unwrap_or(&[]);
+        Self {
+            value,
+            location,
+            rest,
+        }
+    }
+
+    /// Convert the value, e.g. to decode the PDU of a frame.
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<Decoded<'a, U>> {
+        Ok(Decoded {
+            value: f(self.value)?,
+            location: self.location,
+            rest: self.rest,
+        })
+    }
+}
+
 /// Decode TCP PDU frames from a buffer.
 pub fn decode(
     decoder_type: DecoderType,
     buf: &[u8],
-) -> Result<Option<(DecodedFrame, FrameLocation)>> {
+) -> Result<Option<(DecodedFrame<'_>, FrameLocation)>> {
+    decode_status(decoder_type, buf).map(DecodeStatus::complete)
+}
+
+/// Decode the next TCP PDU frame from a buffer.
+///
+/// In contrast to [`decode`] the bytes after the frame are returned,
+/// so that a loop can continue with them. An empty buffer contains
+/// no frame.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{tcp, DecoderType};
+///
+/// let mut rx: &[u8] = &[
+///     0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02,
+///     0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x12, 0x84, 0x02,
+///     0x00, 0x03, 0x00,
+/// ];
+/// let mut transactions = Vec::new();
+/// while let Some(frame) = tcp::decode_next(DecoderType::Response, rx)? {
+///     transactions.push(frame.value.transaction_id);
+///     rx = frame.rest;
+/// }
+/// assert_eq!(transactions, [1, 2]);
+/// assert_eq!(rx, [0x00, 0x03, 0x00]);
+/// # Ok::<(), modbus_core::DecodeError>(())
+/// ```
+pub fn decode_next(
+    decoder_type: DecoderType,
+    buf: &[u8],
+) -> Result<Option<Decoded<'_, DecodedFrame<'_>>>> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    let frame = decode(decoder_type, buf)?;
+    Ok(frame.map(|(frame, location)| Decoded::new(frame, location, buf)))
+}
+
+/// Decode TCP PDU frames from a buffer.
+///
+/// In contrast to [`decode`] the number of missing bytes
+/// of an incomplete frame is reported as soon as the length
+/// field of the MBAP header has been received.
+pub fn decode_status(
+    decoder_type: DecoderType,
+    buf: &[u8],
+) -> Result<DecodeStatus<(DecodedFrame<'_>, FrameLocation)>> {
+    scan(decoder_type, buf, &[], &mut 0, &mut ())
+}
+
+/// Decode TCP PDU frames from a buffer, see [`decode_status`].
+///
+/// Frames with a function code that is unknown to the framing layer
+/// are delimited according to `custom`.
+pub fn decode_status_with_custom<'b>(
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    custom: &[CustomFunction],
+) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
+    scan(decoder_type, buf, custom, &mut 0, &mut ())
+}
+
+/// Check whether `buf` starts with a complete TCP frame without decoding it.
+///
+/// This is cheaper than [`decode_status`], because only the MBAP header
+/// is inspected. Leading bytes that do not belong to a frame are not skipped.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{tcp, DecoderType, FrameStatus};
+///
+/// let rx = [0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x12, 0x06];
+/// assert_eq!(
+///     tcp::frame_status(DecoderType::Request, &rx),
+///     FrameStatus::NeedMore(Some(4))
+/// );
+/// ```
+#[must_use]
+pub fn frame_status(_decoder_type: DecoderType, buf: &[u8]) -> FrameStatus {
+    let (Some(protocol_id), Some(length)) = (buf.get(2..4), buf.get(4..6)) else {
+        return FrameStatus::NeedMore(None);
+    };
+    let protocol_id = read_u16(protocol_id);
+    if protocol_id != 0 {
+        return FrameStatus::Invalid(DecodeError::ProtocolNotModbus(protocol_id));
+    }
+    match checked_len(6, read_u16(length).into()) {
+        Ok(frame_len) => FrameStatus::of_len(frame_len, buf.len()),
+        Err(err) => FrameStatus::Invalid(err),
+    }
+}
+
+/// Returns `true` if `buf` starts with a complete TCP frame, see [`frame_status`].
+#[must_use]
+pub fn is_frame_complete(decoder_type: DecoderType, buf: &[u8]) -> bool {
+    matches!(frame_status(decoder_type, buf), FrameStatus::Complete(_))
+}
+
+/// Wrap the raw `pdu` into a TCP frame with the MBAP header `hdr`.
+///
+/// Only the header is added, the PDU is not checked. This allows
+/// proxies and test tools to forward frames that [`Request`] and
+/// [`Response`] cannot represent.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::tcp::{self, Header};
+///
+/// let hdr = Header {
+///     transaction_id: 0x0001,
+///     unit_id: 0x12,
+/// };
+/// let buf = &mut [0; 9];
+/// let len = tcp::wrap_raw_pdu(hdr, &[0x41, 0x42], buf).unwrap();
+/// assert_eq!(buf[..len], [0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x12, 0x41, 0x42]);
+/// ```
+pub fn wrap_raw_pdu(hdr: Header, pdu: &[u8], buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let len = MBAP_HEADER_LEN + pdu.len();
+    let Ok(length_field) = u16::try_from(pdu.len() + 1) else {
+        return Err(EncodeError::BufferSize);
+    };
+    if buf.len() < len {
+        return Err(EncodeError::BufferSize);
+    }
+    write_u16(&mut buf[0..2], hdr.transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    write_u16(&mut buf[4..6], length_field);
+    buf[6] = hdr.unit_id;
+    buf[MBAP_HEADER_LEN..len].copy_from_slice(pdu);
+    Ok(len)
+}
+
+/// Extract the PDU length out of the ADU buffer.
+///
+/// Unknown function codes are looked up in `custom`.
+fn pdu_len(
+    decoder_type: DecoderType,
+    adu_buf: &[u8],
+    custom: &[CustomFunction],
+) -> Result<Option<usize>> {
+    match decoder_type {
+        DecoderType::Request => request_pdu_len(adu_buf),
+        DecoderType::Response => response_pdu_len(adu_buf),
+    }
+    .or_else(|err| match err {
+        DecodeError::FnCode(_) => custom_pdu_len(custom, decoder_type, &adu_buf[7..]),
+        err => Err(err),
+    })
+}
+
+/// Decode all complete TCP frames in a buffer.
+///
+/// The locations of the frames are relative to the start of `buf`.
+/// The iteration ends at the first incomplete frame or when no frame
+/// could be found, see [`Frames::rest`].
+#[must_use]
+pub const fn decode_all(decoder_type: DecoderType, buf: &[u8]) -> Frames<'_> {
+    Frames {
+        decoder_type,
+        buf,
+        offset: 0,
+    }
+}
+
+/// Iterator over the frames of a buffer, see [`decode_all`].
+#[derive(Debug, Clone)]
+pub struct Frames<'b> {
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    offset: usize,
+}
+
+impl<'b> Frames<'b> {
+    /// The bytes after the last decoded frame, e.g. to be kept
+    /// until more bytes have been received.
+    #[must_use]
+    pub fn rest(&self) -> &'b [u8] {
+        &self.buf[self.offset..]
+    }
+}
+
+impl<'b> Iterator for Frames<'b> {
+    type Item = (DecodedFrame<'b>, FrameLocation);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest();
+        if rest.is_empty() {
+            return None;
+        }
+        let (frame, location) = decode(self.decoder_type, rest).ok().flatten()?;
+        let location = FrameLocation {
+            start: self.offset + location.start,
+            size: location.size,
+        };
+        self.offset = location.start + location.size;
+        Some((frame, location))
+    }
+}
+
+/// Decode a frame after the first `drop_cnt` bytes of `buf`
+/// which are already known to be invalid.
+fn scan<'b>(
+    decoder_type: DecoderType,
+    buf: &'b [u8],
+    custom: &[CustomFunction],
+    drop_cnt: &mut usize,
+    observer: &mut impl Observer,
+) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
     use DecoderType::{Request, Response};
-    let mut drop_cnt = 0;
 
     if buf.is_empty() {
-        return Err(Error::BufferSize);
+        return Err(DecodeError::BufferSize);
     }
 
     loop {
         let mut retry = false;
-        if drop_cnt + 1 >= buf.len() {
-            return Ok(None);
-        }
-        let raw_frame = &buf[drop_cnt..];
-        let res = match decoder_type {
-            Request => request_pdu_len(raw_frame),
-            Response => response_pdu_len(raw_frame),
+        if *drop_cnt + 1 >= buf.len() {
+            return Ok(DecodeStatus::Incomplete { needed: None });
         }
-        .and_then(|pdu_len| {
+        let raw_frame = &buf[*drop_cnt..];
+        let res = pdu_len(decoder_type, raw_frame, custom).and_then(|pdu_len| {
             retry = false;
-            if let Some(pdu_len) = pdu_len {
-                extract_frame(raw_frame, pdu_len).map(|x| {
-                    x.map(|res| {
-                        (
-                            res,
-                            FrameLocation {
-                                start: drop_cnt,
-                                size: pdu_len + 7,
-                            },
-                        )
-                    })
-                })
-            } else {
+            let Some(pdu_len) = pdu_len else {
                 // Incomplete frame
-                Ok(None)
-            }
+                return Ok(DecodeStatus::Incomplete {
+                    needed: missing_bytes(raw_frame),
+                });
+            };
+            let frame_location = FrameLocation {
+                start: *drop_cnt,
+                size: checked_len(7, pdu_len)?,
+            };
+            let needed = frame_location.size.saturating_sub(raw_frame.len());
+            extract_frame(raw_frame, pdu_len).map(|x| match x {
+                Some(res) => DecodeStatus::Complete((res, frame_location)),
+                None => DecodeStatus::Incomplete {
+                    needed: Some(needed),
+                },
+            })
         })
         .or_else(|err| {
             let pdu_type = match decoder_type {
                 Request => "request",
                 Response => "response",
             };
-            if drop_cnt + 1 >= MAX_FRAME_LEN {
+            if *drop_cnt + 1 >= MAX_FRAME_LEN {
                 log::error!(
                     "Giving up to decode frame after dropping {drop_cnt} byte(s): {:X?}",
-                    &buf[0..drop_cnt]
+                    &buf[0..*drop_cnt]
                 );
+                #[cfg(feature = "tracing")]
+                tracing::error!(dropped = *drop_cnt, error = %err, "Giving up to decode TCP frame");
                 return Err(err);
             }
             log::warn!("Failed to decode {pdu_type} frame: {err}");
-            drop_cnt += 1;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                decoder = ?decoder_type,
+                dropped = *drop_cnt + 1,
+                error = %err,
+                "Dropped byte to resynchronize TCP frame"
+            );
+            observer.dropped();
+            *drop_cnt += 1;
             retry = true;
-            Ok(None)
+            Ok(DecodeStatus::Incomplete { needed: None })
         });
 
         if !retry {
+            if let Ok(DecodeStatus::Complete((frame, _))) = &res {
+                observer.frame(frame.pdu);
+            }
+            #[cfg(feature = "tracing")]
+            if let Ok(DecodeStatus::Complete((frame, location))) = &res {
+                tracing::trace!(
+                    decoder = ?decoder_type,
+                    transaction_id = frame.transaction_id,
+                    unit_id = frame.unit_id,
+                    fn_code = frame.pdu.first().copied(),
+                    len = location.size,
+                    dropped = location.start,
+                    "Decoded TCP frame"
+                );
+            }
             return res;
         }
     }
 }
 
+/// A decoder that resumes where the previous call stopped.
+///
+/// [`decode_status`] starts from the beginning of the buffer on every call.
+/// Decoding a buffer full of line noise after every received byte therefore
+/// costs quadratic time. The `Decoder` remembers the leading bytes that it
+/// has already dropped and continues behind them.
+///
+/// Between two calls bytes may only be appended to the buffer.
+/// After a complete frame or an error the decoder starts over,
+/// i.e. the caller is expected to remove the frame or to clear the buffer.
+///
+/// With [`with_stats`](Self::with_stats) the decoder feeds an
+/// [`Observer`] like [`Stats`] with the frames and errors it encounters.
+/// Vendor-specific functions can be registered with
+/// [`with_custom_functions`](Self::with_custom_functions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Decoder<S = ()> {
+    direction: DecoderType,
+    dropped: usize,
+    custom: &'static [CustomFunction],
+    stats: S,
+}
+
+impl Decoder {
+    /// Create a decoder for requests or responses.
+    #[must_use]
+    pub const fn new(decoder_type: DecoderType) -> Self {
+        Self::with_stats(decoder_type, ())
+    }
+}
+
+impl<S: Observer> Decoder<S> {
+    /// Create a decoder that reports to `stats`.
+    #[must_use]
+    pub const fn with_stats(decoder_type: DecoderType, stats: S) -> Self {
+        Self {
+            direction: decoder_type,
+            dropped: 0,
+            custom: &[],
+            stats,
+        }
+    }
+
+    /// Delimit frames of the vendor-specific functions in `custom`.
+    #[must_use]
+    pub const fn with_custom_functions(mut self, custom: &'static [CustomFunction]) -> Self {
+        self.custom = custom;
+        self
+    }
+
+    /// The statistics that have been collected so far.
+    #[must_use]
+    pub const fn stats(&self) -> &S {
+        &self.stats
+    }
+
+    /// The statistics, e.g. to reset them.
+    pub fn stats_mut(&mut self) -> &mut S {
+        &mut self.stats
+    }
+
+    /// The number of leading bytes that do not belong to a frame.
+    #[must_use]
+    pub const fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Start over, e.g. after the dropped bytes have been removed from the buffer.
+    pub fn reset(&mut self) {
+        self.dropped = 0;
+    }
+
+    /// Decode the next frame, see [`decode_status`].
+    pub fn decode_status<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> Result<DecodeStatus<(DecodedFrame<'b>, FrameLocation)>> {
+        let res = scan(
+            self.direction,
+            buf,
+            self.custom,
+            &mut self.dropped,
+            &mut self.stats,
+        );
+        if !matches!(res, Ok(DecodeStatus::Incomplete { .. })) {
+            self.reset();
+        }
+        res
+    }
+}
+
+/// The number of missing bytes of the ADU according to the length
+/// field of the MBAP header, if it has already been received.
+fn missing_bytes(adu_buf: &[u8]) -> Option<usize> {
+    let length = usize::from(read_u16(adu_buf.get(4..6)?));
+    match length.saturating_add(6).saturating_sub(adu_buf.len()) {
+        0 => None,
+        needed => Some(needed),
+    }
+}
+
+impl Encode for RequestAdu<'_> {
+    fn encoded_len(&self) -> usize {
+        self.adu_len()
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        client::encode_request(*self, buf)
+    }
+}
+
+impl Encode for ResponseAdu<'_> {
+    fn encoded_len(&self) -> usize {
+        self.pdu.encoded_len() + 7
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        server::encode_response(*self, buf)
+    }
+}
+
 /// Extract a PDU frame out of a buffer.
-pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame>> {
+pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame<'_>>> {
     if buf.is_empty() {
-        return Err(Error::BufferSize);
+        return Err(DecodeError::BufferSize);
     }
-    let adu_len = 7 + pdu_len;
+    let adu_len = checked_len(7, pdu_len)?;
     if buf.len() >= adu_len {
         let (adu_buf, _next_frame) = buf.split_at(adu_len);
         let (adu_buf, pdu_data) = adu_buf.split_at(7);
         let (transaction_buf, adu_buf) = adu_buf.split_at(2);
         let (protocol_buf, adu_buf) = adu_buf.split_at(2);
         let (length_buf, adu_buf) = adu_buf.split_at(2);
-        let protocol_id = BigEndian::read_u16(protocol_buf);
+        let protocol_id = read_u16(protocol_buf);
         if protocol_id != 0 {
-            return Err(Error::ProtocolNotModbus(protocol_id));
+            return Err(DecodeError::ProtocolNotModbus(protocol_id));
         }
-        let transaction = BigEndian::read_u16(transaction_buf);
-        let m_length = BigEndian::read_u16(length_buf) as usize;
+        let transaction = read_u16(transaction_buf);
+        let m_length = read_u16(length_buf) as usize;
         let unit = adu_buf[0];
+        // `pdu_len + 1` cannot overflow, because the PDU has been received.
         if m_length != pdu_len + 1 {
-            return Err(Error::LengthMismatch(m_length, pdu_len + 1));
+            return Err(DecodeError::LengthMismatch(m_length, pdu_len + 1));
         }
         return Ok(Some(DecodedFrame {
             transaction_id: transaction,
@@ -125,68 +546,19 @@ pub fn extract_frame(buf: &[u8], pdu_len: usize) -> Result<Option<DecodedFrame>>
 }
 
 /// Extract the PDU length out of the ADU request buffer.
-pub const fn request_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
-    if adu_buf.len() < 8 {
-        return Ok(None);
-    }
-    let fn_code = adu_buf[7];
-    let len = match fn_code {
-        0x01..=0x06 => Some(5),
-        0x07 | 0x0B | 0x0C | 0x11 => Some(1),
-        0x0F | 0x10 => {
-            if adu_buf.len() > 10 {
-                Some(6 + adu_buf[12] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        0x16 => Some(7),
-        0x18 => Some(3),
-        0x17 => {
-            if adu_buf.len() > 16 {
-                Some(10 + adu_buf[16] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        _ => {
-            return Err(Error::FnCode(fn_code));
-        }
-    };
-    Ok(len)
+///
+/// The length is determined by the [layout](FunctionDescriptor::request_layout)
+/// of the function code.
+pub fn request_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
+    framed_pdu_len(DecoderType::Request, adu_buf.get(7..).unwrap_or_default())
 }
 
 /// Extract the PDU length out of the ADU response buffer.
+///
+/// The length is determined by the [layout](FunctionDescriptor::response_layout)
+/// of the function code.
 pub fn response_pdu_len(adu_buf: &[u8]) -> Result<Option<usize>> {
-    if adu_buf.len() < 8 {
-        return Ok(None);
-    }
-    let fn_code = adu_buf[7];
-    let len = match fn_code {
-        0x01..=0x04 | 0x0C | 0x17 => {
-            if adu_buf.len() > 8 {
-                Some(2 + adu_buf[8] as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        0x05 | 0x06 | 0x0B | 0x0F | 0x10 => Some(5),
-        0x07 | 0x81..=0xAB => Some(2),
-        0x16 => Some(7),
-        0x18 => {
-            if adu_buf.len() > 9 {
-                Some(3 + BigEndian::read_u16(&adu_buf[8..=9]) as usize)
-            } else {
-                // incomplete frame
-                None
-            }
-        }
-        _ => return Err(Error::FnCode(fn_code)),
-    };
-    Ok(len)
+    framed_pdu_len(DecoderType::Response, adu_buf.get(7..).unwrap_or_default())
 }
 
 #[cfg(test)]
@@ -216,17 +588,6 @@ mod tests {
         buf[7] = 0x06;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(5));
 
-        buf[7] = 0x07;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
-        // TODO: 0x08
-
-        buf[7] = 0x0B;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
-        buf[7] = 0x0C;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
-
         buf[7] = 0x0F;
         buf[12] = 99;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(105));
@@ -235,12 +596,12 @@ mod tests {
         buf[12] = 99;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(105));
 
-        buf[7] = 0x11;
-        assert_eq!(request_pdu_len(buf).unwrap(), Some(1));
+        buf[7] = 0x14;
+        buf[8] = 14; // byte count
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(16));
 
-        // TODO: 0x14
-
-        // TODO: 0x15
+        buf[7] = 0x15;
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(16));
 
         buf[7] = 0x16;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(7));
@@ -252,7 +613,11 @@ mod tests {
         buf[7] = 0x18;
         assert_eq!(request_pdu_len(buf).unwrap(), Some(3));
 
-        // TODO: 0x2B
+        buf[7] = 0x2B;
+        buf[8] = 0x0E; // read device identification
+        assert_eq!(request_pdu_len(buf).unwrap(), Some(4));
+        buf[8] = 0x0D; // CANopen general reference
+        assert_eq!(request_pdu_len(buf), Err(DecodeError::FnCode(0x2B)));
     }
 
     #[test]
@@ -261,10 +626,13 @@ mod tests {
         assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
         let buf = &mut [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0x00, 99, 0x00];
-        assert_eq!(response_pdu_len(buf).err().unwrap(), Error::FnCode(0));
+        assert_eq!(response_pdu_len(buf).err().unwrap(), DecodeError::FnCode(0));
 
         let buf = &mut [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0xee, 99, 0x00];
-        assert_eq!(response_pdu_len(buf).err().unwrap(), Error::FnCode(0xee));
+        assert_eq!(
+            response_pdu_len(buf).err().unwrap(),
+            DecodeError::FnCode(0xee)
+        );
 
         buf[7] = 0x01;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
@@ -284,28 +652,17 @@ mod tests {
         buf[7] = 0x06;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
-        buf[7] = 0x07;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(2));
-
-        // TODO: 0x08
-
-        buf[7] = 0x0B;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
-
-        buf[7] = 0x0C;
-        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
-
         buf[7] = 0x0F;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
         buf[7] = 0x10;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(5));
 
-        // TODO: 0x11
-
-        // TODO: 0x14
+        buf[7] = 0x14;
+        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
-        // TODO: 0x15
+        buf[7] = 0x15;
+        assert_eq!(response_pdu_len(buf).unwrap(), Some(101));
 
         buf[7] = 0x16;
         assert_eq!(response_pdu_len(buf).unwrap(), Some(7));
@@ -318,12 +675,48 @@ mod tests {
         buf[9] = 0x00; // byte count Lo
         assert_eq!(response_pdu_len(buf).unwrap(), Some(259));
 
-        // TODO: 0x2B
+        let device_id = &[
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x0F, 0x01, // MBAP header
+            0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x01, // one object
+            0x00, 0x03, b'A', b'B', b'C', // vendor name
+        ];
+        assert_eq!(response_pdu_len(device_id), Ok(Some(12)));
+        assert_eq!(response_pdu_len(&device_id[..15]), Ok(None));
 
-        for i in 0x81..0xAB {
-            buf[7] = i;
+        for descriptor in FunctionDescriptor::ALL {
+            buf[7] = descriptor.function().value() | 0x80;
             assert_eq!(response_pdu_len(buf).unwrap(), Some(2));
         }
+        // Exception responses of unknown functions cannot be told apart from garbage
+        buf[7] = 0x89;
+        assert_eq!(response_pdu_len(buf), Err(DecodeError::FnCode(0x89)));
+    }
+
+    #[test]
+    fn serial_line_pdu_lens() {
+        let requests = [(0x07, 1), (0x08, 5), (0x0B, 1), (0x0C, 1), (0x11, 1)];
+        let responses = [(0x07, 2), (0x08, 5), (0x0B, 5), (0x0C, 101), (0x11, 101)];
+        let buf = &mut [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0x00, 99, 0x00, 0x00, 0x00, 0x00,
+        ];
+        for (fn_code, len) in requests {
+            buf[7] = fn_code;
+            let expected = if cfg!(feature = "rtu") {
+                Ok(Some(len))
+            } else {
+                Err(DecodeError::FnCode(fn_code))
+            };
+            assert_eq!(request_pdu_len(buf), expected);
+        }
+        for (fn_code, len) in responses {
+            buf[7] = fn_code;
+            let expected = if cfg!(feature = "rtu") {
+                Ok(Some(len))
+            } else {
+                Err(DecodeError::FnCode(fn_code))
+            };
+            assert_eq!(response_pdu_len(buf), expected);
+        }
     }
 
     mod frame_decoder {
@@ -380,6 +773,25 @@ mod tests {
             assert_eq!(pdu.len(), 6);
         }
 
+        #[test]
+        fn decode_tcp_response_needed_bytes() {
+            let buf = &[0x01, 0x02, 0x00, 0x00];
+            assert_eq!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Incomplete { needed: None }
+            );
+            let buf = &[0x01, 0x02, 0x00, 0x00, 0x00, 0x07];
+            assert_eq!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Incomplete { needed: Some(7) }
+            );
+            let buf = &[0x01, 0x02, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x89];
+            assert_eq!(
+                decode_status(DecoderType::Response, buf).unwrap(),
+                DecodeStatus::Incomplete { needed: Some(3) }
+            );
+        }
+
         #[test]
         fn decode_tcp_response_drop_invalid_bytes() {
             let buf = &[
@@ -408,6 +820,86 @@ mod tests {
             assert_eq!(location.size, 13);
         }
 
+        #[test]
+        fn convert_decoded_frames() {
+            let frame = DecodedFrame {
+                transaction_id: 7,
+                unit_id: 0x12,
+                pdu: &[0x83, 0x02],
+            };
+            let adu = frame.try_into_response_adu().unwrap();
+            assert_eq!(adu.hdr.transaction_id, 7);
+            assert_eq!(adu.hdr.unit_id, 0x12);
+            assert!(adu.pdu.is_exception());
+            assert!(frame.try_into_request_adu().is_err());
+        }
+
+        #[test]
+        fn check_frame_status() {
+            use DecoderType::Request;
+
+            let buf = &[
+                0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x12, 0x34,
+            ];
+            assert_eq!(
+                frame_status(Request, &buf[..5]),
+                FrameStatus::NeedMore(None)
+            );
+            assert_eq!(
+                frame_status(Request, &buf[..6]),
+                FrameStatus::NeedMore(Some(6))
+            );
+            assert_eq!(frame_status(Request, buf), FrameStatus::Complete(12));
+            assert!(is_frame_complete(Request, buf));
+            assert!(!is_frame_complete(Request, &buf[..11]));
+            assert_eq!(
+                frame_status(Request, &[0x00, 0x01, 0x00, 0x02, 0x00, 0x06]),
+                FrameStatus::Invalid(DecodeError::ProtocolNotModbus(2))
+            );
+        }
+
+        #[test]
+        fn decode_all_frames() {
+            let buf = &[
+                0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x12, 0x34, // 1st
+                0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x12, 0x34, // 2nd
+                0x00, 0x03, 0x00, 0x00, // incomplete
+            ];
+            let mut frames = decode_all(DecoderType::Request, buf);
+            let (frame, location) = frames.next().unwrap();
+            assert_eq!(frame.transaction_id, 1);
+            assert_eq!(location, FrameLocation { start: 0, size: 12 });
+            let (frame, location) = frames.next().unwrap();
+            assert_eq!(frame.transaction_id, 2);
+            assert_eq!(
+                location,
+                FrameLocation {
+                    start: 12,
+                    size: 12
+                }
+            );
+            assert!(frames.next().is_none());
+            assert_eq!(frames.rest(), &buf[24..]);
+        }
+
+        #[test]
+        fn decode_next_frames() {
+            let buf = &[
+                0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x12, 0x34, // 1st
+                0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x10, 0x12, 0x34, // 2nd
+                0x00, 0x03, 0x00, 0x00, // incomplete
+            ];
+            let decoded = server::decode_next_request(buf).unwrap().unwrap();
+            assert_eq!(decoded.value.hdr.transaction_id, 1);
+            assert_eq!(decoded.location, FrameLocation { start: 0, size: 12 });
+            assert_eq!(decoded.rest, &buf[12..]);
+            let decoded = server::decode_next_request(decoded.rest).unwrap().unwrap();
+            assert_eq!(decoded.value.hdr.transaction_id, 2);
+            assert_eq!(decoded.rest, &buf[24..]);
+            assert_eq!(server::decode_next_request(decoded.rest), Ok(None));
+            assert_eq!(decode_next(DecoderType::Request, &[]), Ok(None));
+        }
+
         #[test]
         fn decode_tcp_response_with_max_drops() {
             let buf = &[0x42; 10];
@@ -423,5 +915,71 @@ mod tests {
             buf[262] = 0xC7; //
             assert!(decode(DecoderType::Response, buf).is_err());
         }
+
+        #[test]
+        fn resume_after_dropped_bytes() {
+            let buf = &[
+                0x42, 0x43, 0x01, 0x02, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x89, 0x02, 0x42,
+                0xC7,
+            ];
+            let mut decoder = Decoder::new(DecoderType::Response);
+            for len in 1..buf.len() {
+                let status = decoder.decode_status(&buf[..len]).unwrap();
+                assert_eq!(
+                    status,
+                    decode_status(DecoderType::Response, &buf[..len]).unwrap()
+                );
+                assert!(status.complete().is_none());
+            }
+            assert_eq!(decoder.dropped(), 2);
+            let (frame, location) = decoder.decode_status(buf).unwrap().complete().unwrap();
+            assert_eq!(frame.transaction_id, 258);
+            assert_eq!(location, FrameLocation { start: 2, size: 13 });
+            assert_eq!(decoder.dropped(), 0);
+        }
+
+        #[test]
+        fn decode_custom_functions() {
+            const CUSTOM: &[CustomFunction] = &[CustomFunction {
+                fn_code: 0x41,
+                request: PduLen::Fixed(3),
+                response: PduLen::ByteCount { offset: 1, base: 2 },
+            }];
+            let buf = &[0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x01, 0x41, 0x01, 0xAB];
+            assert!(decode(DecoderType::Response, buf).unwrap().is_none());
+            let (frame, location) = decode_status_with_custom(DecoderType::Response, buf, CUSTOM)
+                .unwrap()
+                .complete()
+                .unwrap();
+            assert_eq!(frame.pdu, &[0x41, 0x01, 0xAB]);
+            assert_eq!(location, FrameLocation { start: 0, size: 10 });
+
+            let buf = &[0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0xC1, 0x01];
+            let mut decoder = Decoder::new(DecoderType::Response).with_custom_functions(CUSTOM);
+            let (frame, _) = decoder.decode_status(buf).unwrap().complete().unwrap();
+            assert_eq!(frame.pdu, &[0xC1, 0x01]);
+        }
+    }
+
+    #[test]
+    fn wrap_unknown_pdu() {
+        let hdr = Header {
+            transaction_id: 0x1234,
+            unit_id: 0x01,
+        };
+        let buf = &mut [0; 10];
+        assert_eq!(wrap_raw_pdu(hdr, &[0x64, 0x01, 0x02], buf), Ok(10));
+        assert_eq!(
+            buf,
+            &[0x12, 0x34, 0x00, 0x00, 0x00, 0x04, 0x01, 0x64, 0x01, 0x02]
+        );
+        assert_eq!(
+            frame_status(DecoderType::Request, buf),
+            FrameStatus::Complete(10)
+        );
+        assert_eq!(
+            wrap_raw_pdu(hdr, &[0x64, 0x01, 0x02], &mut [0; 9]),
+            Err(EncodeError::BufferSize)
+        );
     }
 }
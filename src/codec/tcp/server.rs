@@ -1,111 +1,84 @@
 //! Modbus TCP server (slave) specific functions.
 use super::*;
 
+// The client side functions used to live in this module.
+pub use super::client::{decode_response, encode_request};
+
 /// Decode an TCP request.
-pub fn decode_request(buf: &[u8]) -> Result<Option<RequestAdu>> {
-    if buf.is_empty() {
-        return Ok(None);
-    }
-    let frame = decode(DecoderType::Request, buf)?;
-    let Some((decoded_frame, _frame_pos)) = frame else {
+pub fn decode_request(buf: &[u8]) -> Result<Option<RequestAdu<'_>>> {
+    Ok(decode_next_request(buf)?.map(|decoded| decoded.value))
+}
+
+/// Decode the next TCP request and the bytes that follow it, see [`decode_next`].
+pub fn decode_next_request(buf: &[u8]) -> Result<Option<Decoded<'_, RequestAdu<'_>>>> {
+    let Some(decoded) = decode_next(DecoderType::Request, buf)? else {
         return Ok(None);
     };
-    let DecodedFrame {
-        transaction_id,
-        unit_id,
-        pdu,
-    } = decoded_frame;
-    let hdr = Header {
-        transaction_id,
-        unit_id,
-    };
+    #[cfg(feature = "tracing")]
+    let frame = decoded.value;
     // Decoding of the PDU should are unlikely to fail due
     // to transmission errors, because the frame's bytes
     // have already been verified at the TCP level.
-    Request::try_from(pdu)
-        .map(RequestPdu)
-        .map(|pdu| Some(RequestAdu { hdr, pdu }))
+    decoded
+        .try_map(DecodedFrame::try_into_request_adu)
+        .map(Some)
         .map_err(|err| {
             // Unrecoverable error
             log::error!("Failed to decode request PDU: {err}");
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                transaction_id = frame.transaction_id,
+                unit_id = frame.unit_id,
+                error = %err,
+                "Failed to decode request PDU"
+            );
             err
         })
 }
 
-// Decode a TCP response
-pub fn decode_response(buf: &[u8]) -> Result<Option<ResponseAdu>> {
-    if buf.is_empty() {
-        return Err(Error::BufferSize);
-    }
-    decode(DecoderType::Response, buf)
-        .and_then(|frame| {
-            let Some((decoded_frame, _frame_pos)) = frame else {
-                return Ok(None);
-            };
-            let DecodedFrame {
-                transaction_id,
-                unit_id,
-                pdu,
-            } = decoded_frame;
-            let hdr = Header {
-                transaction_id,
-                unit_id,
-            };
-            // Decoding of the PDU should are unlikely to fail due
-            // to transmission errors, because the frame's bytes
-            // have already been verified at the TCP level.
-
-            Response::try_from(pdu)
-                .map(Ok)
-                .or_else(|_| ExceptionResponse::try_from(pdu).map(Err))
-                .map(ResponsePdu)
-                .map(|pdu| Some(ResponseAdu { hdr, pdu }))
-                .map_err(|err| {
-                    // Unrecoverable error
-                    log::error!("Failed to decode response PDU: {err}");
-                    err
-                })
-        })
-        .map_err(|_| {
-            // Decoding the transport frame is non-destructive and must
-            // never fail!
-            unreachable!();
-        })
+/// Encode a TCP response into a buffer that does not have to be initialized.
+///
+/// Returns the bytes of the encoded ADU.
+pub fn encode_response_into_uninit<'b>(
+    adu: ResponseAdu,
+    buf: &'b mut [MaybeUninit<u8>],
+) -> Result<&'b [u8], EncodeError> {
+    encode_uninit(buf, adu.encoded_len(), |buf| encode_response(adu, buf))
 }
 
 /// Encode an TCP response.
-pub fn encode_response(adu: ResponseAdu, buf: &mut [u8]) -> Result<usize> {
+pub fn encode_response(adu: ResponseAdu, buf: &mut [u8]) -> Result<usize, EncodeError> {
     let ResponseAdu { hdr, pdu } = adu;
     if buf.len() < 7 {
-        return Err(Error::BufferSize);
+        return Err(EncodeError::BufferSize);
     }
-    BigEndian::write_u16(&mut buf[0..2], hdr.transaction_id);
-    BigEndian::write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    write_u16(&mut buf[0..2], hdr.transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
     buf[6] = hdr.unit_id;
     let len = pdu.encode(&mut buf[7..])?;
     if buf.len() < len + 7 {
-        return Err(Error::BufferSize);
+        return Err(EncodeError::BufferSize);
     }
-    BigEndian::write_u16(&mut buf[4..6], (len + 1) as u16);
+    write_u16(&mut buf[4..6], (len + 1) as u16);
 
     Ok(len + 7)
 }
 
-pub fn encode_request(adu: RequestAdu, buf: &mut [u8]) -> Result<usize> {
-    let RequestAdu { hdr, pdu } = adu;
-    if buf.len() < 7 {
-        return Err(Error::BufferSize);
+/// Reject a request with [`Exception::IllegalFunction`].
+///
+/// The response echoes the transaction and unit ID and the function code
+/// of the request, e.g. to answer all requests that are not
+/// implemented by a minimal server.
+#[must_use]
+pub fn unsupported_function_response(request: &RequestAdu<'_>) -> ResponseAdu<'static> {
+    let function = FunctionCode::from(request.pdu.0);
+    ResponseAdu {
+        hdr: request.hdr,
+        pdu: ResponsePdu(Err(ExceptionResponse::new(
+            function,
+            Exception::IllegalFunction,
+        ))),
     }
-    BigEndian::write_u16(&mut buf[0..2], hdr.transaction_id);
-    BigEndian::write_u16(&mut buf[2..4], 0); //MODBUS Protocol
-    buf[6] = hdr.unit_id;
-    let len = pdu.encode(&mut buf[7..])?;
-    if buf.len() < len + 7 {
-        return Err(Error::BufferSize);
-    }
-    BigEndian::write_u16(&mut buf[4..6], (len + 1) as u16);
-
-    Ok(len + 7)
 }
 
 #[cfg(test)]
@@ -198,30 +171,78 @@ mod tests {
     }
 
     #[test]
-    fn response_buffer_too_small() {
+    fn encode_response_adu() {
         let adu = ResponseAdu {
             hdr: Header {
                 transaction_id: 42,
                 unit_id: 0x12,
             },
-            pdu: ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD))),
+            pdu: ResponsePdu(Err(ExceptionResponse::new(
+                FunctionCode::ReadHoldingRegisters,
+                Exception::IllegalDataAddress,
+            ))),
         };
-        let buf = &mut [0; 11];
-        let res = encode_response(adu, buf).err().unwrap();
-        assert_eq!(res, Error::BufferSize);
+        assert_eq!(adu.encoded_len(), 9);
+        assert_eq!(
+            adu.encode_checked(&mut [0; 100]).unwrap(),
+            &[0x00, 0x2A, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02]
+        );
+        assert_eq!(adu.encode(&mut [0; 8]), Err(EncodeError::BufferSize));
+        assert_eq!(response_adu_len(&Response::WriteSingleCoil(0, true)), 12);
+        assert_eq!(request_adu_len(&Request::ReadHoldingRegisters(0, 1)), 12);
     }
 
     #[test]
-    fn request_buffer_too_small() {
-        let adu = RequestAdu {
+    fn encode_exception_response_into_uninit_buffer() {
+        let adu = ResponseAdu {
             hdr: Header {
                 transaction_id: 42,
                 unit_id: 0x12,
             },
-            pdu: RequestPdu(Request::WriteSingleRegister(0x2222, 0xABCD)),
+            pdu: ResponsePdu(Err(ExceptionResponse::new(
+                FunctionCode::ReadHoldingRegisters,
+                Exception::IllegalDataAddress,
+            ))),
+        };
+        let buf = &mut [MaybeUninit::uninit(); 100];
+        assert_eq!(
+            encode_response_into_uninit(adu, buf),
+            Ok(&[0x00, 0x2A, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x02][..])
+        );
+    }
+
+    #[test]
+    fn response_buffer_too_small() {
+        let adu = ResponseAdu {
+            hdr: Header {
+                transaction_id: 42,
+                unit_id: 0x12,
+            },
+            pdu: ResponsePdu(Ok(Response::WriteSingleRegister(0x2222, 0xABCD))),
         };
         let buf = &mut [0; 11];
-        let res = encode_request(adu, buf).err().unwrap();
-        assert_eq!(res, Error::BufferSize);
+        let res = encode_response(adu, buf).err().unwrap();
+        assert_eq!(res, EncodeError::BufferSize);
+    }
+
+    #[test]
+    fn reject_unsupported_function() {
+        let hdr = Header {
+            transaction_id: 42,
+            unit_id: 0x12,
+        };
+        let request = RequestAdu {
+            hdr,
+            pdu: RequestPdu(Request::Custom(RawPdu {
+                function: FunctionCode::Custom(0x41),
+                data: &[0x01],
+            })),
+        };
+        let buf = &mut [0; 16];
+        let len = encode_response(unsupported_function_response(&request), buf).unwrap();
+        assert_eq!(
+            &buf[..len],
+            &[0x00, 0x2A, 0x00, 0x00, 0x00, 0x03, 0x12, 0xC1, 0x01]
+        );
     }
 }
@@ -1,7 +1,15 @@
+use crate::{
+    codec::{DecoderType, Transport},
+    frame::FunctionCode,
+};
 use core::fmt;
 
 /// modbus-core Error
+///
+/// Both [`EncodeError`] and [`DecodeError`] can be converted into it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// Invalid coil value
     CoilValue(u16),
@@ -21,6 +29,176 @@ pub enum Error {
     LengthMismatch(usize, usize),
     /// Protocol not Modbus
     ProtocolNotModbus(u16),
+    /// Invalid reference type
+    ReferenceType(u8),
+    /// Response of an unexpected slave or unit
+    ServerId(u8, u8),
+    /// Frame ended before it could be decoded completely
+    Truncated(DecodeContext),
+    /// Quantity outside the limits of the specification
+    Quantity(u16),
+    /// Byte count that does not match the quantity
+    QuantityBytesMismatch(u16, u8),
+    /// Unused bits of the last byte of packed coils are set
+    CoilPadding(u8),
+    /// Length of a frame that does not fit into `usize`
+    LengthOverflow,
+    /// Event of a communications event log that is not defined
+    CommEvent(u8),
+}
+
+/// Errors that may occur while encoding a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The buffer is too small
+    BufferSize,
+    /// Quantity outside the limits of the specification
+    Quantity(u16),
+    /// Function code that cannot be encoded
+    FnCode(u8),
+    /// Payload that is shorter than its quantity requires
+    LengthMismatch(usize, usize),
+}
+
+/// Errors that may occur while decoding a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// Invalid coil value
+    CoilValue(u16),
+    /// Invalid buffer size
+    BufferSize,
+    /// Invalid function code
+    FnCode(u8),
+    /// Invalid exception code
+    ExceptionCode(u8),
+    /// Invalid exception function code
+    ExceptionFnCode(u8),
+    /// Invalid CRC
+    Crc(u16, u16),
+    /// Invalid byte count
+    ByteCount(u8),
+    /// Length Mismatch
+    LengthMismatch(usize, usize),
+    /// Protocol not Modbus
+    ProtocolNotModbus(u16),
+    /// Invalid reference type
+    ReferenceType(u8),
+    /// Response of an unexpected slave or unit
+    ServerId(u8, u8),
+    /// Frame ended before it could be decoded completely
+    Truncated(DecodeContext),
+    /// Quantity outside the limits of the specification
+    Quantity(u16),
+    /// Byte count that does not match the quantity
+    QuantityBytesMismatch(u16, u8),
+    /// Unused bits of the last byte of packed coils are set
+    CoilPadding(u8),
+    /// Length of a frame that does not fit into `usize`
+    LengthOverflow,
+    /// Event of a communications event log that is not defined
+    CommEvent(u8),
+}
+
+impl DecodeError {
+    /// Move the context of a PDU error into the ADU of `transport`
+    /// that is preceded by a header of `header_len` bytes.
+    pub(crate) const fn in_adu(self, transport: Transport, header_len: usize) -> Self {
+        match self {
+            Self::Truncated(ctx) if ctx.transport.is_none() => Self::Truncated(DecodeContext {
+                transport: Some(transport),
+                offset: ctx.offset + header_len,
+                ..ctx
+            }),
+            err => err,
+        }
+    }
+}
+
+impl From<EncodeError> for Error {
+    fn from(err: EncodeError) -> Self {
+        match err {
+            EncodeError::BufferSize => Self::BufferSize,
+            EncodeError::Quantity(quantity) => Self::Quantity(quantity),
+            EncodeError::FnCode(fn_code) => Self::FnCode(fn_code),
+            EncodeError::LengthMismatch(expected, actual) => Self::LengthMismatch(expected, actual),
+        }
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        use DecodeError as E;
+
+        match err {
+            E::CoilValue(v) => Self::CoilValue(v),
+            E::BufferSize => Self::BufferSize,
+            E::FnCode(fn_code) => Self::FnCode(fn_code),
+            E::ExceptionCode(code) => Self::ExceptionCode(code),
+            E::ExceptionFnCode(code) => Self::ExceptionFnCode(code),
+            E::Crc(expected, actual) => Self::Crc(expected, actual),
+            E::ByteCount(cnt) => Self::ByteCount(cnt),
+            E::LengthMismatch(length_field, pdu_len) => Self::LengthMismatch(length_field, pdu_len),
+            E::ProtocolNotModbus(protocol_id) => Self::ProtocolNotModbus(protocol_id),
+            E::ReferenceType(ref_type) => Self::ReferenceType(ref_type),
+            E::ServerId(expected, actual) => Self::ServerId(expected, actual),
+            E::Truncated(ctx) => Self::Truncated(ctx),
+            E::Quantity(quantity) => Self::Quantity(quantity),
+            E::QuantityBytesMismatch(quantity, byte_count) => {
+                Self::QuantityBytesMismatch(quantity, byte_count)
+            }
+            E::CoilPadding(byte) => Self::CoilPadding(byte),
+            E::LengthOverflow => Self::LengthOverflow,
+            E::CommEvent(event) => Self::CommEvent(event),
+        }
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Error::from(*self))
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Error::from(*self))
+    }
+}
+
+/// Where decoding a frame failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DecodeContext {
+    /// Whether a request or a response has been decoded.
+    pub decoder: DecoderType,
+    /// The transport of the ADU or `None` if only the PDU has been decoded.
+    pub transport: Option<Transport>,
+    /// The function code, if it has already been read.
+    pub fn_code: Option<u8>,
+    /// The byte offset within the PDU or ADU of the field that could not be decoded.
+    pub offset: usize,
+}
+
+impl fmt::Display for DecodeContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decoder = match self.decoder {
+            DecoderType::Request => "request",
+            DecoderType::Response => "response",
+        };
+        match self.transport {
+            Some(Transport::Rtu) => write!(f, "RTU {decoder} ADU")?,
+            Some(Transport::Tcp) => write!(f, "TCP {decoder} ADU")?,
+            None => write!(f, "{decoder} PDU")?,
+        }
+        if let Some(fn_code) = self.fn_code {
+            write!(f, " (function 0x{fn_code:0>2X})")?;
+        }
+        write!(f, " at byte {}", self.offset)
+    }
 }
 
 impl fmt::Display for Error {
@@ -45,6 +223,76 @@ impl fmt::Display for Error {
             Self::ProtocolNotModbus(protocol_id) => {
                 write!(f, "Protocol not Modbus(0), recieved {protocol_id} instead")
             }
+            Self::ReferenceType(ref_type) => write!(f, "Invalid reference type: {ref_type}"),
+            Self::ServerId(expected, actual) => write!(
+                f,
+                "Unexpected slave or unit ID: expected = {expected}, actual = {actual}"
+            ),
+            Self::Truncated(ctx) => write!(f, "Truncated {ctx}"),
+            Self::Quantity(quantity) => write!(f, "Invalid quantity: {quantity}"),
+            Self::QuantityBytesMismatch(quantity, byte_count) => write!(
+                f,
+                "Byte count {byte_count} does not match the quantity {quantity}"
+            ),
+            Self::CoilPadding(byte) => {
+                write!(f, "Padding bits of packed coils are set: 0x{byte:0>2X}")
+            }
+            Self::LengthOverflow => write!(f, "Frame length overflows"),
+            Self::CommEvent(event) => write!(f, "Invalid communication event: 0x{event:0>2X}"),
+        }
+    }
+}
+
+/// A response that does not match the originating request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MismatchError {
+    /// The response belongs to a different function.
+    FnCode {
+        expected: FunctionCode,
+        received: FunctionCode,
+    },
+    /// The echoed address differs from the requested one.
+    Address { expected: u16, received: u16 },
+    /// The echoed quantity differs from the requested one.
+    Quantity { expected: u16, received: u16 },
+    /// The echoed value differs from the written one.
+    Value { expected: u16, received: u16 },
+    /// The number of bytes does not match the requested quantity.
+    ByteCount { expected: usize, received: usize },
+    /// The echoed sub-function code differs from the requested one.
+    SubFunction { expected: u16, received: u16 },
+}
+
+impl fmt::Display for MismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FnCode { expected, received } => write!(
+                f,
+                "Function code mismatch: expected = 0x{:0>2X}, received = 0x{:0>2X}",
+                expected.value(),
+                received.value()
+            ),
+            Self::Address { expected, received } => write!(
+                f,
+                "Address mismatch: expected = 0x{expected:0>4X}, received = 0x{received:0>4X}"
+            ),
+            Self::Quantity { expected, received } => write!(
+                f,
+                "Quantity mismatch: expected = {expected}, received = {received}"
+            ),
+            Self::Value { expected, received } => write!(
+                f,
+                "Value mismatch: expected = 0x{expected:0>4X}, received = 0x{received:0>4X}"
+            ),
+            Self::ByteCount { expected, received } => write!(
+                f,
+                "Byte count mismatch: expected = {expected}, received = {received}"
+            ),
+            Self::SubFunction { expected, received } => write!(
+                f,
+                "Sub-function mismatch: expected = 0x{expected:0>4X}, received = 0x{received:0>4X}"
+            ),
         }
     }
 }
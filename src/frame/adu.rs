@@ -0,0 +1,31 @@
+/// An application data unit (ADU) of any transport.
+///
+/// `H` is the transport specific header, e.g. [`rtu::Header`](crate::rtu::Header)
+/// or [`tcp::Header`](crate::tcp::Header), and `P` the PDU. Code that only
+/// inspects the PDU or the addressed unit can be written once for both
+/// transports:
+///
+/// ```
+/// use modbus_core::{rtu, tcp, Adu, AduHeader, Request, RequestPdu};
+///
+/// fn is_allowed<H: AduHeader>(adu: &Adu<H, RequestPdu<'_>>) -> bool {
+///     adu.hdr.unit_id() == 1 || adu.pdu.0.address() < Some(0x100)
+/// }
+///
+/// let pdu = RequestPdu(Request::ReadHoldingRegisters(0x200, 1));
+/// assert!(is_allowed(&rtu::RequestAdu { hdr: rtu::Header { slave: 1 }, pdu }));
+/// let hdr = tcp::Header { transaction_id: 7, unit_id: 2 };
+/// assert!(!is_allowed(&tcp::RequestAdu { hdr, pdu }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Adu<H, P> {
+    pub hdr: H,
+    pub pdu: P,
+}
+
+/// The header of an [`Adu`].
+pub trait AduHeader {
+    /// The RTU slave ID or the TCP unit ID.
+    fn unit_id(&self) -> u8;
+}
@@ -0,0 +1,266 @@
+//! Frames that copy their payload into an inline buffer.
+//!
+//! Unlike the borrowed frames they can be built in one place and
+//! encoded later, without an allocator.
+
+use super::*;
+use crate::error::EncodeError;
+
+/// A [`Request`] with a payload of up to `N` bytes.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{Data, Request, RequestBuf};
+///
+/// let buf = &mut [0; 4];
+/// let words = Data::from_words(&[0x1234, 0x5678], buf).unwrap();
+/// let queued = RequestBuf::<4>::new(Request::WriteMultipleRegisters(0x10, words)).unwrap();
+/// assert_eq!(queued.as_ref(), Request::WriteMultipleRegisters(0x10, words));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RequestBuf<const N: usize> {
+    request: Request<'static>,
+    payload: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> RequestBuf<N> {
+    /// Copy `request`, failing if its payload does not fit.
+    pub fn new(request: Request<'_>) -> Result<Self, EncodeError> {
+        let src = request_payload(&request);
+        let mut payload = [0; N];
+        payload
+            .get_mut(..src.len())
+            .ok_or(EncodeError::BufferSize)?
+            .copy_from_slice(src);
+        Ok(Self {
+            request: with_request_payload(request, &[]),
+            payload,
+            len: src.len(),
+        })
+    }
+
+    /// Borrow the request, e.g. to encode it.
+    #[must_use]
+    pub fn as_ref(&self) -> Request<'_> {
+        with_request_payload(self.request, &self.payload[..self.len])
+    }
+}
+
+impl<const N: usize> TryFrom<Request<'_>> for RequestBuf<N> {
+    type Error = EncodeError;
+
+    fn try_from(request: Request<'_>) -> Result<Self, Self::Error> {
+        Self::new(request)
+    }
+}
+
+/// A [`Response`] with a payload of up to `N` bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResponseBuf<const N: usize> {
+    response: Response<'static>,
+    payload: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ResponseBuf<N> {
+    /// Copy `response`, failing if its payload does not fit.
+    pub fn new(response: Response<'_>) -> Result<Self, EncodeError> {
+        let src = response_payload(&response);
+        let mut payload = [0; N];
+        payload
+            .get_mut(..src.len())
+            .ok_or(EncodeError::BufferSize)?
+            .copy_from_slice(src);
+        Ok(Self {
+            response: with_response_payload(response, &[]),
+            payload,
+            len: src.len(),
+        })
+    }
+
+    /// Borrow the response, e.g. to encode it.
+    #[must_use]
+    pub fn as_ref(&self) -> Response<'_> {
+        with_response_payload(self.response, &self.payload[..self.len])
+    }
+}
+
+impl<const N: usize> TryFrom<Response<'_>> for ResponseBuf<N> {
+    type Error = EncodeError;
+
+    fn try_from(response: Response<'_>) -> Result<Self, Self::Error> {
+        Self::new(response)
+    }
+}
+
+/// The bytes that `request` borrows.
+const fn request_payload<'r>(request: &Request<'r>) -> &'r [u8] {
+    match request {
+        Request::WriteMultipleCoils(_, Coils { data, .. })
+        | Request::WriteMultipleRegisters(_, Data { data, .. })
+        | Request::ReadWriteMultipleRegisters(_, _, _, Data { data, .. })
+        | Request::ReadFileRecord(FileRecordRequests { data })
+        | Request::WriteFileRecord(FileRecords { data })
+        | Request::Custom(RawPdu { data, .. }) => data,
+        #[cfg(feature = "rtu")]
+        Request::Diagnostics(_, Data { data, .. }) => data,
+        _ => &[],
+    }
+}
+
+/// Replace the bytes that `request` borrows by `data`.
+const fn with_request_payload<'a>(request: Request<'_>, data: &'a [u8]) -> Request<'a> {
+    use Request as R;
+
+    match request {
+        R::ReadCoils(address, quantity) => R::ReadCoils(address, quantity),
+        R::ReadDiscreteInputs(address, quantity) => R::ReadDiscreteInputs(address, quantity),
+        R::WriteSingleCoil(address, coil) => R::WriteSingleCoil(address, coil),
+        R::WriteMultipleCoils(address, Coils { quantity, .. }) => {
+            R::WriteMultipleCoils(address, Coils { data, quantity })
+        }
+        R::ReadInputRegisters(address, quantity) => R::ReadInputRegisters(address, quantity),
+        R::ReadHoldingRegisters(address, quantity) => R::ReadHoldingRegisters(address, quantity),
+        R::WriteSingleRegister(address, word) => R::WriteSingleRegister(address, word),
+        R::WriteMultipleRegisters(address, Data { quantity, .. }) => {
+            R::WriteMultipleRegisters(address, Data { data, quantity })
+        }
+        R::ReadWriteMultipleRegisters(read_address, read_quantity, write_address, words) => {
+            R::ReadWriteMultipleRegisters(
+                read_address,
+                read_quantity,
+                write_address,
+                Data {
+                    data,
+                    quantity: words.quantity,
+                },
+            )
+        }
+        R::ReadFifoQueue(address) => R::ReadFifoQueue(address),
+        R::ReadFileRecord(_) => R::ReadFileRecord(FileRecordRequests { data }),
+        R::WriteFileRecord(_) => R::WriteFileRecord(FileRecords { data }),
+        #[cfg(feature = "rtu")]
+        R::ReadExceptionStatus => R::ReadExceptionStatus,
+        #[cfg(feature = "rtu")]
+        R::Diagnostics(sub_function, Data { quantity, .. }) => {
+            R::Diagnostics(sub_function, Data { data, quantity })
+        }
+        #[cfg(feature = "rtu")]
+        R::GetCommEventCounter => R::GetCommEventCounter,
+        #[cfg(feature = "rtu")]
+        R::GetCommEventLog => R::GetCommEventLog,
+        #[cfg(feature = "rtu")]
+        R::ReportServerId => R::ReportServerId,
+        R::Custom(RawPdu { function, .. }) => R::Custom(RawPdu { function, data }),
+    }
+}
+
+/// The bytes that `response` borrows.
+const fn response_payload<'r>(response: &Response<'r>) -> &'r [u8] {
+    match response {
+        Response::ReadCoils(Coils { data, .. })
+        | Response::ReadDiscreteInputs(Coils { data, .. })
+        | Response::ReadInputRegisters(Data { data, .. })
+        | Response::ReadHoldingRegisters(Data { data, .. })
+        | Response::ReadWriteMultipleRegisters(Data { data, .. })
+        | Response::ReadFifoQueue(Data { data, .. })
+        | Response::ReadFileRecord(FileRecordData { data })
+        | Response::WriteFileRecord(FileRecords { data })
+        | Response::Custom(RawPdu { data, .. }) => data,
+        #[cfg(feature = "rtu")]
+        Response::Diagnostics(_, Data { data, .. })
+        | Response::GetCommEventLog(_, _, _, data)
+        | Response::ReportServerId(data, _) => data,
+        _ => &[],
+    }
+}
+
+/// Replace the bytes that `response` borrows by `data`.
+const fn with_response_payload<'a>(response: Response<'_>, data: &'a [u8]) -> Response<'a> {
+    use Response as R;
+
+    match response {
+        R::ReadCoils(Coils { quantity, .. }) => R::ReadCoils(Coils { data, quantity }),
+        R::ReadDiscreteInputs(Coils { quantity, .. }) => {
+            R::ReadDiscreteInputs(Coils { data, quantity })
+        }
+        R::WriteSingleCoil(address, coil) => R::WriteSingleCoil(address, coil),
+        R::WriteMultipleCoils(address, quantity) => R::WriteMultipleCoils(address, quantity),
+        R::ReadInputRegisters(Data { quantity, .. }) => {
+            R::ReadInputRegisters(Data { data, quantity })
+        }
+        R::ReadHoldingRegisters(Data { quantity, .. }) => {
+            R::ReadHoldingRegisters(Data { data, quantity })
+        }
+        R::WriteSingleRegister(address, word) => R::WriteSingleRegister(address, word),
+        R::WriteMultipleRegisters(address, quantity) => {
+            R::WriteMultipleRegisters(address, quantity)
+        }
+        R::ReadWriteMultipleRegisters(Data { quantity, .. }) => {
+            R::ReadWriteMultipleRegisters(Data { data, quantity })
+        }
+        R::ReadFifoQueue(Data { quantity, .. }) => R::ReadFifoQueue(Data { data, quantity }),
+        R::ReadFileRecord(_) => R::ReadFileRecord(FileRecordData { data }),
+        R::WriteFileRecord(_) => R::WriteFileRecord(FileRecords { data }),
+        #[cfg(feature = "rtu")]
+        R::ReadExceptionStatus(status) => R::ReadExceptionStatus(status),
+        #[cfg(feature = "rtu")]
+        R::Diagnostics(sub_function, Data { quantity, .. }) => {
+            R::Diagnostics(sub_function, Data { data, quantity })
+        }
+        #[cfg(feature = "rtu")]
+        R::GetCommEventCounter(status, events) => R::GetCommEventCounter(status, events),
+        #[cfg(feature = "rtu")]
+        R::GetCommEventLog(status, events, messages, _) => {
+            R::GetCommEventLog(status, events, messages, data)
+        }
+        #[cfg(feature = "rtu")]
+        R::ReportServerId(_, running) => R::ReportServerId(data, running),
+        R::Custom(RawPdu { function, .. }) => R::Custom(RawPdu { function, data }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> RequestBuf<8> {
+        let bytes = [0x10, 0x00, 0x20, 0x00, 0x02, 0x04, 0x12, 0x34, 0x56, 0x78];
+        RequestBuf::new(Request::try_from(&bytes[..]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn request_outlives_buffer() {
+        let req = queue();
+        let Request::WriteMultipleRegisters(address, words) = req.as_ref() else {
+            panic!("unexpected request");
+        };
+        assert_eq!(address, 0x20);
+        assert_eq!(words.get(1), Some(0x5678));
+    }
+
+    #[test]
+    fn payload_too_large() {
+        let req = Request::Custom(RawPdu {
+            function: FunctionCode::Custom(0x42),
+            data: &[0; 5],
+        });
+        assert_eq!(RequestBuf::<4>::new(req), Err(EncodeError::BufferSize));
+        let req = Request::ReadCoils(0x10, 8);
+        assert_eq!(RequestBuf::<0>::new(req).unwrap().as_ref(), req);
+    }
+
+    #[test]
+    fn response_keeps_quantity() {
+        let coils = Coils {
+            data: &[0b101],
+            quantity: 3,
+        };
+        let rsp = ResponseBuf::<1>::new(Response::ReadCoils(coils)).unwrap();
+        assert_eq!(rsp.as_ref(), Response::ReadCoils(coils));
+    }
+}
@@ -0,0 +1,92 @@
+//! Big-endian integer access, the byte order of all Modbus fields,
+//! and conversion of registers in other byte orders.
+
+/// Read the `u16` in the first two bytes of `bytes`.
+///
+/// # Panics
+///
+/// Panics if `bytes` is shorter than two bytes.
+pub(crate) const fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+/// Write `value` into the first two bytes of `bytes`.
+///
+/// # Panics
+///
+/// Panics if `bytes` is shorter than two bytes.
+pub(crate) fn write_u16(bytes: &mut [u8], value: u16) {
+    bytes[..2].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Swap the two bytes of each register in `bytes`.
+///
+/// Converts the registers of devices that transmit them in little-endian
+/// byte order. A trailing odd byte is left untouched.
+///
+/// ```
+/// use modbus_core::swap_register_bytes;
+///
+/// let mut buf = [0x34, 0x12, 0x78, 0x56];
+/// swap_register_bytes(&mut buf);
+/// assert_eq!(buf, [0x12, 0x34, 0x56, 0x78]);
+/// ```
+pub fn swap_register_bytes(bytes: &mut [u8]) {
+    for register in bytes.chunks_exact_mut(2) {
+        register.swap(0, 1);
+    }
+}
+
+/// Swap the two registers of each 32-bit value in `bytes`.
+///
+/// Converts 32-bit values of devices that transmit the low word first.
+/// Trailing bytes that do not form a complete pair of registers are
+/// left untouched.
+///
+/// ```
+/// use modbus_core::swap_register_words;
+///
+/// let mut buf = [0x56, 0x78, 0x12, 0x34];
+/// swap_register_words(&mut buf);
+/// assert_eq!(u32::from_be_bytes(buf), 0x1234_5678);
+/// ```
+pub fn swap_register_words(bytes: &mut [u8]) {
+    for pair in bytes.chunks_exact_mut(4) {
+        let (high, low) = pair.split_at_mut(2);
+        high.swap_with_slice(low);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn big_endian() {
+        let buf = &mut [0; 3];
+        write_u16(&mut buf[1..], 0x1234);
+        assert_eq!(buf, &[0x00, 0x12, 0x34]);
+        assert_eq!(read_u16(&buf[1..]), 0x1234);
+    }
+
+    #[test]
+    fn swap_bytes() {
+        let buf = &mut [0x01, 0x02, 0x03, 0x04, 0x05];
+        swap_register_bytes(buf);
+        assert_eq!(buf, &[0x02, 0x01, 0x04, 0x03, 0x05]);
+        swap_register_bytes(&mut []);
+    }
+
+    #[test]
+    fn swap_words() {
+        let buf = &mut [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        swap_register_words(buf);
+        assert_eq!(buf, &[0x03, 0x04, 0x01, 0x02, 0x05, 0x06, 0x07]);
+
+        // Little-endian 32-bit value
+        let buf = &mut 0x1234_5678_u32.to_le_bytes();
+        swap_register_bytes(buf);
+        swap_register_words(buf);
+        assert_eq!(u32::from_be_bytes(*buf), 0x1234_5678);
+    }
+}
@@ -0,0 +1,392 @@
+use core::marker::PhantomData;
+
+use super::*;
+
+/// The maximum number of registers of a cell,
+/// the maximum quantity of a read request.
+pub const MAX_CELL_WORDS: usize = 125;
+
+/// The order of the registers of a value that spans several registers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WordOrder {
+    /// The most significant register first, as specified for Modbus.
+    #[default]
+    HighFirst,
+    /// The least significant register first.
+    LowFirst,
+}
+
+/// The operations that are permitted on a [`RegisterCell`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    /// Returns `true` if the value may be read.
+    #[must_use]
+    pub const fn is_readable(self) -> bool {
+        matches!(self, Self::ReadOnly | Self::ReadWrite)
+    }
+
+    /// Returns `true` if the value may be written.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        matches!(self, Self::WriteOnly | Self::ReadWrite)
+    }
+}
+
+/// A typed value in the registers of a device.
+///
+/// Device drivers declare their values as cells and let them
+/// build the requests and convert the registers.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{Data, RegisterCell, Request, WordOrder};
+///
+/// const ENERGY: RegisterCell<u32> =
+///     RegisterCell::holding(0x0100).with_word_order(WordOrder::LowFirst);
+///
+/// assert_eq!(
+///     ENERGY.to_request(),
+///     Some(Request::ReadHoldingRegisters(0x0100, 2))
+/// );
+/// let buf = &mut [0; 4];
+/// let response = Data::from_words(&[0x5678, 0x1234], buf).unwrap();
+/// assert_eq!(ENERGY.decode(&response), Some(0x1234_5678));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterCell<T> {
+    address: Address,
+    input: bool,
+    word_order: WordOrder,
+    access: Access,
+    value: PhantomData<T>,
+}
+
+impl<T> RegisterCell<T> {
+    /// A readable and writable value in the holding registers at `address`.
+    #[must_use]
+    pub const fn holding(address: Address) -> Self {
+        Self {
+            address,
+            input: false,
+            word_order: WordOrder::HighFirst,
+            access: Access::ReadWrite,
+            value: PhantomData,
+        }
+    }
+
+    /// A value in the input registers at `address`.
+    ///
+    /// Input registers are always read-only.
+    #[must_use]
+    pub const fn input(address: Address) -> Self {
+        Self {
+            address,
+            input: true,
+            word_order: WordOrder::HighFirst,
+            access: Access::ReadOnly,
+            value: PhantomData,
+        }
+    }
+
+    /// Use `word_order` for values that span several registers.
+    #[must_use]
+    pub const fn with_word_order(mut self, word_order: WordOrder) -> Self {
+        self.word_order = word_order;
+        self
+    }
+
+    /// Restrict the permitted operations of a holding register cell.
+    ///
+    /// The access of input register cells is not changed.
+    #[must_use]
+    pub const fn with_access(mut self, access: Access) -> Self {
+        if !self.input {
+            self.access = access;
+        }
+        self
+    }
+
+    /// The address of the first register.
+    #[must_use]
+    pub const fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns `true` for cells in the input registers.
+    #[must_use]
+    pub const fn is_input(&self) -> bool {
+        self.input
+    }
+
+    #[must_use]
+    pub const fn word_order(&self) -> WordOrder {
+        self.word_order
+    }
+
+    #[must_use]
+    pub const fn access(&self) -> Access {
+        self.access
+    }
+}
+
+impl<T> RegisterCell<T>
+where
+    T: FromRegisters,
+{
+    /// The number of registers of the value.
+    #[must_use]
+    pub const fn quantity(&self) -> Quantity {
+        T::WORDS as Quantity
+    }
+
+    /// The addresses of the registers.
+    ///
+    /// Returns `None` if the value exceeds the highest address.
+    #[must_use]
+    pub const fn range(&self) -> Option<AddressRange> {
+        AddressRange::new(self.address, self.quantity())
+    }
+
+    /// The request that reads the value.
+    ///
+    /// Returns `None` if the cell is not readable.
+    #[must_use]
+    pub const fn to_request(&self) -> Option<Request<'static>> {
+        if !self.access.is_readable() {
+            return None;
+        }
+        Some(if self.input {
+            Request::ReadInputRegisters(self.address, self.quantity())
+        } else {
+            Request::ReadHoldingRegisters(self.address, self.quantity())
+        })
+    }
+
+    /// Decode the value from the registers of the response to [`Self::to_request`].
+    #[must_use]
+    pub fn decode(&self, data: &Data<'_>) -> Option<T> {
+        self.decode_from(self.address, data)
+    }
+
+    /// Decode the value from registers that have been read starting at `start`,
+    /// e.g. a block that contains several cells.
+    ///
+    /// Returns `None` if the registers do not contain the value.
+    #[must_use]
+    pub fn decode_from(&self, start: Address, data: &Data<'_>) -> Option<T> {
+        if T::WORDS > MAX_CELL_WORDS {
+            return None;
+        }
+        let offset = usize::from(self.address.checked_sub(start)?);
+        let words = &mut [0; MAX_CELL_WORDS][..T::WORDS];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = data.get(offset + i)?;
+        }
+        if self.word_order == WordOrder::LowFirst {
+            words.reverse();
+        }
+        T::from_registers(words)
+    }
+}
+
+impl<T> RegisterCell<T>
+where
+    T: ToRegisters,
+{
+    /// The request that writes `value`, using `buf` for the registers.
+    ///
+    /// Values of a single register are written with
+    /// [`Request::WriteSingleRegister`], all others with
+    /// [`Request::WriteMultipleRegisters`].
+    ///
+    /// Returns `None` if the cell is not writable
+    /// or if `buf` is too small.
+    pub fn to_write_request<'b>(&self, value: &T, buf: &'b mut [u8]) -> Option<Request<'b>> {
+        if !self.access.is_writable() || T::WORDS > MAX_CELL_WORDS {
+            return None;
+        }
+        let words = &mut [0; MAX_CELL_WORDS][..T::WORDS];
+        value.to_registers(words);
+        if self.word_order == WordOrder::LowFirst {
+            words.reverse();
+        }
+        if let [word] = words {
+            return Some(Request::WriteSingleRegister(self.address, *word));
+        }
+        let data = Data::from_words(words, buf).ok()?;
+        Some(Request::WriteMultipleRegisters(self.address, data))
+    }
+
+    /// Encode `value` into `buf` that holds the registers starting at `start`.
+    ///
+    /// The other registers of `buf` are not modified. The access
+    /// rights are not checked, e.g. to encode read-only values
+    /// into the response of a server.
+    ///
+    /// Returns `None` if `buf` does not contain the registers of the value.
+    pub fn encode_into(&self, start: Address, value: &T, buf: &mut [u8]) -> Option<()> {
+        if T::WORDS > MAX_CELL_WORDS {
+            return None;
+        }
+        let offset = usize::from(self.address.checked_sub(start)?) * 2;
+        let target = buf.get_mut(offset..offset + T::WORDS * 2)?;
+        let words = &mut [0; MAX_CELL_WORDS][..T::WORDS];
+        value.to_registers(words);
+        if self.word_order == WordOrder::LowFirst {
+            words.reverse();
+        }
+        for (chunk, word) in target.chunks_exact_mut(2).zip(words.iter()) {
+            write_u16(chunk, *word);
+        }
+        Some(())
+    }
+}
+
+/// A struct that is mapped to a block of consecutive registers.
+///
+/// Usually implemented with `#[derive(RegisterBlock)]` of the
+/// `derive` feature. Each field is a value that implements
+/// [`FromRegisters`] and [`ToRegisters`] at an offset
+/// from the start of the block:
+///
+/// - `#[register(offset = 4)]` places the field at the register `4`.
+///   Without an offset a field follows the previous one.
+/// - `#[register(word_order = LowFirst)]` selects the [`WordOrder`].
+/// - `#[register(ty = i16)]` stores the field as another
+///   type that is converted with `From` and `TryFrom`.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use modbus_core::{Data, RegisterBlock};
+///
+/// #[derive(Debug, PartialEq, RegisterBlock)]
+/// struct Meter {
+///     voltage: u16,
+///     #[register(offset = 2, word_order = LowFirst)]
+///     energy: u32,
+///     #[register(ty = i16)]
+///     temperature: i32,
+/// }
+///
+/// assert_eq!(Meter::WORDS, 5);
+/// let buf = &mut [0; 10];
+/// let data = Data::from_words(&[230, 0, 0x5678, 0x1234, 0xFFFF], buf).unwrap();
+/// let meter = Meter::decode(&data).unwrap();
+/// assert_eq!(
+///     meter,
+///     Meter {
+///         voltage: 230,
+///         energy: 0x1234_5678,
+///         temperature: -1,
+///     }
+/// );
+/// let buf = &mut [0; 10];
+/// assert_eq!(meter.encode(buf), Some(data));
+/// # }
+/// ```
+pub trait RegisterBlock: Sized {
+    /// The number of registers of the block.
+    const WORDS: usize;
+
+    /// Decode the block from `data` that starts with its first register.
+    ///
+    /// Returns `None` if `data` is too short.
+    fn decode(data: &Data<'_>) -> Option<Self>;
+
+    /// Encode the block into the registers of `buf`.
+    ///
+    /// Registers between the fields are set to zero. Returns `None`
+    /// if `buf` is too small or if a field does not fit into its
+    /// register type.
+    fn encode<'b>(&self, buf: &'b mut [u8]) -> Option<Data<'b>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_cells() {
+        let cell = RegisterCell::<f32>::input(0x0010).with_access(Access::ReadWrite);
+        assert_eq!(cell.access(), Access::ReadOnly);
+        assert_eq!(
+            cell.to_request(),
+            Some(Request::ReadInputRegisters(0x0010, 2))
+        );
+        assert!(cell.to_write_request(&1.0, &mut [0; 4]).is_none());
+
+        let write_only = RegisterCell::<u16>::holding(0x0010).with_access(Access::WriteOnly);
+        assert_eq!(write_only.to_request(), None);
+
+        assert_eq!(
+            RegisterCell::<u16>::holding(0xFFFF).range().unwrap().len(),
+            1
+        );
+        assert_eq!(RegisterCell::<u32>::holding(0xFFFF).range(), None);
+    }
+
+    #[test]
+    fn decode_cells_from_a_block() {
+        let buf = &mut [0; 10];
+        let block = Data::from_words(&[0x0001, 0x1234, 0x5678, 0x5678, 0x1234], buf).unwrap();
+        let high_first = RegisterCell::<u32>::holding(0x0101);
+        let low_first = RegisterCell::<u32>::holding(0x0103).with_word_order(WordOrder::LowFirst);
+        assert_eq!(high_first.decode_from(0x0100, &block), Some(0x1234_5678));
+        assert_eq!(low_first.decode_from(0x0100, &block), Some(0x1234_5678));
+        assert_eq!(low_first.decode_from(0x00FF, &block), None);
+        assert_eq!(low_first.decode_from(0x0104, &block), None);
+        assert_eq!(high_first.decode(&block), Some(0x0001_1234));
+    }
+
+    #[test]
+    fn write_cells() {
+        let buf = &mut [0; 4];
+        let cell = RegisterCell::<i16>::holding(0x0020);
+        assert_eq!(
+            cell.to_write_request(&-1, buf),
+            Some(Request::WriteSingleRegister(0x0020, 0xFFFF))
+        );
+        let cell = RegisterCell::<u32>::holding(0x0020).with_word_order(WordOrder::LowFirst);
+        let Some(Request::WriteMultipleRegisters(address, data)) =
+            cell.to_write_request(&0x1234_5678, buf)
+        else {
+            panic!("unexpected request");
+        };
+        assert_eq!(address, 0x0020);
+        assert_eq!(data.get(0), Some(0x5678));
+        assert_eq!(data.get(1), Some(0x1234));
+        assert!(cell.to_write_request(&0, &mut [0; 3]).is_none());
+    }
+
+    #[test]
+    fn decode_strings() {
+        let buf = &mut [0; 6];
+        let data = Data::from_words(&[0x0000, 0x4142, 0x4300], buf).unwrap();
+        let cell = RegisterCell::<RegisterString<4>>::input(0x0001);
+        assert_eq!(cell.quantity(), 2);
+        assert_eq!(cell.decode_from(0x0000, &data).unwrap().as_str(), "ABC");
+    }
+
+    #[test]
+    fn encode_cells_into_a_block() {
+        let buf = &mut [0xFF; 8];
+        let cell = RegisterCell::<u32>::input(0x0101).with_word_order(WordOrder::LowFirst);
+        assert_eq!(cell.encode_into(0x0100, &0x1234_5678, buf), Some(()));
+        assert_eq!(buf, &[0xFF, 0xFF, 0x56, 0x78, 0x12, 0x34, 0xFF, 0xFF]);
+        assert_eq!(cell.encode_into(0x0102, &0, buf), None);
+        assert_eq!(cell.encode_into(0x00FE, &0, buf), None);
+    }
+}
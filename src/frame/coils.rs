@@ -1,8 +1,10 @@
 use super::*;
 use crate::error::*;
+use core::ops::Index;
 
 /// Packed coils
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Coils<'c> {
     pub(crate) data: RawData<'c>,
     pub(crate) quantity: usize,
@@ -10,6 +12,8 @@ pub struct Coils<'c> {
 
 impl<'c> Coils<'c> {
     /// Pack coils defined by an bool slice into a byte buffer.
+    ///
+    /// The used bytes of `target` are overwritten, see [`pack_coils`].
     pub fn from_bools(bools: &[bool], target: &'c mut [u8]) -> Result<Self, Error> {
         if bools.is_empty() {
             return Err(Error::BufferSize);
@@ -21,13 +25,50 @@ impl<'c> Coils<'c> {
         })
     }
 
-    //TODO: add tests
-    pub(crate) fn copy_to(&self, buf: &mut [u8]) {
+    /// Pack the coils of an iterator into a byte buffer, e.g. to
+    /// serialize values that are generated on demand.
+    ///
+    /// Fails if there are no coils or if they do not fit into `target`.
+    /// The used bytes of `target` are overwritten.
+    pub fn from_bools_iter(
+        bools: impl IntoIterator<Item = Coil>,
+        target: &'c mut [u8],
+    ) -> Result<Self, Error> {
+        let mut quantity = 0;
+        for coil in bools {
+            let byte = target.get_mut(quantity / 8).ok_or(Error::BufferSize)?;
+            if quantity % 8 == 0 {
+                *byte = 0;
+            }
+            *byte |= u8::from(coil) << (quantity % 8);
+            quantity += 1;
+        }
+        if quantity == 0 {
+            return Err(Error::BufferSize);
+        }
+        Ok(Coils {
+            data: target,
+            quantity,
+        })
+    }
+
+    /// The bytes of the packed coils.
+    ///
+    /// Fails if `data` is too short for the quantity.
+    pub(crate) fn packed(&self) -> Result<&'c [u8], EncodeError> {
         let packed_len = self.packed_len();
-        debug_assert!(buf.len() >= packed_len);
-        (0..packed_len).for_each(|idx| {
-            buf[idx] = self.data[idx];
-        });
+        self.data
+            .get(..packed_len)
+            .ok_or(EncodeError::LengthMismatch(packed_len, self.data.len()))
+    }
+
+    /// Copy the packed coils to the start of `buf`.
+    pub(crate) fn copy_to(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let packed = self.packed()?;
+        buf.get_mut(..packed.len())
+            .ok_or(EncodeError::BufferSize)?
+            .copy_from_slice(packed);
+        Ok(packed.len())
     }
 
     /// Quantity of coils
@@ -48,6 +89,38 @@ impl<'c> Coils<'c> {
         self.quantity == 0
     }
 
+    /// Returns `true` if the unused bits of the last byte are zero,
+    /// as the specification requires.
+    #[must_use]
+    pub const fn has_clean_padding(&self) -> bool {
+        let Some(last) = self.last_byte() else {
+            return true;
+        };
+        last & !padding_mask(self.quantity) == 0
+    }
+
+    /// Copy the packed coils to `target` and clear the unused bits
+    /// of the last byte.
+    pub fn normalized<'t>(&self, target: &'t mut [u8]) -> Result<Coils<'t>, Error> {
+        let packed_len = self.copy_to(target)?;
+        if let Some(last) = packed_len.checked_sub(1).map(|idx| &mut target[idx]) {
+            *last &= padding_mask(self.quantity);
+        }
+        Ok(Coils {
+            data: target,
+            quantity: self.quantity,
+        })
+    }
+
+    /// The byte that contains the padding bits.
+    pub(crate) const fn last_byte(&self) -> Option<u8> {
+        let packed_len = self.packed_len();
+        if packed_len == 0 || packed_len > self.data.len() {
+            return None;
+        }
+        Some(self.data[packed_len - 1])
+    }
+
     /// Get a specific coil.
     #[must_use]
     pub const fn get(&self, idx: usize) -> Option<Coil> {
@@ -56,6 +129,58 @@ impl<'c> Coils<'c> {
         }
         Some((self.data[(idx as u16 / 8u16) as usize] >> (idx % 8)) & 0b1 > 0)
     }
+
+    /// The first coil, or `None` if there are no coils.
+    #[must_use]
+    pub const fn first(&self) -> Option<Coil> {
+        self.get(0)
+    }
+
+    /// The last coil, or `None` if there are no coils.
+    #[must_use]
+    pub const fn last(&self) -> Option<Coil> {
+        match self.quantity.checked_sub(1) {
+            Some(idx) => self.get(idx),
+            None => None,
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl Coils<'_> {
+    /// Unpack the coils into a vector with a capacity of `N`.
+    ///
+    /// Fails with [`Error::BufferSize`] if there are more than `N` coils.
+    pub fn to_heapless<const N: usize>(&self) -> Result<heapless::Vec<Coil, N>, Error> {
+        if self.quantity > N {
+            return Err(Error::BufferSize);
+        }
+        Ok(self.into_iter().collect())
+    }
+}
+
+/// Panics if `idx` is out of bounds, like indexing a slice.
+///
+/// ```
+/// use modbus_core::Coils;
+///
+/// let buf = &mut [0; 1];
+/// let coils = Coils::from_bools(&[true, false, true], buf).unwrap();
+/// assert!(coils[0] && !coils[1] && coils[2]);
+/// ```
+impl Index<usize> for Coils<'_> {
+    type Output = Coil;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        match self.get(idx) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {idx}",
+                self.quantity
+            ),
+        }
+    }
 }
 
 /// Coils iterator.
@@ -66,7 +191,7 @@ pub struct CoilsIter<'c> {
     coils: Coils<'c>,
 }
 
-impl<'c> Iterator for CoilsIter<'c> {
+impl Iterator for CoilsIter<'_> {
     type Item = Coil;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -99,28 +224,41 @@ pub const fn bool_to_u16_coil(state: bool) -> u16 {
 }
 
 /// Turn a u16 coil value into a boolean value.
-pub const fn u16_coil_to_bool(coil: u16) -> Result<bool, Error> {
+pub const fn u16_coil_to_bool(coil: u16) -> Result<bool, DecodeError> {
     match coil {
         0xFF00 => Ok(true),
         0x0000 => Ok(false),
-        _ => Err(Error::CoilValue(coil)),
+        _ => Err(DecodeError::CoilValue(coil)),
+    }
+}
+
+/// The bits of the last byte that are used by `bitcount` coils.
+const fn padding_mask(bitcount: usize) -> u8 {
+    match bitcount % 8 {
+        0 => 0xFF,
+        used => (1 << used) - 1,
     }
 }
 
 /// Calculate the number of bytes required for a given number of coils.
 #[must_use]
 pub const fn packed_coils_len(bitcount: usize) -> usize {
-    (bitcount + 7) / 8
+    // Unlike `(bitcount + 7) / 8` this cannot overflow.
+    bitcount / 8 + (bitcount % 8 != 0) as usize
 }
 
 ///  Pack coils into a byte array.
 ///
 ///  It returns the number of bytes used to pack the coils.
+///  These bytes are cleared first, so a buffer can be reused and
+///  the unused bits of the last byte are always zero.
+///  Bytes after the packed coils are left untouched.
 pub fn pack_coils(coils: &[Coil], bytes: &mut [u8]) -> Result<usize, Error> {
     let packed_size = packed_coils_len(coils.len());
-    if bytes.len() < packed_size {
+    let Some(bytes) = bytes.get_mut(..packed_size) else {
         return Err(Error::BufferSize);
-    }
+    };
+    bytes.fill(0);
     coils.iter().enumerate().for_each(|(i, b)| {
         let v = u8::from(*b);
         bytes[i / 8] |= v << (i % 8);
@@ -139,6 +277,32 @@ pub fn unpack_coils(bytes: &[u8], count: u16, coils: &mut [Coil]) -> Result<(),
     Ok(())
 }
 
+///  Iterate over `count` coils packed in `bytes`.
+///
+///  In contrast to [`unpack_coils`] no `bool` buffer is needed, so the
+///  coils can be collected directly into any container.
+///  Coils beyond the end of `bytes` are omitted.
+///
+///  ```
+///  use modbus_core::unpack_coils_iter;
+///
+///  let mut coils = [false; 10];
+///  coils
+///      .iter_mut()
+///      .zip(unpack_coils_iter(&[0b_0000_0101, 0b_10], 10))
+///      .for_each(|(c, v)| *c = v);
+///  assert_eq!(coils.iter().filter(|c| **c).count(), 3);
+///  assert!(coils[9]);
+///  ```
+#[must_use]
+pub fn unpack_coils_iter(bytes: &[u8], count: u16) -> CoilsIter<'_> {
+    Coils {
+        data: bytes,
+        quantity: usize::from(count).min(bytes.len() * 8),
+    }
+    .into_iter()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -158,6 +322,43 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn from_bool_iter() {
+        let buf = &mut [0xFF; 2];
+        let coils = Coils::from_bools_iter((0..9).map(|i| i % 3 == 0), buf).unwrap();
+        assert_eq!(coils.len(), 9);
+        assert_eq!(coils.data, &[0b_0100_1001, 0b_0000_0000]);
+        assert!(coils.has_clean_padding());
+        let buf = &mut [0; 1];
+        assert_eq!(
+            Coils::from_bools_iter([true; 9], buf),
+            Err(Error::BufferSize)
+        );
+        assert_eq!(
+            Coils::from_bools_iter(core::iter::empty(), buf),
+            Err(Error::BufferSize)
+        );
+    }
+
+    #[test]
+    fn copy_packed_coils() {
+        let coils = Coils {
+            data: &[0xFF, 0x01, 0xAA],
+            quantity: 9,
+        };
+        let buf = &mut [0; 3];
+        assert_eq!(coils.copy_to(buf), Ok(2));
+        assert_eq!(buf, &[0xFF, 0x01, 0x00]);
+        assert_eq!(coils.copy_to(&mut buf[..1]), Err(EncodeError::BufferSize));
+    }
+
+    #[test]
+    fn packed_len_of_many_coils() {
+        assert_eq!(packed_coils_len(0), 0);
+        assert_eq!(packed_coils_len(9), 2);
+        assert_eq!(packed_coils_len(usize::MAX), usize::MAX / 8 + 1);
+    }
+
     #[test]
     fn coils_len() {
         let coils = Coils {
@@ -174,6 +375,76 @@ mod tests {
             quantity: 0,
         };
         assert!(coils.is_empty());
+        assert_eq!(coils.first(), None);
+        assert_eq!(coils.last(), None);
+    }
+
+    #[test]
+    fn index_coils() {
+        let coils = Coils {
+            data: &[0b_0000_0001, 0b_0000_0010],
+            quantity: 10,
+        };
+        assert!(coils[0]);
+        assert!(!coils[8]);
+        assert!(coils[9]);
+        assert_eq!(coils.first(), Some(true));
+        assert_eq!(coils.last(), Some(true));
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn coils_to_heapless() {
+        let coils = Coils {
+            data: &[0b_0000_0101],
+            quantity: 3,
+        };
+        assert_eq!(coils.to_heapless::<3>().unwrap(), [true, false, true]);
+        assert_eq!(coils.to_heapless::<2>(), Err(Error::BufferSize));
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_coils_out_of_bounds() {
+        let coils = Coils {
+            data: &[0xFF],
+            quantity: 3,
+        };
+        let _ = coils[3];
+    }
+
+    #[test]
+    fn check_padding() {
+        let coils = Coils {
+            data: &[0xFF, 0b_0001],
+            quantity: 9,
+        };
+        assert!(coils.has_clean_padding());
+        let coils = Coils {
+            data: &[0xFF, 0b_0011],
+            quantity: 9,
+        };
+        assert!(!coils.has_clean_padding());
+        let coils = Coils {
+            data: &[0xFF],
+            quantity: 8,
+        };
+        assert!(coils.has_clean_padding());
+    }
+
+    #[test]
+    fn normalize_padding() {
+        let coils = Coils {
+            data: &[0xFF, 0xFF],
+            quantity: 10,
+        };
+        let buf = &mut [0xAA; 3];
+        let normalized = coils.normalized(buf).unwrap();
+        assert!(normalized.has_clean_padding());
+        assert_eq!(normalized.len(), 10);
+        assert!(normalized.into_iter().all(|coil| coil));
+        assert_eq!(buf, &[0xFF, 0b_11, 0xAA]);
+        assert_eq!(coils.normalized(&mut [0]), Err(Error::BufferSize));
     }
 
     #[test]
@@ -256,7 +527,7 @@ mod tests {
         assert!(!u16_coil_to_bool(0x0000).unwrap());
         assert_eq!(
             u16_coil_to_bool(0x1234).err().unwrap(),
-            Error::CoilValue(0x1234)
+            DecodeError::CoilValue(0x1234)
         );
     }
 
@@ -302,6 +573,29 @@ mod tests {
         assert_eq!(buff, &[0xff, 1]);
     }
 
+    #[test]
+    fn pack_coils_into_dirty_buffer() {
+        let buff = &mut [0xFF, 0xFF, 0xAA];
+        assert_eq!(pack_coils(&[false, true, false], buff).unwrap(), 1);
+        assert_eq!(buff, &[0b_010, 0xFF, 0xAA]);
+
+        let buff = &mut [0xFF; 2];
+        let coils = Coils::from_bools(&[true; 9], buff).unwrap();
+        assert_eq!(coils.into_iter().filter(|c| *c).count(), 9);
+        assert_eq!(buff, &[0xFF, 0b_1]);
+    }
+
+    #[test]
+    fn unpack_coils_lazily() {
+        assert_eq!(unpack_coils_iter(&[], 3).count(), 0);
+        assert_eq!(unpack_coils_iter(&[0xFF], 12).count(), 8);
+        let mut iter = unpack_coils_iter(&[0b_101, 0xFF], 3);
+        assert_eq!(iter.next(), Some(true));
+        assert_eq!(iter.next(), Some(false));
+        assert_eq!(iter.next(), Some(true));
+        assert_eq!(iter.next(), None);
+    }
+
     #[test]
     fn unpack_coils_from_a_byte_array() {
         assert!(unpack_coils(&[], 0, &mut []).is_ok());
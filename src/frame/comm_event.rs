@@ -0,0 +1,209 @@
+use crate::error::DecodeError;
+
+/// The maximum number of events in a `GetCommEventLog` response.
+pub const MAX_COMM_EVENTS: usize = 64;
+
+/// An entry of the communications event log of a serial line server.
+///
+/// Each event is encoded as a single byte
+/// (see [`CommEvent::value`] and [`CommEvent::new`]).
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{CommEvent, ReceiveEvent};
+///
+/// let event = CommEvent::try_from(0x90).unwrap();
+/// assert_eq!(
+///     event,
+///     CommEvent::Receive(ReceiveEvent {
+///         char_overrun: true,
+///         ..Default::default()
+///     })
+/// );
+/// assert_eq!(event.value(), 0x90);
+/// assert!(CommEvent::try_from(0x01).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommEvent {
+    /// A message has been received (bit 7 set).
+    Receive(ReceiveEvent),
+    /// A response has been sent or should have been sent (bits 7 and 6 `01`).
+    Send(SendEvent),
+    /// The server entered the listen only mode (`0x04`).
+    EnteredListenOnly,
+    /// The communications have been restarted (`0x00`).
+    Restart,
+}
+
+/// The flags of a [`CommEvent::Receive`] event.
+///
+/// The bits that are not used by the specification are ignored while decoding.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ReceiveEvent {
+    /// A communication error, e.g. a parity or CRC error (bit 1).
+    pub comm_error: bool,
+    /// A character overrun (bit 4).
+    pub char_overrun: bool,
+    /// The server is in listen only mode (bit 5).
+    pub listen_only: bool,
+    /// The message has been broadcast (bit 6).
+    pub broadcast: bool,
+}
+
+/// The flags of a [`CommEvent::Send`] event.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SendEvent {
+    /// An exception with the codes `1` - `3` has been sent (bit 0).
+    pub read_exception: bool,
+    /// A server device failure exception (`4`) has been sent (bit 1).
+    pub abort_exception: bool,
+    /// An acknowledge or busy exception (`5` - `6`) has been sent (bit 2).
+    pub busy_exception: bool,
+    /// A negative acknowledge exception (`7`) has been sent (bit 3).
+    pub nak_exception: bool,
+    /// A write timeout occurred (bit 4).
+    pub write_timeout: bool,
+    /// The server is in listen only mode and the response has not been sent (bit 5).
+    pub listen_only: bool,
+}
+
+const RECEIVE: u8 = 0x80;
+const SEND: u8 = 0x40;
+const ENTERED_LISTEN_ONLY: u8 = 0x04;
+const RESTART: u8 = 0x00;
+
+const fn bit(value: u8, n: u8) -> bool {
+    value & (1 << n) != 0
+}
+
+const fn flag(set: bool, n: u8) -> u8 {
+    (set as u8) << n
+}
+
+impl CommEvent {
+    /// Get the [`u8`] value of the event.
+    #[must_use]
+    pub const fn value(self) -> u8 {
+        match self {
+            Self::Receive(ReceiveEvent {
+                comm_error,
+                char_overrun,
+                listen_only,
+                broadcast,
+            }) => {
+                RECEIVE
+                    | flag(comm_error, 1)
+                    | flag(char_overrun, 4)
+                    | flag(listen_only, 5)
+                    | flag(broadcast, 6)
+            }
+            Self::Send(SendEvent {
+                read_exception,
+                abort_exception,
+                busy_exception,
+                nak_exception,
+                write_timeout,
+                listen_only,
+            }) => {
+                SEND | flag(read_exception, 0)
+                    | flag(abort_exception, 1)
+                    | flag(busy_exception, 2)
+                    | flag(nak_exception, 3)
+                    | flag(write_timeout, 4)
+                    | flag(listen_only, 5)
+            }
+            Self::EnteredListenOnly => ENTERED_LISTEN_ONLY,
+            Self::Restart => RESTART,
+        }
+    }
+
+    /// Decode the event `value`.
+    ///
+    /// Fails with [`DecodeError::CommEvent`] if it is not defined by the specification.
+    pub const fn new(value: u8) -> Result<Self, DecodeError> {
+        let event = if bit(value, 7) {
+            Self::Receive(ReceiveEvent {
+                comm_error: bit(value, 1),
+                char_overrun: bit(value, 4),
+                listen_only: bit(value, 5),
+                broadcast: bit(value, 6),
+            })
+        } else if bit(value, 6) {
+            Self::Send(SendEvent {
+                read_exception: bit(value, 0),
+                abort_exception: bit(value, 1),
+                busy_exception: bit(value, 2),
+                nak_exception: bit(value, 3),
+                write_timeout: bit(value, 4),
+                listen_only: bit(value, 5),
+            })
+        } else if value == ENTERED_LISTEN_ONLY {
+            Self::EnteredListenOnly
+        } else if value == RESTART {
+            Self::Restart
+        } else {
+            return Err(DecodeError::CommEvent(value));
+        };
+        Ok(event)
+    }
+}
+
+impl TryFrom<u8> for CommEvent {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<CommEvent> for u8 {
+    fn from(event: CommEvent) -> Self {
+        event.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_bytes() {
+        assert_eq!(CommEvent::try_from(0x00), Ok(CommEvent::Restart));
+        assert_eq!(CommEvent::try_from(0x04), Ok(CommEvent::EnteredListenOnly));
+        assert_eq!(
+            CommEvent::try_from(0xA2),
+            Ok(CommEvent::Receive(ReceiveEvent {
+                comm_error: true,
+                listen_only: true,
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            CommEvent::try_from(0x43),
+            Ok(CommEvent::Send(SendEvent {
+                read_exception: true,
+                abort_exception: true,
+                ..Default::default()
+            }))
+        );
+        assert_eq!(CommEvent::try_from(0x3F), Err(DecodeError::CommEvent(0x3F)));
+    }
+
+    #[test]
+    fn value_round_trip() {
+        for value in 0..=u8::MAX {
+            let Ok(event) = CommEvent::try_from(value) else {
+                continue;
+            };
+            // Unused bits of receive events are dropped
+            let used = if value & RECEIVE == 0 { 0xFF } else { 0xF2 };
+            assert_eq!(u8::from(event), value & used);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::error::*;
 
 /// Modbus data (u16 values)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Data<'d> {
     pub(crate) data: RawData<'d>,
     pub(crate) quantity: usize,
@@ -15,20 +16,74 @@ impl<'d> Data<'d> {
             return Err(Error::BufferSize);
         }
         for (i, w) in words.iter().enumerate() {
-            BigEndian::write_u16(&mut target[i * 2..], *w);
+            write_u16(&mut target[i * 2..], *w);
         }
         Ok(Data {
             data: target,
             quantity: words.len(),
         })
     }
-    //TODO: add tests
-    pub(crate) fn copy_to(&self, buf: &mut [u8]) {
+    /// Pack the words of an iterator into a byte buffer, e.g. to
+    /// serialize values that are generated on demand.
+    ///
+    /// Fails if there are no words or if they do not fit into `target`.
+    ///
+    /// ```
+    /// use modbus_core::Data;
+    ///
+    /// let buf = &mut [0; 6];
+    /// let data = Data::from_words_iter((1..=3).map(|i| i * 0x100), buf).unwrap();
+    /// assert_eq!(data.len(), 3);
+    /// assert_eq!(data.get(2), Some(0x0300));
+    /// ```
+    pub fn from_words_iter(
+        words: impl IntoIterator<Item = Word>,
+        target: &'d mut [u8],
+    ) -> Result<Self, Error> {
+        let mut quantity = 0;
+        for word in words {
+            let bytes = target
+                .get_mut(quantity * 2..quantity * 2 + 2)
+                .ok_or(Error::BufferSize)?;
+            write_u16(bytes, word);
+            quantity += 1;
+        }
+        if quantity == 0 {
+            return Err(Error::BufferSize);
+        }
+        Ok(Data {
+            data: target,
+            quantity,
+        })
+    }
+    /// Use `bytes` that contain words in big-endian byte order.
+    ///
+    /// Fails if `bytes` is empty or has an odd length.
+    pub const fn from_bytes(bytes: &'d [u8]) -> Result<Self, Error> {
+        if bytes.is_empty() || bytes.len() % 2 != 0 {
+            return Err(Error::BufferSize);
+        }
+        Ok(Data {
+            data: bytes,
+            quantity: bytes.len() / 2,
+        })
+    }
+    /// The bytes of the packed words.
+    ///
+    /// Fails if `data` is too short for the quantity.
+    pub(crate) fn packed(&self) -> Result<&'d [u8], EncodeError> {
         let cnt = self.quantity * 2;
-        debug_assert!(buf.len() >= cnt);
-        (0..cnt).for_each(|idx| {
-            buf[idx] = self.data[idx];
-        });
+        self.data
+            .get(..cnt)
+            .ok_or(EncodeError::LengthMismatch(cnt, self.data.len()))
+    }
+    /// Copy the packed words to the start of `buf`.
+    pub(crate) fn copy_to(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let packed = self.packed()?;
+        buf.get_mut(..packed.len())
+            .ok_or(EncodeError::BufferSize)?
+            .copy_from_slice(packed);
+        Ok(packed.len())
     }
     /// Quantity of words (u16 values)
     #[must_use]
@@ -41,13 +96,58 @@ impl<'d> Data<'d> {
         self.quantity == 0
     }
     /// Get a specific word.
+    ///
+    /// There is no `Index` implementation, because the words
+    /// are stored as big-endian bytes and cannot be borrowed.
     #[must_use]
     pub fn get(&self, idx: usize) -> Option<Word> {
         if idx + 1 > self.quantity {
             return None;
         }
         let idx = idx * 2;
-        Some(BigEndian::read_u16(&self.data[idx..idx + 2]))
+        Some(read_u16(&self.data[idx..idx + 2]))
+    }
+    /// The first word, or `None` if the data is empty.
+    #[must_use]
+    pub fn first(&self) -> Option<Word> {
+        self.get(0)
+    }
+    /// The last word, or `None` if the data is empty.
+    #[must_use]
+    pub fn last(&self) -> Option<Word> {
+        self.get(self.quantity.checked_sub(1)?)
+    }
+    /// Iterate over the words without consuming the data.
+    ///
+    /// ```
+    /// use modbus_core::Data;
+    ///
+    /// let buf = &mut [0; 4];
+    /// let data = Data::from_words(&[0x1234, 0x5678], buf).unwrap();
+    /// let mut words = [0; 2];
+    /// words.iter_mut().zip(data.words()).for_each(|(w, v)| *w = v);
+    /// assert_eq!(words, [0x1234, 0x5678]);
+    /// assert_eq!(data.words().max(), Some(0x5678));
+    /// ```
+    #[must_use]
+    pub const fn words(&self) -> DataIter<'d> {
+        DataIter {
+            cnt: 0,
+            data: *self,
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl Data<'_> {
+    /// Collect the words into a vector with a capacity of `N`.
+    ///
+    /// Fails with [`Error::BufferSize`] if there are more than `N` words.
+    pub fn to_heapless<const N: usize>(&self) -> Result<heapless::Vec<Word, N>, Error> {
+        if self.quantity > N {
+            return Err(Error::BufferSize);
+        }
+        Ok(self.words().collect())
     }
 }
 
@@ -59,7 +159,7 @@ pub struct DataIter<'d> {
     data: Data<'d>,
 }
 
-impl<'d> Iterator for DataIter<'d> {
+impl Iterator for DataIter<'_> {
     type Item = Word;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -74,7 +174,7 @@ impl<'d> IntoIterator for Data<'d> {
     type IntoIter = DataIter<'d>;
 
     fn into_iter(self) -> Self::IntoIter {
-        DataIter { cnt: 0, data: self }
+        self.words()
     }
 }
 
@@ -98,6 +198,57 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn from_word_iter() {
+        let buf = &mut [0; 5];
+        assert_eq!(
+            Data::from_words_iter([1, 2, 3], buf),
+            Err(Error::BufferSize)
+        );
+        assert_eq!(
+            Data::from_words_iter(core::iter::empty(), buf),
+            Err(Error::BufferSize)
+        );
+        let data = Data::from_words_iter([0xABCD, 0xEF00], buf).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.data, &[0xAB, 0xCD, 0xEF, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn from_byte_slice() {
+        assert_eq!(Data::from_bytes(&[]), Err(Error::BufferSize));
+        assert_eq!(
+            Data::from_bytes(&[0x12, 0x34, 0x56]),
+            Err(Error::BufferSize)
+        );
+        let data = Data::from_bytes(&[0x12, 0x34, 0x56, 0x78]).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.get(1), Some(0x5678));
+    }
+
+    #[test]
+    fn copy_words() {
+        let data = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        let buf = &mut [0; 5];
+        assert_eq!(data.copy_to(buf), Ok(4));
+        assert_eq!(buf, &[0x12, 0x34, 0x56, 0x78, 0x00]);
+        assert_eq!(data.copy_to(&mut buf[..3]), Err(EncodeError::BufferSize));
+    }
+
+    #[test]
+    fn iterate_words() {
+        let data = Data {
+            data: &[0x12, 0x34, 0x56, 0x78, 0x9A],
+            quantity: 2,
+        };
+        assert_eq!(data.words().count(), 2);
+        assert_eq!(data.words().last(), Some(0x5678));
+        assert_eq!(data.len(), 2);
+    }
+
     #[test]
     fn data_len() {
         let data = Data {
@@ -134,6 +285,33 @@ mod tests {
         assert_eq!(data.get(2), None);
     }
 
+    #[test]
+    fn data_first_and_last() {
+        let data = Data {
+            data: &[0xFF, 0xAB, 0xCD, 0xEF],
+            quantity: 2,
+        };
+        assert_eq!(data.first(), Some(0xFFAB));
+        assert_eq!(data.last(), Some(0xCDEF));
+        let data = Data {
+            data: &[],
+            quantity: 0,
+        };
+        assert_eq!(data.first(), None);
+        assert_eq!(data.last(), None);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn data_to_heapless() {
+        let data = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        assert_eq!(data.to_heapless::<4>().unwrap(), [0x1234, 0x5678]);
+        assert_eq!(data.to_heapless::<1>(), Err(Error::BufferSize));
+    }
+
     #[test]
     fn data_iter() {
         let data = Data {
@@ -0,0 +1,192 @@
+use super::*;
+use crate::error::DecodeError;
+
+pub(crate) const RETURN_QUERY_DATA: SubFunctionCode = 0x00;
+pub(crate) const RESTART_COMMUNICATIONS: SubFunctionCode = 0x01;
+pub(crate) const RETURN_DIAGNOSTIC_REGISTER: SubFunctionCode = 0x02;
+pub(crate) const CHANGE_ASCII_INPUT_DELIMITER: SubFunctionCode = 0x03;
+pub(crate) const FORCE_LISTEN_ONLY: SubFunctionCode = 0x04;
+pub(crate) const CLEAR_COUNTERS: SubFunctionCode = 0x0A;
+pub(crate) const CLEAR_OVERRUN_COUNTER: SubFunctionCode = 0x14;
+
+/// A counter of a serial line server that is returned
+/// by the `Diagnostics` sub-functions `0x0B` - `0x12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DiagnosticsCounter {
+    /// Return Bus Message Count (`0x0B`).
+    BusMessages,
+    /// Return Bus Communication Error Count (`0x0C`).
+    BusCommErrors,
+    /// Return Bus Exception Error Count (`0x0D`).
+    BusExceptionErrors,
+    /// Return Server Message Count (`0x0E`).
+    ServerMessages,
+    /// Return Server No Response Count (`0x0F`).
+    ServerNoResponses,
+    /// Return Server NAK Count (`0x10`).
+    ServerNaks,
+    /// Return Server Busy Count (`0x11`).
+    ServerBusy,
+    /// Return Bus Character Overrun Count (`0x12`).
+    BusCharOverruns,
+}
+
+impl DiagnosticsCounter {
+    /// The counter that is returned by `sub_function`, if any.
+    #[must_use]
+    pub const fn new(sub_function: u16) -> Option<Self> {
+        let counter = match sub_function {
+            0x0B => Self::BusMessages,
+            0x0C => Self::BusCommErrors,
+            0x0D => Self::BusExceptionErrors,
+            0x0E => Self::ServerMessages,
+            0x0F => Self::ServerNoResponses,
+            0x10 => Self::ServerNaks,
+            0x11 => Self::ServerBusy,
+            0x12 => Self::BusCharOverruns,
+            _ => return None,
+        };
+        Some(counter)
+    }
+
+    /// The sub-function code that returns the counter.
+    #[must_use]
+    pub const fn sub_function(self) -> u16 {
+        match self {
+            Self::BusMessages => 0x0B,
+            Self::BusCommErrors => 0x0C,
+            Self::BusExceptionErrors => 0x0D,
+            Self::ServerMessages => 0x0E,
+            Self::ServerNoResponses => 0x0F,
+            Self::ServerNaks => 0x10,
+            Self::ServerBusy => 0x11,
+            Self::BusCharOverruns => 0x12,
+        }
+    }
+}
+
+/// The data of a `Diagnostics` response, interpreted according to its sub-function.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{DiagnosticsCounter, DiagnosticsPayload, Response};
+///
+/// let bytes: &[u8] = &[0x08, 0x00, 0x0E, 0x00, 0x2A];
+/// let rsp = Response::try_from(bytes).unwrap();
+/// assert_eq!(
+///     DiagnosticsPayload::try_from(rsp),
+///     Ok(DiagnosticsPayload::Counter(DiagnosticsCounter::ServerMessages, 42))
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DiagnosticsPayload<'r> {
+    /// The echoed request data, e.g. of Return Query Data (`0x00`),
+    /// Restart Communications (`0x01`) or Clear Counters (`0x0A`).
+    Echo(Data<'r>),
+    /// The contents of the diagnostic register (`0x02`).
+    DiagnosticRegister(Word),
+    /// The value of a counter (`0x0B` - `0x12`).
+    Counter(DiagnosticsCounter, u16),
+    /// The data of a sub-function that is not interpreted by this crate.
+    Other(Data<'r>),
+}
+
+impl<'r> DiagnosticsPayload<'r> {
+    /// Interpret the response data `words` of `sub_function`.
+    ///
+    /// Fails with [`DecodeError::ByteCount`] if a register or
+    /// counter is not returned as a single word.
+    pub fn new(sub_function: u16, words: Data<'r>) -> Result<Self, DecodeError> {
+        let single_word = || match words.get(0) {
+            Some(word) if words.len() == 1 => Ok(word),
+            _ => Err(DecodeError::ByteCount(words.data.len() as u8)),
+        };
+        let payload = match sub_function {
+            RETURN_QUERY_DATA
+            | RESTART_COMMUNICATIONS
+            | CHANGE_ASCII_INPUT_DELIMITER
+            | FORCE_LISTEN_ONLY
+            | CLEAR_COUNTERS
+            | CLEAR_OVERRUN_COUNTER => Self::Echo(words),
+            RETURN_DIAGNOSTIC_REGISTER => Self::DiagnosticRegister(single_word()?),
+            _ => match DiagnosticsCounter::new(sub_function) {
+                Some(counter) => Self::Counter(counter, single_word()?),
+                None => Self::Other(words),
+            },
+        };
+        Ok(payload)
+    }
+}
+
+impl<'r> TryFrom<Response<'r>> for DiagnosticsPayload<'r> {
+    type Error = DecodeError;
+
+    /// Fails with [`DecodeError::FnCode`] if `rsp` is not a `Diagnostics` response.
+    fn try_from(rsp: Response<'r>) -> Result<Self, Self::Error> {
+        match rsp {
+            Response::Diagnostics(sub_function, words) => Self::new(sub_function, words),
+            rsp => Err(DecodeError::FnCode(rsp.function_code().value())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_sub_functions() {
+        for sub_function in 0..=0xFF {
+            if let Some(counter) = DiagnosticsCounter::new(sub_function) {
+                assert_eq!(counter.sub_function(), sub_function);
+            }
+        }
+        assert_eq!(
+            DiagnosticsCounter::new(0x12),
+            Some(DiagnosticsCounter::BusCharOverruns)
+        );
+        assert_eq!(DiagnosticsCounter::new(0x13), None);
+    }
+
+    #[test]
+    fn payload_of_sub_functions() {
+        let word = Data {
+            data: &[0x12, 0x34],
+            quantity: 1,
+        };
+        let words = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        assert_eq!(
+            DiagnosticsPayload::new(0x00, words),
+            Ok(DiagnosticsPayload::Echo(words))
+        );
+        assert_eq!(
+            DiagnosticsPayload::new(0x02, word),
+            Ok(DiagnosticsPayload::DiagnosticRegister(0x1234))
+        );
+        assert_eq!(
+            DiagnosticsPayload::new(0x0C, word),
+            Ok(DiagnosticsPayload::Counter(
+                DiagnosticsCounter::BusCommErrors,
+                0x1234
+            ))
+        );
+        assert_eq!(
+            DiagnosticsPayload::new(0x0C, words),
+            Err(DecodeError::ByteCount(4))
+        );
+        assert_eq!(
+            DiagnosticsPayload::new(0x15, words),
+            Ok(DiagnosticsPayload::Other(words))
+        );
+        assert_eq!(
+            DiagnosticsPayload::try_from(Response::ReadExceptionStatus(0)),
+            Err(DecodeError::FnCode(0x07))
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use super::*;
+use crate::{codec::checked_words_len, error::*};
+
+/// The reference type of all file record sub-requests.
+pub(crate) const FILE_RECORD_REF_TYPE: u8 = 0x06;
+
+/// The maximum byte count of a `ReadFileRecord` response and of each of its sub-responses.
+const MAX_FILE_RECORD_DATA_LEN: usize = 0xF5;
+
+/// The maximum byte count of a `WriteFileRecord` request or response.
+const MAX_FILE_RECORDS_LEN: usize = 0xFB;
+
+/// A file number (`1` - `65535`).
+pub type FileNumber = u16;
+
+/// A record number within a file (`0` - `9999`).
+pub type RecordNumber = u16;
+
+/// A sub-request of a `ReadFileRecord` request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FileRecordRequest {
+    pub file_number: FileNumber,
+    pub record_number: RecordNumber,
+    /// Number of registers to read.
+    pub record_length: Quantity,
+}
+
+/// The packed sub-requests of a `ReadFileRecord` request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FileRecordRequests<'r> {
+    pub(crate) data: RawData<'r>,
+}
+
+impl<'r> FileRecordRequests<'r> {
+    /// Pack sub-requests into a byte buffer.
+    pub fn from_requests(
+        requests: &[FileRecordRequest],
+        target: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        let len = requests.len() * 7;
+        if requests.is_empty() || target.len() < len {
+            return Err(Error::BufferSize);
+        }
+        for (req, buf) in requests.iter().zip(target.chunks_exact_mut(7)) {
+            buf[0] = FILE_RECORD_REF_TYPE;
+            write_u16(&mut buf[1..3], req.file_number);
+            write_u16(&mut buf[3..5], req.record_number);
+            write_u16(&mut buf[5..7], req.record_length);
+        }
+        Ok(Self {
+            data: &target[..len],
+        })
+    }
+
+    /// Check the layout of packed sub-requests.
+    pub(crate) fn parse(data: RawData<'r>) -> Result<Self, DecodeError> {
+        if data.is_empty() || data.len() % 7 != 0 {
+            return Err(DecodeError::ByteCount(data.len() as u8));
+        }
+        if let Some(ref_type) = data
+            .chunks_exact(7)
+            .map(|sub| sub[0])
+            .find(|t| *t != FILE_RECORD_REF_TYPE)
+        {
+            return Err(DecodeError::ReferenceType(ref_type));
+        }
+        Ok(Self { data })
+    }
+
+    /// Number of sub-requests.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.data.len() / 7
+    }
+
+    ///  Returns `true` if there are no sub-requests.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Iterate over all sub-requests.
+    pub fn iter(&self) -> impl Iterator<Item = FileRecordRequest> + 'r {
+        self.data.chunks_exact(7).map(|sub| FileRecordRequest {
+            file_number: read_u16(&sub[1..3]),
+            record_number: read_u16(&sub[3..5]),
+            record_length: read_u16(&sub[5..7]),
+        })
+    }
+}
+
+/// A record of a file including its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FileRecord<'r> {
+    pub file_number: FileNumber,
+    pub record_number: RecordNumber,
+    pub data: Data<'r>,
+}
+
+/// The packed sub-requests of a `WriteFileRecord` request or response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FileRecords<'r> {
+    pub(crate) data: RawData<'r>,
+}
+
+impl<'r> FileRecords<'r> {
+    /// Pack records into a byte buffer.
+    ///
+    /// Fails with [`Error::BufferSize`] if the records
+    /// do not fit into the byte count of the request.
+    pub fn from_records(records: &[FileRecord<'_>], target: &'r mut [u8]) -> Result<Self, Error> {
+        let len = records.iter().map(|r| 7 + r.data.len() * 2).sum();
+        if records.is_empty() || target.len() < len || len > MAX_FILE_RECORDS_LEN {
+            return Err(Error::BufferSize);
+        }
+        let mut pos = 0;
+        for record in records {
+            let buf = &mut target[pos..];
+            buf[0] = FILE_RECORD_REF_TYPE;
+            write_u16(&mut buf[1..3], record.file_number);
+            write_u16(&mut buf[3..5], record.record_number);
+            write_u16(&mut buf[5..7], record.data.len() as u16);
+            record.data.copy_to(&mut buf[7..])?;
+            pos += 7 + record.data.len() * 2;
+        }
+        Ok(Self {
+            data: &target[..len],
+        })
+    }
+
+    /// Check the layout of packed records.
+    pub(crate) fn parse(data: RawData<'r>) -> Result<Self, DecodeError> {
+        if data.is_empty() {
+            return Err(DecodeError::ByteCount(0));
+        }
+        let mut rest = data;
+        while !rest.is_empty() {
+            if rest.len() < 7 {
+                return Err(DecodeError::ByteCount(data.len() as u8));
+            }
+            if rest[0] != FILE_RECORD_REF_TYPE {
+                return Err(DecodeError::ReferenceType(rest[0]));
+            }
+            let len = checked_words_len(7, read_u16(&rest[5..7]).into())?;
+            if rest.len() < len {
+                return Err(DecodeError::ByteCount(data.len() as u8));
+            }
+            rest = &rest[len..];
+        }
+        Ok(Self { data })
+    }
+
+    /// Iterate over all records.
+    pub fn iter(&self) -> impl Iterator<Item = FileRecord<'r>> + 'r {
+        let mut rest = self.data;
+        core::iter::from_fn(move || {
+            if rest.len() < 7 {
+                return None;
+            }
+            let quantity = read_u16(&rest[5..7]) as usize;
+            let (record, tail) = rest.split_at(7 + quantity * 2);
+            rest = tail;
+            Some(FileRecord {
+                file_number: read_u16(&record[1..3]),
+                record_number: read_u16(&record[3..5]),
+                data: Data {
+                    data: &record[7..],
+                    quantity,
+                },
+            })
+        })
+    }
+}
+
+/// The packed sub-responses of a `ReadFileRecord` response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FileRecordData<'r> {
+    pub(crate) data: RawData<'r>,
+}
+
+impl<'r> FileRecordData<'r> {
+    /// Pack the data of records into a byte buffer.
+    ///
+    /// Fails with [`Error::BufferSize`] if a sub-response or the
+    /// whole response does not fit into its byte count.
+    pub fn from_records(records: &[Data<'_>], target: &'r mut [u8]) -> Result<Self, Error> {
+        let len = records.iter().map(|r| 2 + r.len() * 2).sum();
+        if records.is_empty()
+            || target.len() < len
+            || len > MAX_FILE_RECORD_DATA_LEN
+            || records
+                .iter()
+                .any(|r| 1 + r.len() * 2 > MAX_FILE_RECORD_DATA_LEN)
+        {
+            return Err(Error::BufferSize);
+        }
+        let mut pos = 0;
+        for record in records {
+            target[pos] = (1 + record.len() * 2) as u8;
+            target[pos + 1] = FILE_RECORD_REF_TYPE;
+            record.copy_to(&mut target[pos + 2..])?;
+            pos += 2 + record.len() * 2;
+        }
+        Ok(Self {
+            data: &target[..len],
+        })
+    }
+
+    /// Check the layout of packed sub-responses.
+    pub(crate) fn parse(data: RawData<'r>) -> Result<Self, DecodeError> {
+        let mut rest = data;
+        while !rest.is_empty() {
+            let len = rest[0] as usize;
+            if len % 2 == 0 || rest.len() < 1 + len {
+                return Err(DecodeError::ByteCount(data.len() as u8));
+            }
+            if rest[1] != FILE_RECORD_REF_TYPE {
+                return Err(DecodeError::ReferenceType(rest[1]));
+            }
+            rest = &rest[1 + len..];
+        }
+        Ok(Self { data })
+    }
+
+    /// Iterate over the data of all records.
+    pub fn iter(&self) -> impl Iterator<Item = Data<'r>> + 'r {
+        let mut rest = self.data;
+        core::iter::from_fn(move || {
+            let len = *rest.first()? as usize;
+            let (record, tail) = rest.split_at(1 + len);
+            rest = tail;
+            Some(Data {
+                data: &record[2..],
+                quantity: (len - 1) / 2,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_file_record_requests() {
+        let requests = &[
+            FileRecordRequest {
+                file_number: 4,
+                record_number: 1,
+                record_length: 2,
+            },
+            FileRecordRequest {
+                file_number: 3,
+                record_number: 9,
+                record_length: 2,
+            },
+        ];
+        let buf = &mut [0; 14];
+        let packed = FileRecordRequests::from_requests(requests, buf).unwrap();
+        assert_eq!(
+            packed.data,
+            &[0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, 0x06, 0x00, 0x03, 0x00, 0x09, 0x00, 0x02]
+        );
+        assert_eq!(packed.len(), 2);
+        let mut iter = packed.iter();
+        assert_eq!(iter.next(), Some(requests[0]));
+        assert_eq!(iter.next(), Some(requests[1]));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn parse_file_record_requests() {
+        assert!(FileRecordRequests::parse(&[]).is_err());
+        assert!(FileRecordRequests::parse(&[0x06, 0x00, 0x04, 0x00, 0x01, 0x00]).is_err());
+        assert_eq!(
+            FileRecordRequests::parse(&[0x07, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02]).err(),
+            Some(DecodeError::ReferenceType(0x07))
+        );
+        assert!(FileRecordRequests::parse(&[0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02]).is_ok());
+    }
+
+    #[test]
+    fn pack_file_records() {
+        let words = &mut [0; 6];
+        let record = FileRecord {
+            file_number: 4,
+            record_number: 7,
+            data: Data::from_words(&[0x06AF, 0x04BE, 0x100D], words).unwrap(),
+        };
+        let buf = &mut [0; 13];
+        let packed = FileRecords::from_records(&[record], buf).unwrap();
+        assert_eq!(
+            packed.data,
+            &[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x03, 0x06, 0xAF, 0x04, 0xBE, 0x10, 0x0D]
+        );
+        let mut iter = packed.iter();
+        assert_eq!(iter.next(), Some(record));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn reject_file_records_exceeding_byte_count() {
+        let buf = &mut [0; 260];
+        let words = &mut [0; 246];
+        let record = FileRecord {
+            file_number: 4,
+            record_number: 7,
+            data: Data::from_words(&[0; 123], words).unwrap(),
+        };
+        assert_eq!(
+            FileRecords::from_records(&[record], buf).err(),
+            Some(Error::BufferSize)
+        );
+        let words = &mut [0; 244];
+        let record = FileRecord {
+            data: Data::from_words(&[0; 122], words).unwrap(),
+            ..record
+        };
+        assert!(FileRecords::from_records(&[record], buf).is_ok());
+        let words = &mut [0; 122];
+        let record = FileRecord {
+            data: Data::from_words(&[0; 61], words).unwrap(),
+            ..record
+        };
+        assert_eq!(
+            FileRecords::from_records(&[record, record], buf).err(),
+            Some(Error::BufferSize)
+        );
+    }
+
+    #[test]
+    fn parse_file_records() {
+        assert!(FileRecords::parse(&[]).is_err());
+        assert!(FileRecords::parse(&[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06]).is_err());
+        let records =
+            FileRecords::parse(&[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF]).unwrap();
+        assert_eq!(records.iter().count(), 1);
+    }
+
+    #[test]
+    fn pack_file_record_data() {
+        let words = &mut [0; 4];
+        let first = Data::from_words(&[0x0DFE, 0x0020], words).unwrap();
+        let words = &mut [0; 2];
+        let second = Data::from_words(&[0x33CD], words).unwrap();
+        let buf = &mut [0; 10];
+        let packed = FileRecordData::from_records(&[first, second], buf).unwrap();
+        assert_eq!(
+            packed.data,
+            &[0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20, 0x03, 0x06, 0x33, 0xCD]
+        );
+        let mut iter = packed.iter();
+        assert_eq!(iter.next(), Some(first));
+        assert_eq!(iter.next(), Some(second));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn reject_file_record_data_exceeding_byte_count() {
+        let words = &mut [0; 256];
+        let buf = &mut [0; 260];
+        let record = Data::from_words(&[0; 128], words).unwrap();
+        assert_eq!(
+            FileRecordData::from_records(&[record], buf).err(),
+            Some(Error::BufferSize)
+        );
+        let words = &mut [0; 242];
+        let record = Data::from_words(&[0; 121], words).unwrap();
+        assert!(FileRecordData::from_records(&[record], buf).is_ok());
+        let words = &mut [0; 122];
+        let record = Data::from_words(&[0; 61], words).unwrap();
+        assert_eq!(
+            FileRecordData::from_records(&[record, record], buf).err(),
+            Some(Error::BufferSize)
+        );
+    }
+
+    #[test]
+    fn parse_file_record_data() {
+        assert!(FileRecordData::parse(&[0x05, 0x06, 0x0D, 0xFE, 0x00]).is_err());
+        assert!(FileRecordData::parse(&[0x04, 0x06, 0x0D, 0xFE, 0x00]).is_err());
+        assert_eq!(
+            FileRecordData::parse(&[0x03, 0x07, 0x0D, 0xFE]).err(),
+            Some(DecodeError::ReferenceType(0x07))
+        );
+        assert!(FileRecordData::parse(&[0x03, 0x06, 0x0D, 0xFE]).is_ok());
+    }
+}
@@ -0,0 +1,260 @@
+//! [`Arbitrary`] implementations for structure-aware fuzzing.
+//!
+//! Payloads borrow the fuzzer input, so no buffers are needed.
+//! Quantities and byte counts are kept consistent, i.e. every
+//! generated request and response can be encoded.
+
+use super::*;
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+/// Function codes that are not defined by the specification.
+const CUSTOM_FN_CODES: core::ops::RangeInclusive<u8> = 0x41..=0x48;
+
+/// The largest payload of a PDU.
+const MAX_PAYLOAD_LEN: usize = 252;
+
+impl<'a> Arbitrary<'a> for FunctionCode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.int_in_range(0x01..=0x7F)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Exception {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ExceptionResponse {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Coils<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        coils(u, MAX_READ_COILS)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Data<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        words(u, MAX_READ_REGISTERS)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Request<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let req = match u.int_in_range(0..=17)? {
+            0 => Self::ReadCoils(u.arbitrary()?, u.arbitrary()?),
+            1 => Self::ReadDiscreteInputs(u.arbitrary()?, u.arbitrary()?),
+            2 => Self::WriteSingleCoil(u.arbitrary()?, u.arbitrary()?),
+            3 => Self::WriteMultipleCoils(u.arbitrary()?, coils(u, MAX_WRITE_COILS)?),
+            4 => Self::ReadInputRegisters(u.arbitrary()?, u.arbitrary()?),
+            5 => Self::ReadHoldingRegisters(u.arbitrary()?, u.arbitrary()?),
+            6 => Self::WriteSingleRegister(u.arbitrary()?, u.arbitrary()?),
+            7 => Self::WriteMultipleRegisters(u.arbitrary()?, words(u, MAX_WRITE_REGISTERS)?),
+            8 => Self::ReadWriteMultipleRegisters(
+                u.arbitrary()?,
+                u.arbitrary()?,
+                u.arbitrary()?,
+                words(u, MAX_READ_WRITE_REGISTERS)?,
+            ),
+            9 => Self::ReadFifoQueue(u.arbitrary()?),
+            10 => Self::ReadFileRecord(
+                FileRecordRequests::parse(payload(u)?).map_err(|_| Error::IncorrectFormat)?,
+            ),
+            11 => Self::WriteFileRecord(
+                FileRecords::parse(payload(u)?).map_err(|_| Error::IncorrectFormat)?,
+            ),
+            #[cfg(feature = "rtu")]
+            12 => Self::ReadExceptionStatus,
+            #[cfg(feature = "rtu")]
+            13 => Self::Diagnostics(u.arbitrary()?, words(u, 1)?),
+            #[cfg(feature = "rtu")]
+            14 => Self::GetCommEventCounter,
+            #[cfg(feature = "rtu")]
+            15 => Self::GetCommEventLog,
+            #[cfg(feature = "rtu")]
+            16 => Self::ReportServerId,
+            _ => Self::Custom(RawPdu {
+                function: custom_fn_code(u)?,
+                data: payload(u)?,
+            }),
+        };
+        Ok(req)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Response<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // The response to `ReportServerId` is not generated
+        // because it cannot be encoded yet.
+        let rsp = match u.int_in_range(0..=16)? {
+            0 => Self::ReadCoils(byte_coils(u)?),
+            1 => Self::ReadDiscreteInputs(byte_coils(u)?),
+            2 => Self::WriteSingleCoil(u.arbitrary()?, u.arbitrary()?),
+            3 => Self::WriteMultipleCoils(u.arbitrary()?, u.arbitrary()?),
+            4 => Self::ReadInputRegisters(u.arbitrary()?),
+            5 => Self::ReadHoldingRegisters(u.arbitrary()?),
+            6 => Self::WriteSingleRegister(u.arbitrary()?, u.arbitrary()?),
+            7 => Self::WriteMultipleRegisters(u.arbitrary()?, u.arbitrary()?),
+            8 => Self::ReadWriteMultipleRegisters(u.arbitrary()?),
+            9 => Self::ReadFifoQueue(words(u, 31)?),
+            10 => Self::ReadFileRecord(
+                FileRecordData::parse(payload(u)?).map_err(|_| Error::IncorrectFormat)?,
+            ),
+            11 => Self::WriteFileRecord(
+                FileRecords::parse(payload(u)?).map_err(|_| Error::IncorrectFormat)?,
+            ),
+            #[cfg(feature = "rtu")]
+            12 => Self::ReadExceptionStatus(u.arbitrary()?),
+            #[cfg(feature = "rtu")]
+            13 => {
+                let sub_function = u.arbitrary()?;
+                let words = words(u, 1)?;
+                DiagnosticsPayload::new(sub_function, words).map_err(|_| Error::IncorrectFormat)?;
+                Self::Diagnostics(sub_function, words)
+            }
+            #[cfg(feature = "rtu")]
+            14 => Self::GetCommEventCounter(u.arbitrary()?, u.arbitrary()?),
+            #[cfg(feature = "rtu")]
+            15 => Self::GetCommEventLog(
+                u.arbitrary()?,
+                u.arbitrary()?,
+                u.arbitrary()?,
+                comm_events(u)?,
+            ),
+            _ => Self::Custom(RawPdu {
+                function: custom_fn_code(u)?,
+                data: payload(u)?,
+            }),
+        };
+        Ok(rsp)
+    }
+}
+
+impl<'a> Arbitrary<'a> for RequestPdu<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ResponsePdu<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for rtu::Header {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            slave: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for tcp::Header {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            transaction_id: u.arbitrary()?,
+            unit_id: u.arbitrary()?,
+        })
+    }
+}
+
+/// Between 1 and `max` coils.
+fn coils<'a>(u: &mut Unstructured<'a>, max: usize) -> Result<Coils<'a>> {
+    let quantity = u.int_in_range(1..=max)?;
+    let data = u.bytes(packed_coils_len(quantity))?;
+    Ok(Coils { data, quantity })
+}
+
+/// Coils that fill whole bytes, as they are decoded from a response.
+fn byte_coils<'a>(u: &mut Unstructured<'a>) -> Result<Coils<'a>> {
+    let len = u.int_in_range(1..=MAX_READ_COILS / 8)?;
+    let data = u.bytes(len)?;
+    Ok(Coils {
+        data,
+        quantity: data.len() * 8,
+    })
+}
+
+/// Between 1 and `max` words.
+fn words<'a>(u: &mut Unstructured<'a>, max: usize) -> Result<Data<'a>> {
+    let quantity = u.int_in_range(1..=max)?;
+    let data = u.bytes(quantity * 2)?;
+    Ok(Data { data, quantity })
+}
+
+/// Up to [`MAX_COMM_EVENTS`] valid events.
+///
+/// The events end in front of the first byte that is not a valid event.
+#[cfg(feature = "rtu")]
+fn comm_events<'a>(u: &mut Unstructured<'a>) -> Result<&'a [u8]> {
+    let len = u.int_in_range(0..=MAX_COMM_EVENTS)?;
+    let events = u.bytes(len)?;
+    let valid = events
+        .iter()
+        .take_while(|event| CommEvent::try_from(**event).is_ok())
+        .count();
+    Ok(&events[..valid])
+}
+
+fn custom_fn_code(u: &mut Unstructured<'_>) -> Result<FunctionCode> {
+    Ok(FunctionCode::Custom(u.int_in_range(CUSTOM_FN_CODES)?))
+}
+
+fn payload<'a>(u: &mut Unstructured<'a>) -> Result<&'a [u8]> {
+    let len = u.int_in_range(0..=MAX_PAYLOAD_LEN)?;
+    u.bytes(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecodeOptions, Encode};
+
+    /// Pseudo random fuzzer input.
+    fn input(seed: u32) -> [u8; 1024] {
+        let mut state = seed.wrapping_mul(0x9E37_79B9) | 1;
+        let mut bytes = [0; 1024];
+        for byte in &mut bytes {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            *byte = state as u8;
+        }
+        bytes
+    }
+
+    #[test]
+    fn request_roundtrip() {
+        let buf = &mut [0; 256];
+        for seed in 0..500 {
+            let input = input(seed);
+            let Ok(req) = Request::arbitrary(&mut Unstructured::new(&input)) else {
+                continue;
+            };
+            let len = req.encode(buf).unwrap();
+            assert_eq!(Request::try_from(&buf[..len]), Ok(req));
+        }
+    }
+
+    #[test]
+    fn response_roundtrip() {
+        let buf = &mut [0; 256];
+        for seed in 0..500 {
+            let input = input(seed);
+            let Ok(rsp) = ResponsePdu::arbitrary(&mut Unstructured::new(&input)) else {
+                continue;
+            };
+            let len = rsp.encode(buf).unwrap();
+            let options = DecodeOptions::lenient();
+            assert_eq!(
+                ResponsePdu::try_from_with_options(&buf[..len], &options),
+                Ok(rsp)
+            );
+        }
+    }
+}
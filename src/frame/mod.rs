@@ -1,17 +1,76 @@
+use crate::error::Error;
 use core::fmt;
 
+mod adu;
+mod buf;
+mod bytes;
+mod cell;
 mod coils;
+#[cfg(feature = "rtu")]
+mod comm_event;
 mod data;
+#[cfg(feature = "rtu")]
+mod diagnostics;
+mod file_record;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod numbering;
+#[cfg(feature = "alloc")]
+mod owned;
+mod range;
+mod raw;
+mod registers;
 pub(crate) mod rtu;
 pub(crate) mod tcp;
+mod validate;
+
+pub(crate) use self::bytes::{read_u16, write_u16};
+pub use self::bytes::{swap_register_bytes, swap_register_words};
+#[cfg(feature = "alloc")]
+pub use self::owned::*;
+pub(crate) use self::validate::{check_address, check_byte_count, check_quantity, check_value};
+pub use self::{
+    adu::*, buf::*, cell::*, coils::*, data::*, file_record::*, numbering::*, range::*, raw::*,
+    registers::*,
+};
+#[cfg(feature = "rtu")]
+pub use self::{comm_event::*, diagnostics::*};
+
+/// Maximum length of a PDU in bytes, including the function code.
+///
+/// It is limited by the size of an RTU frame.
+pub const MAX_PDU_LEN: usize = 253;
+
+/// Maximum length of an RTU ADU in bytes: slave address, PDU and CRC.
+pub const MAX_RTU_ADU_LEN: usize = MAX_PDU_LEN + 3;
+
+/// Length of the MBAP header of a TCP ADU in bytes, including the unit ID.
+pub const MBAP_HEADER_LEN: usize = 7;
+
+/// Maximum length of a TCP ADU in bytes: MBAP header and PDU.
+pub const MAX_TCP_ADU_LEN: usize = MBAP_HEADER_LEN + MAX_PDU_LEN;
+
+/// Maximum number of coils or discrete inputs that can be read with a single request.
+pub(crate) const MAX_READ_COILS: usize = 2000;
+
+/// Maximum number of registers that can be read with a single request.
+pub(crate) const MAX_READ_REGISTERS: usize = 125;
+
+/// Maximum number of coils that can be written with a single request.
+pub(crate) const MAX_WRITE_COILS: usize = 1968;
+
+/// Maximum number of registers that can be written with a single request.
+pub(crate) const MAX_WRITE_REGISTERS: usize = 123;
 
-pub use self::{coils::*, data::*};
-use byteorder::{BigEndian, ByteOrder};
+/// Maximum number of registers that can be written with a single
+/// `ReadWriteMultipleRegisters` request.
+pub(crate) const MAX_READ_WRITE_REGISTERS: usize = 121;
 
 /// A Modbus function code.
 ///
 /// It is represented by an unsigned 8 bit integer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FunctionCode {
     /// Modbus Function Code: `01` (`0x01`).
     ReadCoils,
@@ -43,6 +102,9 @@ pub enum FunctionCode {
     /// Modbus Function Code: `23` (`0x17`).
     ReadWriteMultipleRegisters,
 
+    /// Modbus Function Code: `24` (`0x18`).
+    ReadFifoQueue,
+
     #[cfg(feature = "rtu")]
     ReadExceptionStatus,
 
@@ -58,11 +120,13 @@ pub enum FunctionCode {
     #[cfg(feature = "rtu")]
     ReportServerId,
 
+    /// Modbus Function Code: `20` (`0x14`).
+    ReadFileRecord,
+
+    /// Modbus Function Code: `21` (`0x15`).
+    WriteFileRecord,
+
     // TODO:
-    // - ReadFileRecord
-    // - WriteFileRecord
-    // TODO:
-    // - Read FifoQueue
     // - EncapsulatedInterfaceTransport
     // - CanOpenGeneralReferenceRequestAndResponsePdu
     // - ReadDeviceIdentification
@@ -85,6 +149,9 @@ impl FunctionCode {
             0x10 => Self::WriteMultipleRegisters,
             0x16 => Self::MaskWriteRegister,
             0x17 => Self::ReadWriteMultipleRegisters,
+            0x18 => Self::ReadFifoQueue,
+            0x14 => Self::ReadFileRecord,
+            0x15 => Self::WriteFileRecord,
             #[cfg(feature = "rtu")]
             0x07 => Self::ReadExceptionStatus,
             #[cfg(feature = "rtu")]
@@ -113,6 +180,9 @@ impl FunctionCode {
             Self::WriteMultipleRegisters => 0x10,
             Self::MaskWriteRegister => 0x16,
             Self::ReadWriteMultipleRegisters => 0x17,
+            Self::ReadFifoQueue => 0x18,
+            Self::ReadFileRecord => 0x14,
+            Self::WriteFileRecord => 0x15,
             #[cfg(feature = "rtu")]
             Self::ReadExceptionStatus => 0x07,
             #[cfg(feature = "rtu")]
@@ -126,6 +196,54 @@ impl FunctionCode {
             Self::Custom(code) => code,
         }
     }
+
+    /// Returns `true` if the function is only defined for serial lines,
+    /// e.g. `Diagnostics`, independent of the `rtu` feature.
+    #[must_use]
+    pub const fn is_serial_line_only(self) -> bool {
+        matches!(self.value(), 0x07 | 0x08 | 0x0B | 0x0C | 0x11)
+    }
+
+    /// The kind of access of the function.
+    #[must_use]
+    pub const fn category(self) -> Category {
+        match self {
+            Self::ReadCoils
+            | Self::ReadDiscreteInputs
+            | Self::ReadHoldingRegisters
+            | Self::ReadInputRegisters
+            | Self::ReadFifoQueue
+            | Self::ReadFileRecord => Category::Read,
+            Self::WriteSingleCoil
+            | Self::WriteSingleRegister
+            | Self::WriteMultipleCoils
+            | Self::WriteMultipleRegisters
+            | Self::MaskWriteRegister
+            | Self::ReadWriteMultipleRegisters
+            | Self::WriteFileRecord => Category::Write,
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus
+            | Self::Diagnostics
+            | Self::GetCommEventCounter
+            | Self::GetCommEventLog
+            | Self::ReportServerId => Category::Diagnostics,
+            Self::Custom(_) => Category::Other,
+        }
+    }
+}
+
+/// The kind of access of a [`FunctionCode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Category {
+    /// Reads data without changing it.
+    Read,
+    /// Changes data, possibly reading it, too.
+    Write,
+    /// Serial line diagnostics and device information.
+    Diagnostics,
+    /// Custom and unsupported functions.
+    Other,
 }
 
 impl fmt::Display for FunctionCode {
@@ -158,6 +276,7 @@ type RawData<'r> = &'r [u8];
 
 /// A request represents a message from the client (master) to the server (slave).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Request<'r> {
     ReadCoils(Address, Quantity),
     ReadDiscreteInputs(Address, Quantity),
@@ -168,6 +287,9 @@ pub enum Request<'r> {
     WriteSingleRegister(Address, Word),
     WriteMultipleRegisters(Address, Data<'r>),
     ReadWriteMultipleRegisters(Address, Quantity, Address, Data<'r>),
+    ReadFifoQueue(Address),
+    ReadFileRecord(FileRecordRequests<'r>),
+    WriteFileRecord(FileRecords<'r>),
     #[cfg(feature = "rtu")]
     ReadExceptionStatus,
     #[cfg(feature = "rtu")]
@@ -179,30 +301,43 @@ pub enum Request<'r> {
     #[cfg(feature = "rtu")]
     ReportServerId,
     //TODO:
-    //- ReadFileRecord
-    //- WriteFileRecord
     //- MaskWriteRegiger
     //TODO:
-    //- Read FifoQueue
     //- EncapsulatedInterfaceTransport
     //- CanOpenGeneralReferenceRequestAndResponsePdu
     //- ReadDeviceIdentification
-    Custom(FunctionCode, &'r [u8]),
+    Custom(RawPdu<'r>),
 }
 
 /// A server (slave) exception response.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ExceptionResponse {
     pub function: FunctionCode,
     pub exception: Exception,
 }
 
+impl ExceptionResponse {
+    /// Create an exception response to a request of `function`.
+    ///
+    /// The function code is marked as an exception (`+ 0x80`) when encoding.
+    #[must_use]
+    pub const fn new(function: FunctionCode, exception: Exception) -> Self {
+        Self {
+            function,
+            exception,
+        }
+    }
+}
+
 /// Represents a message from the client (slave) to the server (master).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RequestPdu<'r>(pub Request<'r>);
 
 /// Represents a message from the server (slave) to the client (master).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ResponsePdu<'r>(pub Result<Response<'r>, ExceptionResponse>);
 
 #[cfg(feature = "rtu")]
@@ -214,20 +349,24 @@ type MessageCount = u16;
 
 /// The response data of a successfull request.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Response<'r> {
     ReadCoils(Coils<'r>),
     ReadDiscreteInputs(Coils<'r>),
-    WriteSingleCoil(Address),
+    WriteSingleCoil(Address, Coil),
     WriteMultipleCoils(Address, Quantity),
     ReadInputRegisters(Data<'r>),
     ReadHoldingRegisters(Data<'r>),
     WriteSingleRegister(Address, Word),
     WriteMultipleRegisters(Address, Quantity),
     ReadWriteMultipleRegisters(Data<'r>),
+    ReadFifoQueue(Data<'r>),
+    ReadFileRecord(FileRecordData<'r>),
+    WriteFileRecord(FileRecords<'r>),
     #[cfg(feature = "rtu")]
     ReadExceptionStatus(u8),
     #[cfg(feature = "rtu")]
-    Diagnostics(Data<'r>),
+    Diagnostics(SubFunctionCode, Data<'r>),
     #[cfg(feature = "rtu")]
     GetCommEventCounter(Status, EventCount),
     #[cfg(feature = "rtu")]
@@ -235,87 +374,79 @@ pub enum Response<'r> {
     #[cfg(feature = "rtu")]
     ReportServerId(&'r [u8], bool),
     //TODO:
-    //- ReadFileRecord
-    //- WriteFileRecord
     //- MaskWriteRegiger
     //TODO:
-    //- Read FifoQueue
     //- EncapsulatedInterfaceTransport
     //- CanOpenGeneralReferenceRequestAndResponsePdu
     //- ReadDeviceIdentification
-    Custom(FunctionCode, &'r [u8]),
+    Custom(RawPdu<'r>),
 }
 
 impl<'r> From<Request<'r>> for FunctionCode {
     fn from(r: Request<'r>) -> Self {
-        use Request as R;
-
-        match r {
-            R::ReadCoils(_, _) => Self::ReadCoils,
-            R::ReadDiscreteInputs(_, _) => Self::ReadDiscreteInputs,
-            R::WriteSingleCoil(_, _) => Self::WriteSingleCoil,
-            R::WriteMultipleCoils(_, _) => Self::WriteMultipleCoils,
-            R::ReadInputRegisters(_, _) => Self::ReadInputRegisters,
-            R::ReadHoldingRegisters(_, _) => Self::ReadHoldingRegisters,
-            R::WriteSingleRegister(_, _) => Self::WriteSingleRegister,
-            R::WriteMultipleRegisters(_, _) => Self::WriteMultipleRegisters,
-            R::ReadWriteMultipleRegisters(_, _, _, _) => Self::ReadWriteMultipleRegisters,
-            #[cfg(feature = "rtu")]
-            R::ReadExceptionStatus => Self::ReadExceptionStatus,
-            #[cfg(feature = "rtu")]
-            R::Diagnostics(_, _) => Self::Diagnostics,
-            #[cfg(feature = "rtu")]
-            R::GetCommEventCounter => Self::GetCommEventCounter,
-            #[cfg(feature = "rtu")]
-            R::GetCommEventLog => Self::GetCommEventLog,
-            #[cfg(feature = "rtu")]
-            R::ReportServerId => Self::ReportServerId,
-            R::Custom(code, _) => code,
-        }
+        r.function_code()
     }
 }
 
 impl<'r> From<Response<'r>> for FunctionCode {
     fn from(r: Response<'r>) -> Self {
-        use Response as R;
-
-        match r {
-            R::ReadCoils(_) => Self::ReadCoils,
-            R::ReadDiscreteInputs(_) => Self::ReadDiscreteInputs,
-            R::WriteSingleCoil(_) => Self::WriteSingleCoil,
-            R::WriteMultipleCoils(_, _) => Self::WriteMultipleCoils,
-            R::ReadInputRegisters(_) => Self::ReadInputRegisters,
-            R::ReadHoldingRegisters(_) => Self::ReadHoldingRegisters,
-            R::WriteSingleRegister(_, _) => Self::WriteSingleRegister,
-            R::WriteMultipleRegisters(_, _) => Self::WriteMultipleRegisters,
-            R::ReadWriteMultipleRegisters(_) => Self::ReadWriteMultipleRegisters,
-            #[cfg(feature = "rtu")]
-            R::ReadExceptionStatus(_) => Self::ReadExceptionStatus,
-            #[cfg(feature = "rtu")]
-            R::Diagnostics(_) => Self::Diagnostics,
-            #[cfg(feature = "rtu")]
-            R::GetCommEventCounter(_, _) => Self::GetCommEventCounter,
-            #[cfg(feature = "rtu")]
-            R::GetCommEventLog(_, _, _, _) => Self::GetCommEventLog,
-            #[cfg(feature = "rtu")]
-            R::ReportServerId(_, _) => Self::ReportServerId,
-            R::Custom(code, _) => code,
-        }
+        r.function_code()
     }
 }
 
 /// A server (slave) exception.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Exception {
-    IllegalFunction = 0x01,
-    IllegalDataAddress = 0x02,
-    IllegalDataValue = 0x03,
-    ServerDeviceFailure = 0x04,
-    Acknowledge = 0x05,
-    ServerDeviceBusy = 0x06,
-    MemoryParityError = 0x08,
-    GatewayPathUnavailable = 0x0A,
-    GatewayTargetDevice = 0x0B,
+    IllegalFunction,
+    IllegalDataAddress,
+    IllegalDataValue,
+    ServerDeviceFailure,
+    Acknowledge,
+    ServerDeviceBusy,
+    MemoryParityError,
+    GatewayPathUnavailable,
+    GatewayTargetDevice,
+    /// An exception code that is not defined by the specification.
+    Custom(u8),
+}
+
+impl Exception {
+    /// Create a new [`Exception`] with `value`.
+    ///
+    /// Unknown codes are mapped to [`Exception::Custom`].
+    #[must_use]
+    pub const fn new(value: u8) -> Self {
+        match value {
+            0x01 => Self::IllegalFunction,
+            0x02 => Self::IllegalDataAddress,
+            0x03 => Self::IllegalDataValue,
+            0x04 => Self::ServerDeviceFailure,
+            0x05 => Self::Acknowledge,
+            0x06 => Self::ServerDeviceBusy,
+            0x08 => Self::MemoryParityError,
+            0x0A => Self::GatewayPathUnavailable,
+            0x0B => Self::GatewayTargetDevice,
+            code => Self::Custom(code),
+        }
+    }
+
+    /// Get the [`u8`] value of the current [`Exception`].
+    #[must_use]
+    pub const fn value(self) -> u8 {
+        match self {
+            Self::IllegalFunction => 0x01,
+            Self::IllegalDataAddress => 0x02,
+            Self::IllegalDataValue => 0x03,
+            Self::ServerDeviceFailure => 0x04,
+            Self::Acknowledge => 0x05,
+            Self::ServerDeviceBusy => 0x06,
+            Self::MemoryParityError => 0x08,
+            Self::GatewayPathUnavailable => 0x0A,
+            Self::GatewayTargetDevice => 0x0B,
+            Self::Custom(code) => code,
+        }
+    }
 }
 
 impl fmt::Display for Exception {
@@ -330,15 +461,16 @@ impl fmt::Display for Exception {
             Self::MemoryParityError => "Memory parity error",
             Self::GatewayPathUnavailable => "Gateway path unavailable",
             Self::GatewayTargetDevice => "Gateway target device failed to respond",
+            Self::Custom(code) => return write!(f, "Exception 0x{code:0>2X}"),
         };
         write!(f, "{desc}")
     }
 }
 
-impl<'r> Request<'r> {
+impl Request<'_> {
     /// Number of bytes required for a serialized PDU frame.
     #[must_use]
-    pub fn pdu_len(&self) -> usize {
+    pub const fn pdu_len(&self) -> usize {
         match *self {
             Self::ReadCoils(_, _)
             | Self::ReadDiscreteInputs(_, _)
@@ -349,30 +481,302 @@ impl<'r> Request<'r> {
             Self::WriteMultipleCoils(_, coils) => 6 + coils.packed_len(),
             Self::WriteMultipleRegisters(_, words) => 6 + words.data.len(),
             Self::ReadWriteMultipleRegisters(_, _, _, words) => 10 + words.data.len(),
-            Self::Custom(_, data) => 1 + data.len(),
+            Self::ReadFifoQueue(_) => 3,
+            Self::ReadFileRecord(FileRecordRequests { data })
+            | Self::WriteFileRecord(FileRecords { data }) => 2 + data.len(),
+            Self::Custom(pdu) => 1 + pdu.data.len(),
             #[cfg(feature = "rtu")]
-            _ => todo!(), // TODO
+            Self::ReadExceptionStatus
+            | Self::GetCommEventCounter
+            | Self::GetCommEventLog
+            | Self::ReportServerId => 1,
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(_, words) => 3 + words.data.len(),
+        }
+    }
+}
+
+impl<'r> Request<'r> {
+    /// The function code of the request.
+    #[must_use]
+    pub const fn function_code(&self) -> FunctionCode {
+        use Request as R;
+
+        match *self {
+            R::ReadCoils(_, _) => FunctionCode::ReadCoils,
+            R::ReadDiscreteInputs(_, _) => FunctionCode::ReadDiscreteInputs,
+            R::WriteSingleCoil(_, _) => FunctionCode::WriteSingleCoil,
+            R::WriteMultipleCoils(_, _) => FunctionCode::WriteMultipleCoils,
+            R::ReadInputRegisters(_, _) => FunctionCode::ReadInputRegisters,
+            R::ReadHoldingRegisters(_, _) => FunctionCode::ReadHoldingRegisters,
+            R::WriteSingleRegister(_, _) => FunctionCode::WriteSingleRegister,
+            R::WriteMultipleRegisters(_, _) => FunctionCode::WriteMultipleRegisters,
+            R::ReadWriteMultipleRegisters(_, _, _, _) => FunctionCode::ReadWriteMultipleRegisters,
+            R::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            R::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            R::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            #[cfg(feature = "rtu")]
+            R::ReadExceptionStatus => FunctionCode::ReadExceptionStatus,
+            #[cfg(feature = "rtu")]
+            R::Diagnostics(_, _) => FunctionCode::Diagnostics,
+            #[cfg(feature = "rtu")]
+            R::GetCommEventCounter => FunctionCode::GetCommEventCounter,
+            #[cfg(feature = "rtu")]
+            R::GetCommEventLog => FunctionCode::GetCommEventLog,
+            #[cfg(feature = "rtu")]
+            R::ReportServerId => FunctionCode::ReportServerId,
+            R::Custom(pdu) => pdu.function,
+        }
+    }
+
+    /// The (first read) address, if the request addresses coils or registers.
+    #[must_use]
+    pub const fn address(&self) -> Option<Address> {
+        match *self {
+            Self::ReadCoils(address, _)
+            | Self::ReadDiscreteInputs(address, _)
+            | Self::WriteSingleCoil(address, _)
+            | Self::WriteMultipleCoils(address, _)
+            | Self::ReadInputRegisters(address, _)
+            | Self::ReadHoldingRegisters(address, _)
+            | Self::WriteSingleRegister(address, _)
+            | Self::WriteMultipleRegisters(address, _)
+            | Self::ReadWriteMultipleRegisters(address, _, _, _)
+            | Self::ReadFifoQueue(address) => Some(address),
+            _ => None,
+        }
+    }
+
+    /// The number of (read) coils or registers, if the request has a quantity.
+    #[must_use]
+    pub const fn quantity(&self) -> Option<Quantity> {
+        match *self {
+            Self::ReadCoils(_, quantity)
+            | Self::ReadDiscreteInputs(_, quantity)
+            | Self::ReadInputRegisters(_, quantity)
+            | Self::ReadHoldingRegisters(_, quantity)
+            | Self::ReadWriteMultipleRegisters(_, quantity, _, _) => Some(quantity),
+            Self::WriteMultipleCoils(_, coils) => Some(coils.quantity as Quantity),
+            Self::WriteMultipleRegisters(_, words) => Some(words.quantity as Quantity),
+            _ => None,
+        }
+    }
+
+    /// The registers that are written, if any.
+    #[must_use]
+    pub const fn data(&self) -> Option<Data<'r>> {
+        match *self {
+            Self::WriteMultipleRegisters(_, words)
+            | Self::ReadWriteMultipleRegisters(_, _, _, words) => Some(words),
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(_, words) => Some(words),
+            _ => None,
+        }
+    }
+
+    /// The coils that are written, if any.
+    #[must_use]
+    pub const fn coils(&self) -> Option<Coils<'r>> {
+        match *self {
+            Self::WriteMultipleCoils(_, coils) => Some(coils),
+            _ => None,
         }
     }
 }
 
 impl<'r> Response<'r> {
+    /// Read coils, packed from `coils` into `buf`, see [`Coils::from_bools_iter`].
+    pub fn read_coils_from_iter(
+        coils: impl IntoIterator<Item = Coil>,
+        buf: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        Coils::from_bools_iter(coils, buf).map(Self::ReadCoils)
+    }
+
+    /// Read discrete inputs, packed from `inputs` into `buf`,
+    /// see [`Coils::from_bools_iter`].
+    pub fn read_discrete_inputs_from_iter(
+        inputs: impl IntoIterator<Item = Coil>,
+        buf: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        Coils::from_bools_iter(inputs, buf).map(Self::ReadDiscreteInputs)
+    }
+
+    /// Read input registers, packed from `words` into `buf`,
+    /// see [`Data::from_words_iter`].
+    pub fn read_input_registers_from_iter(
+        words: impl IntoIterator<Item = Word>,
+        buf: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        Data::from_words_iter(words, buf).map(Self::ReadInputRegisters)
+    }
+
+    /// Read holding registers, packed from `words` into `buf`,
+    /// see [`Data::from_words_iter`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use modbus_core::{rtu, Response, ResponsePdu};
+    ///
+    /// let temperature = |channel: u16| 200 + channel;
+    /// let payload = &mut [0; 8];
+    /// let rsp = Response::read_holding_registers_from_iter((0..4).map(temperature), payload).unwrap();
+    /// let adu = rtu::ResponseAdu {
+    ///     hdr: rtu::Header { slave: 1 },
+    ///     pdu: ResponsePdu(Ok(rsp)),
+    /// };
+    /// let buf = &mut [0; 16];
+    /// let len = rtu::server::encode_response(adu, buf).unwrap();
+    /// assert_eq!(&buf[1..5], &[0x03, 0x08, 0x00, 0xC8]);
+    /// assert_eq!(len, 13);
+    /// ```
+    pub fn read_holding_registers_from_iter(
+        words: impl IntoIterator<Item = Word>,
+        buf: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        Data::from_words_iter(words, buf).map(Self::ReadHoldingRegisters)
+    }
+
+    /// Read/write multiple registers with the read registers packed
+    /// from `words` into `buf`, see [`Data::from_words_iter`].
+    pub fn read_write_multiple_registers_from_iter(
+        words: impl IntoIterator<Item = Word>,
+        buf: &'r mut [u8],
+    ) -> Result<Self, Error> {
+        Data::from_words_iter(words, buf).map(Self::ReadWriteMultipleRegisters)
+    }
+
+    /// The function code of the response.
+    #[must_use]
+    pub const fn function_code(&self) -> FunctionCode {
+        use Response as R;
+
+        match *self {
+            R::ReadCoils(_) => FunctionCode::ReadCoils,
+            R::ReadDiscreteInputs(_) => FunctionCode::ReadDiscreteInputs,
+            R::WriteSingleCoil(_, _) => FunctionCode::WriteSingleCoil,
+            R::WriteMultipleCoils(_, _) => FunctionCode::WriteMultipleCoils,
+            R::ReadInputRegisters(_) => FunctionCode::ReadInputRegisters,
+            R::ReadHoldingRegisters(_) => FunctionCode::ReadHoldingRegisters,
+            R::WriteSingleRegister(_, _) => FunctionCode::WriteSingleRegister,
+            R::WriteMultipleRegisters(_, _) => FunctionCode::WriteMultipleRegisters,
+            R::ReadWriteMultipleRegisters(_) => FunctionCode::ReadWriteMultipleRegisters,
+            R::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            R::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            R::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            #[cfg(feature = "rtu")]
+            R::ReadExceptionStatus(_) => FunctionCode::ReadExceptionStatus,
+            #[cfg(feature = "rtu")]
+            R::Diagnostics(_, _) => FunctionCode::Diagnostics,
+            #[cfg(feature = "rtu")]
+            R::GetCommEventCounter(_, _) => FunctionCode::GetCommEventCounter,
+            #[cfg(feature = "rtu")]
+            R::GetCommEventLog(_, _, _, _) => FunctionCode::GetCommEventLog,
+            #[cfg(feature = "rtu")]
+            R::ReportServerId(_, _) => FunctionCode::ReportServerId,
+            R::Custom(pdu) => pdu.function,
+        }
+    }
+
+    /// The address that a write request has been applied to, if any.
+    #[must_use]
+    pub const fn address(&self) -> Option<Address> {
+        match *self {
+            Self::WriteSingleCoil(address, _)
+            | Self::WriteMultipleCoils(address, _)
+            | Self::WriteSingleRegister(address, _)
+            | Self::WriteMultipleRegisters(address, _) => Some(address),
+            _ => None,
+        }
+    }
+
+    /// The registers that have been read, if any.
+    #[must_use]
+    pub const fn data(&self) -> Option<Data<'r>> {
+        match *self {
+            Self::ReadInputRegisters(words)
+            | Self::ReadHoldingRegisters(words)
+            | Self::ReadWriteMultipleRegisters(words)
+            | Self::ReadFifoQueue(words) => Some(words),
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(_, words) => Some(words),
+            _ => None,
+        }
+    }
+
+    /// The coils or discrete inputs that have been read, if any.
+    #[must_use]
+    pub const fn coils(&self) -> Option<Coils<'r>> {
+        match *self {
+            Self::ReadCoils(coils) | Self::ReadDiscreteInputs(coils) => Some(coils),
+            _ => None,
+        }
+    }
+}
+
+impl<'r> ResponsePdu<'r> {
+    /// Returns `true` if the request has been rejected.
+    #[must_use]
+    pub const fn is_exception(&self) -> bool {
+        self.0.is_err()
+    }
+
+    /// The function code of the request, also for exceptions.
+    #[must_use]
+    pub const fn function_code(&self) -> FunctionCode {
+        match self.0 {
+            Ok(rsp) => rsp.function_code(),
+            Err(ExceptionResponse { function, .. }) => function,
+        }
+    }
+
+    /// The response, unless the request has been rejected.
+    #[must_use]
+    pub const fn ok(&self) -> Option<Response<'r>> {
+        match self.0 {
+            Ok(rsp) => Some(rsp),
+            Err(_) => None,
+        }
+    }
+
+    /// The exception, if the request has been rejected.
+    #[must_use]
+    pub const fn exception(&self) -> Option<ExceptionResponse> {
+        match self.0 {
+            Ok(_) => None,
+            Err(ex) => Some(ex),
+        }
+    }
+}
+
+impl Response<'_> {
     /// Number of bytes required for a serialized PDU frame.
     #[must_use]
-    pub fn pdu_len(&self) -> usize {
+    pub const fn pdu_len(&self) -> usize {
         match *self {
             Self::ReadCoils(coils) | Self::ReadDiscreteInputs(coils) => 2 + coils.packed_len(),
-            Self::WriteSingleCoil(_) => 3,
-            Self::WriteMultipleCoils(_, _)
+            Self::WriteSingleCoil(_, _)
+            | Self::WriteMultipleCoils(_, _)
             | Self::WriteMultipleRegisters(_, _)
             | Self::WriteSingleRegister(_, _) => 5,
             Self::ReadInputRegisters(words)
             | Self::ReadHoldingRegisters(words)
             | Self::ReadWriteMultipleRegisters(words) => 2 + words.len() * 2,
-            Self::Custom(_, data) => 1 + data.len(),
+            Self::ReadFifoQueue(words) => 5 + words.len() * 2,
+            Self::ReadFileRecord(FileRecordData { data })
+            | Self::WriteFileRecord(FileRecords { data }) => 2 + data.len(),
+            Self::Custom(pdu) => 1 + pdu.data.len(),
+            #[cfg(feature = "rtu")]
             Self::ReadExceptionStatus(_) => 2,
             #[cfg(feature = "rtu")]
-            _ => unimplemented!(), // TODO
+            Self::Diagnostics(_, words) => 3 + words.data.len(),
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter(_, _) => 5,
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog(_, _, _, events) => 8 + events.len(),
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId(id, _) => 3 + id.len(),
         }
     }
 }
@@ -390,12 +794,102 @@ mod tests {
         assert_eq!(x, 0xBB);
     }
 
+    #[test]
+    fn function_code_category() {
+        assert_eq!(FunctionCode::ReadFifoQueue.category(), Category::Read);
+        assert_eq!(
+            FunctionCode::ReadWriteMultipleRegisters.category(),
+            Category::Write
+        );
+        assert_eq!(FunctionCode::new(0x2B).category(), Category::Other);
+    }
+
+    #[test]
+    fn function_code_serial_line_only() {
+        assert!(FunctionCode::new(0x08).is_serial_line_only());
+        assert!(FunctionCode::new(0x11).is_serial_line_only());
+        assert!(!FunctionCode::ReadFileRecord.is_serial_line_only());
+        assert!(!FunctionCode::Custom(0x41).is_serial_line_only());
+    }
+
     #[test]
     fn function_code_from_u8() {
         assert_eq!(FunctionCode::new(15), FunctionCode::WriteMultipleCoils);
         assert_eq!(FunctionCode::new(0xBB), FunctionCode::Custom(0xBB));
     }
 
+    #[test]
+    fn exception_value_roundtrip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(Exception::new(value).value(), value);
+        }
+        assert_eq!(Exception::new(0x0A), Exception::GatewayPathUnavailable);
+        assert_eq!(Exception::new(0x07), Exception::Custom(0x07));
+    }
+
+    #[test]
+    fn request_accessors() {
+        let words = Data {
+            data: &[0x12, 0x34],
+            quantity: 1,
+        };
+        let req = Request::ReadWriteMultipleRegisters(0x10, 3, 0x20, words);
+        assert_eq!(
+            req.function_code(),
+            FunctionCode::ReadWriteMultipleRegisters
+        );
+        assert_eq!(req.address(), Some(0x10));
+        assert_eq!(req.quantity(), Some(3));
+        assert_eq!(req.data(), Some(words));
+        assert_eq!(req.coils(), None);
+
+        let coils = Coils {
+            data: &[0b101],
+            quantity: 3,
+        };
+        let req = Request::WriteMultipleCoils(0x05, coils);
+        assert_eq!(req.quantity(), Some(3));
+        assert_eq!(req.coils(), Some(coils));
+
+        let req = Request::WriteSingleRegister(0x07, 0xABCD);
+        assert_eq!(req.address(), Some(0x07));
+        assert_eq!(req.quantity(), None);
+        let req = Request::Custom(RawPdu {
+            function: FunctionCode::Custom(0x42),
+            data: &[],
+        });
+        assert_eq!(req.address(), None);
+        assert_eq!(req.data(), None);
+    }
+
+    #[test]
+    fn response_accessors() {
+        let coils = Coils {
+            data: &[0b101],
+            quantity: 3,
+        };
+        let rsp = Response::ReadCoils(coils);
+        assert_eq!(rsp.coils(), Some(coils));
+        assert_eq!(rsp.data(), None);
+        assert_eq!(rsp.address(), None);
+        let rsp = Response::WriteMultipleRegisters(0x10, 2);
+        assert_eq!(rsp.address(), Some(0x10));
+        assert_eq!(rsp.function_code(), FunctionCode::WriteMultipleRegisters);
+
+        let pdu = ResponsePdu(Ok(rsp));
+        assert!(!pdu.is_exception());
+        assert_eq!(pdu.ok(), Some(rsp));
+        assert_eq!(pdu.exception(), None);
+        assert_eq!(pdu.function_code(), FunctionCode::WriteMultipleRegisters);
+
+        let ex = ExceptionResponse::new(FunctionCode::ReadCoils, Exception::IllegalDataAddress);
+        let pdu = ResponsePdu(Err(ex));
+        assert!(pdu.is_exception());
+        assert_eq!(pdu.ok(), None);
+        assert_eq!(pdu.exception(), Some(ex));
+        assert_eq!(pdu.function_code(), FunctionCode::ReadCoils);
+    }
+
     #[test]
     fn function_code_from_request() {
         use Request::*;
@@ -438,7 +932,16 @@ mod tests {
                 ),
                 0x17,
             ),
-            (Custom(FunctionCode::Custom(88), &[]), 88),
+            (ReadFifoQueue(0), 0x18),
+            (ReadFileRecord(FileRecordRequests { data: &[] }), 0x14),
+            (WriteFileRecord(FileRecords { data: &[] }), 0x15),
+            (
+                Custom(RawPdu {
+                    function: FunctionCode::Custom(88),
+                    data: &[],
+                }),
+                88,
+            ),
         ];
         for (req, expected) in requests {
             let code: u8 = FunctionCode::from(*req).value();
@@ -464,7 +967,7 @@ mod tests {
                 }),
                 2,
             ),
-            (WriteSingleCoil(0x0), 5),
+            (WriteSingleCoil(0x0, true), 5),
             (WriteMultipleCoils(0x0, 0x0), 0x0F),
             (
                 ReadInputRegisters(Data {
@@ -489,7 +992,22 @@ mod tests {
                 }),
                 0x17,
             ),
-            (Custom(FunctionCode::Custom(99), &[]), 99),
+            (
+                ReadFifoQueue(Data {
+                    quantity: 0,
+                    data: &[],
+                }),
+                0x18,
+            ),
+            (ReadFileRecord(FileRecordData { data: &[] }), 0x14),
+            (WriteFileRecord(FileRecords { data: &[] }), 0x15),
+            (
+                Custom(RawPdu {
+                    function: FunctionCode::Custom(99),
+                    data: &[],
+                }),
+                99,
+            ),
         ];
         for (req, expected) in responses {
             let code: u8 = FunctionCode::from(*req).value();
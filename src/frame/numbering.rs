@@ -0,0 +1,212 @@
+//! Conventions of datasheets to number registers.
+
+use super::*;
+use crate::server::Table;
+
+/// A convention to number the registers of a device.
+///
+/// The protocol addresses start at `0`, but datasheets often
+/// document register numbers starting at `1`, possibly prefixed
+/// with the digit of the table, e.g. `40001` for the holding
+/// register with the address `0`.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{server::Table, Numbering};
+///
+/// let table = Table::HoldingRegisters;
+/// assert_eq!(Numbering::FiveDigit.address(table, 40001), Some(0));
+/// assert_eq!(Numbering::FiveDigit.address(table, 30001), None);
+/// assert_eq!(Numbering::OneBased.address(table, 100), Some(99));
+/// assert_eq!(Numbering::SixDigit.number(table, 0x1000), Some(404097));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Numbering {
+    /// The protocol addresses, starting at `0`.
+    #[default]
+    ZeroBased,
+    /// Numbers starting at `1`.
+    OneBased,
+    /// Numbers starting at `1` with five digits, the first
+    /// digit denotes the table (up to `9999` registers).
+    FiveDigit,
+    /// Numbers starting at `1` with six digits, the first
+    /// digit denotes the table (up to `65536` registers).
+    SixDigit,
+}
+
+impl Numbering {
+    /// The protocol address of the register `number` of `table`.
+    ///
+    /// Returns `None` if the number is out of range or
+    /// its prefix does not denote `table`.
+    #[must_use]
+    pub const fn address(self, table: Table, number: u32) -> Option<Address> {
+        let offset = match self.prefix_base() {
+            None => number,
+            Some(base) => {
+                if number / base != table_digit(table) {
+                    return None;
+                }
+                number % base
+            }
+        };
+        let offset = if matches!(self, Self::ZeroBased) {
+            offset
+        } else if offset == 0 {
+            return None;
+        } else {
+            offset - 1
+        };
+        if offset > self.max_address() as u32 {
+            return None;
+        }
+        Some(offset as Address)
+    }
+
+    /// The number of the register with the protocol `address` in `table`.
+    ///
+    /// Returns `None` if the address cannot be represented,
+    /// e.g. with more than four digits after the table prefix.
+    #[must_use]
+    pub const fn number(self, table: Table, address: Address) -> Option<u32> {
+        if address > self.max_address() {
+            return None;
+        }
+        let number = match self {
+            Self::ZeroBased => address as u32,
+            _ => address as u32 + 1,
+        };
+        Some(match self.prefix_base() {
+            None => number,
+            Some(base) => table_digit(table) * base + number,
+        })
+    }
+
+    const fn prefix_base(self) -> Option<u32> {
+        match self {
+            Self::ZeroBased | Self::OneBased => None,
+            Self::FiveDigit => Some(10_000),
+            Self::SixDigit => Some(100_000),
+        }
+    }
+
+    const fn max_address(self) -> Address {
+        match self {
+            Self::FiveDigit => 9998,
+            _ => Address::MAX,
+        }
+    }
+}
+
+/// The digit that denotes `table` in prefixed numbers.
+const fn table_digit(table: Table) -> u32 {
+    match table {
+        Table::Coils => 0,
+        Table::DiscreteInputs => 1,
+        Table::InputRegisters => 3,
+        Table::HoldingRegisters => 4,
+    }
+}
+
+/// A register of a table, e.g. as documented by `40001`.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{server::Table, Reference};
+///
+/// let reference = Reference::parse("300010").unwrap();
+/// assert_eq!(reference.table, Table::InputRegisters);
+/// assert_eq!(reference.address, 9);
+/// assert_eq!(Reference::parse("00001").unwrap().table, Table::Coils);
+/// assert_eq!(reference.to_string(), "30010");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Reference {
+    pub table: Table,
+    pub address: Address,
+}
+
+impl Reference {
+    /// Parse a table prefixed number with five or six digits.
+    ///
+    /// The number of digits, including leading zeros,
+    /// decides about the convention.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let numbering = match s.len() {
+            5 => Numbering::FiveDigit,
+            6 => Numbering::SixDigit,
+            _ => return None,
+        };
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let number = s.parse().ok()?;
+        let base = numbering.prefix_base()?;
+        let table = match number / base {
+            0 => Table::Coils,
+            1 => Table::DiscreteInputs,
+            3 => Table::InputRegisters,
+            4 => Table::HoldingRegisters,
+            _ => return None,
+        };
+        let address = numbering.address(table, number)?;
+        Some(Self { table, address })
+    }
+}
+
+/// Formatted with five digits if possible, otherwise with six digits.
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(number) = Numbering::FiveDigit.number(self.table, self.address) {
+            return write!(f, "{number:05}");
+        }
+        let number = Numbering::SixDigit
+            .number(self.table, self.address)
+            .unwrap_or_default();
+        write!(f, "{number:06}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_numbers() {
+        let table = Table::InputRegisters;
+        assert_eq!(Numbering::ZeroBased.address(table, 0xFFFF), Some(0xFFFF));
+        assert_eq!(Numbering::ZeroBased.address(table, 0x10000), None);
+        assert_eq!(Numbering::OneBased.address(table, 0), None);
+        assert_eq!(Numbering::OneBased.address(table, 0x10000), Some(0xFFFF));
+        assert_eq!(Numbering::FiveDigit.address(table, 39999), Some(9998));
+        assert_eq!(Numbering::FiveDigit.address(table, 30000), None);
+        assert_eq!(Numbering::SixDigit.address(table, 365_536), Some(0xFFFF));
+        assert_eq!(Numbering::SixDigit.address(table, 365_537), None);
+
+        assert_eq!(Numbering::OneBased.number(table, 0xFFFF), Some(0x10000));
+        assert_eq!(Numbering::FiveDigit.number(table, 9998), Some(39999));
+        assert_eq!(Numbering::FiveDigit.number(table, 9999), None);
+        assert_eq!(Numbering::FiveDigit.number(Table::Coils, 0), Some(1));
+    }
+
+    #[test]
+    fn parse_references() {
+        let coil = Reference {
+            table: Table::Coils,
+            address: 0x1234,
+        };
+        assert_eq!(Reference::parse("004661"), Some(coil));
+        assert_eq!(Reference::parse("04661"), Some(coil));
+        assert_eq!(Reference::parse("4661"), None);
+        assert_eq!(Reference::parse("20001"), None);
+        assert_eq!(Reference::parse("+0001"), None);
+        assert_eq!(Reference::parse("40000"), None);
+        assert_eq!(Reference::parse("465536").unwrap().address, 0xFFFF);
+    }
+}
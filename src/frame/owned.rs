@@ -0,0 +1,489 @@
+//! Frames that own their payload and can therefore be stored or queued.
+
+use super::*;
+use crate::error::Error;
+use alloc::vec::Vec;
+
+/// Owned variant of [`Data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataOwned {
+    data: Vec<u8>,
+    quantity: usize,
+}
+
+impl DataOwned {
+    /// Borrow the words.
+    #[must_use]
+    pub fn as_ref(&self) -> Data<'_> {
+        Data {
+            data: &self.data,
+            quantity: self.quantity,
+        }
+    }
+}
+
+impl TryFrom<&[Word]> for DataOwned {
+    type Error = Error;
+
+    /// Pack `words`, fails if there are none.
+    fn try_from(words: &[Word]) -> Result<Self, Self::Error> {
+        let mut data = alloc::vec![0; words.len() * 2];
+        Data::from_words(words, &mut data)?;
+        Ok(Self {
+            data,
+            quantity: words.len(),
+        })
+    }
+}
+
+impl Data<'_> {
+    /// Copy the words.
+    #[must_use]
+    pub fn into_owned(self) -> DataOwned {
+        DataOwned {
+            data: self.data.to_vec(),
+            quantity: self.quantity,
+        }
+    }
+
+    /// Collect the words.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<Word> {
+        self.words().collect()
+    }
+}
+
+/// Owned variant of [`Coils`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoilsOwned {
+    data: Vec<u8>,
+    quantity: usize,
+}
+
+impl CoilsOwned {
+    /// Borrow the coils.
+    #[must_use]
+    pub fn as_ref(&self) -> Coils<'_> {
+        Coils {
+            data: &self.data,
+            quantity: self.quantity,
+        }
+    }
+}
+
+impl TryFrom<&[Coil]> for CoilsOwned {
+    type Error = Error;
+
+    /// Pack `coils`, fails if there are none.
+    fn try_from(coils: &[Coil]) -> Result<Self, Self::Error> {
+        let mut data = alloc::vec![0; packed_coils_len(coils.len())];
+        Coils::from_bools(coils, &mut data)?;
+        Ok(Self {
+            data,
+            quantity: coils.len(),
+        })
+    }
+}
+
+impl Coils<'_> {
+    /// Copy the coils.
+    #[must_use]
+    pub fn into_owned(self) -> CoilsOwned {
+        CoilsOwned {
+            data: self.data.to_vec(),
+            quantity: self.quantity,
+        }
+    }
+
+    /// Unpack the coils.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<Coil> {
+        self.into_iter().collect()
+    }
+}
+
+/// Owned variant of [`FileRecordRequests`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRecordRequestsOwned {
+    data: Vec<u8>,
+}
+
+impl FileRecordRequestsOwned {
+    /// Borrow the sub-requests.
+    #[must_use]
+    pub fn as_ref(&self) -> FileRecordRequests<'_> {
+        FileRecordRequests { data: &self.data }
+    }
+}
+
+impl FileRecordRequests<'_> {
+    /// Copy the sub-requests.
+    #[must_use]
+    pub fn into_owned(self) -> FileRecordRequestsOwned {
+        FileRecordRequestsOwned {
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+/// Owned variant of [`FileRecords`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRecordsOwned {
+    data: Vec<u8>,
+}
+
+impl FileRecordsOwned {
+    /// Borrow the records.
+    #[must_use]
+    pub fn as_ref(&self) -> FileRecords<'_> {
+        FileRecords { data: &self.data }
+    }
+}
+
+impl FileRecords<'_> {
+    /// Copy the records.
+    #[must_use]
+    pub fn into_owned(self) -> FileRecordsOwned {
+        FileRecordsOwned {
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+/// Owned variant of [`FileRecordData`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRecordDataOwned {
+    data: Vec<u8>,
+}
+
+impl FileRecordDataOwned {
+    /// Borrow the sub-responses.
+    #[must_use]
+    pub fn as_ref(&self) -> FileRecordData<'_> {
+        FileRecordData { data: &self.data }
+    }
+}
+
+impl FileRecordData<'_> {
+    /// Copy the sub-responses.
+    #[must_use]
+    pub fn into_owned(self) -> FileRecordDataOwned {
+        FileRecordDataOwned {
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+/// Owned variant of [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestOwned {
+    ReadCoils(Address, Quantity),
+    ReadDiscreteInputs(Address, Quantity),
+    WriteSingleCoil(Address, Coil),
+    WriteMultipleCoils(Address, CoilsOwned),
+    ReadInputRegisters(Address, Quantity),
+    ReadHoldingRegisters(Address, Quantity),
+    WriteSingleRegister(Address, Word),
+    WriteMultipleRegisters(Address, DataOwned),
+    ReadWriteMultipleRegisters(Address, Quantity, Address, DataOwned),
+    ReadFifoQueue(Address),
+    ReadFileRecord(FileRecordRequestsOwned),
+    WriteFileRecord(FileRecordsOwned),
+    #[cfg(feature = "rtu")]
+    ReadExceptionStatus,
+    #[cfg(feature = "rtu")]
+    Diagnostics(SubFunctionCode, DataOwned),
+    #[cfg(feature = "rtu")]
+    GetCommEventCounter,
+    #[cfg(feature = "rtu")]
+    GetCommEventLog,
+    #[cfg(feature = "rtu")]
+    ReportServerId,
+    Custom(FunctionCode, Vec<u8>),
+}
+
+impl RequestOwned {
+    /// Borrow the request, e.g. to encode it.
+    #[must_use]
+    pub fn as_ref(&self) -> Request<'_> {
+        use Request as R;
+
+        match self {
+            Self::ReadCoils(address, quantity) => R::ReadCoils(*address, *quantity),
+            Self::ReadDiscreteInputs(address, quantity) => {
+                R::ReadDiscreteInputs(*address, *quantity)
+            }
+            Self::WriteSingleCoil(address, coil) => R::WriteSingleCoil(*address, *coil),
+            Self::WriteMultipleCoils(address, coils) => {
+                R::WriteMultipleCoils(*address, coils.as_ref())
+            }
+            Self::ReadInputRegisters(address, quantity) => {
+                R::ReadInputRegisters(*address, *quantity)
+            }
+            Self::ReadHoldingRegisters(address, quantity) => {
+                R::ReadHoldingRegisters(*address, *quantity)
+            }
+            Self::WriteSingleRegister(address, word) => R::WriteSingleRegister(*address, *word),
+            Self::WriteMultipleRegisters(address, words) => {
+                R::WriteMultipleRegisters(*address, words.as_ref())
+            }
+            Self::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => {
+                R::ReadWriteMultipleRegisters(
+                    *read_address,
+                    *quantity,
+                    *write_address,
+                    words.as_ref(),
+                )
+            }
+            Self::ReadFifoQueue(address) => R::ReadFifoQueue(*address),
+            Self::ReadFileRecord(requests) => R::ReadFileRecord(requests.as_ref()),
+            Self::WriteFileRecord(records) => R::WriteFileRecord(records.as_ref()),
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus => R::ReadExceptionStatus,
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(sub_function, words) => R::Diagnostics(*sub_function, words.as_ref()),
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter => R::GetCommEventCounter,
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog => R::GetCommEventLog,
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId => R::ReportServerId,
+            Self::Custom(function, data) => R::Custom(RawPdu {
+                function: *function,
+                data,
+            }),
+        }
+    }
+}
+
+impl Request<'_> {
+    /// Copy the request including its payload.
+    #[must_use]
+    pub fn into_owned(self) -> RequestOwned {
+        use RequestOwned as R;
+
+        match self {
+            Self::ReadCoils(address, quantity) => R::ReadCoils(address, quantity),
+            Self::ReadDiscreteInputs(address, quantity) => R::ReadDiscreteInputs(address, quantity),
+            Self::WriteSingleCoil(address, coil) => R::WriteSingleCoil(address, coil),
+            Self::WriteMultipleCoils(address, coils) => {
+                R::WriteMultipleCoils(address, coils.into_owned())
+            }
+            Self::ReadInputRegisters(address, quantity) => R::ReadInputRegisters(address, quantity),
+            Self::ReadHoldingRegisters(address, quantity) => {
+                R::ReadHoldingRegisters(address, quantity)
+            }
+            Self::WriteSingleRegister(address, word) => R::WriteSingleRegister(address, word),
+            Self::WriteMultipleRegisters(address, words) => {
+                R::WriteMultipleRegisters(address, words.into_owned())
+            }
+            Self::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => {
+                R::ReadWriteMultipleRegisters(
+                    read_address,
+                    quantity,
+                    write_address,
+                    words.into_owned(),
+                )
+            }
+            Self::ReadFifoQueue(address) => R::ReadFifoQueue(address),
+            Self::ReadFileRecord(requests) => R::ReadFileRecord(requests.into_owned()),
+            Self::WriteFileRecord(records) => R::WriteFileRecord(records.into_owned()),
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus => R::ReadExceptionStatus,
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(sub_function, words) => {
+                R::Diagnostics(sub_function, words.into_owned())
+            }
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter => R::GetCommEventCounter,
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog => R::GetCommEventLog,
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId => R::ReportServerId,
+            Self::Custom(RawPdu { function, data }) => R::Custom(function, data.to_vec()),
+        }
+    }
+}
+
+impl From<Request<'_>> for RequestOwned {
+    fn from(req: Request<'_>) -> Self {
+        req.into_owned()
+    }
+}
+
+/// Owned variant of [`Response`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseOwned {
+    ReadCoils(CoilsOwned),
+    ReadDiscreteInputs(CoilsOwned),
+    WriteSingleCoil(Address, Coil),
+    WriteMultipleCoils(Address, Quantity),
+    ReadInputRegisters(DataOwned),
+    ReadHoldingRegisters(DataOwned),
+    WriteSingleRegister(Address, Word),
+    WriteMultipleRegisters(Address, Quantity),
+    ReadWriteMultipleRegisters(DataOwned),
+    ReadFifoQueue(DataOwned),
+    ReadFileRecord(FileRecordDataOwned),
+    WriteFileRecord(FileRecordsOwned),
+    #[cfg(feature = "rtu")]
+    ReadExceptionStatus(u8),
+    #[cfg(feature = "rtu")]
+    Diagnostics(SubFunctionCode, DataOwned),
+    #[cfg(feature = "rtu")]
+    GetCommEventCounter(Status, EventCount),
+    #[cfg(feature = "rtu")]
+    GetCommEventLog(Status, EventCount, MessageCount, Vec<u8>),
+    #[cfg(feature = "rtu")]
+    ReportServerId(Vec<u8>, bool),
+    Custom(FunctionCode, Vec<u8>),
+}
+
+impl ResponseOwned {
+    /// Borrow the response, e.g. to encode it.
+    #[must_use]
+    pub fn as_ref(&self) -> Response<'_> {
+        use Response as R;
+
+        match self {
+            Self::ReadCoils(coils) => R::ReadCoils(coils.as_ref()),
+            Self::ReadDiscreteInputs(coils) => R::ReadDiscreteInputs(coils.as_ref()),
+            Self::WriteSingleCoil(address, coil) => R::WriteSingleCoil(*address, *coil),
+            Self::WriteMultipleCoils(address, quantity) => {
+                R::WriteMultipleCoils(*address, *quantity)
+            }
+            Self::ReadInputRegisters(words) => R::ReadInputRegisters(words.as_ref()),
+            Self::ReadHoldingRegisters(words) => R::ReadHoldingRegisters(words.as_ref()),
+            Self::WriteSingleRegister(address, word) => R::WriteSingleRegister(*address, *word),
+            Self::WriteMultipleRegisters(address, quantity) => {
+                R::WriteMultipleRegisters(*address, *quantity)
+            }
+            Self::ReadWriteMultipleRegisters(words) => {
+                R::ReadWriteMultipleRegisters(words.as_ref())
+            }
+            Self::ReadFifoQueue(words) => R::ReadFifoQueue(words.as_ref()),
+            Self::ReadFileRecord(data) => R::ReadFileRecord(data.as_ref()),
+            Self::WriteFileRecord(records) => R::WriteFileRecord(records.as_ref()),
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus(status) => R::ReadExceptionStatus(*status),
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(sub_function, words) => R::Diagnostics(*sub_function, words.as_ref()),
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter(status, events) => R::GetCommEventCounter(*status, *events),
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog(status, events, messages, log) => {
+                R::GetCommEventLog(*status, *events, *messages, log)
+            }
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId(id, running) => R::ReportServerId(id, *running),
+            Self::Custom(function, data) => R::Custom(RawPdu {
+                function: *function,
+                data,
+            }),
+        }
+    }
+}
+
+impl Response<'_> {
+    /// Copy the response including its payload.
+    #[must_use]
+    pub fn into_owned(self) -> ResponseOwned {
+        use ResponseOwned as R;
+
+        match self {
+            Self::ReadCoils(coils) => R::ReadCoils(coils.into_owned()),
+            Self::ReadDiscreteInputs(coils) => R::ReadDiscreteInputs(coils.into_owned()),
+            Self::WriteSingleCoil(address, coil) => R::WriteSingleCoil(address, coil),
+            Self::WriteMultipleCoils(address, quantity) => R::WriteMultipleCoils(address, quantity),
+            Self::ReadInputRegisters(words) => R::ReadInputRegisters(words.into_owned()),
+            Self::ReadHoldingRegisters(words) => R::ReadHoldingRegisters(words.into_owned()),
+            Self::WriteSingleRegister(address, word) => R::WriteSingleRegister(address, word),
+            Self::WriteMultipleRegisters(address, quantity) => {
+                R::WriteMultipleRegisters(address, quantity)
+            }
+            Self::ReadWriteMultipleRegisters(words) => {
+                R::ReadWriteMultipleRegisters(words.into_owned())
+            }
+            Self::ReadFifoQueue(words) => R::ReadFifoQueue(words.into_owned()),
+            Self::ReadFileRecord(data) => R::ReadFileRecord(data.into_owned()),
+            Self::WriteFileRecord(records) => R::WriteFileRecord(records.into_owned()),
+            #[cfg(feature = "rtu")]
+            Self::ReadExceptionStatus(status) => R::ReadExceptionStatus(status),
+            #[cfg(feature = "rtu")]
+            Self::Diagnostics(sub_function, words) => {
+                R::Diagnostics(sub_function, words.into_owned())
+            }
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventCounter(status, events) => R::GetCommEventCounter(status, events),
+            #[cfg(feature = "rtu")]
+            Self::GetCommEventLog(status, events, messages, log) => {
+                R::GetCommEventLog(status, events, messages, log.to_vec())
+            }
+            #[cfg(feature = "rtu")]
+            Self::ReportServerId(id, running) => R::ReportServerId(id.to_vec(), running),
+            Self::Custom(RawPdu { function, data }) => R::Custom(function, data.to_vec()),
+        }
+    }
+}
+
+impl From<Response<'_>> for ResponseOwned {
+    fn from(rsp: Response<'_>) -> Self {
+        rsp.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encode, EncodeError};
+
+    #[test]
+    fn request_outlives_buffer() {
+        let owned = {
+            let bytes = [0x10, 0x00, 0x20, 0x00, 0x02, 0x04, 0x12, 0x34, 0x56, 0x78];
+            Request::try_from(&bytes[..]).unwrap().into_owned()
+        };
+        let Request::WriteMultipleRegisters(address, words) = owned.as_ref() else {
+            panic!("unexpected request");
+        };
+        assert_eq!(address, 0x20);
+        assert_eq!(words.get(1), Some(0x5678));
+        assert_eq!(RequestOwned::from(owned.as_ref()), owned);
+    }
+
+    #[test]
+    fn convert_coils_and_words() {
+        let coils = CoilsOwned::try_from(&[true, false, true][..]).unwrap();
+        assert_eq!(coils.as_ref().packed_len(), 1);
+        assert_eq!(coils.as_ref().to_vec(), [true, false, true]);
+        assert_eq!(CoilsOwned::try_from(&[][..]), Err(Error::BufferSize));
+
+        let words = DataOwned::try_from(&[0x1234, 0x5678][..]).unwrap();
+        assert_eq!(words.as_ref().to_vec(), [0x1234, 0x5678]);
+        assert_eq!(DataOwned::try_from(&[][..]), Err(Error::BufferSize));
+    }
+
+    #[test]
+    fn response_roundtrip() {
+        let bytes: &[u8] = &[0x01, 0x01, 0b101];
+        let rsp = Response::try_from(bytes).unwrap();
+        assert_eq!(rsp.into_owned().as_ref(), rsp);
+        let rsp = Response::Custom(RawPdu {
+            function: FunctionCode::Custom(0x42),
+            data: &[1, 2, 3],
+        });
+        assert_eq!(
+            rsp.into_owned(),
+            ResponseOwned::Custom(FunctionCode::Custom(0x42), [1, 2, 3].to_vec())
+        );
+    }
+
+    #[test]
+    fn reject_oversized_custom_payload() {
+        let rsp = ResponseOwned::Custom(FunctionCode::Custom(0x42), alloc::vec![0; 253]);
+        assert_eq!(
+            rsp.as_ref().encode(&mut [0; 300]),
+            Err(EncodeError::BufferSize)
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use super::*;
+
+/// A non-empty, contiguous range of Modbus addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AddressRange {
+    start: Address,
+    end: Address,
+}
+
+impl AddressRange {
+    /// Create a range of `quantity` addresses beginning at `start`.
+    ///
+    /// Returns `None` if `quantity` is zero or if the range
+    /// would exceed the highest address (`65535`).
+    #[must_use]
+    pub const fn new(start: Address, quantity: Quantity) -> Option<Self> {
+        if quantity == 0 {
+            return None;
+        }
+        match start.checked_add(quantity - 1) {
+            Some(end) => Some(Self { start, end }),
+            None => None,
+        }
+    }
+
+    /// The first address of the range.
+    #[must_use]
+    pub const fn start(&self) -> Address {
+        self.start
+    }
+
+    /// The last address of the range (inclusive).
+    #[must_use]
+    pub const fn end(&self) -> Address {
+        self.end
+    }
+
+    /// Number of addresses within the range.
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)] // ranges are never empty
+    pub const fn len(&self) -> usize {
+        (self.end - self.start) as usize + 1
+    }
+
+    /// Returns `true` if `address` is part of the range.
+    #[must_use]
+    pub const fn contains(&self, address: Address) -> bool {
+        self.start <= address && address <= self.end
+    }
+
+    /// Returns `true` if all addresses of `other` are part of the range.
+    #[must_use]
+    pub const fn contains_range(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Returns `true` if both ranges have at least one address in common.
+    #[must_use]
+    pub const fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_range() {
+        assert!(AddressRange::new(0, 0).is_none());
+        assert!(AddressRange::new(0xFFFF, 2).is_none());
+        let range = AddressRange::new(0xFFFF, 1).unwrap();
+        assert_eq!(range.start(), 0xFFFF);
+        assert_eq!(range.end(), 0xFFFF);
+        assert_eq!(range.len(), 1);
+        let range = AddressRange::new(0, 0xFFFF).unwrap();
+        assert_eq!(range.end(), 0xFFFE);
+        assert_eq!(range.len(), 0xFFFF);
+    }
+
+    #[test]
+    fn contains() {
+        let range = AddressRange::new(10, 5).unwrap();
+        assert!(!range.contains(9));
+        assert!(range.contains(10));
+        assert!(range.contains(14));
+        assert!(!range.contains(15));
+        assert!(range.contains_range(&AddressRange::new(12, 3).unwrap()));
+        assert!(!range.contains_range(&AddressRange::new(12, 4).unwrap()));
+        assert!(range.overlaps(&AddressRange::new(14, 4).unwrap()));
+        assert!(!range.overlaps(&AddressRange::new(15, 4).unwrap()));
+    }
+}
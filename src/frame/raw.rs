@@ -0,0 +1,148 @@
+use super::*;
+use crate::error::Error;
+
+/// The function code and payload of a PDU that is not
+/// interpreted by this crate, e.g. of a vendor specific function.
+///
+/// The payload fits into a PDU and the function code
+/// does not denote an exception.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{Error, FunctionCode, RawPdu, Request};
+///
+/// let pdu = RawPdu::new(FunctionCode::Custom(0x41), &[0x01, 0x02]).unwrap();
+/// let request = Request::Custom(pdu);
+/// assert_eq!(request.pdu_len(), 3);
+///
+/// assert_eq!(RawPdu::new(FunctionCode::Custom(0xC1), &[]), Err(Error::FnCode(0xC1)));
+/// assert_eq!(RawPdu::new(FunctionCode::Custom(0x41), &[0; 253]), Err(Error::BufferSize));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawPdu<'r> {
+    pub(crate) function: FunctionCode,
+    pub(crate) data: &'r [u8],
+}
+
+impl<'r> RawPdu<'r> {
+    /// Maximum length of the payload, the PDU without the function code.
+    pub const MAX_DATA_LEN: usize = MAX_PDU_LEN - 1;
+
+    /// Check and wrap the payload `data` of `function`.
+    pub const fn new(function: FunctionCode, data: &'r [u8]) -> Result<Self, Error> {
+        if function.value() >= 0x80 {
+            return Err(Error::FnCode(function.value()));
+        }
+        if data.len() > Self::MAX_DATA_LEN {
+            return Err(Error::BufferSize);
+        }
+        Ok(Self { function, data })
+    }
+
+    #[must_use]
+    pub const fn function(&self) -> FunctionCode {
+        self.function
+    }
+
+    /// The payload without the function code.
+    #[must_use]
+    pub const fn data(&self) -> &'r [u8] {
+        self.data
+    }
+}
+
+/// Builds the payload of a [`RawPdu`] in a buffer.
+///
+/// Every write fails with [`Error::BufferSize`] if it exceeds the
+/// buffer or [`RawPdu::MAX_DATA_LEN`], so a payload is never truncated.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{FunctionCode, RawPduWriter};
+///
+/// let buf = &mut [0; 8];
+/// let mut writer = RawPduWriter::new(buf);
+/// writer.u8(0x01)?.u16(0x1234)?.bytes(b"ok")?;
+/// let pdu = writer.finish(FunctionCode::Custom(0x41))?;
+/// assert_eq!(pdu.data(), &[0x01, 0x12, 0x34, b'o', b'k']);
+/// # Ok::<(), modbus_core::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct RawPduWriter<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> RawPduWriter<'b> {
+    pub fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// The number of bytes that have been written.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn u8(&mut self, value: u8) -> Result<&mut Self, Error> {
+        self.bytes(&[value])
+    }
+
+    /// Append `value` in big-endian byte order.
+    pub fn u16(&mut self, value: u16) -> Result<&mut Self, Error> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<&mut Self, Error> {
+        let end = self.len + bytes.len();
+        if end > RawPdu::MAX_DATA_LEN {
+            return Err(Error::BufferSize);
+        }
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(Error::BufferSize)?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(self)
+    }
+
+    /// The PDU of `function` with the written payload.
+    pub fn finish(self, function: FunctionCode) -> Result<RawPdu<'b>, Error> {
+        RawPdu::new(function, &self.buf[..self.len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_payload() {
+        let buf = &mut [0xFF; 4];
+        let mut writer = RawPduWriter::new(buf);
+        assert!(writer.is_empty());
+        writer.u16(0xABCD).unwrap().u8(0x01).unwrap();
+        assert_eq!(writer.bytes(&[1, 2]).unwrap_err(), Error::BufferSize);
+        assert_eq!(writer.len(), 3);
+        let pdu = writer.finish(FunctionCode::Custom(0x65)).unwrap();
+        assert_eq!(pdu.function(), FunctionCode::Custom(0x65));
+        assert_eq!(pdu.data(), &[0xAB, 0xCD, 0x01]);
+    }
+
+    #[test]
+    fn limit_payload_len() {
+        let buf = &mut [0; 300];
+        let mut writer = RawPduWriter::new(buf);
+        writer.bytes(&[0; RawPdu::MAX_DATA_LEN]).unwrap();
+        assert_eq!(writer.u8(0).unwrap_err(), Error::BufferSize);
+        assert!(writer.finish(FunctionCode::new(0x90)).is_err());
+    }
+}
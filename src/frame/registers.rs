@@ -0,0 +1,275 @@
+//! Conversion of values from and into registers.
+
+use core::fmt;
+
+use super::*;
+
+/// A value that is decoded from one or more consecutive registers.
+pub trait FromRegisters: Sized {
+    /// The number of registers.
+    const WORDS: usize;
+
+    /// Convert `words`, the most significant word first.
+    ///
+    /// `words` contains exactly [`Self::WORDS`] registers.
+    /// Returns `None` if they do not contain a valid value.
+    fn from_registers(words: &[Word]) -> Option<Self>;
+}
+
+/// A value that is encoded into one or more consecutive registers.
+pub trait ToRegisters {
+    /// The number of registers.
+    const WORDS: usize;
+
+    /// Convert the value into `words`, the most significant word first.
+    ///
+    /// `words` has room for exactly [`Self::WORDS`] registers.
+    fn to_registers(&self, words: &mut [Word]);
+}
+
+macro_rules! impl_registers {
+    ($($ty:ty),*) => {
+        $(
+            impl FromRegisters for $ty {
+                const WORDS: usize = core::mem::size_of::<$ty>() / 2;
+
+                fn from_registers(words: &[Word]) -> Option<Self> {
+                    let mut bytes = [0; core::mem::size_of::<$ty>()];
+                    for (chunk, word) in bytes.chunks_exact_mut(2).zip(words) {
+                        write_u16(chunk, *word);
+                    }
+                    Some(<$ty>::from_be_bytes(bytes))
+                }
+            }
+
+            impl ToRegisters for $ty {
+                const WORDS: usize = core::mem::size_of::<$ty>() / 2;
+
+                fn to_registers(&self, words: &mut [Word]) {
+                    let bytes = self.to_be_bytes();
+                    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(2)) {
+                        *word = read_u16(chunk);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_registers!(u16, i16, u32, i32, u64, i64, f32, f64);
+
+/// Consecutive values, the first value in the first registers.
+impl<T, const N: usize> FromRegisters for [T; N]
+where
+    T: FromRegisters + Copy + Default,
+{
+    const WORDS: usize = T::WORDS * N;
+
+    fn from_registers(words: &[Word]) -> Option<Self> {
+        let mut values = [T::default(); N];
+        for (value, words) in values.iter_mut().zip(words.chunks_exact(T::WORDS)) {
+            *value = T::from_registers(words)?;
+        }
+        Some(values)
+    }
+}
+
+impl<T, const N: usize> ToRegisters for [T; N]
+where
+    T: ToRegisters,
+{
+    const WORDS: usize = T::WORDS * N;
+
+    fn to_registers(&self, words: &mut [Word]) {
+        for (value, words) in self.iter().zip(words.chunks_exact_mut(T::WORDS)) {
+            value.to_registers(words);
+        }
+    }
+}
+
+/// Bits packed into registers, the first bit in the least
+/// significant bit of the first register.
+impl<const N: usize> FromRegisters for [bool; N] {
+    const WORDS: usize = (N + 15) / 16;
+
+    fn from_registers(words: &[Word]) -> Option<Self> {
+        let mut bits = [false; N];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            *bit = words[i / 16] & (1 << (i % 16)) != 0;
+        }
+        Some(bits)
+    }
+}
+
+impl<const N: usize> ToRegisters for [bool; N] {
+    const WORDS: usize = (N + 15) / 16;
+
+    fn to_registers(&self, words: &mut [Word]) {
+        words.fill(0);
+        for (i, _) in self.iter().enumerate().filter(|(_, bit)| **bit) {
+            words[i / 16] |= 1 << (i % 16);
+        }
+    }
+}
+
+/// A string of up to `N` bytes that is stored in registers.
+///
+/// Each register holds two bytes, the first byte in the high byte.
+/// Unused bytes are filled with zeros.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{FromRegisters, RegisterString, ToRegisters};
+///
+/// let name = RegisterString::<6>::new("PV1").unwrap();
+/// let words = &mut [0; 3];
+/// name.to_registers(words);
+/// assert_eq!(words, &[0x5056, 0x3100, 0x0000]);
+/// assert_eq!(RegisterString::<6>::from_registers(words), Some(name));
+/// assert_eq!(name.as_str(), "PV1");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RegisterString<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> RegisterString<N> {
+    /// Returns `None` if `s` is longer than `N` bytes or contains a zero byte.
+    #[must_use]
+    pub fn new(s: &str) -> Option<Self> {
+        if s.len() > N || s.bytes().any(|b| b == 0) {
+            return None;
+        }
+        let mut bytes = [0; N];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Some(Self {
+            bytes,
+            len: s.len(),
+        })
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // The bytes have been validated on creation
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl<const N: usize> Default for RegisterString<N> {
+    fn default() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for RegisterString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for RegisterString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const N: usize> defmt::Format for RegisterString<N> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=str}", self.as_str());
+    }
+}
+
+/// Invalid UTF-8 is rejected.
+impl<const N: usize> FromRegisters for RegisterString<N> {
+    const WORDS: usize = (N + 1) / 2;
+
+    fn from_registers(words: &[Word]) -> Option<Self> {
+        let mut bytes = [0; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = words[i / 2].to_be_bytes()[i % 2];
+        }
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(N);
+        bytes[len..].fill(0);
+        core::str::from_utf8(&bytes[..len]).ok()?;
+        Some(Self { bytes, len })
+    }
+}
+
+impl<const N: usize> ToRegisters for RegisterString<N> {
+    const WORDS: usize = (N + 1) / 2;
+
+    fn to_registers(&self, words: &mut [Word]) {
+        words.fill(0);
+        for (i, byte) in self.bytes[..self.len].iter().enumerate() {
+            words[i / 2] |= u16::from(*byte) << (8 * (1 - i % 2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_numbers() {
+        let words = &mut [0; 4];
+        (-2_i32).to_registers(&mut words[..2]);
+        assert_eq!(words[..2], [0xFFFF, 0xFFFE]);
+        assert_eq!(i32::from_registers(&words[..2]), Some(-2));
+        1.0_f32.to_registers(&mut words[..2]);
+        assert_eq!(words[..2], [0x3F80, 0x0000]);
+        0x0123_4567_89AB_CDEF_u64.to_registers(words);
+        assert_eq!(words, &[0x0123, 0x4567, 0x89AB, 0xCDEF]);
+        assert_eq!(u16::from_registers(&[0x1234]), Some(0x1234));
+        assert_eq!(<f64 as FromRegisters>::WORDS, 4);
+    }
+
+    #[test]
+    fn convert_arrays() {
+        let words = &mut [0; 4];
+        [0x1234_5678_u32, 0x9ABC_DEF0].to_registers(words);
+        assert_eq!(words, &[0x1234, 0x5678, 0x9ABC, 0xDEF0]);
+        assert_eq!(
+            <[u32; 2]>::from_registers(words),
+            Some([0x1234_5678, 0x9ABC_DEF0])
+        );
+        assert_eq!(<[i16; 3] as FromRegisters>::WORDS, 3);
+    }
+
+    #[test]
+    fn pack_bits() {
+        let mut bits = [false; 17];
+        bits[0] = true;
+        bits[15] = true;
+        bits[16] = true;
+        let words = &mut [0xFFFF; 2];
+        bits.to_registers(words);
+        assert_eq!(words, &[0x8001, 0x0001]);
+        assert_eq!(<[bool; 17]>::from_registers(words), Some(bits));
+        assert_eq!(<[bool; 16] as ToRegisters>::WORDS, 1);
+    }
+
+    #[test]
+    fn convert_strings() {
+        assert!(RegisterString::<2>::new("abc").is_none());
+        assert!(RegisterString::<4>::new("a\0").is_none());
+        let s = RegisterString::<5>::new("abcde").unwrap();
+        let words = &mut [0xFFFF; 3];
+        s.to_registers(words);
+        assert_eq!(words, &[0x6162, 0x6364, 0x6500]);
+        assert_eq!(RegisterString::<5>::from_registers(words), Some(s));
+        assert_eq!(
+            RegisterString::<4>::from_registers(&[0x6100, 0x6200])
+                .unwrap()
+                .as_str(),
+            "a"
+        );
+        assert_eq!(RegisterString::<2>::from_registers(&[0xFFFE]), None);
+    }
+}
@@ -5,20 +5,101 @@ pub type SlaveId = u8;
 
 /// RTU header
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Header {
     pub slave: SlaveId,
 }
 
+impl AduHeader for Header {
+    fn unit_id(&self) -> u8 {
+        self.slave
+    }
+}
+
+/// The slave ID that addresses all slaves of a serial line.
+pub const BROADCAST: SlaveId = 0;
+
 /// RTU Request ADU
+pub type RequestAdu<'r> = Adu<Header, RequestPdu<'r>>;
+
+/// A request that may be sent to all slaves, see [`BROADCAST`].
+///
+/// Broadcasts are never answered, so only requests that change
+/// data (see [`Category::Write`]) can be broadcast. Other requests
+/// are rejected with their function code.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{rtu::BroadcastRequest, FunctionCode, Request};
+///
+/// assert!(BroadcastRequest::try_from(Request::WriteSingleCoil(0x10, true)).is_ok());
+/// assert_eq!(
+///     BroadcastRequest::try_from(Request::ReadCoils(0x10, 1)),
+///     Err(FunctionCode::ReadCoils)
+/// );
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct RequestAdu<'r> {
-    pub hdr: Header,
-    pub pdu: RequestPdu<'r>,
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BroadcastRequest<'r>(Request<'r>);
+
+impl<'r> BroadcastRequest<'r> {
+    #[must_use]
+    pub const fn request(&self) -> Request<'r> {
+        self.0
+    }
 }
 
-/// RTU Response ADU
+impl<'r> TryFrom<Request<'r>> for BroadcastRequest<'r> {
+    type Error = FunctionCode;
+
+    fn try_from(request: Request<'r>) -> Result<Self, Self::Error> {
+        let function = FunctionCode::from(request);
+        match function.category() {
+            Category::Write => Ok(Self(request)),
+            _ => Err(function),
+        }
+    }
+}
+
+impl<'r> From<BroadcastRequest<'r>> for RequestAdu<'r> {
+    fn from(request: BroadcastRequest<'r>) -> Self {
+        Self {
+            hdr: Header { slave: BROADCAST },
+            pdu: RequestPdu(request.0),
+        }
+    }
+}
+
+/// The length of an encoded request that is never answered.
+///
+/// The client must not wait for a response after sending it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct ResponseAdu<'r> {
-    pub hdr: Header,
-    pub pdu: ResponsePdu<'r>,
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[must_use]
+pub struct Unanswered {
+    pub len: usize,
+}
+
+impl RequestAdu<'_> {
+    /// Number of bytes required for a serialized ADU frame.
+    #[must_use]
+    pub const fn adu_len(&self) -> usize {
+        request_adu_len(&self.pdu.0)
+    }
+}
+
+/// Number of bytes of the RTU ADU with `request`, e.g. to size the encoding buffer.
+#[must_use]
+pub const fn request_adu_len(request: &Request<'_>) -> usize {
+    request.pdu_len() + 3
 }
+
+/// Number of bytes of the RTU ADU with `response`, e.g. to size the encoding buffer.
+#[must_use]
+pub const fn response_adu_len(response: &Response<'_>) -> usize {
+    response.pdu_len() + 3
+}
+
+/// RTU Response ADU
+pub type ResponseAdu<'r> = Adu<Header, ResponsePdu<'r>>;
@@ -4,19 +4,38 @@ pub type TransactionId = u16;
 pub type UnitId = u8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Header {
     pub transaction_id: TransactionId,
     pub unit_id: UnitId,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct RequestAdu<'r> {
-    pub hdr: Header,
-    pub pdu: RequestPdu<'r>,
+impl AduHeader for Header {
+    fn unit_id(&self) -> u8 {
+        self.unit_id
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct ResponseAdu<'r> {
-    pub hdr: Header,
-    pub pdu: ResponsePdu<'r>,
+pub type RequestAdu<'r> = Adu<Header, RequestPdu<'r>>;
+
+impl RequestAdu<'_> {
+    /// Number of bytes required for a serialized ADU frame.
+    #[must_use]
+    pub const fn adu_len(&self) -> usize {
+        request_adu_len(&self.pdu.0)
+    }
+}
+
+/// Number of bytes of the TCP ADU with `request`, e.g. to size the encoding buffer.
+#[must_use]
+pub const fn request_adu_len(request: &Request<'_>) -> usize {
+    request.pdu_len() + MBAP_HEADER_LEN
+}
+
+/// Number of bytes of the TCP ADU with `response`, e.g. to size the encoding buffer.
+#[must_use]
+pub const fn response_adu_len(response: &Response<'_>) -> usize {
+    response.pdu_len() + MBAP_HEADER_LEN
 }
+
+pub type ResponseAdu<'r> = Adu<Header, ResponsePdu<'r>>;
@@ -0,0 +1,194 @@
+use super::*;
+use crate::error::MismatchError;
+
+impl Response<'_> {
+    /// Check that this response answers `request`.
+    ///
+    /// Besides the function code the echoed fields of write responses
+    /// and the number of returned bytes of read responses are compared.
+    pub fn validate_against(&self, request: &Request<'_>) -> Result<(), MismatchError> {
+        use Request as Req;
+        use Response as Rsp;
+
+        let expected = FunctionCode::from(*request);
+        let received = FunctionCode::from(*self);
+        if expected != received {
+            return Err(MismatchError::FnCode { expected, received });
+        }
+        match (*request, *self) {
+            (Req::ReadCoils(_, quantity), Rsp::ReadCoils(coils))
+            | (Req::ReadDiscreteInputs(_, quantity), Rsp::ReadDiscreteInputs(coils)) => {
+                check_byte_count(packed_coils_len(quantity.into()), coils.data.len())
+            }
+            (Req::ReadInputRegisters(_, quantity), Rsp::ReadInputRegisters(words))
+            | (Req::ReadHoldingRegisters(_, quantity), Rsp::ReadHoldingRegisters(words))
+            | (
+                Req::ReadWriteMultipleRegisters(_, quantity, _, _),
+                Rsp::ReadWriteMultipleRegisters(words),
+            ) => check_byte_count(usize::from(quantity) * 2, words.data.len()),
+            (Req::WriteSingleCoil(address, coil), Rsp::WriteSingleCoil(a, c)) => {
+                check_address(address, a)?;
+                check_value(bool_to_u16_coil(coil), bool_to_u16_coil(c))
+            }
+            (Req::WriteMultipleCoils(address, coils), Rsp::WriteMultipleCoils(a, quantity)) => {
+                check_address(address, a)?;
+                check_quantity(coils.len() as Quantity, quantity)
+            }
+            (Req::WriteSingleRegister(address, word), Rsp::WriteSingleRegister(a, w)) => {
+                check_address(address, a)?;
+                check_value(word, w)
+            }
+            (
+                Req::WriteMultipleRegisters(address, words),
+                Rsp::WriteMultipleRegisters(a, quantity),
+            ) => {
+                check_address(address, a)?;
+                check_quantity(words.len() as Quantity, quantity)
+            }
+            (Req::WriteFileRecord(expected), Rsp::WriteFileRecord(received)) => {
+                check_byte_count(expected.data.len(), received.data.len())
+            }
+            #[cfg(feature = "rtu")]
+            (Req::Diagnostics(expected, _), Rsp::Diagnostics(received, _)) => {
+                if expected != received {
+                    return Err(MismatchError::SubFunction { expected, received });
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub(crate) const fn check_address(
+    expected: Address,
+    received: Address,
+) -> Result<(), MismatchError> {
+    if expected != received {
+        return Err(MismatchError::Address { expected, received });
+    }
+    Ok(())
+}
+
+pub(crate) const fn check_quantity(
+    expected: Quantity,
+    received: Quantity,
+) -> Result<(), MismatchError> {
+    if expected != received {
+        return Err(MismatchError::Quantity { expected, received });
+    }
+    Ok(())
+}
+
+pub(crate) const fn check_value(expected: Word, received: Word) -> Result<(), MismatchError> {
+    if expected != received {
+        return Err(MismatchError::Value { expected, received });
+    }
+    Ok(())
+}
+
+pub(crate) const fn check_byte_count(
+    expected: usize,
+    received: usize,
+) -> Result<(), MismatchError> {
+    if expected != received {
+        return Err(MismatchError::ByteCount { expected, received });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_code_mismatch() {
+        let rsp = Response::WriteSingleRegister(0x10, 1);
+        assert_eq!(
+            rsp.validate_against(&Request::ReadHoldingRegisters(0x10, 1)),
+            Err(MismatchError::FnCode {
+                expected: FunctionCode::ReadHoldingRegisters,
+                received: FunctionCode::WriteSingleRegister,
+            })
+        );
+    }
+
+    #[test]
+    fn read_byte_count() {
+        let req = Request::ReadCoils(0, 10);
+        let rsp = Response::ReadCoils(Coils {
+            data: &[0xFF, 0x03],
+            quantity: 16,
+        });
+        assert!(rsp.validate_against(&req).is_ok());
+        let rsp = Response::ReadCoils(Coils {
+            data: &[0xFF],
+            quantity: 8,
+        });
+        assert_eq!(
+            rsp.validate_against(&req),
+            Err(MismatchError::ByteCount {
+                expected: 2,
+                received: 1
+            })
+        );
+
+        let req = Request::ReadHoldingRegisters(0, 2);
+        let rsp = Response::ReadHoldingRegisters(Data {
+            data: &[0, 1, 0, 2],
+            quantity: 2,
+        });
+        assert!(rsp.validate_against(&req).is_ok());
+        let rsp = Response::ReadHoldingRegisters(Data {
+            data: &[0, 1],
+            quantity: 1,
+        });
+        assert_eq!(
+            rsp.validate_against(&req),
+            Err(MismatchError::ByteCount {
+                expected: 4,
+                received: 2
+            })
+        );
+    }
+
+    #[test]
+    fn write_echo() {
+        let req = Request::WriteSingleRegister(0x2222, 0xABCD);
+        assert!(Response::WriteSingleRegister(0x2222, 0xABCD)
+            .validate_against(&req)
+            .is_ok());
+        assert_eq!(
+            Response::WriteSingleRegister(0x2223, 0xABCD).validate_against(&req),
+            Err(MismatchError::Address {
+                expected: 0x2222,
+                received: 0x2223
+            })
+        );
+        assert_eq!(
+            Response::WriteSingleRegister(0x2222, 0xABCE).validate_against(&req),
+            Err(MismatchError::Value {
+                expected: 0xABCD,
+                received: 0xABCE
+            })
+        );
+
+        let req = Request::WriteMultipleRegisters(
+            0x10,
+            Data {
+                data: &[0, 1, 0, 2],
+                quantity: 2,
+            },
+        );
+        assert!(Response::WriteMultipleRegisters(0x10, 2)
+            .validate_against(&req)
+            .is_ok());
+        assert_eq!(
+            Response::WriteMultipleRegisters(0x10, 3).validate_against(&req),
+            Err(MismatchError::Quantity {
+                expected: 2,
+                received: 3
+            })
+        );
+    }
+}
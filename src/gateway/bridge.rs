@@ -0,0 +1,307 @@
+use super::*;
+use crate::{client::Ticks, Encode, Exception, ExceptionResponse, FunctionCode, MAX_PDU_LEN};
+use core::fmt;
+
+/// Errors of a [`Bridge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BridgeError {
+    /// All queue slots are occupied.
+    ///
+    /// The TCP client should be answered with [`Exception::ServerDeviceBusy`].
+    QueueFull,
+    /// The PDU does not fit into an RTU frame.
+    PduSize(usize),
+    /// The output buffer is too small.
+    Encode(EncodeError),
+}
+
+impl From<EncodeError> for BridgeError {
+    fn from(err: EncodeError) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::QueueFull => write!(f, "Request queue is full"),
+            Self::PduSize(len) => write!(f, "PDU of {len} bytes exceeds an RTU frame"),
+            Self::Encode(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// A TCP request that waits for the serial line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pending {
+    hdr: tcp::Header,
+    pdu: [u8; MAX_PDU_LEN],
+    len: usize,
+}
+
+impl Pending {
+    const EMPTY: Self = Self {
+        hdr: tcp::Header {
+            transaction_id: 0,
+            unit_id: 0,
+        },
+        pdu: [0; MAX_PDU_LEN],
+        len: 0,
+    };
+
+    fn pdu(&self) -> &[u8] {
+        &self.pdu[..self.len]
+    }
+
+    /// Answer the TCP client with `exception`.
+    fn reject(&self, exception: Exception, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let function = FunctionCode::new(self.pdu[0]);
+        let pdu = &mut [0; 2];
+        ExceptionResponse::new(function, exception).encode(pdu)?;
+        let frame = rtu::DecodedFrame {
+            slave: self.hdr.unit_id,
+            pdu,
+        };
+        rtu_to_tcp(frame, self.hdr.transaction_id, buf)
+    }
+}
+
+/// Serializes the requests of TCP clients onto a single RTU line.
+///
+/// Up to `N` requests are queued in the order of arrival and
+/// only one RTU transaction is outstanding at a time. The bridge
+/// performs no I/O: the application forwards the encoded frames and
+/// reports the elapsed time with [`tick`](Self::tick).
+///
+/// Requests to unit `0` are sent as RTU broadcasts that are not answered.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{gateway::Bridge, rtu, tcp, DecoderType};
+///
+/// let mut bridge = Bridge::<4>::new(100);
+/// let rx = [0x00, 0x07, 0x00, 0x00, 0x00, 0x06, 0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD];
+/// let (frame, _) = tcp::decode(DecoderType::Request, &rx).unwrap().unwrap();
+/// bridge.push(frame).unwrap();
+///
+/// let buf = &mut [0; 256];
+/// let len = bridge.poll_transmit(buf).unwrap().unwrap();
+/// assert_eq!(&buf[..len], &[0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE]);
+///
+/// // The slave does not answer in time.
+/// let len = bridge.tick(100, buf).unwrap().unwrap();
+/// assert_eq!(&buf[..len], &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x12, 0x86, 0x0B]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bridge<const N: usize> {
+    queue: [Pending; N],
+    head: usize,
+    len: usize,
+    /// The request on the line and the ticks until it times out.
+    active: Option<(Pending, Ticks)>,
+    timeout: Ticks,
+}
+
+impl<const N: usize> Bridge<N> {
+    /// Create a bridge that waits `timeout` ticks for each response.
+    #[must_use]
+    pub const fn new(timeout: Ticks) -> Self {
+        Self {
+            queue: [Pending::EMPTY; N],
+            head: 0,
+            len: 0,
+            active: None,
+            timeout,
+        }
+    }
+
+    /// The number of queued requests that have not been sent yet.
+    #[must_use]
+    pub const fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if a response is awaited on the serial line.
+    #[must_use]
+    pub const fn is_busy(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Queue a request that has been received from a TCP client.
+    pub fn push(&mut self, frame: tcp::DecodedFrame<'_>) -> Result<(), BridgeError> {
+        let tcp::DecodedFrame {
+            transaction_id,
+            unit_id,
+            pdu,
+        } = frame;
+        if pdu.is_empty() || pdu.len() > MAX_PDU_LEN {
+            return Err(BridgeError::PduSize(pdu.len()));
+        }
+        if self.len == N {
+            return Err(BridgeError::QueueFull);
+        }
+        let slot = &mut self.queue[(self.head + self.len) % N];
+        slot.hdr = tcp::Header {
+            transaction_id,
+            unit_id,
+        };
+        slot.pdu[..pdu.len()].copy_from_slice(pdu);
+        slot.len = pdu.len();
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Encode the next RTU request if the line is idle.
+    ///
+    /// Returns the length of the RTU frame in `buf` that
+    /// has to be sent or `None` if there is nothing to send.
+    pub fn poll_transmit(&mut self, buf: &mut [u8]) -> Result<Option<usize>, BridgeError> {
+        if self.active.is_some() || self.len == 0 {
+            return Ok(None);
+        }
+        let next = self.queue[self.head];
+        let frame = tcp::DecodedFrame {
+            transaction_id: next.hdr.transaction_id,
+            unit_id: next.hdr.unit_id,
+            pdu: next.pdu(),
+        };
+        let len = tcp_to_rtu(frame, buf)?;
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        if next.hdr.unit_id != 0 {
+            self.active = Some((next, self.timeout));
+        }
+        Ok(Some(len))
+    }
+
+    /// Forward a response that has been received on the serial line.
+    ///
+    /// Returns the length of the TCP response in `buf` or `None`
+    /// if the frame does not answer the outstanding request.
+    pub fn receive(
+        &mut self,
+        frame: rtu::DecodedFrame<'_>,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>, BridgeError> {
+        let Some((active, _)) = self.active else {
+            return Ok(None);
+        };
+        let Some(&fn_code) = frame.pdu.first() else {
+            return Ok(None);
+        };
+        if frame.slave != active.hdr.unit_id || fn_code & 0x7F != active.pdu[0] {
+            return Ok(None);
+        }
+        let len = rtu_to_tcp(frame, active.hdr.transaction_id, buf)?;
+        self.active = None;
+        Ok(Some(len))
+    }
+
+    /// Let `elapsed` ticks pass.
+    ///
+    /// If the outstanding request times out, the TCP client is answered
+    /// with [`Exception::GatewayTargetDevice`]. Returns the length of
+    /// this response in `buf` or `None` if nothing has timed out.
+    pub fn tick(&mut self, elapsed: Ticks, buf: &mut [u8]) -> Result<Option<usize>, BridgeError> {
+        let Some((active, remaining)) = &mut self.active else {
+            return Ok(None);
+        };
+        *remaining = remaining.saturating_sub(elapsed);
+        if *remaining > 0 {
+            return Ok(None);
+        }
+        let len = active.reject(Exception::GatewayTargetDevice, buf)?;
+        self.active = None;
+        Ok(Some(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_TCP_ADU_LEN;
+
+    fn request(transaction_id: u16, unit_id: u8, pdu: &[u8]) -> tcp::DecodedFrame<'_> {
+        tcp::DecodedFrame {
+            transaction_id,
+            unit_id,
+            pdu,
+        }
+    }
+
+    #[test]
+    fn one_transaction_at_a_time() {
+        let mut bridge = Bridge::<2>::new(10);
+        let buf = &mut [0; MAX_TCP_ADU_LEN];
+        assert_eq!(bridge.poll_transmit(buf), Ok(None));
+        bridge
+            .push(request(1, 0x12, &[0x06, 0x22, 0x22, 0xAB, 0xCD]))
+            .unwrap();
+        bridge
+            .push(request(2, 0x13, &[0x03, 0x00, 0x00, 0x00, 0x01]))
+            .unwrap();
+        assert_eq!(
+            bridge.push(request(3, 0x14, &[0x03, 0x00, 0x00, 0x00, 0x01])),
+            Err(BridgeError::QueueFull)
+        );
+
+        assert_eq!(bridge.poll_transmit(buf), Ok(Some(8)));
+        assert_eq!(buf[0], 0x12);
+        assert!(bridge.is_busy());
+        assert_eq!(bridge.poll_transmit(buf), Ok(None));
+        assert_eq!(bridge.pending(), 1);
+
+        // A response of another slave is ignored.
+        let rsp = rtu::DecodedFrame {
+            slave: 0x13,
+            pdu: &[0x06, 0x22, 0x22, 0xAB, 0xCD],
+        };
+        assert_eq!(bridge.receive(rsp, buf), Ok(None));
+
+        let rsp = rtu::DecodedFrame {
+            slave: 0x12,
+            pdu: &[0x06, 0x22, 0x22, 0xAB, 0xCD],
+        };
+        assert_eq!(bridge.receive(rsp, buf), Ok(Some(12)));
+        assert_eq!(&buf[..7], &[0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x12]);
+        assert!(!bridge.is_busy());
+
+        assert_eq!(bridge.poll_transmit(buf), Ok(Some(8)));
+        assert_eq!(buf[0], 0x13);
+    }
+
+    #[test]
+    fn timeout() {
+        let mut bridge = Bridge::<1>::new(10);
+        let buf = &mut [0; MAX_TCP_ADU_LEN];
+        bridge
+            .push(request(5, 0x12, &[0x03, 0x00, 0x00, 0x00, 0x01]))
+            .unwrap();
+        bridge.poll_transmit(buf).unwrap();
+        assert_eq!(bridge.tick(9, buf), Ok(None));
+        assert_eq!(bridge.tick(1, buf), Ok(Some(9)));
+        assert_eq!(
+            &buf[..9],
+            &[0x00, 0x05, 0x00, 0x00, 0x00, 0x03, 0x12, 0x83, 0x0B]
+        );
+        assert!(!bridge.is_busy());
+        assert_eq!(bridge.tick(10, buf), Ok(None));
+    }
+
+    #[test]
+    fn broadcasts_are_not_answered() {
+        let mut bridge = Bridge::<1>::new(10);
+        let buf = &mut [0; MAX_TCP_ADU_LEN];
+        bridge
+            .push(request(5, 0, &[0x06, 0x22, 0x22, 0xAB, 0xCD]))
+            .unwrap();
+        assert_eq!(bridge.poll_transmit(buf), Ok(Some(8)));
+        assert!(!bridge.is_busy());
+        assert_eq!(
+            bridge.push(request(6, 1, &[])),
+            Err(BridgeError::PduSize(0))
+        );
+    }
+}
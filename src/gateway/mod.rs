@@ -0,0 +1,113 @@
+//! Re-framing between Modbus RTU and Modbus TCP.
+//!
+//! The PDU bytes are forwarded verbatim and only the envelope is rewritten,
+//! i.e. the slave ID and CRC of RTU or the MBAP header of TCP. Function codes
+//! that can't be represented by [`Request`](crate::Request) and
+//! [`Response`](crate::Response) are therefore preserved.
+//!
+//! The [`Bridge`] serializes the requests of several TCP clients
+//! onto a single serial line. A [`RequestFilter`](crate::server::RequestFilter)
+//! restricts what these clients may access.
+
+use crate::{error::EncodeError, frame::write_u16, rtu, tcp};
+
+mod bridge;
+
+pub use self::bridge::*;
+
+/// Frame the PDU of a decoded RTU frame as TCP ADU.
+///
+/// The slave ID becomes the unit ID of the MBAP header.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{gateway, rtu, DecoderType, MAX_TCP_ADU_LEN};
+///
+/// let rx = [0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD, 0x9F, 0xBE];
+/// let (frame, _) = rtu::decode(DecoderType::Response, &rx).unwrap().unwrap();
+/// let buf = &mut [0; MAX_TCP_ADU_LEN];
+/// let len = gateway::rtu_to_tcp(frame, 7, buf).unwrap();
+/// assert_eq!(
+///     &buf[..len],
+///     &[0x00, 0x07, 0x00, 0x00, 0x00, 0x06, 0x12, 0x06, 0x22, 0x22, 0xAB, 0xCD]
+/// );
+/// ```
+pub fn rtu_to_tcp(
+    frame: rtu::DecodedFrame<'_>,
+    transaction_id: tcp::TransactionId,
+    buf: &mut [u8],
+) -> Result<usize, EncodeError> {
+    let rtu::DecodedFrame { slave, pdu } = frame;
+    let len = 7 + pdu.len();
+    if buf.len() < len {
+        return Err(EncodeError::BufferSize);
+    }
+    write_u16(&mut buf[0..2], transaction_id);
+    write_u16(&mut buf[2..4], 0); //MODBUS Protocol
+    write_u16(&mut buf[4..6], (pdu.len() + 1) as u16);
+    buf[6] = slave;
+    buf[7..len].copy_from_slice(pdu);
+    Ok(len)
+}
+
+/// Frame the PDU of a decoded TCP frame as RTU ADU.
+///
+/// The unit ID becomes the slave ID. The transaction ID has
+/// to be kept by the caller to answer the TCP client.
+pub fn tcp_to_rtu(frame: tcp::DecodedFrame<'_>, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let tcp::DecodedFrame { unit_id, pdu, .. } = frame;
+    let len = 3 + pdu.len();
+    if buf.len() < len {
+        return Err(EncodeError::BufferSize);
+    }
+    buf[0] = unit_id;
+    buf[1..=pdu.len()].copy_from_slice(pdu);
+    let crc = rtu::crc16(&buf[..=pdu.len()]);
+    write_u16(&mut buf[len - 2..], crc);
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MAX_RTU_ADU_LEN, MAX_TCP_ADU_LEN};
+
+    #[test]
+    fn custom_function_code_roundtrip() {
+        let adu = &[0x00, 0x2A, 0x00, 0x00, 0x00, 0x04, 0x11, 0x42, 0xAB, 0xCD];
+        let frame = tcp::DecodedFrame {
+            transaction_id: 0x2A,
+            unit_id: 0x11,
+            pdu: &adu[7..],
+        };
+        let rtu_buf = &mut [0; MAX_RTU_ADU_LEN];
+        let len = tcp_to_rtu(frame, rtu_buf).unwrap();
+        assert_eq!(len, 6);
+        let rtu_frame = rtu::extract_frame(&rtu_buf[..len], 3).unwrap().unwrap();
+        assert_eq!(rtu_frame.slave, 0x11);
+        assert_eq!(rtu_frame.pdu, &[0x42, 0xAB, 0xCD]);
+
+        let tcp_buf = &mut [0; MAX_TCP_ADU_LEN];
+        let len = rtu_to_tcp(rtu_frame, frame.transaction_id, tcp_buf).unwrap();
+        assert_eq!(&tcp_buf[..len], adu);
+    }
+
+    #[test]
+    fn buffer_too_small() {
+        let frame = rtu::DecodedFrame {
+            slave: 1,
+            pdu: &[0x03, 0x02, 0x00, 0x01],
+        };
+        assert_eq!(
+            rtu_to_tcp(frame, 0, &mut [0; 10]),
+            Err(EncodeError::BufferSize)
+        );
+        let frame = tcp::DecodedFrame {
+            transaction_id: 0,
+            unit_id: 1,
+            pdu: &[0x03, 0x02, 0x00, 0x01],
+        };
+        assert_eq!(tcp_to_rtu(frame, &mut [0; 6]), Err(EncodeError::BufferSize));
+    }
+}
@@ -16,12 +16,30 @@
 #![allow(clippy::similar_names)] // TODO
 #![allow(clippy::wildcard_imports)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 mod codec;
 mod error;
 mod frame;
+#[cfg(feature = "macros")]
+mod macros;
+
+pub mod client;
+pub mod gateway;
+pub mod server;
+pub mod slave;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
 pub use codec::rtu;
 pub use codec::tcp;
+pub use codec::{
+    classify, Classification, Confidence, CustomFunction, DecodeOptions, DecodeStatus, DecoderType,
+    DisplayAdu, Encode, ExceptionCounts, FrameStatus, FunctionDescriptor, Observer, PduKind,
+    PduLen, QuirkProfile, Segments, Stats, Transport,
+};
 pub use error::*;
 pub use frame::*;
-
+#[cfg(feature = "derive")]
+pub use modbus_core_derive::RegisterBlock;
@@ -0,0 +1,102 @@
+//! Requests that are encoded at compile time.
+
+/// Encode an RTU request at compile time.
+///
+/// The macro evaluates to a constant `[u8; 8]` including the CRC,
+/// e.g. to keep the fixed requests of a poller in flash.
+/// The arguments must be constant expressions.
+///
+/// Supported requests:
+///
+/// - `read_coils = address, qty = quantity`
+/// - `read_discrete_inputs = address, qty = quantity`
+/// - `read_holding = address, qty = quantity`
+/// - `read_input = address, qty = quantity`
+/// - `write_coil = address, value = state`
+/// - `write_register = address, value = word`
+///
+/// Invalid quantities are rejected at compile time.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::modbus_rtu_request;
+///
+/// const POLL: [u8; 8] = modbus_rtu_request!(slave = 1, read_holding = 0x0000, qty = 1);
+/// assert_eq!(POLL, [0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A]);
+/// ```
+///
+/// ```compile_fail
+/// const POLL: [u8; 8] = modbus_core::modbus_rtu_request!(slave = 1, read_holding = 0, qty = 126);
+/// ```
+#[macro_export]
+macro_rules! modbus_rtu_request {
+    (slave = $slave:expr, read_coils = $address:expr, qty = $quantity:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x01, $address, $quantity)
+    };
+    (slave = $slave:expr, read_discrete_inputs = $address:expr, qty = $quantity:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x02, $address, $quantity)
+    };
+    (slave = $slave:expr, read_holding = $address:expr, qty = $quantity:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x03, $address, $quantity)
+    };
+    (slave = $slave:expr, read_input = $address:expr, qty = $quantity:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x04, $address, $quantity)
+    };
+    (slave = $slave:expr, write_coil = $address:expr, value = $state:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x05, $address, if $state { 0xFF00 } else { 0x0000 })
+    };
+    (slave = $slave:expr, write_register = $address:expr, value = $word:expr $(,)?) => {
+        $crate::modbus_rtu_request!(@adu $slave, 0x06, $address, $word)
+    };
+    (@adu $slave:expr, $fn_code:expr, $address:expr, $value:expr) => {{
+        const ADU: [u8; 8] = $crate::rtu::fixed_request_adu($slave, $fn_code, $address, $value);
+        ADU
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rtu::{client::encode_request, Header, RequestAdu},
+        Request, RequestPdu,
+    };
+
+    fn encoded(slave: u8, request: Request<'_>) -> [u8; 8] {
+        let mut buf = [0; 8];
+        let adu = RequestAdu {
+            hdr: Header { slave },
+            pdu: RequestPdu(request),
+        };
+        assert_eq!(encode_request(adu, &mut buf), Ok(8));
+        buf
+    }
+
+    #[test]
+    fn match_the_encoder() {
+        assert_eq!(
+            modbus_rtu_request!(slave = 5, read_coils = 0x0010, qty = 2000),
+            encoded(5, Request::ReadCoils(0x0010, 2000))
+        );
+        assert_eq!(
+            modbus_rtu_request!(slave = 5, read_discrete_inputs = 0x0010, qty = 8),
+            encoded(5, Request::ReadDiscreteInputs(0x0010, 8))
+        );
+        assert_eq!(
+            modbus_rtu_request!(slave = 5, read_holding = 0x0100, qty = 10),
+            encoded(5, Request::ReadHoldingRegisters(0x0100, 10))
+        );
+        assert_eq!(
+            modbus_rtu_request!(slave = 5, read_input = 0x0100, qty = 125),
+            encoded(5, Request::ReadInputRegisters(0x0100, 125))
+        );
+        assert_eq!(
+            modbus_rtu_request!(slave = 0, write_coil = 0x0001, value = true),
+            encoded(0, Request::WriteSingleCoil(0x0001, true))
+        );
+        assert_eq!(
+            modbus_rtu_request!(slave = 0, write_register = 0x0001, value = 0xABCD),
+            encoded(0, Request::WriteSingleRegister(0x0001, 0xABCD))
+        );
+    }
+}
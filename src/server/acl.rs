@@ -0,0 +1,293 @@
+use super::*;
+use core::ops::RangeInclusive;
+
+/// A rule of a [`RequestFilter`].
+///
+/// A rule without any conditions matches all requests. Each
+/// condition narrows the requests that are matched by the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AclRule {
+    /// The first and last slave or unit ID.
+    units: Option<(u8, u8)>,
+    category: Option<Category>,
+    function: Option<FunctionCode>,
+    target: Option<(Table, AddressRange)>,
+    verdict: Result<(), Exception>,
+}
+
+impl AclRule {
+    /// Permit the matching requests.
+    #[must_use]
+    pub const fn allow() -> Self {
+        Self::new(Ok(()))
+    }
+
+    /// Reject the matching requests with `exception`.
+    #[must_use]
+    pub const fn deny(exception: Exception) -> Self {
+        Self::new(Err(exception))
+    }
+
+    const fn new(verdict: Result<(), Exception>) -> Self {
+        Self {
+            units: None,
+            category: None,
+            function: None,
+            target: None,
+            verdict,
+        }
+    }
+
+    /// Only match requests addressed to a slave or unit ID within `units`.
+    #[must_use]
+    pub const fn units(self, units: RangeInclusive<u8>) -> Self {
+        Self {
+            units: Some((*units.start(), *units.end())),
+            ..self
+        }
+    }
+
+    /// Only match requests of functions of `category`.
+    #[must_use]
+    pub const fn category(self, category: Category) -> Self {
+        Self {
+            category: Some(category),
+            ..self
+        }
+    }
+
+    /// Only match requests of `function`.
+    #[must_use]
+    pub const fn function(self, function: FunctionCode) -> Self {
+        Self {
+            function: Some(function),
+            ..self
+        }
+    }
+
+    /// Only match requests that access nothing but
+    /// the addresses of `range` in `table`.
+    ///
+    /// Requests that do not access any table,
+    /// e.g. `Diagnostics`, are not matched.
+    #[must_use]
+    pub const fn range(self, table: Table, range: AddressRange) -> Self {
+        Self {
+            target: Some((table, range)),
+            ..self
+        }
+    }
+
+    fn matches(&self, unit: u8, request: &Request<'_>) -> bool {
+        let function = FunctionCode::from(*request);
+        if matches!(self.units, Some((first, last)) if unit < first || unit > last) {
+            return false;
+        }
+        if matches!(self.category, Some(category) if category != function.category()) {
+            return false;
+        }
+        if matches!(self.function, Some(f) if f != function) {
+            return false;
+        }
+        let Some((table, range)) = self.target else {
+            return true;
+        };
+        let accessed = accessed_ranges(request);
+        accessed.iter().any(Option::is_some)
+            && accessed
+                .iter()
+                .flatten()
+                .all(|(t, r)| *t == table && matches!(r, Some(r) if range.contains_range(r)))
+    }
+}
+
+/// The tables and addresses that are accessed by `request`.
+///
+/// The range is `None` if the quantity of the request is invalid.
+fn accessed_ranges(request: &Request<'_>) -> [Option<(Table, Option<AddressRange>)>; 2] {
+    use Request as R;
+
+    let access = |table, address, quantity: usize| {
+        let range = Quantity::try_from(quantity)
+            .ok()
+            .and_then(|quantity| AddressRange::new(address, quantity));
+        Some((table, range))
+    };
+    match *request {
+        R::ReadCoils(address, quantity) => [access(Table::Coils, address, quantity.into()), None],
+        R::ReadDiscreteInputs(address, quantity) => [
+            access(Table::DiscreteInputs, address, quantity.into()),
+            None,
+        ],
+        R::ReadInputRegisters(address, quantity) => [
+            access(Table::InputRegisters, address, quantity.into()),
+            None,
+        ],
+        R::ReadHoldingRegisters(address, quantity) => [
+            access(Table::HoldingRegisters, address, quantity.into()),
+            None,
+        ],
+        R::WriteSingleCoil(address, _) => [access(Table::Coils, address, 1), None],
+        R::WriteMultipleCoils(address, coils) => [access(Table::Coils, address, coils.len()), None],
+        R::WriteSingleRegister(address, _) | R::ReadFifoQueue(address) => {
+            [access(Table::HoldingRegisters, address, 1), None]
+        }
+        R::WriteMultipleRegisters(address, words) => {
+            [access(Table::HoldingRegisters, address, words.len()), None]
+        }
+        R::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => [
+            access(Table::HoldingRegisters, read_address, quantity.into()),
+            access(Table::HoldingRegisters, write_address, words.len()),
+        ],
+        _ => [None, None],
+    }
+}
+
+/// Decides whether requests are permitted, e.g. to expose only
+/// a read-only subset of a device to untrusted TCP clients.
+///
+/// The first of up to `N` rules that matches a request decides.
+/// If no rule matches, the default verdict applies.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     server::{AclRule, RequestFilter, Table},
+///     AddressRange, Category, Exception, Request,
+/// };
+///
+/// let mut filter = RequestFilter::<4>::deny_all(Exception::IllegalFunction);
+/// let public = AddressRange::new(0x1000, 0x100).unwrap();
+/// filter
+///     .add(
+///         AclRule::allow()
+///             .units(1..=10)
+///             .category(Category::Read)
+///             .range(Table::HoldingRegisters, public),
+///     )
+///     .unwrap();
+///
+/// assert_eq!(filter.check(1, &Request::ReadHoldingRegisters(0x1000, 4)), Ok(()));
+/// assert_eq!(
+///     filter.check(1, &Request::WriteSingleRegister(0x1000, 4)),
+///     Err(Exception::IllegalFunction)
+/// );
+/// assert!(filter.check(11, &Request::ReadHoldingRegisters(0x1000, 4)).is_err());
+/// assert!(filter.check(1, &Request::ReadHoldingRegisters(0x0FFF, 4)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RequestFilter<const N: usize> {
+    rules: [Option<AclRule>; N],
+    default: Result<(), Exception>,
+}
+
+impl<const N: usize> RequestFilter<N> {
+    /// Permit all requests that are not denied by a rule.
+    #[must_use]
+    pub const fn allow_all() -> Self {
+        Self {
+            rules: [None; N],
+            default: Ok(()),
+        }
+    }
+
+    /// Reject all requests that are not permitted by a rule with `exception`.
+    #[must_use]
+    pub const fn deny_all(exception: Exception) -> Self {
+        Self {
+            rules: [None; N],
+            default: Err(exception),
+        }
+    }
+
+    /// Append `rule`.
+    ///
+    /// Fails with [`Error::BufferSize`] if `N` rules have already been added.
+    pub fn add(&mut self, rule: AclRule) -> Result<(), Error> {
+        let slot = self
+            .rules
+            .iter_mut()
+            .find(|r| r.is_none())
+            .ok_or(Error::BufferSize)?;
+        *slot = Some(rule);
+        Ok(())
+    }
+
+    /// Check a `request` that is addressed to the slave or unit `unit`.
+    ///
+    /// Returns the exception to answer with if it is not permitted.
+    pub fn check(&self, unit: u8, request: &Request<'_>) -> Result<(), Exception> {
+        self.rules
+            .iter()
+            .flatten()
+            .find(|rule| rule.matches(unit, request))
+            .map_or(self.default, |rule| rule.verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let range = AddressRange::new(0x10, 0x10).unwrap();
+        let mut filter = RequestFilter::<3>::allow_all();
+        filter
+            .add(AclRule::allow().units(5..=5).range(Table::Coils, range))
+            .unwrap();
+        filter
+            .add(AclRule::deny(Exception::IllegalDataAddress).range(Table::Coils, range))
+            .unwrap();
+        filter
+            .add(
+                AclRule::deny(Exception::IllegalFunction)
+                    .function(FunctionCode::ReadWriteMultipleRegisters),
+            )
+            .unwrap();
+        assert_eq!(filter.add(AclRule::allow()), Err(Error::BufferSize));
+
+        assert_eq!(
+            filter.check(5, &Request::WriteSingleCoil(0x10, true)),
+            Ok(())
+        );
+        assert_eq!(
+            filter.check(6, &Request::WriteSingleCoil(0x10, true)),
+            Err(Exception::IllegalDataAddress)
+        );
+        // Partially outside of the range
+        assert_eq!(filter.check(6, &Request::ReadCoils(0x1F, 2)), Ok(()));
+        assert_eq!(
+            filter.check(6, &Request::ReadHoldingRegisters(0x10, 1)),
+            Ok(())
+        );
+        let words = Data {
+            data: &[0, 0],
+            quantity: 1,
+        };
+        assert_eq!(
+            filter.check(
+                6,
+                &Request::ReadWriteMultipleRegisters(0x10, 1, 0x10, words)
+            ),
+            Err(Exception::IllegalFunction)
+        );
+    }
+
+    #[test]
+    fn match_all_accessed_ranges() {
+        let range = AddressRange::new(0, 10).unwrap();
+        let rule = AclRule::allow().range(Table::HoldingRegisters, range);
+        let words = Data {
+            data: &[0, 0],
+            quantity: 1,
+        };
+        assert!(rule.matches(1, &Request::ReadWriteMultipleRegisters(0, 2, 9, words)));
+        assert!(!rule.matches(1, &Request::ReadWriteMultipleRegisters(0, 2, 10, words)));
+        assert!(!rule.matches(1, &Request::ReadInputRegisters(0, 1)));
+        assert!(!rule.matches(1, &Request::ReadHoldingRegisters(0, 0)));
+    }
+}
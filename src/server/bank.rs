@@ -0,0 +1,521 @@
+use super::*;
+use core::fmt;
+
+/// A block of values mapped to consecutive addresses.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Block<'a, T> {
+    start: Address,
+    values: &'a mut [T],
+}
+
+impl<'a, T> Block<'a, T> {
+    /// Map `values` to the addresses beginning at `start`.
+    pub fn new(start: Address, values: &'a mut [T]) -> Result<Self, Error> {
+        let fits = match Quantity::try_from(values.len()) {
+            Ok(0) => true,
+            Ok(quantity) => AddressRange::new(start, quantity).is_some(),
+            Err(_) => false,
+        };
+        if !fits {
+            return Err(Error::BufferSize);
+        }
+        Ok(Self { start, values })
+    }
+
+    /// The range of addresses covered by this block.
+    ///
+    /// Returns `None` if the block is empty.
+    #[must_use]
+    pub fn range(&self) -> Option<AddressRange> {
+        AddressRange::new(self.start, self.values.len() as Quantity)
+    }
+
+    /// All values of this block.
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        self.values
+    }
+
+    /// All values of this block.
+    pub fn values_mut(&mut self) -> &mut [T] {
+        self.values
+    }
+
+    /// Get `cnt` values beginning at `address`.
+    #[must_use]
+    pub fn get(&self, address: Address, cnt: usize) -> Option<&[T]> {
+        let offset = usize::from(address.checked_sub(self.start)?);
+        self.values.get(offset..offset.checked_add(cnt)?)
+    }
+
+    /// Get `cnt` values beginning at `address`.
+    pub fn get_mut(&mut self, address: Address, cnt: usize) -> Option<&mut [T]> {
+        let offset = usize::from(address.checked_sub(self.start)?);
+        self.values.get_mut(offset..offset.checked_add(cnt)?)
+    }
+}
+
+/// A ready-to-use [`DataModel`] that stores the values of
+/// all four tables in caller provided buffers.
+///
+/// Tables without values are reported as unsupported
+/// with [`Exception::IllegalFunction`].
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::server::{Block, RegisterBank};
+///
+/// let mut holding_registers = [0; 16];
+/// let mut bank = RegisterBank {
+///     holding_registers: Block::new(0x1000, &mut holding_registers).unwrap(),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RegisterBank<'a> {
+    pub coils: Block<'a, Coil>,
+    pub discrete_inputs: Block<'a, Coil>,
+    pub input_registers: Block<'a, Word>,
+    pub holding_registers: Block<'a, Word>,
+}
+
+/// The version of the byte layout of [`RegisterBank::snapshot`].
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// The length of the start address and the number
+/// of values of a table in a snapshot.
+const SNAPSHOT_TABLE_HEADER_LEN: usize = 4;
+
+/// Errors of [`RegisterBank::restore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SnapshotError {
+    /// The snapshot is truncated.
+    Truncated,
+    /// The snapshot has an unsupported layout version.
+    Version(u8),
+    /// The block of the table in the snapshot has a different
+    /// start address or length than the block of the bank.
+    Layout(Table),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "Truncated snapshot"),
+            Self::Version(version) => write!(f, "Unsupported snapshot version {version}"),
+            Self::Layout(table) => write!(f, "Snapshot does not match the block of {table:?}"),
+        }
+    }
+}
+
+impl RegisterBank<'_> {
+    /// The start addresses and lengths of the blocks in the
+    /// order of the snapshot.
+    fn layout(&self) -> [(Table, Address, usize); 4] {
+        [
+            (Table::Coils, self.coils.start, self.coils.values.len()),
+            (
+                Table::DiscreteInputs,
+                self.discrete_inputs.start,
+                self.discrete_inputs.values.len(),
+            ),
+            (
+                Table::InputRegisters,
+                self.input_registers.start,
+                self.input_registers.values.len(),
+            ),
+            (
+                Table::HoldingRegisters,
+                self.holding_registers.start,
+                self.holding_registers.values.len(),
+            ),
+        ]
+    }
+
+    /// The number of bytes of a snapshot.
+    #[must_use]
+    pub fn snapshot_len(&self) -> usize {
+        1 + self
+            .layout()
+            .iter()
+            .map(|(table, _, cnt)| SNAPSHOT_TABLE_HEADER_LEN + values_len(*table, *cnt))
+            .sum::<usize>()
+    }
+
+    /// Write the values of all tables into `out`, e.g. to
+    /// persist them in flash memory, and return the number
+    /// of bytes that have been written.
+    ///
+    /// The layout starts with the version byte [`SNAPSHOT_VERSION`]
+    /// followed by the coils, discrete inputs, input registers and
+    /// holding registers. Each table consists of the start address
+    /// and the number of values, both big-endian, followed by the
+    /// values: coils are packed like in a `ReadCoils` response and
+    /// registers are big-endian.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use modbus_core::server::{Block, RegisterBank};
+    ///
+    /// let mut holding_registers = [0x1234; 2];
+    /// let bank = RegisterBank {
+    ///     holding_registers: Block::new(0x1000, &mut holding_registers).unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let buf = &mut [0; 64];
+    /// let len = bank.snapshot(buf).unwrap();
+    /// assert_eq!(&buf[13..len], &[0x10, 0x00, 0x00, 0x02, 0x12, 0x34, 0x12, 0x34]);
+    ///
+    /// let mut holding_registers = [0; 2];
+    /// let mut bank = RegisterBank {
+    ///     holding_registers: Block::new(0x1000, &mut holding_registers).unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// bank.restore(&buf[..len]).unwrap();
+    /// assert_eq!(bank.holding_registers.values(), &[0x1234; 2]);
+    /// ```
+    pub fn snapshot(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let len = self.snapshot_len();
+        let out = out.get_mut(..len).ok_or(Error::BufferSize)?;
+        out[0] = SNAPSHOT_VERSION;
+        let mut pos = 1;
+        for (table, start, cnt) in self.layout() {
+            write_u16(&mut out[pos..], start);
+            write_u16(&mut out[pos + 2..], cnt as u16);
+            pos += SNAPSHOT_TABLE_HEADER_LEN;
+            let values = &mut out[pos..pos + values_len(table, cnt)];
+            match table {
+                Table::Coils => pack_coils(self.coils.values, values)?,
+                Table::DiscreteInputs => pack_coils(self.discrete_inputs.values, values)?,
+                Table::InputRegisters => write_words(self.input_registers.values, values),
+                Table::HoldingRegisters => write_words(self.holding_registers.values, values),
+            };
+            pos += values.len();
+        }
+        Ok(len)
+    }
+
+    /// Load the values of all tables from a `snapshot`.
+    ///
+    /// The blocks of the bank must have the same start addresses and
+    /// lengths as the blocks of the snapshot. Nothing is changed if
+    /// the snapshot does not match. Trailing bytes, e.g. the erased
+    /// rest of a flash page, are ignored.
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), SnapshotError> {
+        match snapshot.first() {
+            Some(&SNAPSHOT_VERSION) => {}
+            Some(&version) => return Err(SnapshotError::Version(version)),
+            None => return Err(SnapshotError::Truncated),
+        }
+        let mut pos = 1;
+        for (table, start, cnt) in self.layout() {
+            let header = snapshot
+                .get(pos..pos + SNAPSHOT_TABLE_HEADER_LEN)
+                .ok_or(SnapshotError::Truncated)?;
+            if read_u16(header) != start || usize::from(read_u16(&header[2..])) != cnt {
+                return Err(SnapshotError::Layout(table));
+            }
+            pos += SNAPSHOT_TABLE_HEADER_LEN + values_len(table, cnt);
+        }
+        if snapshot.len() < pos {
+            return Err(SnapshotError::Truncated);
+        }
+        let mut pos = 1;
+        for (table, _, cnt) in self.layout() {
+            pos += SNAPSHOT_TABLE_HEADER_LEN;
+            let values = &snapshot[pos..pos + values_len(table, cnt)];
+            match table {
+                Table::Coils => read_coils(values, self.coils.values),
+                Table::DiscreteInputs => read_coils(values, self.discrete_inputs.values),
+                Table::InputRegisters => read_words(values, self.input_registers.values),
+                Table::HoldingRegisters => read_words(values, self.holding_registers.values),
+            }
+            pos += values.len();
+        }
+        Ok(())
+    }
+}
+
+/// The number of bytes of `cnt` values of `table` in a snapshot.
+const fn values_len(table: Table, cnt: usize) -> usize {
+    match table {
+        Table::Coils | Table::DiscreteInputs => packed_coils_len(cnt),
+        Table::InputRegisters | Table::HoldingRegisters => cnt * 2,
+    }
+}
+
+fn write_words(words: &[Word], out: &mut [u8]) -> usize {
+    for (chunk, word) in out.chunks_exact_mut(2).zip(words) {
+        write_u16(chunk, *word);
+    }
+    words.len() * 2
+}
+
+fn read_words(bytes: &[u8], words: &mut [Word]) {
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(2)) {
+        *word = read_u16(chunk);
+    }
+}
+
+fn read_coils(bytes: &[u8], coils: &mut [Coil]) {
+    let cnt = coils.len() as u16;
+    for (coil, value) in coils.iter_mut().zip(unpack_coils_iter(bytes, cnt)) {
+        *coil = value;
+    }
+}
+
+fn read<T: Copy>(block: &Block<'_, T>, address: Address, out: &mut [T]) -> Result<(), Exception> {
+    if block.values.is_empty() {
+        return Err(Exception::IllegalFunction);
+    }
+    let values = block
+        .get(address, out.len())
+        .ok_or(Exception::IllegalDataAddress)?;
+    out.copy_from_slice(values);
+    Ok(())
+}
+
+fn write<T>(
+    block: &mut Block<'_, T>,
+    address: Address,
+    values: impl ExactSizeIterator<Item = T>,
+) -> Result<(), Exception> {
+    if block.values.is_empty() {
+        return Err(Exception::IllegalFunction);
+    }
+    let target = block
+        .get_mut(address, values.len())
+        .ok_or(Exception::IllegalDataAddress)?;
+    for (t, v) in target.iter_mut().zip(values) {
+        *t = v;
+    }
+    Ok(())
+}
+
+impl DataModel for RegisterBank<'_> {
+    fn address_range(&self, table: Table) -> Option<AddressRange> {
+        match table {
+            Table::Coils => self.coils.range(),
+            Table::DiscreteInputs => self.discrete_inputs.range(),
+            Table::InputRegisters => self.input_registers.range(),
+            Table::HoldingRegisters => self.holding_registers.range(),
+        }
+    }
+
+    fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+        read(&self.coils, address, coils)
+    }
+
+    fn read_discrete_inputs(
+        &mut self,
+        address: Address,
+        inputs: &mut [Coil],
+    ) -> Result<(), Exception> {
+        read(&self.discrete_inputs, address, inputs)
+    }
+
+    fn read_input_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        read(&self.input_registers, address, registers)
+    }
+
+    fn read_holding_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        read(&self.holding_registers, address, registers)
+    }
+
+    fn write_single_coil(&mut self, address: Address, coil: Coil) -> Result<(), Exception> {
+        write(&mut self.coils, address, core::iter::once(coil))
+    }
+
+    fn write_multiple_coils(
+        &mut self,
+        address: Address,
+        coils: Coils<'_>,
+    ) -> Result<(), Exception> {
+        write(
+            &mut self.coils,
+            address,
+            (0..coils.len()).map(|i| coils.get(i) == Some(true)),
+        )
+    }
+
+    fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+        write(&mut self.holding_registers, address, core::iter::once(word))
+    }
+
+    fn write_multiple_registers(
+        &mut self,
+        address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        write(
+            &mut self.holding_registers,
+            address,
+            (0..words.len()).map(|i| words.get(i).unwrap_or_default()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_out_of_address_space() {
+        let values = &mut [0; 2];
+        assert!(Block::new(0xFFFE, values).is_ok());
+        let values = &mut [0; 3];
+        assert_eq!(Block::new(0xFFFE, values).err(), Some(Error::BufferSize));
+    }
+
+    #[test]
+    fn block_access() {
+        let values = &mut [1, 2, 3, 4];
+        let mut block = Block::new(10, values).unwrap();
+        assert_eq!(block.range(), AddressRange::new(10, 4));
+        assert_eq!(block.get(11, 2), Some(&[2, 3][..]));
+        assert_eq!(block.get(9, 1), None);
+        assert_eq!(block.get(13, 2), None);
+        block.get_mut(13, 1).unwrap()[0] = 7;
+        assert_eq!(block.values(), &[1, 2, 3, 7]);
+    }
+
+    #[test]
+    fn process_requests() {
+        let holding = &mut [0; 4];
+        let coils = &mut [false; 4];
+        let mut bank = RegisterBank {
+            holding_registers: Block::new(0x100, holding).unwrap(),
+            coils: Block::new(0, coils).unwrap(),
+            ..Default::default()
+        };
+        let buf = &mut [0; 8];
+
+        let words = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        let rsp = process_request(
+            &mut bank,
+            Request::WriteMultipleRegisters(0x102, words),
+            buf,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Ok(Response::WriteMultipleRegisters(0x102, 2)))
+        );
+        assert_eq!(bank.holding_registers.values(), &[0, 0, 0x1234, 0x5678]);
+
+        let rsp = process_request(&mut bank, Request::WriteSingleCoil(3, true), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp, ResponsePdu(Ok(Response::WriteSingleCoil(3, true))));
+        assert_eq!(bank.coils.values(), &[false, false, false, true]);
+
+        let rsp = process_request(&mut bank, Request::ReadHoldingRegisters(0x103, 2), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            }))
+        );
+
+        let words = Data {
+            data: &[0xAB, 0xCD],
+            quantity: 1,
+        };
+        let rsp = process_request(
+            &mut bank,
+            Request::ReadWriteMultipleRegisters(0x101, 2, 0x102, words),
+            buf,
+        )
+        .unwrap()
+        .unwrap();
+        let ResponsePdu(Ok(Response::ReadWriteMultipleRegisters(data))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        // The write operation is performed before the read operation
+        assert_eq!(data.get(0), Some(0));
+        assert_eq!(data.get(1), Some(0xABCD));
+
+        let rsp = process_request(&mut bank, Request::ReadInputRegisters(0, 1), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadInputRegisters,
+                exception: Exception::IllegalFunction,
+            }))
+        );
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let coils = &mut [true, false, true];
+        let inputs = &mut [0xABCD];
+        let holding = &mut [1, 2];
+        let bank = RegisterBank {
+            coils: Block::new(0x10, coils).unwrap(),
+            input_registers: Block::new(0, inputs).unwrap(),
+            holding_registers: Block::new(0x100, holding).unwrap(),
+            ..Default::default()
+        };
+        let buf = &mut [0xFF; 32];
+        assert_eq!(bank.snapshot(&mut buf[..10]), Err(Error::BufferSize));
+        let len = bank.snapshot(buf).unwrap();
+        assert_eq!(len, bank.snapshot_len());
+        assert_eq!(buf[0], SNAPSHOT_VERSION);
+        // Coils
+        assert_eq!(&buf[1..6], &[0x00, 0x10, 0x00, 0x03, 0b101]);
+        // Discrete inputs
+        assert_eq!(&buf[6..10], &[0x00, 0x00, 0x00, 0x00]);
+        // Input registers
+        assert_eq!(&buf[10..16], &[0x00, 0x00, 0x00, 0x01, 0xAB, 0xCD]);
+        // Holding registers
+        assert_eq!(
+            &buf[16..len],
+            &[0x01, 0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x02]
+        );
+
+        let coils = &mut [false; 3];
+        let inputs = &mut [0];
+        let holding = &mut [0; 2];
+        let mut bank = RegisterBank {
+            coils: Block::new(0x10, coils).unwrap(),
+            input_registers: Block::new(0, inputs).unwrap(),
+            holding_registers: Block::new(0x101, holding).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            bank.restore(&buf[..len]),
+            Err(SnapshotError::Layout(Table::HoldingRegisters))
+        );
+        assert_eq!(bank.input_registers.values(), &[0]);
+        bank.holding_registers = Block::new(0x100, bank.holding_registers.values).unwrap();
+        assert_eq!(bank.restore(&buf[..len - 1]), Err(SnapshotError::Truncated));
+        // Trailing bytes are ignored
+        assert_eq!(bank.restore(buf), Ok(()));
+        assert_eq!(bank.coils.values(), &[true, false, true]);
+        assert_eq!(bank.input_registers.values(), &[0xABCD]);
+        assert_eq!(bank.holding_registers.values(), &[1, 2]);
+
+        buf[0] = 2;
+        assert_eq!(bank.restore(buf), Err(SnapshotError::Version(2)));
+    }
+}
@@ -0,0 +1,730 @@
+use super::*;
+
+/// The data word of a `RestartCommunications` request that
+/// additionally clears the communications event log.
+const CLEAR_LOG: Word = 0xFF00;
+
+/// The status word of event log responses if no
+/// previous request is still being processed.
+const READY: Word = 0x0000;
+
+/// The communication counters and the diagnostic register of a
+/// serial line server that can be queried with `Diagnostics` (`0x08`) requests.
+///
+/// The server related counters are maintained by [`process_request`].
+/// The bus related counters (e.g. CRC errors) have to be updated by
+/// the application that receives the frames.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CommCounters {
+    /// The contents of the diagnostic register.
+    pub diagnostic_register: Word,
+    /// Number of messages detected on the bus.
+    pub bus_messages: u16,
+    /// Number of CRC errors.
+    pub bus_comm_errors: u16,
+    /// Number of returned exception responses.
+    pub bus_exception_errors: u16,
+    /// Number of processed messages addressed to this server.
+    pub server_messages: u16,
+    /// Number of processed messages that have not been answered.
+    pub server_no_responses: u16,
+    /// Number of returned negative acknowledge exception responses.
+    pub server_naks: u16,
+    /// Number of returned server device busy exception responses.
+    pub server_busy: u16,
+    /// Number of messages that could not be handled due to a character overrun.
+    pub bus_char_overruns: u16,
+}
+
+/// The communication mode of a serial line server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommMode {
+    /// Requests are processed and answered.
+    #[default]
+    Online,
+    /// Requests are monitored but neither applied nor answered.
+    ///
+    /// Entered with a Force Listen Only Mode request and
+    /// left with a Restart Communications request.
+    ListenOnly,
+}
+
+impl CommMode {
+    /// Returns `true` if requests must not be answered.
+    #[must_use]
+    pub const fn is_listen_only(self) -> bool {
+        matches!(self, Self::ListenOnly)
+    }
+}
+
+impl CommCounters {
+    /// Reset all counters and the diagnostic register.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The value of `counter`.
+    #[must_use]
+    pub const fn get(&self, counter: DiagnosticsCounter) -> u16 {
+        use DiagnosticsCounter as C;
+
+        match counter {
+            C::BusMessages => self.bus_messages,
+            C::BusCommErrors => self.bus_comm_errors,
+            C::BusExceptionErrors => self.bus_exception_errors,
+            C::ServerMessages => self.server_messages,
+            C::ServerNoResponses => self.server_no_responses,
+            C::ServerNaks => self.server_naks,
+            C::ServerBusy => self.server_busy,
+            C::BusCharOverruns => self.bus_char_overruns,
+        }
+    }
+
+    /// Update the server related counters after processing `request`.
+    pub(crate) fn count_response(&mut self, request: &Request<'_>, rsp: Option<&ResponsePdu<'_>>) {
+        // A restart clears the counters, including its own request
+        if matches!(request, Request::Diagnostics(RESTART_COMMUNICATIONS, _))
+            && !matches!(rsp, Some(ResponsePdu(Err(_))))
+        {
+            return;
+        }
+        self.server_messages = self.server_messages.wrapping_add(1);
+        match rsp {
+            Some(ResponsePdu(Ok(_))) => {}
+            Some(ResponsePdu(Err(ExceptionResponse { exception, .. }))) => {
+                self.bus_exception_errors = self.bus_exception_errors.wrapping_add(1);
+                match exception.value() {
+                    // Server device busy
+                    6 => self.server_busy = self.server_busy.wrapping_add(1),
+                    // Negative acknowledge
+                    7 => self.server_naks = self.server_naks.wrapping_add(1),
+                    _ => {}
+                }
+            }
+            None => {
+                self.server_no_responses = self.server_no_responses.wrapping_add(1);
+            }
+        }
+    }
+}
+
+/// The communications event log of a serial line server that can be queried
+/// with `GetCommEventCounter` (`0x0B`) and `GetCommEventLog` (`0x0C`) requests.
+///
+/// [`process_request`] records a receive event for each request and
+/// a send event for each response. Events that are detected while
+/// receiving frames, e.g. CRC errors, have to be recorded by the application.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{server::CommEventLog, CommEvent};
+///
+/// let mut log = CommEventLog::new();
+/// log.push(CommEvent::Restart);
+/// log.push(CommEvent::EnteredListenOnly);
+/// assert!(log.events().eq([CommEvent::EnteredListenOnly, CommEvent::Restart]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CommEventLog {
+    /// Number of successfully processed requests.
+    ///
+    /// Exception responses and `GetCommEventCounter` requests are not counted.
+    pub event_count: u16,
+    /// The encoded events, the most recent first.
+    events: [u8; MAX_COMM_EVENTS],
+    len: usize,
+}
+
+impl Default for CommEventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommEventLog {
+    /// Create an empty log.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            event_count: 0,
+            events: [0; MAX_COMM_EVENTS],
+            len: 0,
+        }
+    }
+
+    /// Record an `event`.
+    ///
+    /// The oldest event is dropped if the log already
+    /// contains [`MAX_COMM_EVENTS`] events.
+    pub fn push(&mut self, event: CommEvent) {
+        self.events.copy_within(..MAX_COMM_EVENTS - 1, 1);
+        self.events[0] = event.value();
+        self.len = (self.len + 1).min(MAX_COMM_EVENTS);
+    }
+
+    /// The recorded events, the most recent first.
+    pub fn events(&self) -> impl Iterator<Item = CommEvent> + '_ {
+        // Only valid events are recorded
+        self.as_bytes()
+            .iter()
+            .filter_map(|event| CommEvent::try_from(*event).ok())
+    }
+
+    /// The number of recorded events.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all events, but keep the event counter.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// The encoded events, the most recent first.
+    fn as_bytes(&self) -> &[u8] {
+        &self.events[..self.len]
+    }
+}
+
+/// Record the receive event of a request before it is processed.
+pub(crate) fn log_request<M>(model: &mut M)
+where
+    M: DataModel + ?Sized,
+{
+    let listen_only = is_listen_only(model);
+    if let Some(log) = model.comm_event_log() {
+        log.push(CommEvent::Receive(ReceiveEvent {
+            listen_only,
+            ..Default::default()
+        }));
+    }
+}
+
+/// Record the send event of the response to `request` and
+/// count the successfully processed requests.
+///
+/// Responses that have been `suppressed` in listen only mode
+/// are recorded as well, other missing responses are not.
+pub(crate) fn log_response<M>(
+    model: &mut M,
+    request: &Request<'_>,
+    rsp: Option<&ResponsePdu<'_>>,
+    suppressed: bool,
+) where
+    M: DataModel + ?Sized,
+{
+    let Some(log) = model.comm_event_log() else {
+        return;
+    };
+    let mut event = SendEvent::default();
+    match rsp {
+        Some(ResponsePdu(Ok(_))) => {
+            if !matches!(request, Request::GetCommEventCounter) {
+                log.event_count = log.event_count.wrapping_add(1);
+            }
+        }
+        Some(ResponsePdu(Err(ExceptionResponse { exception, .. }))) => match exception.value() {
+            1..=3 => event.read_exception = true,
+            4 => event.abort_exception = true,
+            5 | 6 => event.busy_exception = true,
+            7 => event.nak_exception = true,
+            _ => {}
+        },
+        None if suppressed => event.listen_only = true,
+        None => return,
+    }
+    log.push(CommEvent::Send(event));
+}
+
+/// Apply a `GetCommEventCounter` request.
+pub(crate) fn get_comm_event_counter<'b, M>(model: &mut M) -> Result<Response<'b>, Failure>
+where
+    M: DataModel + ?Sized,
+{
+    let log = model.comm_event_log().ok_or(Exception::IllegalFunction)?;
+    Ok(Response::GetCommEventCounter(READY, log.event_count))
+}
+
+/// Apply a `GetCommEventLog` request.
+pub(crate) fn get_comm_event_log<'b, M>(
+    model: &mut M,
+    buf: &'b mut [u8],
+) -> Result<Response<'b>, Failure>
+where
+    M: DataModel + ?Sized,
+{
+    let message_count = model
+        .comm_counters()
+        .map_or(0, |counters| counters.bus_messages);
+    let log = model.comm_event_log().ok_or(Exception::IllegalFunction)?;
+    let events = buf.get_mut(..log.len()).ok_or(Error::BufferSize)?;
+    events.copy_from_slice(log.as_bytes());
+    Ok(Response::GetCommEventLog(
+        READY,
+        log.event_count,
+        message_count,
+        events,
+    ))
+}
+
+/// Apply a `Diagnostics` request.
+pub(crate) fn apply_diagnostics<'b, M>(
+    model: &mut M,
+    sub_function: SubFunctionCode,
+    words: Data<'_>,
+    buf: &'b mut [u8],
+) -> Result<Response<'b>, Failure>
+where
+    M: DataModel + ?Sized,
+{
+    let value = match sub_function {
+        RETURN_QUERY_DATA => None,
+        RESTART_COMMUNICATIONS => {
+            let clear_log = match words.get(0) {
+                Some(0) if words.len() == 1 => false,
+                Some(CLEAR_LOG) if words.len() == 1 => true,
+                _ => return Err(Exception::IllegalDataValue.into()),
+            };
+            let listen_only = is_listen_only(model);
+            model.restart_communications(clear_log)?;
+            if let Some(counters) = model.comm_counters() {
+                counters.clear();
+            }
+            if let Some(mode) = model.comm_mode() {
+                *mode = CommMode::Online;
+            }
+            if let Some(log) = model.comm_event_log() {
+                if clear_log {
+                    log.clear();
+                }
+                log.event_count = 0;
+                log.push(CommEvent::Restart);
+            }
+            if listen_only {
+                // The port is restarted, but the request is not answered.
+                return Err(Failure::NoResponse);
+            }
+            None
+        }
+        FORCE_LISTEN_ONLY => {
+            check_zero(words)?;
+            model.force_listen_only()?;
+            if let Some(mode) = model.comm_mode() {
+                *mode = CommMode::ListenOnly;
+            }
+            if let Some(log) = model.comm_event_log() {
+                log.push(CommEvent::EnteredListenOnly);
+            }
+            return Err(Failure::NoResponse);
+        }
+        _ => {
+            check_zero(words)?;
+            let counters = model.comm_counters().ok_or(Exception::IllegalFunction)?;
+            match sub_function {
+                RETURN_DIAGNOSTIC_REGISTER => Some(counters.diagnostic_register),
+                CLEAR_COUNTERS => {
+                    counters.clear();
+                    if let Some(log) = model.comm_event_log() {
+                        log.event_count = 0;
+                    }
+                    None
+                }
+                CLEAR_OVERRUN_COUNTER => {
+                    counters.bus_char_overruns = 0;
+                    None
+                }
+                _ => {
+                    let counter =
+                        DiagnosticsCounter::new(sub_function).ok_or(Exception::IllegalFunction)?;
+                    Some(counters.get(counter))
+                }
+            }
+        }
+    };
+    let words = if let Some(value) = value {
+        Data::from_words(&[value], buf)?
+    } else {
+        // Echo the request data
+        let data = buf.get_mut(..words.data.len()).ok_or(Error::BufferSize)?;
+        data.copy_from_slice(words.data);
+        Data {
+            data,
+            quantity: words.len(),
+        }
+    };
+    Ok(Response::Diagnostics(sub_function, words))
+}
+
+/// Check if a request must be ignored, because the server
+/// is in listen only mode.
+///
+/// Only Restart Communications requests are processed in listen only mode,
+/// but they are not answered either.
+pub(crate) fn is_suppressed<M>(model: &mut M, request: &Request<'_>) -> bool
+where
+    M: DataModel + ?Sized,
+{
+    is_listen_only(model) && !matches!(request, Request::Diagnostics(RESTART_COMMUNICATIONS, _))
+}
+
+fn is_listen_only<M>(model: &mut M) -> bool
+where
+    M: DataModel + ?Sized,
+{
+    match model.comm_mode() {
+        Some(mode) => mode.is_listen_only(),
+        None => false,
+    }
+}
+
+/// Most sub-functions expect a single data word with the value `0`.
+fn check_zero(words: Data<'_>) -> Result<(), Exception> {
+    if words.data == [0, 0] {
+        Ok(())
+    } else {
+        Err(Exception::IllegalDataValue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Device {
+        counters: CommCounters,
+        log: CommEventLog,
+        listen_only: bool,
+        restarts: usize,
+        cleared_log: bool,
+    }
+
+    impl DataModel for Device {
+        fn comm_counters(&mut self) -> Option<&mut CommCounters> {
+            Some(&mut self.counters)
+        }
+
+        fn comm_event_log(&mut self) -> Option<&mut CommEventLog> {
+            Some(&mut self.log)
+        }
+
+        fn restart_communications(&mut self, clear_log: bool) -> Result<(), Exception> {
+            self.restarts += 1;
+            self.cleared_log = clear_log;
+            Ok(())
+        }
+
+        fn force_listen_only(&mut self) -> Result<(), Exception> {
+            self.listen_only = true;
+            Ok(())
+        }
+    }
+
+    fn diagnostics(sub_function: SubFunctionCode, data: &[u8]) -> Request<'_> {
+        Request::Diagnostics(
+            sub_function,
+            Data {
+                data,
+                quantity: data.len() / 2,
+            },
+        )
+    }
+
+    #[test]
+    fn return_query_data() {
+        let mut model = Device::default();
+        let buf = &mut [0; 4];
+        let req = diagnostics(RETURN_QUERY_DATA, &[0xA5, 0x37]);
+        let rsp = process_request(&mut model, req, buf).unwrap();
+        assert_eq!(
+            rsp,
+            Some(ResponsePdu(Ok(Response::Diagnostics(
+                RETURN_QUERY_DATA,
+                Data {
+                    data: &[0xA5, 0x37],
+                    quantity: 1
+                }
+            ))))
+        );
+    }
+
+    #[test]
+    fn return_counters() {
+        let mut model = Device::default();
+        model.counters.bus_messages = 42;
+        let buf = &mut [0; 4];
+
+        let rsp = process_request(&mut model, diagnostics(0x0B, &[0, 0]), buf).unwrap();
+        let Some(ResponsePdu(Ok(Response::Diagnostics(0x0B, words)))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(words.get(0), Some(42));
+
+        let rsp = process_request(&mut model, diagnostics(0x0E, &[0, 0]), buf).unwrap();
+        let Some(ResponsePdu(Ok(Response::Diagnostics(0x0E, words)))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(words.get(0), Some(1));
+
+        let rsp = process_request(&mut model, diagnostics(0x0B, &[0, 1]), buf).unwrap();
+        assert_eq!(
+            rsp,
+            Some(ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::Diagnostics,
+                exception: Exception::IllegalDataValue,
+            })))
+        );
+        assert_eq!(model.counters.bus_exception_errors, 1);
+
+        let rsp = process_request(&mut model, diagnostics(CLEAR_COUNTERS, &[0, 0]), buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Ok(_)))));
+        assert_eq!(
+            model.counters,
+            CommCounters {
+                server_messages: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn count_exception_responses() {
+        let mut counters = CommCounters::default();
+        let req = Request::ReadHoldingRegisters(0, 1);
+        let exception = |exception| {
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception,
+            }))
+        };
+        counters.count_response(&req, Some(&exception(Exception::ServerDeviceBusy)));
+        counters.count_response(&req, Some(&exception(Exception::Custom(0x07))));
+        counters.count_response(&req, Some(&exception(Exception::IllegalDataAddress)));
+        counters.count_response(&req, None);
+        assert_eq!(
+            counters,
+            CommCounters {
+                bus_exception_errors: 3,
+                server_messages: 4,
+                server_no_responses: 1,
+                server_naks: 1,
+                server_busy: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(counters.get(DiagnosticsCounter::ServerNaks), 1);
+    }
+
+    #[test]
+    fn restart_communications() {
+        let mut model = Device::default();
+        model.counters.bus_comm_errors = 3;
+        let buf = &mut [0; 4];
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0xFF, 0x00]);
+        let rsp = process_request(&mut model, req, buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Ok(_)))));
+        assert_eq!(model.restarts, 1);
+        assert!(model.cleared_log);
+        assert_eq!(model.counters, CommCounters::default());
+
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0x12, 0x34]);
+        let rsp = process_request(&mut model, req, buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Err(_)))));
+        assert_eq!(model.restarts, 1);
+    }
+
+    #[test]
+    fn restart_and_clear_comm_event_log() {
+        let mut model = Device::default();
+        let buf = &mut [0; 4];
+        model.log.push(CommEvent::EnteredListenOnly);
+        model.log.event_count = 7;
+
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0x00, 0x00]);
+        process_request(&mut model, req, buf).unwrap();
+        assert!(!model.cleared_log);
+        assert_eq!(model.log.len(), 4);
+        assert_eq!(model.log.event_count, 1);
+
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0xFF, 0x00]);
+        process_request(&mut model, req, buf).unwrap();
+        assert!(model.cleared_log);
+        assert!(model
+            .log
+            .events()
+            .eq([CommEvent::Send(SendEvent::default()), CommEvent::Restart]));
+        assert_eq!(model.log.event_count, 1);
+    }
+
+    #[test]
+    fn force_listen_only() {
+        let mut model = Device::default();
+        let buf = &mut [0; 4];
+        let req = diagnostics(FORCE_LISTEN_ONLY, &[0, 0]);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+        assert!(model.listen_only);
+        assert_eq!(model.counters.server_no_responses, 1);
+    }
+
+    #[test]
+    fn comm_event_log() {
+        let mut model = Device::default();
+        model.counters.bus_messages = 3;
+        let buf = &mut [0; 8];
+
+        let rsp = process_request(&mut model, diagnostics(0x0B, &[0, 0]), buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Ok(_)))));
+        let rsp = process_request(&mut model, diagnostics(0x0B, &[0, 1]), buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Err(_)))));
+        let rsp = process_request(&mut model, Request::GetCommEventCounter, buf).unwrap();
+        assert_eq!(
+            rsp,
+            Some(ResponsePdu(Ok(Response::GetCommEventCounter(READY, 1))))
+        );
+
+        let rsp = process_request(&mut model, Request::GetCommEventLog, buf).unwrap();
+        let receive = CommEvent::Receive(ReceiveEvent::default()).value();
+        let sent = CommEvent::Send(SendEvent::default()).value();
+        let exception = CommEvent::Send(SendEvent {
+            read_exception: true,
+            ..Default::default()
+        })
+        .value();
+        assert_eq!(
+            rsp,
+            Some(ResponsePdu(Ok(Response::GetCommEventLog(
+                READY,
+                1,
+                3,
+                &[receive, sent, receive, exception, receive, sent, receive]
+            ))))
+        );
+
+        let req = diagnostics(FORCE_LISTEN_ONLY, &[0, 0]);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0, 0]);
+        process_request(&mut model, req, buf).unwrap();
+        assert_eq!(model.log.event_count, 1);
+        assert!(model.log.events().take(5).eq([
+            CommEvent::Send(SendEvent::default()),
+            CommEvent::Restart,
+            CommEvent::Receive(ReceiveEvent::default()),
+            CommEvent::EnteredListenOnly,
+            CommEvent::Receive(ReceiveEvent::default()),
+        ]));
+    }
+
+    #[test]
+    fn keep_most_recent_events() {
+        let mut log = CommEventLog::new();
+        log.push(CommEvent::Restart);
+        for _ in 0..MAX_COMM_EVENTS {
+            log.push(CommEvent::EnteredListenOnly);
+        }
+        assert_eq!(log.len(), MAX_COMM_EVENTS);
+        assert!(log
+            .events()
+            .all(|event| event == CommEvent::EnteredListenOnly));
+        log.clear();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn without_counters() {
+        let mut model = Fixed;
+        let buf = &mut [0; 4];
+        let rsp = process_request(&mut model, diagnostics(0x0B, &[0, 0]), buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Err(_)))));
+        let rsp =
+            process_request(&mut model, diagnostics(RETURN_QUERY_DATA, &[1, 2]), buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Ok(_)))));
+    }
+
+    struct Fixed;
+
+    impl DataModel for Fixed {}
+
+    #[derive(Default)]
+    struct Listener {
+        mode: CommMode,
+        counters: CommCounters,
+        log: CommEventLog,
+        holding: Word,
+    }
+
+    impl DataModel for Listener {
+        fn comm_mode(&mut self) -> Option<&mut CommMode> {
+            Some(&mut self.mode)
+        }
+
+        fn comm_counters(&mut self) -> Option<&mut CommCounters> {
+            Some(&mut self.counters)
+        }
+
+        fn comm_event_log(&mut self) -> Option<&mut CommEventLog> {
+            Some(&mut self.log)
+        }
+
+        fn write_single_register(&mut self, _: Address, word: Word) -> Result<(), Exception> {
+            self.holding = word;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn listen_only_mode() {
+        let mut model = Listener::default();
+        let buf = &mut [0; 4];
+
+        let req = diagnostics(FORCE_LISTEN_ONLY, &[0, 0]);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+        assert_eq!(model.mode, CommMode::ListenOnly);
+
+        let req = Request::WriteSingleRegister(0, 7);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+        assert_eq!(model.holding, 0);
+        assert!(model.log.events().take(3).eq([
+            CommEvent::Send(SendEvent {
+                listen_only: true,
+                ..Default::default()
+            }),
+            CommEvent::Receive(ReceiveEvent {
+                listen_only: true,
+                ..Default::default()
+            }),
+            CommEvent::EnteredListenOnly,
+        ]));
+
+        let req = diagnostics(RETURN_QUERY_DATA, &[0, 1]);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+
+        let req = diagnostics(RESTART_COMMUNICATIONS, &[0, 0]);
+        assert_eq!(process_request(&mut model, req, buf).unwrap(), None);
+        assert_eq!(model.mode, CommMode::Online);
+        assert_eq!(model.counters, CommCounters::default());
+
+        let req = Request::WriteSingleRegister(0, 7);
+        let rsp = process_request(&mut model, req, buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Ok(_)))));
+        assert_eq!(model.holding, 7);
+    }
+
+    #[test]
+    fn listen_only_unsupported() {
+        let mut model = Fixed;
+        let buf = &mut [0; 4];
+        let req = diagnostics(FORCE_LISTEN_ONLY, &[0, 0]);
+        let rsp = process_request(&mut model, req, buf).unwrap();
+        assert!(matches!(rsp, Some(ResponsePdu(Err(_)))));
+    }
+}
@@ -0,0 +1,372 @@
+use super::*;
+
+/// A misbehavior that is injected by a [`FaultInjector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Fault {
+    /// Reject every access with the exception.
+    Exception(Exception),
+    /// Flip the bits of the mask in all read registers.
+    ///
+    /// Coils and discrete inputs are inverted if the mask is not zero.
+    /// Writes are not affected.
+    Corrupt(Word),
+    /// Do not answer at all.
+    ///
+    /// The access is not forwarded to the data model and
+    /// [`FaultInjector::take_no_response`] returns `true`.
+    NoResponse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Rule {
+    table: Table,
+    range: AddressRange,
+    fault: Fault,
+}
+
+/// Wraps a [`DataModel`] and injects faults into the
+/// accesses of up to `N` address ranges.
+///
+/// Useful to test clients against misbehaving servers. If the
+/// ranges of several rules with an exception or without a response
+/// overlap, the rule that was added first wins.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     server::{process_request, Block, Fault, FaultInjector, RegisterBank, Table},
+///     AddressRange, Exception, Request,
+/// };
+///
+/// let mut holding_registers = [0; 16];
+/// let bank = RegisterBank {
+///     holding_registers: Block::new(0, &mut holding_registers).unwrap(),
+///     ..Default::default()
+/// };
+/// let mut model = FaultInjector::<_, 2>::new(bank);
+/// let range = AddressRange::new(8, 8).unwrap();
+/// model
+///     .inject(Table::HoldingRegisters, range, Fault::NoResponse)
+///     .unwrap();
+///
+/// let buf = &mut [0; 256];
+/// process_request(&mut model, Request::ReadHoldingRegisters(0, 4), buf).unwrap();
+/// assert!(!model.take_no_response());
+/// process_request(&mut model, Request::ReadHoldingRegisters(6, 4), buf).unwrap();
+/// assert!(model.take_no_response()); // drop the response
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultInjector<M, const N: usize> {
+    model: M,
+    rules: [Option<Rule>; N],
+    no_response: bool,
+}
+
+impl<M, const N: usize> FaultInjector<M, N> {
+    /// Wrap `model` without any faults.
+    pub const fn new(model: M) -> Self {
+        Self {
+            model,
+            rules: [None; N],
+            no_response: false,
+        }
+    }
+
+    /// Inject `fault` into all accesses of `table` that overlap `range`.
+    ///
+    /// Fails with [`Error::BufferSize`] if `N` faults are already injected.
+    pub fn inject(&mut self, table: Table, range: AddressRange, fault: Fault) -> Result<(), Error> {
+        let slot = self
+            .rules
+            .iter_mut()
+            .find(|rule| rule.is_none())
+            .ok_or(Error::BufferSize)?;
+        *slot = Some(Rule {
+            table,
+            range,
+            fault,
+        });
+        Ok(())
+    }
+
+    /// Remove all faults.
+    pub fn clear(&mut self) {
+        self.rules = [None; N];
+    }
+
+    /// Returns `true` once if the last request must not be answered.
+    pub fn take_no_response(&mut self) -> bool {
+        core::mem::take(&mut self.no_response)
+    }
+
+    pub const fn inner(&self) -> &M {
+        &self.model
+    }
+
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.model
+    }
+
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    fn rules(&self, table: Table, address: Address, len: usize) -> impl Iterator<Item = &Rule> {
+        let accessed = Quantity::try_from(len)
+            .ok()
+            .and_then(|quantity| AddressRange::new(address, quantity));
+        self.rules.iter().flatten().filter(move |rule| {
+            rule.table == table && matches!(accessed, Some(a) if a.overlaps(&rule.range))
+        })
+    }
+
+    /// Reject the access if an exception or no response is injected.
+    fn check(&mut self, table: Table, address: Address, len: usize) -> Result<(), Exception> {
+        let fault = self
+            .rules(table, address, len)
+            .map(|rule| rule.fault)
+            .find(|fault| !matches!(fault, Fault::Corrupt(_)));
+        match fault {
+            Some(Fault::Exception(exception)) => Err(exception),
+            Some(Fault::NoResponse) => {
+                self.no_response = true;
+                Err(Exception::ServerDeviceFailure)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn corrupt<T>(
+        &self,
+        table: Table,
+        address: Address,
+        values: &mut [T],
+        corrupt: impl Fn(&mut T, Word),
+    ) {
+        for rule in self.rules(table, address, values.len()) {
+            let Fault::Corrupt(mask) = rule.fault else {
+                continue;
+            };
+            for (address, value) in (address..=Address::MAX).zip(values.iter_mut()) {
+                if rule.range.contains(address) {
+                    corrupt(value, mask);
+                }
+            }
+        }
+    }
+
+    fn corrupt_coils(&self, table: Table, address: Address, coils: &mut [Coil]) {
+        self.corrupt(table, address, coils, |coil, mask| *coil ^= mask != 0);
+    }
+
+    fn corrupt_registers(&self, table: Table, address: Address, registers: &mut [Word]) {
+        self.corrupt(table, address, registers, |word, mask| *word ^= mask);
+    }
+}
+
+impl<M: DataModel, const N: usize> DataModel for FaultInjector<M, N> {
+    fn address_range(&self, table: Table) -> Option<AddressRange> {
+        self.model.address_range(table)
+    }
+
+    fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+        self.check(Table::Coils, address, coils.len())?;
+        self.model.read_coils(address, coils)?;
+        self.corrupt_coils(Table::Coils, address, coils);
+        Ok(())
+    }
+
+    fn read_discrete_inputs(
+        &mut self,
+        address: Address,
+        inputs: &mut [Coil],
+    ) -> Result<(), Exception> {
+        self.check(Table::DiscreteInputs, address, inputs.len())?;
+        self.model.read_discrete_inputs(address, inputs)?;
+        self.corrupt_coils(Table::DiscreteInputs, address, inputs);
+        Ok(())
+    }
+
+    fn read_input_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        self.check(Table::InputRegisters, address, registers.len())?;
+        self.model.read_input_registers(address, registers)?;
+        self.corrupt_registers(Table::InputRegisters, address, registers);
+        Ok(())
+    }
+
+    fn read_holding_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        self.check(Table::HoldingRegisters, address, registers.len())?;
+        self.model.read_holding_registers(address, registers)?;
+        self.corrupt_registers(Table::HoldingRegisters, address, registers);
+        Ok(())
+    }
+
+    fn write_single_coil(&mut self, address: Address, coil: Coil) -> Result<(), Exception> {
+        self.check(Table::Coils, address, 1)?;
+        self.model.write_single_coil(address, coil)
+    }
+
+    fn write_multiple_coils(
+        &mut self,
+        address: Address,
+        coils: Coils<'_>,
+    ) -> Result<(), Exception> {
+        self.check(Table::Coils, address, coils.len())?;
+        self.model.write_multiple_coils(address, coils)
+    }
+
+    fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+        self.check(Table::HoldingRegisters, address, 1)?;
+        self.model.write_single_register(address, word)
+    }
+
+    fn write_multiple_registers(
+        &mut self,
+        address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        self.check(Table::HoldingRegisters, address, words.len())?;
+        self.model.write_multiple_registers(address, words)
+    }
+
+    fn read_write_multiple_registers(
+        &mut self,
+        read_address: Address,
+        registers: &mut [Word],
+        write_address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        self.check(Table::HoldingRegisters, write_address, words.len())?;
+        self.check(Table::HoldingRegisters, read_address, registers.len())?;
+        self.model
+            .read_write_multiple_registers(read_address, registers, write_address, words)?;
+        self.corrupt_registers(Table::HoldingRegisters, read_address, registers);
+        Ok(())
+    }
+
+    fn read_fifo_queue(
+        &mut self,
+        address: Address,
+        values: &mut [Word],
+    ) -> Result<usize, Exception> {
+        self.check(Table::HoldingRegisters, address, 1)?;
+        self.model.read_fifo_queue(address, values)
+    }
+
+    fn file_record_store(&mut self) -> Option<&mut dyn FileRecordStore> {
+        self.model.file_record_store()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn read_exception_status(&mut self) -> Result<u8, Exception> {
+        self.model.read_exception_status()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn comm_counters(&mut self) -> Option<&mut CommCounters> {
+        self.model.comm_counters()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn comm_mode(&mut self) -> Option<&mut CommMode> {
+        self.model.comm_mode()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn restart_communications(&mut self, clear_log: bool) -> Result<(), Exception> {
+        self.model.restart_communications(clear_log)
+    }
+
+    #[cfg(feature = "rtu")]
+    fn force_listen_only(&mut self) -> Result<(), Exception> {
+        self.model.force_listen_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: Address, quantity: Quantity) -> AddressRange {
+        AddressRange::new(start, quantity).unwrap()
+    }
+
+    #[test]
+    fn inject_exceptions() {
+        let mut registers = [0; 8];
+        let bank = RegisterBank {
+            holding_registers: Block::new(0, &mut registers).unwrap(),
+            ..Default::default()
+        };
+        let mut model = FaultInjector::<_, 2>::new(bank);
+        let busy = Fault::Exception(Exception::ServerDeviceBusy);
+        model
+            .inject(Table::HoldingRegisters, range(4, 2), busy)
+            .unwrap();
+        model
+            .inject(Table::InputRegisters, range(0, 8), Fault::NoResponse)
+            .unwrap();
+        assert_eq!(
+            model.inject(Table::Coils, range(0, 1), busy),
+            Err(Error::BufferSize)
+        );
+
+        assert_eq!(model.write_single_register(3, 1), Ok(()));
+        assert_eq!(
+            model.write_single_register(5, 1),
+            Err(Exception::ServerDeviceBusy)
+        );
+        assert_eq!(
+            model.read_holding_registers(2, &mut [0; 3]),
+            Err(Exception::ServerDeviceBusy)
+        );
+        assert!(!model.take_no_response());
+
+        model.clear();
+        assert_eq!(model.write_single_register(5, 1), Ok(()));
+        assert_eq!(model.inner().holding_registers.values()[3..6], [1, 0, 1]);
+    }
+
+    #[test]
+    fn corrupt_read_values() {
+        let mut registers = [0x1234; 4];
+        let mut coils = [true; 4];
+        let bank = RegisterBank {
+            holding_registers: Block::new(0x10, &mut registers).unwrap(),
+            coils: Block::new(0, &mut coils).unwrap(),
+            ..Default::default()
+        };
+        let mut model = FaultInjector::<_, 2>::new(bank);
+        model
+            .inject(
+                Table::HoldingRegisters,
+                range(0x12, 1),
+                Fault::Corrupt(0xFF00),
+            )
+            .unwrap();
+        model
+            .inject(Table::Coils, range(1, 2), Fault::Corrupt(1))
+            .unwrap();
+
+        let words = &mut [0; 4];
+        model.read_holding_registers(0x10, words).unwrap();
+        assert_eq!(words, &[0x1234, 0x1234, 0xED34, 0x1234]);
+        let bits = &mut [false; 4];
+        model.read_coils(0, bits).unwrap();
+        assert_eq!(bits, &[true, false, false, true]);
+        // The stored values are not affected
+        assert_eq!(model.into_inner().holding_registers.values()[2], 0x1234);
+    }
+}
@@ -0,0 +1,192 @@
+use super::*;
+
+/// A fixed-capacity FIFO queue of registers that can be read with
+/// a `ReadFifoQueue` (`0x18`) request addressed to its pointer address.
+///
+/// The queue may hold up to `N` values but a single response can only
+/// carry [`MAX_FIFO_COUNT`] of them. A request for a queue that holds
+/// more values is answered with [`Exception::IllegalDataValue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FifoQueue<const N: usize> {
+    pointer: Address,
+    values: [Word; N],
+    len: usize,
+}
+
+impl<const N: usize> FifoQueue<N> {
+    /// Create an empty queue that is attached to the `pointer` address.
+    #[must_use]
+    pub const fn new(pointer: Address) -> Self {
+        Self {
+            pointer,
+            values: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The pointer address of the queue.
+    #[must_use]
+    pub const fn pointer(&self) -> Address {
+        self.pointer
+    }
+
+    /// Number of queued values.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    ///  Returns `true` if the queue has no values.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The queued values, oldest first.
+    #[must_use]
+    pub fn values(&self) -> &[Word] {
+        &self.values[..self.len]
+    }
+
+    /// Append a value.
+    ///
+    /// The value is handed back if the queue is full.
+    pub fn push(&mut self, value: Word) -> Result<(), Word> {
+        if self.len == N {
+            return Err(value);
+        }
+        self.values[self.len] = value;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove and return the oldest value.
+    pub fn pop(&mut self) -> Option<Word> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = self.values[0];
+        self.values.copy_within(1..self.len, 0);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Remove all values.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Copy the queued values into `values` without removing them.
+    ///
+    /// Returns the number of copied values.
+    pub fn read(&self, values: &mut [Word]) -> Result<usize, Exception> {
+        if self.len > MAX_FIFO_COUNT {
+            return Err(Exception::IllegalDataValue);
+        }
+        let target = values
+            .get_mut(..self.len)
+            .ok_or(Exception::ServerDeviceFailure)?;
+        target.copy_from_slice(self.values());
+        Ok(self.len)
+    }
+}
+
+impl<const N: usize> DataModel for FifoQueue<N> {
+    fn read_fifo_queue(
+        &mut self,
+        address: Address,
+        values: &mut [Word],
+    ) -> Result<usize, Exception> {
+        if address != self.pointer {
+            return Err(Exception::IllegalDataAddress);
+        }
+        self.read(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_pop() {
+        let mut fifo = FifoQueue::<2>::new(0);
+        assert!(fifo.is_empty());
+        assert_eq!(fifo.pop(), None);
+        fifo.push(1).unwrap();
+        fifo.push(2).unwrap();
+        assert_eq!(fifo.push(3), Err(3));
+        assert_eq!(fifo.values(), &[1, 2]);
+        assert_eq!(fifo.pop(), Some(1));
+        assert_eq!(fifo.values(), &[2]);
+        fifo.clear();
+        assert!(fifo.is_empty());
+    }
+
+    #[test]
+    fn read_fifo_queue() {
+        let mut fifo = FifoQueue::<4>::new(0x04DE);
+        fifo.push(0x01B8).unwrap();
+        fifo.push(0x1284).unwrap();
+        let buf = &mut [0; 8];
+        let rsp = process_request(&mut fifo, Request::ReadFifoQueue(0x04DE), buf)
+            .unwrap()
+            .unwrap();
+        let ResponsePdu(Ok(Response::ReadFifoQueue(data))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.get(0), Some(0x01B8));
+        assert_eq!(data.get(1), Some(0x1284));
+        // Reading does not remove the values
+        assert_eq!(fifo.len(), 2);
+    }
+
+    #[test]
+    fn read_empty_fifo_queue() {
+        let mut fifo = FifoQueue::<4>::new(0x04DE);
+        let buf = &mut [];
+        let rsp = process_request(&mut fifo, Request::ReadFifoQueue(0x04DE), buf)
+            .unwrap()
+            .unwrap();
+        let ResponsePdu(Ok(Response::ReadFifoQueue(data))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn read_fifo_queue_with_wrong_pointer() {
+        let mut fifo = FifoQueue::<4>::new(0x04DE);
+        let buf = &mut [0; 8];
+        let rsp = process_request(&mut fifo, Request::ReadFifoQueue(0x04DF), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadFifoQueue,
+                exception: Exception::IllegalDataAddress,
+            }))
+        );
+    }
+
+    #[test]
+    fn read_overflowed_fifo_queue() {
+        let mut fifo = FifoQueue::<32>::new(0);
+        for i in 0..32 {
+            fifo.push(i).unwrap();
+        }
+        let buf = &mut [0; 64];
+        let rsp = process_request(&mut fifo, Request::ReadFifoQueue(0), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadFifoQueue,
+                exception: Exception::IllegalDataValue,
+            }))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use super::*;
+
+/// The highest record number that can be addressed.
+pub const MAX_RECORD_NUMBER: RecordNumber = 9999;
+
+/// Storage of the files that can be accessed with
+/// `ReadFileRecord` (`0x14`) and `WriteFileRecord` (`0x15`) requests.
+///
+/// A file is a sequence of registers and every register
+/// is a record that can be addressed by its record number.
+/// File numbers start at `1`, record numbers at `0`.
+pub trait FileRecordStore {
+    /// Read `values.len()` registers of a file beginning at `record_number`.
+    fn read_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: &mut [Word],
+    ) -> Result<(), Exception>;
+
+    /// Write the registers of a file beginning at `record_number`.
+    fn write_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: Data<'_>,
+    ) -> Result<(), Exception>;
+}
+
+impl<S: FileRecordStore + ?Sized> FileRecordStore for &mut S {
+    fn read_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: &mut [Word],
+    ) -> Result<(), Exception> {
+        (**self).read_record(file_number, record_number, values)
+    }
+
+    fn write_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: Data<'_>,
+    ) -> Result<(), Exception> {
+        (**self).write_record(file_number, record_number, values)
+    }
+}
+
+/// A [`FileRecordStore`] that keeps its files in caller provided buffers.
+///
+/// The file with number `n` is stored in `files[n - 1]`.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::server::SliceFileStore;
+///
+/// let mut first = [0; 100];
+/// let mut second = [0; 20];
+/// let files = &mut [&mut first[..], &mut second[..]];
+/// let store = SliceFileStore::new(files);
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct SliceFileStore<'a, 'f> {
+    files: &'a mut [&'f mut [Word]],
+}
+
+impl<'a, 'f> SliceFileStore<'a, 'f> {
+    /// Use `files` as storage.
+    pub fn new(files: &'a mut [&'f mut [Word]]) -> Self {
+        Self { files }
+    }
+
+    /// Get the registers of a file.
+    #[must_use]
+    pub fn file(&self, file_number: FileNumber) -> Option<&[Word]> {
+        let index = usize::from(file_number).checked_sub(1)?;
+        self.files.get(index).map(|file| &**file)
+    }
+
+    fn records_mut(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        cnt: usize,
+    ) -> Result<&mut [Word], Exception> {
+        let start = usize::from(record_number);
+        usize::from(file_number)
+            .checked_sub(1)
+            .and_then(|index| self.files.get_mut(index))
+            .and_then(|file| file.get_mut(start..start + cnt))
+            .ok_or(Exception::IllegalDataAddress)
+    }
+}
+
+impl FileRecordStore for SliceFileStore<'_, '_> {
+    fn read_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: &mut [Word],
+    ) -> Result<(), Exception> {
+        let records = self.records_mut(file_number, record_number, values.len())?;
+        values.copy_from_slice(records);
+        Ok(())
+    }
+
+    fn write_record(
+        &mut self,
+        file_number: FileNumber,
+        record_number: RecordNumber,
+        values: Data<'_>,
+    ) -> Result<(), Exception> {
+        let records = self.records_mut(file_number, record_number, values.len())?;
+        for (i, record) in records.iter_mut().enumerate() {
+            *record = values.get(i).unwrap_or_default();
+        }
+        Ok(())
+    }
+}
+
+impl DataModel for SliceFileStore<'_, '_> {
+    fn file_record_store(&mut self) -> Option<&mut dyn FileRecordStore> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_and_write_records() {
+        let mut first = [1, 2, 3, 4];
+        let mut second = [0; 2];
+        let files = &mut [&mut first[..], &mut second[..]];
+        let mut store = SliceFileStore::new(files);
+
+        let values = &mut [0; 2];
+        store.read_record(1, 2, values).unwrap();
+        assert_eq!(values, &[3, 4]);
+        assert_eq!(
+            store.read_record(1, 3, values),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            store.read_record(0, 0, values),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            store.read_record(3, 0, values),
+            Err(Exception::IllegalDataAddress)
+        );
+
+        let words = Data {
+            data: &[0x12, 0x34],
+            quantity: 1,
+        };
+        store.write_record(2, 1, words).unwrap();
+        assert_eq!(store.file(2), Some(&[0, 0x1234][..]));
+    }
+}
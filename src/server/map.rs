@@ -0,0 +1,430 @@
+use super::*;
+use core::fmt;
+
+/// Errors of a [`ServerBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MapError {
+    /// More blocks have been added than the server can hold.
+    Capacity,
+    /// A block without any values has been added.
+    EmptyBlock(Table),
+    /// The address ranges of two blocks of the same table overlap.
+    Overlap {
+        table: Table,
+        first: AddressRange,
+        second: AddressRange,
+    },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Capacity => write!(f, "Too many blocks"),
+            Self::EmptyBlock(table) => write!(f, "Empty block of table {table:?}"),
+            Self::Overlap {
+                table,
+                first,
+                second,
+            } => write!(
+                f,
+                "Overlapping blocks of table {table:?}: 0x{:0>4X}..=0x{:0>4X} and 0x{:0>4X}..=0x{:0>4X}",
+                first.start(),
+                first.end(),
+                second.start(),
+                second.end()
+            ),
+        }
+    }
+}
+
+enum Backing<'a> {
+    /// A bank that only contains the values of this block.
+    Storage(RegisterBank<'a>),
+    /// A data model that is accessed with addresses
+    /// relative to the start of the block.
+    Handler(&'a mut dyn DataModel),
+}
+
+struct MappedBlock<'a> {
+    table: Table,
+    range: AddressRange,
+    backing: Backing<'a>,
+}
+
+/// Composes a [`MappedServer`] of up to `N` blocks.
+///
+/// Each block covers a range of addresses of a single table and
+/// is either backed by a caller provided buffer or by a handler.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     server::{process_request, Block, DataModel, ServerBuilder, Table},
+///     AddressRange, Exception, Request,
+/// };
+///
+/// /// Reports the number of the requested register.
+/// struct Echo;
+///
+/// impl DataModel for Echo {
+///     fn read_input_registers(&mut self, address: u16, words: &mut [u16]) -> Result<(), Exception> {
+///         for (word, address) in words.iter_mut().zip(address..=u16::MAX) {
+///             *word = address;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut settings = [0; 16];
+/// let mut measurements = [0; 8];
+/// let mut echo = Echo;
+/// let mut server = ServerBuilder::<4>::new()
+///     .holding_registers(Block::new(0x0000, &mut settings).unwrap())
+///     .input_registers(Block::new(0x1000, &mut measurements).unwrap())
+///     .handler(Table::InputRegisters, AddressRange::new(0x3000, 0x100).unwrap(), &mut echo)
+///     .build()
+///     .unwrap();
+///
+/// let buf = &mut [0; 256];
+/// let words = &mut [0; 2];
+/// server.read_input_registers(0x3002, words).unwrap();
+/// assert_eq!(words, &[2, 3]);
+/// let rsp = process_request(&mut server, Request::ReadInputRegisters(0x3002, 1), buf).unwrap();
+/// assert!(rsp.is_some());
+/// ```
+pub struct ServerBuilder<'a, const N: usize> {
+    blocks: [Option<MappedBlock<'a>>; N],
+    error: Option<MapError>,
+}
+
+impl<'a, const N: usize> ServerBuilder<'a, N> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            blocks: core::array::from_fn(|_| None),
+            error: None,
+        }
+    }
+
+    /// Add a block of coils.
+    #[must_use]
+    pub fn coils(self, block: Block<'a, Coil>) -> Self {
+        self.storage(
+            Table::Coils,
+            block.range(),
+            RegisterBank {
+                coils: block,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Add a block of discrete inputs.
+    #[must_use]
+    pub fn discrete_inputs(self, block: Block<'a, Coil>) -> Self {
+        self.storage(
+            Table::DiscreteInputs,
+            block.range(),
+            RegisterBank {
+                discrete_inputs: block,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Add a block of input registers.
+    #[must_use]
+    pub fn input_registers(self, block: Block<'a, Word>) -> Self {
+        self.storage(
+            Table::InputRegisters,
+            block.range(),
+            RegisterBank {
+                input_registers: block,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Add a block of holding registers.
+    #[must_use]
+    pub fn holding_registers(self, block: Block<'a, Word>) -> Self {
+        self.storage(
+            Table::HoldingRegisters,
+            block.range(),
+            RegisterBank {
+                holding_registers: block,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Forward all accesses of `table` within `range` to `handler`.
+    ///
+    /// The handler is called with addresses relative to
+    /// the start of `range`.
+    #[must_use]
+    pub fn handler(
+        self,
+        table: Table,
+        range: AddressRange,
+        handler: &'a mut dyn DataModel,
+    ) -> Self {
+        self.add(MappedBlock {
+            table,
+            range,
+            backing: Backing::Handler(handler),
+        })
+    }
+
+    fn storage(self, table: Table, range: Option<AddressRange>, bank: RegisterBank<'a>) -> Self {
+        let Some(range) = range else {
+            return self.fail(MapError::EmptyBlock(table));
+        };
+        self.add(MappedBlock {
+            table,
+            range,
+            backing: Backing::Storage(bank),
+        })
+    }
+
+    fn add(mut self, block: MappedBlock<'a>) -> Self {
+        match self.blocks.iter_mut().find(|b| b.is_none()) {
+            Some(slot) => {
+                *slot = Some(block);
+                self
+            }
+            None => self.fail(MapError::Capacity),
+        }
+    }
+
+    fn fail(mut self, err: MapError) -> Self {
+        self.error.get_or_insert(err);
+        self
+    }
+
+    /// Create the server.
+    ///
+    /// Fails if a block could not be added or if blocks overlap.
+    pub fn build(self) -> Result<MappedServer<'a, N>, MapError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let blocks = self.blocks.iter().flatten();
+        for (i, first) in blocks.clone().enumerate() {
+            if let Some(second) = blocks
+                .clone()
+                .skip(i + 1)
+                .find(|b| b.table == first.table && b.range.overlaps(&first.range))
+            {
+                return Err(MapError::Overlap {
+                    table: first.table,
+                    first: first.range,
+                    second: second.range,
+                });
+            }
+        }
+        Ok(MappedServer {
+            blocks: self.blocks,
+        })
+    }
+}
+
+impl<const N: usize> Default for ServerBuilder<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`DataModel`] that is composed of several blocks, see [`ServerBuilder`].
+///
+/// Accesses that are not completely within a single block
+/// are rejected with [`Exception::IllegalDataAddress`].
+pub struct MappedServer<'a, const N: usize> {
+    blocks: [Option<MappedBlock<'a>>; N],
+}
+
+impl<const N: usize> MappedServer<'_, N> {
+    /// Find the block of an access and the address to access it with.
+    fn route(
+        &mut self,
+        table: Table,
+        address: Address,
+        cnt: usize,
+    ) -> Result<(&mut dyn DataModel, Address), Exception> {
+        let accessed = Quantity::try_from(cnt)
+            .ok()
+            .and_then(|quantity| AddressRange::new(address, quantity))
+            .ok_or(Exception::IllegalDataValue)?;
+        let block = self
+            .blocks
+            .iter_mut()
+            .flatten()
+            .find(|b| b.table == table && b.range.contains_range(&accessed))
+            .ok_or(Exception::IllegalDataAddress)?;
+        let start = block.range.start();
+        Ok(match &mut block.backing {
+            Backing::Storage(bank) => (bank, address),
+            Backing::Handler(handler) => (&mut **handler, address - start),
+        })
+    }
+}
+
+impl<const N: usize> DataModel for MappedServer<'_, N> {
+    fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::Coils, address, coils.len())?;
+        model.read_coils(address, coils)
+    }
+
+    fn read_discrete_inputs(
+        &mut self,
+        address: Address,
+        inputs: &mut [Coil],
+    ) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::DiscreteInputs, address, inputs.len())?;
+        model.read_discrete_inputs(address, inputs)
+    }
+
+    fn read_input_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::InputRegisters, address, registers.len())?;
+        model.read_input_registers(address, registers)
+    }
+
+    fn read_holding_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::HoldingRegisters, address, registers.len())?;
+        model.read_holding_registers(address, registers)
+    }
+
+    fn write_single_coil(&mut self, address: Address, coil: Coil) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::Coils, address, 1)?;
+        model.write_single_coil(address, coil)
+    }
+
+    fn write_multiple_coils(
+        &mut self,
+        address: Address,
+        coils: Coils<'_>,
+    ) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::Coils, address, coils.len())?;
+        model.write_multiple_coils(address, coils)
+    }
+
+    fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::HoldingRegisters, address, 1)?;
+        model.write_single_register(address, word)
+    }
+
+    fn write_multiple_registers(
+        &mut self,
+        address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        let (model, address) = self.route(Table::HoldingRegisters, address, words.len())?;
+        model.write_multiple_registers(address, words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant(Word);
+
+    impl DataModel for Constant {
+        fn read_holding_registers(
+            &mut self,
+            address: Address,
+            registers: &mut [Word],
+        ) -> Result<(), Exception> {
+            for (register, offset) in registers.iter_mut().zip(address..=Address::MAX) {
+                *register = self.0 + offset;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn route_to_blocks() {
+        let low = &mut [1, 2, 3, 4];
+        let coils = &mut [false; 8];
+        let constant = &mut Constant(0x100);
+        let mut server = ServerBuilder::<3>::new()
+            .holding_registers(Block::new(0x0000, low).unwrap())
+            .coils(Block::new(0x0000, coils).unwrap())
+            .handler(
+                Table::HoldingRegisters,
+                AddressRange::new(0x1000, 0x10).unwrap(),
+                constant,
+            )
+            .build()
+            .unwrap();
+
+        let words = &mut [0; 2];
+        server.read_holding_registers(0x0002, words).unwrap();
+        assert_eq!(words, &[3, 4]);
+        server.read_holding_registers(0x1004, words).unwrap();
+        assert_eq!(words, &[0x104, 0x105]);
+        assert_eq!(
+            server.read_holding_registers(0x0003, words),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            server.read_holding_registers(0x100F, words),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            server.write_single_register(0x1000, 0),
+            Err(Exception::IllegalFunction)
+        );
+
+        server.write_single_coil(7, true).unwrap();
+        let bits = &mut [false; 2];
+        server.read_coils(6, bits).unwrap();
+        assert_eq!(bits, &[false, true]);
+        assert_eq!(
+            server.read_input_registers(0, words),
+            Err(Exception::IllegalDataAddress)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_maps() {
+        let a = &mut [0; 4];
+        let b = &mut [0; 4];
+        let err = ServerBuilder::<2>::new()
+            .holding_registers(Block::new(0x10, a).unwrap())
+            .holding_registers(Block::new(0x13, b).unwrap())
+            .build()
+            .err();
+        assert_eq!(
+            err,
+            Some(MapError::Overlap {
+                table: Table::HoldingRegisters,
+                first: AddressRange::new(0x10, 4).unwrap(),
+                second: AddressRange::new(0x13, 4).unwrap(),
+            })
+        );
+
+        let a = &mut [0; 4];
+        let b = &mut [0; 4];
+        let builder = ServerBuilder::<1>::new()
+            .holding_registers(Block::new(0x10, a).unwrap())
+            .input_registers(Block::new(0x10, b).unwrap());
+        assert_eq!(builder.build().err(), Some(MapError::Capacity));
+
+        let builder = ServerBuilder::<1>::new().coils(Block::default());
+        assert_eq!(
+            builder.build().err(),
+            Some(MapError::EmptyBlock(Table::Coils))
+        );
+    }
+}
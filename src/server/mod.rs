@@ -0,0 +1,909 @@
+//! Modbus server (slave) data model and request processing.
+//!
+//! The transport specific [`rtu::server`](crate::rtu::server) and
+//! [`tcp::server`](crate::tcp::server) modules take care of the framing.
+//! This module maps decoded requests onto an application provided
+//! [`DataModel`] and builds the matching response.
+
+use crate::{error::*, frame::*};
+
+mod acl;
+mod bank;
+#[cfg(feature = "rtu")]
+mod diagnostics;
+mod fault;
+mod fifo;
+mod file_record;
+mod map;
+mod router;
+mod validate;
+
+pub use self::{
+    acl::*, bank::*, fault::*, fifo::*, file_record::*, map::*, router::*, validate::*,
+};
+
+#[cfg(feature = "rtu")]
+pub use self::diagnostics::{CommCounters, CommEventLog, CommMode};
+
+/// Maximum number of bytes of all sub-requests or sub-responses
+/// of a `ReadFileRecord` request or response.
+const MAX_FILE_RECORD_BYTES: usize = 0xF5;
+
+/// Maximum number of bytes of all records of a `WriteFileRecord` request.
+const MAX_WRITE_FILE_RECORD_BYTES: usize = 0xFB;
+
+/// Maximum number of values that can be read from a FIFO queue.
+pub const MAX_FIFO_COUNT: usize = 31;
+
+/// The primary tables of the Modbus data model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Table {
+    Coils,
+    DiscreteInputs,
+    InputRegisters,
+    HoldingRegisters,
+}
+
+/// The data model of a Modbus server (slave).
+///
+/// Every method returns [`Exception::IllegalFunction`] by default,
+/// so an implementation only has to provide the tables it actually has.
+pub trait DataModel {
+    /// The range of valid addresses of a table.
+    ///
+    /// If a range is returned, requests that start or extend outside of it are
+    /// answered with [`Exception::IllegalDataAddress`] without calling the
+    /// read or write methods.
+    /// The default implementation returns `None` and leaves
+    /// the address validation to the implementor.
+    fn address_range(&self, table: Table) -> Option<AddressRange> {
+        let _ = table;
+        None
+    }
+
+    /// Read `coils.len()` coils starting at `address`.
+    fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+        let _ = (address, coils);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read `inputs.len()` discrete inputs starting at `address`.
+    fn read_discrete_inputs(
+        &mut self,
+        address: Address,
+        inputs: &mut [Coil],
+    ) -> Result<(), Exception> {
+        let _ = (address, inputs);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read `registers.len()` input registers starting at `address`.
+    fn read_input_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        let _ = (address, registers);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read `registers.len()` holding registers starting at `address`.
+    fn read_holding_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        let _ = (address, registers);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write a single coil.
+    fn write_single_coil(&mut self, address: Address, coil: Coil) -> Result<(), Exception> {
+        let _ = (address, coil);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write multiple coils starting at `address`.
+    ///
+    /// All coils of the request are passed at once and have to be
+    /// written all or nothing: no coil may have changed if an
+    /// exception is returned.
+    fn write_multiple_coils(
+        &mut self,
+        address: Address,
+        coils: Coils<'_>,
+    ) -> Result<(), Exception> {
+        let _ = (address, coils);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write a single holding register.
+    fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+        let _ = (address, word);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write multiple holding registers starting at `address`.
+    ///
+    /// All registers of the request are passed at once and have to be
+    /// written all or nothing: no register may have changed if an
+    /// exception is returned. This way related values, e.g. a setpoint
+    /// and its enable flag, are never observed partially applied.
+    fn write_multiple_registers(
+        &mut self,
+        address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        let _ = (address, words);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write `words` starting at `write_address` and read `registers.len()`
+    /// holding registers starting at `read_address` afterwards.
+    ///
+    /// The specification demands that the write operation is performed
+    /// before the read operation. As with a single write nothing may
+    /// have been written if an exception is returned.
+    ///
+    /// The default implementation reads the holding registers into
+    /// `registers` once before anything is written, so that an invalid
+    /// read range is rejected without any changes. Then it calls
+    /// [`write_multiple_registers`](Self::write_multiple_registers)
+    /// and [`read_holding_registers`](Self::read_holding_registers).
+    /// Override it if reading has side effects, e.g. of clear-on-read
+    /// registers, or if both operations have to be applied atomically.
+    fn read_write_multiple_registers(
+        &mut self,
+        read_address: Address,
+        registers: &mut [Word],
+        write_address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        self.read_holding_registers(read_address, registers)?;
+        self.write_multiple_registers(write_address, words)?;
+        self.read_holding_registers(read_address, registers)
+    }
+
+    /// Read the FIFO queue with the pointer `address` without clearing it.
+    ///
+    /// `values` has room for [`MAX_FIFO_COUNT`] values.
+    /// Returns the number of values that have been copied.
+    fn read_fifo_queue(
+        &mut self,
+        address: Address,
+        values: &mut [Word],
+    ) -> Result<usize, Exception> {
+        let _ = (address, values);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// The files that can be accessed with `ReadFileRecord` and
+    /// `WriteFileRecord` requests.
+    ///
+    /// The default implementation returns `None` and both
+    /// requests are answered with [`Exception::IllegalFunction`].
+    fn file_record_store(&mut self) -> Option<&mut dyn FileRecordStore> {
+        None
+    }
+
+    /// Read the eight exception status coils (`ReadExceptionStatus`).
+    ///
+    /// The meaning of the bits is device specific.
+    #[cfg(feature = "rtu")]
+    fn read_exception_status(&mut self) -> Result<u8, Exception> {
+        Err(Exception::IllegalFunction)
+    }
+
+    /// The communication counters that are reported and
+    /// maintained by `Diagnostics` requests.
+    ///
+    /// The default implementation returns `None` and all sub-functions
+    /// that access the counters are answered with [`Exception::IllegalFunction`].
+    #[cfg(feature = "rtu")]
+    fn comm_counters(&mut self) -> Option<&mut CommCounters> {
+        None
+    }
+
+    /// The communication mode that is switched by `Diagnostics` requests.
+    ///
+    /// While the mode is [`CommMode::ListenOnly`] all requests except
+    /// Restart Communications are ignored and never answered.
+    /// The default implementation returns `None`, i.e. the server
+    /// does not support the listen only mode.
+    #[cfg(feature = "rtu")]
+    fn comm_mode(&mut self) -> Option<&mut CommMode> {
+        None
+    }
+
+    /// The communications event log that is maintained by [`process_request`]
+    /// and reported by `GetCommEventCounter` and `GetCommEventLog` requests.
+    ///
+    /// The default implementation returns `None` and both
+    /// requests are answered with [`Exception::IllegalFunction`].
+    #[cfg(feature = "rtu")]
+    fn comm_event_log(&mut self) -> Option<&mut CommEventLog> {
+        None
+    }
+
+    /// Restart the serial line port (Diagnostics sub-function `0x01`).
+    ///
+    /// `clear_log` is set if the request asks to clear the communications
+    /// event log, too. Afterwards the communication counters are cleared,
+    /// the listen only mode is left and the event log is updated.
+    /// The request is not answered if the server was in listen only mode.
+    /// The restart is rejected if this returns an exception.
+    /// The default implementation does nothing.
+    #[cfg(feature = "rtu")]
+    fn restart_communications(&mut self, clear_log: bool) -> Result<(), Exception> {
+        let _ = clear_log;
+        Ok(())
+    }
+
+    /// Stop answering requests (Diagnostics sub-function `0x04`).
+    ///
+    /// The request itself is not answered if this succeeds.
+    /// The default implementation only succeeds if the data model
+    /// provides a [`comm_mode`](Self::comm_mode).
+    #[cfg(feature = "rtu")]
+    fn force_listen_only(&mut self) -> Result<(), Exception> {
+        if self.comm_mode().is_some() {
+            Ok(())
+        } else {
+            Err(Exception::IllegalFunction)
+        }
+    }
+}
+
+impl<M: DataModel + ?Sized> DataModel for &mut M {
+    fn address_range(&self, table: Table) -> Option<AddressRange> {
+        (**self).address_range(table)
+    }
+
+    fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+        (**self).read_coils(address, coils)
+    }
+
+    fn read_discrete_inputs(
+        &mut self,
+        address: Address,
+        inputs: &mut [Coil],
+    ) -> Result<(), Exception> {
+        (**self).read_discrete_inputs(address, inputs)
+    }
+
+    fn read_input_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        (**self).read_input_registers(address, registers)
+    }
+
+    fn read_holding_registers(
+        &mut self,
+        address: Address,
+        registers: &mut [Word],
+    ) -> Result<(), Exception> {
+        (**self).read_holding_registers(address, registers)
+    }
+
+    fn write_single_coil(&mut self, address: Address, coil: Coil) -> Result<(), Exception> {
+        (**self).write_single_coil(address, coil)
+    }
+
+    fn write_multiple_coils(
+        &mut self,
+        address: Address,
+        coils: Coils<'_>,
+    ) -> Result<(), Exception> {
+        (**self).write_multiple_coils(address, coils)
+    }
+
+    fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+        (**self).write_single_register(address, word)
+    }
+
+    fn write_multiple_registers(
+        &mut self,
+        address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        (**self).write_multiple_registers(address, words)
+    }
+
+    fn read_write_multiple_registers(
+        &mut self,
+        read_address: Address,
+        registers: &mut [Word],
+        write_address: Address,
+        words: Data<'_>,
+    ) -> Result<(), Exception> {
+        (**self).read_write_multiple_registers(read_address, registers, write_address, words)
+    }
+
+    fn read_fifo_queue(
+        &mut self,
+        address: Address,
+        values: &mut [Word],
+    ) -> Result<usize, Exception> {
+        (**self).read_fifo_queue(address, values)
+    }
+
+    fn file_record_store(&mut self) -> Option<&mut dyn FileRecordStore> {
+        (**self).file_record_store()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn read_exception_status(&mut self) -> Result<u8, Exception> {
+        (**self).read_exception_status()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn comm_counters(&mut self) -> Option<&mut CommCounters> {
+        (**self).comm_counters()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn comm_mode(&mut self) -> Option<&mut CommMode> {
+        (**self).comm_mode()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn comm_event_log(&mut self) -> Option<&mut CommEventLog> {
+        (**self).comm_event_log()
+    }
+
+    #[cfg(feature = "rtu")]
+    fn restart_communications(&mut self, clear_log: bool) -> Result<(), Exception> {
+        (**self).restart_communications(clear_log)
+    }
+
+    #[cfg(feature = "rtu")]
+    fn force_listen_only(&mut self) -> Result<(), Exception> {
+        (**self).force_listen_only()
+    }
+}
+
+/// Process a request by applying it to a [`DataModel`].
+///
+/// The payload of the response (e.g. the values of read registers)
+/// is written into `buf`.
+/// Requests that violate the limits of the specification are rejected
+/// (see [`validate_request`]) before the data model is accessed.
+/// Exceptions raised by the data model are returned as exception response.
+/// `None` is returned if the request must not be answered at all
+/// (e.g. after switching to listen only mode).
+/// An [`Error`] is only returned if `buf` is too small.
+pub fn process_request<'b, M>(
+    model: &mut M,
+    request: Request<'_>,
+    buf: &'b mut [u8],
+) -> Result<Option<ResponsePdu<'b>>, Error>
+where
+    M: DataModel + ?Sized,
+{
+    let function = FunctionCode::from(request);
+    #[cfg(feature = "rtu")]
+    diagnostics::log_request(model);
+    #[cfg(feature = "rtu")]
+    let suppressed = diagnostics::is_suppressed(model, &request);
+    let rsp = match apply_request(model, request, buf) {
+        Ok(rsp) => Some(ResponsePdu(Ok(rsp))),
+        Err(Failure::Exception(exception)) => Some(ResponsePdu(Err(ExceptionResponse {
+            function,
+            exception,
+        }))),
+        Err(Failure::NoResponse) => None,
+        Err(Failure::Error(err)) => return Err(err),
+    };
+    #[cfg(feature = "rtu")]
+    if let Some(counters) = model.comm_counters() {
+        counters.count_response(&request, rsp.as_ref());
+    }
+    #[cfg(feature = "rtu")]
+    diagnostics::log_response(model, &request, rsp.as_ref(), suppressed);
+    Ok(rsp)
+}
+
+/// Reasons why a request could not be applied.
+pub(crate) enum Failure {
+    /// The request has to be answered with an exception.
+    Exception(Exception),
+    /// The request has been processed but must not be answered.
+    #[cfg_attr(not(feature = "rtu"), allow(dead_code))]
+    NoResponse,
+    /// No response could be created at all.
+    Error(Error),
+}
+
+impl From<Exception> for Failure {
+    fn from(exception: Exception) -> Self {
+        Self::Exception(exception)
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Self::Error(err)
+    }
+}
+
+fn apply_request<'b, M>(
+    model: &mut M,
+    request: Request<'_>,
+    buf: &'b mut [u8],
+) -> Result<Response<'b>, Failure>
+where
+    M: DataModel + ?Sized,
+{
+    use Request as R;
+
+    #[cfg(feature = "rtu")]
+    if diagnostics::is_suppressed(model, &request) {
+        return Err(Failure::NoResponse);
+    }
+
+    validate_request(&request)?;
+    validate_addresses(model, &request)?;
+
+    let rsp = match request {
+        R::ReadCoils(address, quantity) | R::ReadDiscreteInputs(address, quantity) => {
+            let mut coils = [false; MAX_READ_COILS];
+            let coils = coils
+                .get_mut(..usize::from(quantity))
+                .ok_or(Exception::IllegalDataValue)?;
+            if matches!(request, R::ReadCoils(_, _)) {
+                model.read_coils(address, coils)?;
+            } else {
+                model.read_discrete_inputs(address, coils)?;
+            }
+            buf.get_mut(..packed_coils_len(coils.len()))
+                .ok_or(Error::BufferSize)?
+                .fill(0);
+            let coils = Coils::from_bools(coils, buf)?;
+            if matches!(request, R::ReadCoils(_, _)) {
+                Response::ReadCoils(coils)
+            } else {
+                Response::ReadDiscreteInputs(coils)
+            }
+        }
+        R::ReadInputRegisters(address, quantity) | R::ReadHoldingRegisters(address, quantity) => {
+            let mut words = [0; MAX_READ_REGISTERS];
+            let words = words
+                .get_mut(..usize::from(quantity))
+                .ok_or(Exception::IllegalDataValue)?;
+            if matches!(request, R::ReadInputRegisters(_, _)) {
+                model.read_input_registers(address, words)?;
+            } else {
+                model.read_holding_registers(address, words)?;
+            }
+            let data = Data::from_words(words, buf)?;
+            if matches!(request, R::ReadInputRegisters(_, _)) {
+                Response::ReadInputRegisters(data)
+            } else {
+                Response::ReadHoldingRegisters(data)
+            }
+        }
+        R::WriteSingleCoil(address, coil) => {
+            model.write_single_coil(address, coil)?;
+            Response::WriteSingleCoil(address, coil)
+        }
+        R::WriteMultipleCoils(address, coils) => {
+            model.write_multiple_coils(address, coils)?;
+            Response::WriteMultipleCoils(address, coils.len() as Quantity)
+        }
+        R::WriteSingleRegister(address, word) => {
+            model.write_single_register(address, word)?;
+            Response::WriteSingleRegister(address, word)
+        }
+        R::WriteMultipleRegisters(address, words) => {
+            model.write_multiple_registers(address, words)?;
+            Response::WriteMultipleRegisters(address, words.len() as Quantity)
+        }
+        R::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => {
+            let mut registers = [0; MAX_READ_REGISTERS];
+            let registers = registers
+                .get_mut(..usize::from(quantity))
+                .ok_or(Exception::IllegalDataValue)?;
+            model.read_write_multiple_registers(read_address, registers, write_address, words)?;
+            Response::ReadWriteMultipleRegisters(Data::from_words(registers, buf)?)
+        }
+        R::ReadFifoQueue(address) => Response::ReadFifoQueue(read_fifo_queue(model, address, buf)?),
+        R::ReadFileRecord(requests) => {
+            let store = model
+                .file_record_store()
+                .ok_or(Exception::IllegalFunction)?;
+            Response::ReadFileRecord(read_file_records(store, requests, buf)?)
+        }
+        R::WriteFileRecord(records) => {
+            let store = model
+                .file_record_store()
+                .ok_or(Exception::IllegalFunction)?;
+            Response::WriteFileRecord(write_file_records(store, records, buf)?)
+        }
+        #[cfg(feature = "rtu")]
+        R::ReadExceptionStatus => Response::ReadExceptionStatus(model.read_exception_status()?),
+        #[cfg(feature = "rtu")]
+        R::Diagnostics(sub_function, words) => {
+            diagnostics::apply_diagnostics(model, sub_function, words, buf)?
+        }
+        #[cfg(feature = "rtu")]
+        R::GetCommEventCounter => diagnostics::get_comm_event_counter(model)?,
+        #[cfg(feature = "rtu")]
+        R::GetCommEventLog => diagnostics::get_comm_event_log(model, buf)?,
+        _ => return Err(Exception::IllegalFunction.into()),
+    };
+    Ok(rsp)
+}
+
+fn read_fifo_queue<'b, M>(
+    model: &mut M,
+    address: Address,
+    buf: &'b mut [u8],
+) -> Result<Data<'b>, Failure>
+where
+    M: DataModel + ?Sized,
+{
+    let mut values = [0; MAX_FIFO_COUNT];
+    let cnt = model.read_fifo_queue(address, &mut values)?;
+    let values = values.get(..cnt).ok_or(Exception::ServerDeviceFailure)?;
+    if values.is_empty() {
+        return Ok(Data {
+            data: &[],
+            quantity: 0,
+        });
+    }
+    Ok(Data::from_words(values, buf)?)
+}
+
+fn read_file_records<'b>(
+    store: &mut dyn FileRecordStore,
+    requests: FileRecordRequests<'_>,
+    buf: &'b mut [u8],
+) -> Result<FileRecordData<'b>, Failure> {
+    let mut len = 0;
+    for req in requests.iter() {
+        check_file_record(req.file_number, req.record_number)?;
+        let mut words = [0; MAX_READ_REGISTERS];
+        let words = words
+            .get_mut(..usize::from(req.record_length))
+            .ok_or(Exception::IllegalDataValue)?;
+        let sub_len = 2 + words.len() * 2;
+        if len + sub_len > MAX_FILE_RECORD_BYTES {
+            return Err(Exception::IllegalDataValue.into());
+        }
+        store.read_record(req.file_number, req.record_number, words)?;
+        let sub = buf.get_mut(len..len + sub_len).ok_or(Error::BufferSize)?;
+        sub[0] = (sub_len - 1) as u8;
+        sub[1] = FILE_RECORD_REF_TYPE;
+        for (chunk, word) in sub[2..].chunks_exact_mut(2).zip(words.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        len += sub_len;
+    }
+    Ok(FileRecordData { data: &buf[..len] })
+}
+
+fn write_file_records<'b>(
+    store: &mut dyn FileRecordStore,
+    records: FileRecords<'_>,
+    buf: &'b mut [u8],
+) -> Result<FileRecords<'b>, Failure> {
+    for record in records.iter() {
+        check_file_record(record.file_number, record.record_number)?;
+        store.write_record(record.file_number, record.record_number, record.data)?;
+    }
+    let data = buf.get_mut(..records.data.len()).ok_or(Error::BufferSize)?;
+    data.copy_from_slice(records.data);
+    Ok(FileRecords { data })
+}
+
+const fn check_file_record(
+    file_number: FileNumber,
+    record_number: RecordNumber,
+) -> Result<(), Exception> {
+    if file_number == 0 || record_number > MAX_RECORD_NUMBER {
+        return Err(Exception::IllegalDataAddress);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Registers {
+        holding: [Word; 4],
+        coils: [Coil; 10],
+        holding_reads: usize,
+    }
+
+    impl DataModel for Registers {
+        fn address_range(&self, table: Table) -> Option<AddressRange> {
+            match table {
+                Table::HoldingRegisters => AddressRange::new(0, 4),
+                Table::Coils => AddressRange::new(0, 10),
+                _ => None,
+            }
+        }
+
+        fn read_coils(&mut self, address: Address, coils: &mut [Coil]) -> Result<(), Exception> {
+            let start = usize::from(address);
+            let values = self
+                .coils
+                .get(start..start + coils.len())
+                .ok_or(Exception::IllegalDataAddress)?;
+            coils.copy_from_slice(values);
+            Ok(())
+        }
+
+        fn read_holding_registers(
+            &mut self,
+            address: Address,
+            registers: &mut [Word],
+        ) -> Result<(), Exception> {
+            self.holding_reads += 1;
+            let start = usize::from(address);
+            let values = self
+                .holding
+                .get(start..start + registers.len())
+                .ok_or(Exception::IllegalDataAddress)?;
+            registers.copy_from_slice(values);
+            Ok(())
+        }
+
+        fn write_single_register(&mut self, address: Address, word: Word) -> Result<(), Exception> {
+            let register = self
+                .holding
+                .get_mut(usize::from(address))
+                .ok_or(Exception::IllegalDataAddress)?;
+            *register = word;
+            Ok(())
+        }
+
+        fn write_multiple_registers(
+            &mut self,
+            address: Address,
+            words: Data<'_>,
+        ) -> Result<(), Exception> {
+            let start = usize::from(address);
+            let registers = self
+                .holding
+                .get_mut(start..start + words.len())
+                .ok_or(Exception::IllegalDataAddress)?;
+            for (register, word) in registers.iter_mut().zip(words) {
+                *register = word;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn read_holding_registers() {
+        let mut model = Registers {
+            holding: [0x1234, 0xABCD, 0, 0],
+            ..Default::default()
+        };
+        let buf = &mut [0; 8];
+        let rsp = process_request(&mut model, Request::ReadHoldingRegisters(0, 2), buf)
+            .unwrap()
+            .unwrap();
+        let ResponsePdu(Ok(Response::ReadHoldingRegisters(data))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(data.get(0), Some(0x1234));
+        assert_eq!(data.get(1), Some(0xABCD));
+    }
+
+    #[test]
+    fn read_coils_into_dirty_buffer() {
+        let mut model = Registers::default();
+        model.coils[1] = true;
+        let buf = &mut [0xFF; 2];
+        let rsp = process_request(&mut model, Request::ReadCoils(0, 10), buf)
+            .unwrap()
+            .unwrap();
+        let ResponsePdu(Ok(Response::ReadCoils(coils))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(coils.len(), 10);
+        assert_eq!(coils.data, &[0b10, 0]);
+    }
+
+    #[test]
+    fn write_single_register() {
+        let mut model = Registers::default();
+        let buf = &mut [];
+        let rsp = process_request(&mut model, Request::WriteSingleRegister(3, 0x77), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp, ResponsePdu(Ok(Response::WriteSingleRegister(3, 0x77))));
+        assert_eq!(model.holding[3], 0x77);
+    }
+
+    #[test]
+    fn read_write_all_or_nothing() {
+        let mut model = Registers::default();
+        let buf = &mut [0; 8];
+        let words = Data {
+            data: &[0x12, 0x34, 0x56, 0x78],
+            quantity: 2,
+        };
+        let rsp = process_request(
+            &mut model,
+            Request::ReadWriteMultipleRegisters(3, 2, 0, words),
+            buf,
+        )
+        .unwrap()
+        .unwrap();
+        assert!(rsp.0.is_err());
+        assert_eq!(model.holding, [0; 4]);
+        assert_eq!(model.holding_reads, 0);
+
+        let rsp = process_request(
+            &mut model,
+            Request::ReadWriteMultipleRegisters(1, 2, 0, words),
+            buf,
+        )
+        .unwrap()
+        .unwrap();
+        let ResponsePdu(Ok(Response::ReadWriteMultipleRegisters(data))) = rsp else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(data.get(0), Some(0x5678));
+        assert_eq!(model.holding, [0x1234, 0x5678, 0, 0]);
+        assert_eq!(model.holding_reads, 2);
+    }
+
+    #[test]
+    fn read_write_rejects_read_range_before_writing() {
+        let mut model = Registers::default();
+        let words = Data {
+            data: &[0x12, 0x34],
+            quantity: 1,
+        };
+        let registers = &mut [0; 2];
+        assert_eq!(
+            model.read_write_multiple_registers(3, registers, 0, words),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(model.holding, [0; 4]);
+    }
+
+    #[test]
+    fn exception_from_data_model() {
+        let mut model = Registers::default();
+        let buf = &mut [0; 8];
+        let rsp = process_request(&mut model, Request::ReadHoldingRegisters(3, 2), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            }))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rtu")]
+    fn read_exception_status() {
+        struct Status;
+
+        impl DataModel for Status {
+            fn read_exception_status(&mut self) -> Result<u8, Exception> {
+                Ok(0b0110_1101)
+            }
+        }
+
+        let buf = &mut [];
+        let rsp = process_request(&mut Status, Request::ReadExceptionStatus, buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp, ResponsePdu(Ok(Response::ReadExceptionStatus(0x6D))));
+
+        let mut model = Registers::default();
+        let rsp = process_request(&mut model, Request::ReadExceptionStatus, buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadExceptionStatus,
+                exception: Exception::IllegalFunction,
+            }))
+        );
+    }
+
+    #[test]
+    fn unsupported_function() {
+        let mut model = Registers::default();
+        let buf = &mut [0; 8];
+        let rsp = process_request(&mut model, Request::ReadInputRegisters(0, 1), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadInputRegisters,
+                exception: Exception::IllegalFunction,
+            }))
+        );
+    }
+
+    #[test]
+    fn read_and_write_file_records() {
+        let mut file = [0x0DFE, 0x0020, 0, 0];
+        let files = &mut [&mut file[..]];
+        let mut store = SliceFileStore::new(files);
+        let buf = &mut [0; 16];
+
+        let records = FileRecords {
+            data: &[0x06, 0x00, 0x01, 0x00, 0x03, 0x00, 0x01, 0x12, 0x34],
+        };
+        let rsp = process_request(&mut store, Request::WriteFileRecord(records), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp, ResponsePdu(Ok(Response::WriteFileRecord(records))));
+        assert_eq!(store.file(1), Some(&[0x0DFE, 0x0020, 0, 0x1234][..]));
+
+        let requests = FileRecordRequests {
+            data: &[0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02],
+        };
+        let rsp = process_request(&mut store, Request::ReadFileRecord(requests), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Ok(Response::ReadFileRecord(FileRecordData {
+                data: &[0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20]
+            })))
+        );
+
+        let requests = FileRecordRequests {
+            data: &[0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+        };
+        let rsp = process_request(&mut store, Request::ReadFileRecord(requests), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadFileRecord,
+                exception: Exception::IllegalDataAddress,
+            }))
+        );
+    }
+
+    #[test]
+    fn file_records_unsupported() {
+        let mut model = Registers::default();
+        let buf = &mut [0; 8];
+        let requests = FileRecordRequests {
+            data: &[0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01],
+        };
+        let rsp = process_request(&mut model, Request::ReadFileRecord(requests), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadFileRecord,
+                exception: Exception::IllegalFunction,
+            }))
+        );
+    }
+
+    #[test]
+    fn response_buffer_too_small() {
+        let mut model = Registers::default();
+        let buf = &mut [0; 3];
+        let err = process_request(&mut model, Request::ReadHoldingRegisters(0, 2), buf)
+            .err()
+            .unwrap();
+        assert_eq!(err, Error::BufferSize);
+    }
+}
@@ -0,0 +1,165 @@
+use super::*;
+use crate::tcp::{RequestAdu, ResponseAdu, UnitId};
+use core::ops::RangeInclusive;
+
+/// A route from a range of unit IDs to a [`DataModel`].
+struct Route<'m> {
+    units: RangeInclusive<UnitId>,
+    model: &'m mut dyn DataModel,
+}
+
+/// Routes requests of a multi-unit TCP server (e.g. a gateway)
+/// to different [`DataModel`] instances by their unit ID.
+///
+/// The routing table has a fixed capacity of `N` entries.
+/// If the ranges of several routes overlap the route that was
+/// added first wins.
+pub struct UnitRouter<'m, const N: usize> {
+    routes: [Option<Route<'m>>; N],
+}
+
+impl<'m, const N: usize> UnitRouter<'m, N> {
+    /// Create an empty router.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            routes: core::array::from_fn(|_| None),
+        }
+    }
+
+    /// Route all requests addressed to `units` to `model`.
+    ///
+    /// Fails with [`Error::BufferSize`] if the routing table is full.
+    pub fn add_route(
+        &mut self,
+        units: RangeInclusive<UnitId>,
+        model: &'m mut dyn DataModel,
+    ) -> Result<(), Error> {
+        let slot = self
+            .routes
+            .iter_mut()
+            .find(|r| r.is_none())
+            .ok_or(Error::BufferSize)?;
+        *slot = Some(Route { units, model });
+        Ok(())
+    }
+
+    /// Get the data model that is responsible for `unit_id`.
+    pub fn route(&mut self, unit_id: UnitId) -> Option<&mut dyn DataModel> {
+        self.routes
+            .iter_mut()
+            .flatten()
+            .find(|r| r.units.contains(&unit_id))
+            .map(|r| &mut *r.model as &mut dyn DataModel)
+    }
+
+    /// Process a TCP request.
+    ///
+    /// Requests for unit IDs without a route are answered
+    /// with [`Exception::GatewayPathUnavailable`].
+    /// Returns `None` if the request must not be answered.
+    pub fn process_request<'b>(
+        &mut self,
+        adu: RequestAdu<'_>,
+        buf: &'b mut [u8],
+    ) -> Result<Option<ResponseAdu<'b>>, Error> {
+        let RequestAdu { hdr, pdu } = adu;
+        let RequestPdu(request) = pdu;
+        let pdu = match self.route(hdr.unit_id) {
+            Some(model) => process_request(model, request, buf)?,
+            None => Some(ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::from(request),
+                exception: Exception::GatewayPathUnavailable,
+            }))),
+        };
+        Ok(pdu.map(|pdu| ResponseAdu { hdr, pdu }))
+    }
+}
+
+impl<const N: usize> Default for UnitRouter<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::Header;
+
+    struct Constant(Word);
+
+    impl DataModel for Constant {
+        fn read_holding_registers(
+            &mut self,
+            _: Address,
+            registers: &mut [Word],
+        ) -> Result<(), Exception> {
+            registers.fill(self.0);
+            Ok(())
+        }
+    }
+
+    fn read_request(unit_id: UnitId) -> RequestAdu<'static> {
+        RequestAdu {
+            hdr: Header {
+                transaction_id: 7,
+                unit_id,
+            },
+            pdu: RequestPdu(Request::ReadHoldingRegisters(0, 1)),
+        }
+    }
+
+    #[test]
+    fn route_by_unit_id() {
+        let mut first = Constant(1);
+        let mut second = Constant(2);
+        let mut router = UnitRouter::<2>::new();
+        router.add_route(1..=9, &mut first).unwrap();
+        router.add_route(10..=20, &mut second).unwrap();
+
+        let buf = &mut [0; 2];
+        let rsp = router
+            .process_request(read_request(15), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rsp.hdr.transaction_id, 7);
+        assert_eq!(rsp.hdr.unit_id, 15);
+        let ResponsePdu(Ok(Response::ReadHoldingRegisters(data))) = rsp.pdu else {
+            panic!("unexpected response: {rsp:?}");
+        };
+        assert_eq!(data.get(0), Some(2));
+    }
+
+    #[test]
+    fn unmapped_unit_id() {
+        let mut model = Constant(1);
+        let mut router = UnitRouter::<1>::new();
+        router.add_route(1..=9, &mut model).unwrap();
+
+        let buf = &mut [0; 2];
+        let rsp = router
+            .process_request(read_request(0xFF), buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            rsp.pdu,
+            ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::GatewayPathUnavailable,
+            }))
+        );
+    }
+
+    #[test]
+    fn routing_table_full() {
+        let mut first = Constant(1);
+        let mut second = Constant(2);
+        let mut router = UnitRouter::<1>::new();
+        router.add_route(1..=1, &mut first).unwrap();
+        assert_eq!(
+            router.add_route(2..=2, &mut second).err().unwrap(),
+            Error::BufferSize
+        );
+    }
+}
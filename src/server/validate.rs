@@ -0,0 +1,241 @@
+use super::*;
+
+/// Check a request against the limits of the
+/// [MODBUS Application Protocol Specification](http://modbus.org/docs/Modbus_Application_Protocol_V1_1b3.pdf).
+///
+/// Out-of-spec quantities and byte counts that do not match
+/// the quantity are rejected with [`Exception::IllegalDataValue`].
+pub fn validate_request(request: &Request<'_>) -> Result<(), Exception> {
+    use Request as R;
+
+    let valid = match *request {
+        R::ReadCoils(_, quantity) | R::ReadDiscreteInputs(_, quantity) => {
+            (1..=MAX_READ_COILS).contains(&usize::from(quantity))
+        }
+        R::ReadInputRegisters(_, quantity) | R::ReadHoldingRegisters(_, quantity) => {
+            (1..=MAX_READ_REGISTERS).contains(&usize::from(quantity))
+        }
+        R::WriteMultipleCoils(_, coils) => {
+            (1..=MAX_WRITE_COILS).contains(&coils.len()) && coils.data.len() == coils.packed_len()
+        }
+        R::WriteMultipleRegisters(_, words) => {
+            (1..=MAX_WRITE_REGISTERS).contains(&words.len()) && words.data.len() == words.len() * 2
+        }
+        R::ReadWriteMultipleRegisters(_, quantity, _, words) => {
+            (1..=MAX_READ_REGISTERS).contains(&usize::from(quantity))
+                && (1..=MAX_READ_WRITE_REGISTERS).contains(&words.len())
+                && words.data.len() == words.len() * 2
+        }
+        R::ReadFileRecord(requests) => requests.data.len() <= MAX_FILE_RECORD_BYTES,
+        R::WriteFileRecord(records) => records.data.len() <= MAX_WRITE_FILE_RECORD_BYTES,
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Exception::IllegalDataValue)
+    }
+}
+
+/// Check that all addresses a request touches lie within
+/// the [address ranges](DataModel::address_range) of the data model.
+///
+/// Requests that start or extend outside a range are rejected
+/// with [`Exception::IllegalDataAddress`].
+pub fn validate_addresses<M>(model: &M, request: &Request<'_>) -> Result<(), Exception>
+where
+    M: DataModel + ?Sized,
+{
+    use Request as R;
+
+    let check = |table, address, quantity| {
+        let Some(valid) = model.address_range(table) else {
+            return Ok(());
+        };
+        match AddressRange::new(address, quantity) {
+            Some(range) if valid.contains_range(&range) => Ok(()),
+            _ => Err(Exception::IllegalDataAddress),
+        }
+    };
+
+    match *request {
+        R::ReadCoils(address, quantity) => check(Table::Coils, address, quantity),
+        R::ReadDiscreteInputs(address, quantity) => check(Table::DiscreteInputs, address, quantity),
+        R::ReadInputRegisters(address, quantity) => check(Table::InputRegisters, address, quantity),
+        R::ReadHoldingRegisters(address, quantity) => {
+            check(Table::HoldingRegisters, address, quantity)
+        }
+        R::WriteSingleCoil(address, _) => check(Table::Coils, address, 1),
+        R::WriteMultipleCoils(address, coils) => {
+            check(Table::Coils, address, coils.len() as Quantity)
+        }
+        R::WriteSingleRegister(address, _) => check(Table::HoldingRegisters, address, 1),
+        R::WriteMultipleRegisters(address, words) => {
+            check(Table::HoldingRegisters, address, words.len() as Quantity)
+        }
+        R::ReadWriteMultipleRegisters(read_address, quantity, write_address, words) => {
+            check(
+                Table::HoldingRegisters,
+                write_address,
+                words.len() as Quantity,
+            )?;
+            check(Table::HoldingRegisters, read_address, quantity)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Get the exception a server should answer with
+/// if a request PDU could not be decoded.
+///
+/// E.g. a `WriteSingleCoil` request with the value `0x1234`
+/// fails to decode with [`DecodeError::CoilValue`] and has to be
+/// answered with [`Exception::IllegalDataValue`].
+#[must_use]
+pub const fn decode_error_exception(err: &DecodeError) -> Option<Exception> {
+    match err {
+        DecodeError::CoilValue(_)
+        | DecodeError::ByteCount(_)
+        | DecodeError::Quantity(_)
+        | DecodeError::QuantityBytesMismatch(_, _)
+        | DecodeError::CoilPadding(_) => Some(Exception::IllegalDataValue),
+        DecodeError::FnCode(_) => Some(Exception::IllegalFunction),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_quantities() {
+        assert!(validate_request(&Request::ReadCoils(0, 1)).is_ok());
+        assert!(validate_request(&Request::ReadCoils(0, 2000)).is_ok());
+        assert_eq!(
+            validate_request(&Request::ReadCoils(0, 0)),
+            Err(Exception::IllegalDataValue)
+        );
+        assert_eq!(
+            validate_request(&Request::ReadDiscreteInputs(0, 2001)),
+            Err(Exception::IllegalDataValue)
+        );
+        assert!(validate_request(&Request::ReadHoldingRegisters(0, 125)).is_ok());
+        assert_eq!(
+            validate_request(&Request::ReadHoldingRegisters(0, 0)),
+            Err(Exception::IllegalDataValue)
+        );
+        assert_eq!(
+            validate_request(&Request::ReadInputRegisters(0, 126)),
+            Err(Exception::IllegalDataValue)
+        );
+    }
+
+    #[test]
+    fn write_byte_count_mismatch() {
+        let coils = Coils {
+            data: &[0xFF, 0x01],
+            quantity: 8,
+        };
+        assert_eq!(
+            validate_request(&Request::WriteMultipleCoils(0, coils)),
+            Err(Exception::IllegalDataValue)
+        );
+        let words = Data {
+            data: &[0x00, 0x01, 0x02],
+            quantity: 2,
+        };
+        assert_eq!(
+            validate_request(&Request::WriteMultipleRegisters(0, words)),
+            Err(Exception::IllegalDataValue)
+        );
+        let words = Data {
+            data: &[0x00, 0x01, 0x02, 0x03],
+            quantity: 2,
+        };
+        assert!(validate_request(&Request::WriteMultipleRegisters(0, words)).is_ok());
+        assert_eq!(
+            validate_request(&Request::ReadWriteMultipleRegisters(0, 126, 0, words)),
+            Err(Exception::IllegalDataValue)
+        );
+    }
+
+    struct Ranges;
+
+    impl DataModel for Ranges {
+        fn address_range(&self, table: Table) -> Option<AddressRange> {
+            match table {
+                Table::HoldingRegisters => AddressRange::new(0x100, 10),
+                Table::Coils => AddressRange::new(0, 8),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn addresses_within_range() {
+        assert!(validate_addresses(&Ranges, &Request::ReadHoldingRegisters(0x100, 10)).is_ok());
+        assert!(validate_addresses(&Ranges, &Request::WriteSingleRegister(0x109, 0)).is_ok());
+        assert!(validate_addresses(&Ranges, &Request::ReadCoils(7, 1)).is_ok());
+        // No range means no check
+        assert!(validate_addresses(&Ranges, &Request::ReadInputRegisters(0xFFFF, 1)).is_ok());
+    }
+
+    #[test]
+    fn addresses_out_of_range() {
+        assert_eq!(
+            validate_addresses(&Ranges, &Request::ReadHoldingRegisters(0xFF, 1)),
+            Err(Exception::IllegalDataAddress)
+        );
+        // The start address is valid but the last address is not
+        assert_eq!(
+            validate_addresses(&Ranges, &Request::ReadHoldingRegisters(0x105, 6)),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            validate_addresses(&Ranges, &Request::WriteSingleCoil(8, true)),
+            Err(Exception::IllegalDataAddress)
+        );
+        // The range would exceed the highest address
+        assert_eq!(
+            validate_addresses(&Ranges, &Request::ReadCoils(0xFFFF, 2)),
+            Err(Exception::IllegalDataAddress)
+        );
+    }
+
+    #[test]
+    fn exception_for_invalid_coil_value() {
+        let bytes: &[u8] = &[0x05, 0x00, 0x01, 0x12, 0x34];
+        let err = Request::try_from(bytes).err().unwrap();
+        assert_eq!(
+            decode_error_exception(&err),
+            Some(Exception::IllegalDataValue)
+        );
+        assert_eq!(decode_error_exception(&DecodeError::BufferSize), None);
+    }
+
+    #[test]
+    fn reject_before_accessing_the_data_model() {
+        struct Untouchable;
+
+        impl DataModel for Untouchable {
+            fn read_holding_registers(
+                &mut self,
+                _: Address,
+                _: &mut [Word],
+            ) -> Result<(), Exception> {
+                unreachable!();
+            }
+        }
+
+        let buf = &mut [0; 4];
+        let rsp = process_request(&mut Untouchable, Request::ReadHoldingRegisters(0, 0), buf);
+        assert_eq!(
+            rsp,
+            Ok(Some(ResponsePdu(Err(ExceptionResponse {
+                function: FunctionCode::ReadHoldingRegisters,
+                exception: Exception::IllegalDataValue,
+            }))))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Addresses of RTU slaves and TCP units.
+
+use crate::{rtu, tcp};
+use core::fmt;
+
+/// The address of an RTU slave or a TCP unit.
+///
+/// Only the [broadcast](Self::broadcast) address, the device
+/// addresses from [`Slave::min`] to [`Slave::max`] and the
+/// [TCP device](Self::tcp_device) address can be constructed
+/// with [`Slave::new`]. The remaining reserved addresses have
+/// to be requested explicitly with [`Slave::from_raw`].
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::slave::Slave;
+///
+/// assert_eq!(Slave::new(17).map(u8::from), Some(17));
+/// assert_eq!(Slave::new(248), None);
+/// assert!(Slave::from_raw(248).is_reserved());
+/// assert!(Slave::new(0).unwrap().is_broadcast());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Slave(u8);
+
+impl Slave {
+    /// The address that all slaves listen to without answering.
+    #[must_use]
+    pub const fn broadcast() -> Self {
+        Self(0)
+    }
+
+    /// The lowest address of a single device.
+    #[must_use]
+    pub const fn min() -> Self {
+        Self(1)
+    }
+
+    /// The highest address of a single device.
+    #[must_use]
+    pub const fn max() -> Self {
+        Self(247)
+    }
+
+    /// The unit ID of a TCP device that is not behind a gateway.
+    #[must_use]
+    pub const fn tcp_device() -> Self {
+        Self(0xFF)
+    }
+
+    /// Validate `id`.
+    ///
+    /// Returns `None` for the reserved addresses `248` to `254`.
+    #[must_use]
+    pub const fn new(id: u8) -> Option<Self> {
+        let slave = Self(id);
+        if slave.is_reserved() && id != Self::tcp_device().0 {
+            return None;
+        }
+        Some(slave)
+    }
+
+    /// Accept any `id`, e.g. one that has been received.
+    #[must_use]
+    pub const fn from_raw(id: u8) -> Self {
+        Self(id)
+    }
+
+    /// The raw ID.
+    #[must_use]
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` for the broadcast address `0`.
+    #[must_use]
+    pub const fn is_broadcast(self) -> bool {
+        self.0 == Self::broadcast().0
+    }
+
+    /// Returns `true` for a single device, i.e. from `1` to `247`.
+    #[must_use]
+    pub const fn is_single_device(self) -> bool {
+        Self::min().0 <= self.0 && self.0 <= Self::max().0
+    }
+
+    /// Returns `true` for the addresses `248` to `255` that the
+    /// serial line specification reserves.
+    #[must_use]
+    pub const fn is_reserved(self) -> bool {
+        self.0 > Self::max().0
+    }
+}
+
+impl From<Slave> for u8 {
+    fn from(slave: Slave) -> Self {
+        slave.0
+    }
+}
+
+impl From<Slave> for rtu::Header {
+    fn from(slave: Slave) -> Self {
+        Self { slave: slave.0 }
+    }
+}
+
+impl From<rtu::Header> for Slave {
+    fn from(hdr: rtu::Header) -> Self {
+        Self(hdr.slave)
+    }
+}
+
+impl From<tcp::Header> for Slave {
+    fn from(hdr: tcp::Header) -> Self {
+        Self(hdr.unit_id)
+    }
+}
+
+impl fmt::Display for Slave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (0x{:0>2X})", self.0, self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert_eq!(Slave::new(0), Some(Slave::broadcast()));
+        assert_eq!(Slave::new(1), Some(Slave::min()));
+        assert_eq!(Slave::new(247), Some(Slave::max()));
+        for id in 248..=254 {
+            assert_eq!(Slave::new(id), None);
+        }
+        assert_eq!(Slave::new(255), Some(Slave::tcp_device()));
+    }
+
+    #[test]
+    fn classify() {
+        assert!(Slave::broadcast().is_broadcast());
+        assert!(!Slave::broadcast().is_single_device());
+        assert!(Slave::max().is_single_device());
+        assert!(Slave::tcp_device().is_reserved());
+        assert!(!Slave::tcp_device().is_single_device());
+    }
+
+    #[test]
+    fn convert() {
+        let hdr = tcp::Header {
+            transaction_id: 1,
+            unit_id: 0x12,
+        };
+        let slave = Slave::from(hdr);
+        assert_eq!(rtu::Header::from(slave), rtu::Header { slave: 0x12 });
+        assert_eq!(u8::from(slave), 0x12);
+    }
+}
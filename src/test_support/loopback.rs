@@ -0,0 +1,370 @@
+use super::*;
+use crate::{
+    client::{ClientTransaction, Header, TransactionError},
+    server::{process_request, DataModel},
+    DecodeError, DecoderType, EncodeError,
+};
+use core::fmt;
+
+/// Room for a duplicated ADU.
+const REQUEST_LINE_LEN: usize = 2 * MAX_TCP_ADU_LEN;
+
+/// Room for the duplicated responses of a duplicated request.
+const RESPONSE_LINE_LEN: usize = 2 * REQUEST_LINE_LEN;
+
+/// How the frames that pass a [`Loopback`] in one direction are mangled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Impairments {
+    /// Flip the bits of the mask in the byte at the index of each frame.
+    pub corrupt: Option<(usize, u8)>,
+    /// Deliver the bytes in fragments of at most this length.
+    pub fragment: Option<usize>,
+    /// Send each frame twice.
+    pub duplicate: bool,
+}
+
+impl Impairments {
+    fn fragments<'a>(&self, bytes: &'a [u8]) -> core::slice::Chunks<'a, u8> {
+        let len = self.fragment.unwrap_or(bytes.len());
+        bytes.chunks(len.max(1))
+    }
+}
+
+/// The bytes in transit in one direction.
+struct Line<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Line<N> {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append the `frame` as mangled by `impairments`.
+    fn send(&mut self, impairments: &Impairments, frame: &[u8]) {
+        let copies = if impairments.duplicate { 2 } else { 1 };
+        for _ in 0..copies {
+            let start = self.len;
+            let end = (start + frame.len()).min(N);
+            self.bytes[start..end].copy_from_slice(&frame[..end - start]);
+            if let Some((index, mask)) = impairments.corrupt {
+                if let Some(byte) = self.bytes[start..end].get_mut(index) {
+                    *byte ^= mask;
+                }
+            }
+            self.len = end;
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Errors of a [`Loopback`] transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LoopbackError {
+    /// The client failed to encode the request.
+    Encode(EncodeError),
+    /// The client rejected the received bytes.
+    Transaction(TransactionError),
+}
+
+impl From<EncodeError> for LoopbackError {
+    fn from(from: EncodeError) -> Self {
+        Self::Encode(from)
+    }
+}
+
+impl From<TransactionError> for LoopbackError {
+    fn from(from: TransactionError) -> Self {
+        Self::Transaction(from)
+    }
+}
+
+impl fmt::Display for LoopbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(err) => write!(f, "Failed to encode the request: {err}"),
+            Self::Transaction(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+/// Connects the client codec with the server codec of the
+/// same transport, without any sockets or serial ports.
+///
+/// The encoded request is fed into the server decoder and
+/// applied to a [`DataModel`]. The encoded response is fed back
+/// into the client decoder. On their way the frames can be
+/// corrupted, fragmented and duplicated (see [`Impairments`]).
+///
+/// Requests that the server cannot decode are dropped silently,
+/// like a real server would do. Bytes that are left over after
+/// the client has received the response, e.g. a duplicated
+/// response, are discarded.
+///
+/// # Example
+///
+/// ```
+/// use modbus_core::{
+///     client::ClientTransaction,
+///     rtu,
+///     server::{Block, RegisterBank},
+///     test_support::Loopback,
+///     Request, Response, ResponsePdu,
+/// };
+///
+/// let mut registers = [0x1234; 4];
+/// let bank = RegisterBank {
+///     holding_registers: Block::new(0, &mut registers).unwrap(),
+///     ..Default::default()
+/// };
+/// let mut loopback = Loopback::new(bank);
+/// loopback.responses.fragment = Some(3);
+///
+/// let request = Request::ReadHoldingRegisters(1, 2);
+/// let mut transaction = ClientTransaction::new(rtu::Header { slave: 1 }, request);
+/// let buf = &mut [0; 256];
+/// let Some(ResponsePdu(Ok(Response::ReadHoldingRegisters(words)))) =
+///     loopback.transact(&mut transaction, buf).unwrap()
+/// else {
+///     panic!("unexpected response");
+/// };
+/// assert_eq!(words.get(1), Some(0x1234));
+///
+/// // The server drops the corrupted request
+/// loopback.requests.corrupt = Some((2, 0xFF));
+/// let mut transaction = ClientTransaction::new(rtu::Header { slave: 1 }, request);
+/// assert_eq!(loopback.transact(&mut transaction, buf), Ok(None));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loopback<M> {
+    model: M,
+    /// Impairments of the requests from the client to the server.
+    pub requests: Impairments,
+    /// Impairments of the responses from the server to the client.
+    pub responses: Impairments,
+}
+
+impl<M> Loopback<M> {
+    /// Serve the requests with `model` without any impairments.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            requests: Impairments::default(),
+            responses: Impairments::default(),
+        }
+    }
+
+    pub const fn inner(&self) -> &M {
+        &self.model
+    }
+
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.model
+    }
+
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+}
+
+impl<M: DataModel> Loopback<M> {
+    /// Send the request of `transaction` to the server and
+    /// receive the response into `buf`.
+    ///
+    /// Returns `None` if no complete response has been received,
+    /// i.e. the client would run into a timeout.
+    pub fn transact<'b>(
+        &mut self,
+        transaction: &mut ClientTransaction<'_>,
+        buf: &'b mut [u8],
+    ) -> Result<Option<ResponsePdu<'b>>, LoopbackError> {
+        let frame = &mut [0; MAX_TCP_ADU_LEN];
+        let len = transaction.encode(frame)?;
+        let mut requests = Line::<REQUEST_LINE_LEN>::new();
+        requests.send(&self.requests, &frame[..len]);
+
+        let mut responses = Line::<RESPONSE_LINE_LEN>::new();
+        let rx = &mut [0; REQUEST_LINE_LEN];
+        let mut rx_len = 0;
+        for fragment in self.requests.fragments(requests.as_slice()) {
+            rx[rx_len..rx_len + fragment.len()].copy_from_slice(fragment);
+            rx_len += fragment.len();
+            loop {
+                let consumed = self.serve(transaction.header(), &rx[..rx_len], &mut responses);
+                if consumed == 0 {
+                    break;
+                }
+                rx.copy_within(consumed..rx_len, 0);
+                rx_len -= consumed;
+            }
+        }
+
+        let mut len = 0;
+        for fragment in self.responses.fragments(responses.as_slice()) {
+            let Some(target) = buf.get_mut(len..len + fragment.len()) else {
+                return Err(TransactionError::Decode(DecodeError::BufferSize).into());
+            };
+            target.copy_from_slice(fragment);
+            len += fragment.len();
+            let mut probe = *transaction;
+            if probe.decode(&buf[..len])?.is_some() {
+                return Ok(transaction.decode(&buf[..len])?);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Serve the first request in `rx` and send the response.
+    ///
+    /// Returns the number of bytes that have been consumed.
+    fn serve<const N: usize>(&mut self, hdr: Header, rx: &[u8], tx: &mut Line<N>) -> usize {
+        if rx.is_empty() {
+            return 0;
+        }
+        let (adu, consumed) = match hdr {
+            Header::Rtu(_) => match rtu::decode(DecoderType::Request, rx) {
+                Ok(Some((frame, location))) => (
+                    frame.try_into_request_adu().ok().map(|adu| {
+                        let rtu::RequestAdu { hdr, pdu } = adu;
+                        (Header::Rtu(hdr), pdu.0)
+                    }),
+                    location.start + location.size,
+                ),
+                Ok(None) => return 0,
+                Err(_) => return rx.len(),
+            },
+            Header::Tcp(_) => match tcp::decode(DecoderType::Request, rx) {
+                Ok(Some((frame, location))) => (
+                    frame.try_into_request_adu().ok().map(|adu| {
+                        let tcp::RequestAdu { hdr, pdu } = adu;
+                        (Header::Tcp(hdr), pdu.0)
+                    }),
+                    location.start + location.size,
+                ),
+                Ok(None) => return 0,
+                Err(_) => return rx.len(),
+            },
+        };
+        let Some((hdr, request)) = adu else {
+            return consumed;
+        };
+        let payload = &mut [0; MAX_PDU_LEN];
+        let Ok(Some(pdu)) = process_request(&mut self.model, request, payload) else {
+            return consumed;
+        };
+        let frame = &mut [0; MAX_TCP_ADU_LEN];
+        let len = match hdr {
+            Header::Rtu(rtu::Header {
+                slave: rtu::BROADCAST,
+            }) => return consumed,
+            Header::Rtu(hdr) => rtu::server::encode_response(rtu::ResponseAdu { hdr, pdu }, frame),
+            Header::Tcp(hdr) => tcp::server::encode_response(tcp::ResponseAdu { hdr, pdu }, frame),
+        };
+        if let Ok(len) = len {
+            tx.send(&self.responses, &frame[..len]);
+        }
+        consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{Block, RegisterBank};
+
+    fn bank(registers: &mut [Word]) -> RegisterBank<'_> {
+        RegisterBank {
+            holding_registers: Block::new(0, registers).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transact_with_fragments() {
+        let registers = &mut [0; 4];
+        let mut loopback = Loopback::new(bank(registers));
+        loopback.requests.fragment = Some(1);
+        loopback.responses.fragment = Some(2);
+        let buf = &mut [0; 256];
+        let hdr = tcp::Header {
+            transaction_id: 9,
+            unit_id: 1,
+        };
+        let mut transaction = ClientTransaction::new(hdr, Request::WriteSingleRegister(2, 7));
+        assert_eq!(
+            loopback.transact(&mut transaction, buf),
+            Ok(Some(ResponsePdu(Ok(Response::WriteSingleRegister(2, 7)))))
+        );
+        assert_eq!(loopback.inner().holding_registers.values(), &[0, 0, 7, 0]);
+    }
+
+    #[test]
+    fn duplicate_requests_are_served_twice() {
+        let registers = &mut [0; 4];
+        let mut loopback = Loopback::new(bank(registers));
+        loopback.requests.duplicate = true;
+        loopback.responses.duplicate = true;
+        let buf = &mut [0; 256];
+        let request = Request::ReadWriteMultipleRegisters(
+            0,
+            1,
+            0,
+            Data {
+                data: &[0x00, 0x01],
+                quantity: 1,
+            },
+        );
+        let mut transaction = ClientTransaction::new(rtu::Header { slave: 1 }, request);
+        let rsp = loopback.transact(&mut transaction, buf).unwrap().unwrap();
+        assert!(matches!(
+            rsp,
+            ResponsePdu(Ok(Response::ReadWriteMultipleRegisters(_)))
+        ));
+        assert_eq!(loopback.inner().holding_registers.values()[0], 1);
+    }
+
+    #[test]
+    fn corrupted_responses_are_rejected() {
+        let registers = &mut [0; 4];
+        let mut loopback = Loopback::new(bank(registers));
+        let buf = &mut [0; 256];
+
+        // CRC error
+        loopback.responses.corrupt = Some((3, 0x01));
+        let request = Request::ReadHoldingRegisters(0, 1);
+        let mut transaction = ClientTransaction::new(rtu::Header { slave: 1 }, request);
+        assert_eq!(loopback.transact(&mut transaction, buf), Ok(None));
+
+        // Transaction ID mismatch
+        loopback.responses.corrupt = Some((1, 0x01));
+        let hdr = tcp::Header {
+            transaction_id: 4,
+            unit_id: 1,
+        };
+        let mut transaction = ClientTransaction::new(hdr, request);
+        assert!(matches!(
+            loopback.transact(&mut transaction, buf),
+            Err(LoopbackError::Transaction(_))
+        ));
+    }
+
+    #[test]
+    fn broadcasts_are_not_answered() {
+        let registers = &mut [0; 4];
+        let mut loopback = Loopback::new(bank(registers));
+        let buf = &mut [0; 256];
+        let request = rtu::BroadcastRequest::try_from(Request::WriteSingleRegister(1, 3)).unwrap();
+        let mut transaction = ClientTransaction::broadcast(request);
+        assert_eq!(loopback.transact(&mut transaction, buf), Ok(None));
+        assert_eq!(loopback.inner().holding_registers.values()[1], 3);
+    }
+}
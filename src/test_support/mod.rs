@@ -0,0 +1,349 @@
+//! Valid frames of every function code, roundtrip assertions
+//! and a [`Loopback`] between the client and server codecs.
+//!
+//! Requires the `test-support` feature. Meant for tests and fuzz
+//! targets of crates that build upon this crate, so that they can
+//! verify that encoding and decoding are inverse to each other
+//! without knowing how frames are laid out.
+//!
+//! # Example
+//!
+//! ```
+//! use modbus_core::{test_support, Request};
+//!
+//! for request in test_support::REQUESTS {
+//!     test_support::assert_roundtrip(*request);
+//! }
+//! test_support::assert_roundtrip(Request::ReadHoldingRegisters(0x1000, 4));
+//! ```
+
+mod loopback;
+
+pub use self::loopback::*;
+
+use crate::{
+    frame::*, rtu, tcp, DecodeOptions, Encode, ExceptionResponse, FileRecordData,
+    FileRecordRequests, FileRecords,
+};
+
+/// A valid request of every function code.
+pub const REQUESTS: &[Request<'static>] = &[
+    Request::ReadCoils(0x0013, 19),
+    Request::ReadDiscreteInputs(0x00C4, 22),
+    Request::WriteSingleCoil(0x00AC, true),
+    Request::WriteMultipleCoils(
+        0x0013,
+        Coils {
+            data: &[0xCD, 0x01],
+            quantity: 10,
+        },
+    ),
+    Request::ReadInputRegisters(0x0008, 1),
+    Request::ReadHoldingRegisters(0x006B, 3),
+    Request::WriteSingleRegister(0x0001, 0x0003),
+    Request::WriteMultipleRegisters(
+        0x0001,
+        Data {
+            data: &[0x00, 0x0A, 0x01, 0x02],
+            quantity: 2,
+        },
+    ),
+    Request::ReadWriteMultipleRegisters(
+        0x0003,
+        6,
+        0x000E,
+        Data {
+            data: &[0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+            quantity: 3,
+        },
+    ),
+    Request::ReadFifoQueue(0x04DE),
+    Request::ReadFileRecord(FileRecordRequests {
+        data: &[0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02],
+    }),
+    Request::WriteFileRecord(FileRecords {
+        data: &[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF],
+    }),
+    #[cfg(feature = "rtu")]
+    Request::ReadExceptionStatus,
+    #[cfg(feature = "rtu")]
+    Request::Diagnostics(
+        0x0000,
+        Data {
+            data: &[0xA5, 0x37],
+            quantity: 1,
+        },
+    ),
+    #[cfg(feature = "rtu")]
+    Request::GetCommEventCounter,
+    #[cfg(feature = "rtu")]
+    Request::GetCommEventLog,
+    #[cfg(feature = "rtu")]
+    Request::ReportServerId,
+    Request::Custom(RawPdu {
+        function: FunctionCode::Custom(0x41),
+        data: &[0x01, 0x02, 0x03],
+    }),
+];
+
+/// A valid response of every function code that can be encoded.
+///
+/// The response of `ReportServerId` is missing,
+/// because it cannot be encoded yet.
+pub const RESPONSES: &[Response<'static>] = &[
+    Response::ReadCoils(Coils {
+        data: &[0xCD, 0x6B, 0x05],
+        quantity: 24,
+    }),
+    Response::ReadDiscreteInputs(Coils {
+        data: &[0xAC],
+        quantity: 8,
+    }),
+    Response::WriteSingleCoil(0x00AC, true),
+    Response::WriteMultipleCoils(0x0013, 10),
+    Response::ReadInputRegisters(Data {
+        data: &[0x00, 0x0A],
+        quantity: 1,
+    }),
+    Response::ReadHoldingRegisters(Data {
+        data: &[0x02, 0x2B, 0x00, 0x00, 0x00, 0x64],
+        quantity: 3,
+    }),
+    Response::WriteSingleRegister(0x0001, 0x0003),
+    Response::WriteMultipleRegisters(0x0001, 2),
+    Response::ReadWriteMultipleRegisters(Data {
+        data: &[0x00, 0xFE, 0x0A, 0xCD],
+        quantity: 2,
+    }),
+    Response::ReadFifoQueue(Data {
+        data: &[0x01, 0xB8, 0x12, 0x84],
+        quantity: 2,
+    }),
+    Response::ReadFileRecord(FileRecordData {
+        data: &[0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20],
+    }),
+    Response::WriteFileRecord(FileRecords {
+        data: &[0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x01, 0x06, 0xAF],
+    }),
+    #[cfg(feature = "rtu")]
+    Response::ReadExceptionStatus(0x6D),
+    #[cfg(feature = "rtu")]
+    Response::Diagnostics(
+        0x0000,
+        Data {
+            data: &[0xA5, 0x37],
+            quantity: 1,
+        },
+    ),
+    #[cfg(feature = "rtu")]
+    Response::GetCommEventCounter(0xFFFF, 0x0108),
+    #[cfg(feature = "rtu")]
+    Response::GetCommEventLog(0x0000, 0x0108, 0x0121, &[0x20, 0x00]),
+    Response::Custom(RawPdu {
+        function: FunctionCode::Custom(0x41),
+        data: &[0x04, 0x05],
+    }),
+];
+
+/// RTU request ADUs of all [`REQUESTS`] that are addressed to `slave`.
+pub fn rtu_request_adus(slave: rtu::SlaveId) -> impl Iterator<Item = rtu::RequestAdu<'static>> {
+    REQUESTS.iter().map(move |req| rtu::RequestAdu {
+        hdr: rtu::Header { slave },
+        pdu: RequestPdu(*req),
+    })
+}
+
+/// TCP request ADUs of all [`REQUESTS`] that are addressed to `unit_id`.
+///
+/// The transaction IDs are numbered consecutively, starting at `1`.
+pub fn tcp_request_adus(unit_id: tcp::UnitId) -> impl Iterator<Item = tcp::RequestAdu<'static>> {
+    (1..)
+        .zip(REQUESTS)
+        .map(move |(transaction_id, req)| tcp::RequestAdu {
+            hdr: tcp::Header {
+                transaction_id,
+                unit_id,
+            },
+            pdu: RequestPdu(*req),
+        })
+}
+
+/// All [`RESPONSES`] followed by an exception response for
+/// the function code of each of the [`REQUESTS`].
+pub fn response_pdus() -> impl Iterator<Item = ResponsePdu<'static>> {
+    let exceptions = REQUESTS.iter().map(|req| {
+        ResponsePdu(Err(ExceptionResponse {
+            function: req.function_code(),
+            exception: Exception::IllegalDataAddress,
+        }))
+    });
+    RESPONSES
+        .iter()
+        .map(|rsp| ResponsePdu(Ok(*rsp)))
+        .chain(exceptions)
+}
+
+/// RTU response ADUs of all [`response_pdus`] from `slave`.
+pub fn rtu_response_adus(slave: rtu::SlaveId) -> impl Iterator<Item = rtu::ResponseAdu<'static>> {
+    response_pdus().map(move |pdu| rtu::ResponseAdu {
+        hdr: rtu::Header { slave },
+        pdu,
+    })
+}
+
+/// TCP response ADUs of all [`response_pdus`] from `unit_id`.
+///
+/// The transaction IDs are numbered consecutively, starting at `1`.
+pub fn tcp_response_adus(unit_id: tcp::UnitId) -> impl Iterator<Item = tcp::ResponseAdu<'static>> {
+    (1..)
+        .zip(response_pdus())
+        .map(move |(transaction_id, pdu)| tcp::ResponseAdu {
+            hdr: tcp::Header {
+                transaction_id,
+                unit_id,
+            },
+            pdu,
+        })
+}
+
+/// Assert that `request` is decoded unchanged after it has been
+/// encoded as a PDU, as an RTU ADU and as a TCP ADU.
+///
+/// Custom requests are only checked as PDUs, because the transports
+/// cannot delimit them without a [`CustomFunction`](crate::CustomFunction).
+///
+/// # Panics
+///
+/// Panics if encoding fails or if a decoded request differs.
+#[track_caller]
+pub fn assert_roundtrip(request: Request<'_>) {
+    let buf = &mut [0; MAX_TCP_ADU_LEN];
+
+    let len = request.encode(buf).expect("encode request PDU");
+    assert_eq!(Request::try_from(&buf[..len]), Ok(request), "PDU");
+
+    if matches!(request, Request::Custom(_)) {
+        return;
+    }
+
+    let adu = rtu::RequestAdu {
+        hdr: rtu::Header { slave: 0x12 },
+        pdu: RequestPdu(request),
+    };
+    let len = rtu::client::encode_request(adu, buf).expect("encode RTU request");
+    assert_eq!(
+        rtu::server::decode_request(&buf[..len]),
+        Ok(Some(adu)),
+        "RTU ADU"
+    );
+
+    let adu = tcp::RequestAdu {
+        hdr: tcp::Header {
+            transaction_id: 0x1234,
+            unit_id: 0x12,
+        },
+        pdu: RequestPdu(request),
+    };
+    let len = tcp::client::encode_request(adu, buf).expect("encode TCP request");
+    assert_eq!(
+        tcp::server::decode_request(&buf[..len]),
+        Ok(Some(adu)),
+        "TCP ADU"
+    );
+}
+
+/// Assert that `response` is decoded unchanged after it has been
+/// encoded as a PDU, as an RTU ADU and as a TCP ADU.
+///
+/// Responses do not contain the number of coils, so the coils of
+/// `ReadCoils` and `ReadDiscreteInputs` must fill whole bytes.
+/// Like requests, responses of custom functions are only checked as PDUs.
+///
+/// # Panics
+///
+/// Panics if encoding fails or if a decoded response differs.
+#[track_caller]
+pub fn assert_response_roundtrip(response: ResponsePdu<'_>) {
+    let buf = &mut [0; MAX_TCP_ADU_LEN];
+
+    let len = response.encode(buf).expect("encode response PDU");
+    assert_eq!(
+        ResponsePdu::try_from_with_options(&buf[..len], &DecodeOptions::default()),
+        Ok(response),
+        "PDU"
+    );
+
+    if matches!(
+        response.0,
+        Ok(Response::Custom(_))
+            | Err(ExceptionResponse {
+                function: FunctionCode::Custom(_),
+                ..
+            })
+    ) {
+        return;
+    }
+
+    let adu = rtu::ResponseAdu {
+        hdr: rtu::Header { slave: 0x12 },
+        pdu: response,
+    };
+    let len = rtu::server::encode_response(adu, buf).expect("encode RTU response");
+    assert_eq!(
+        rtu::client::decode_response(&buf[..len]),
+        Ok(Some(adu)),
+        "RTU ADU"
+    );
+
+    let adu = tcp::ResponseAdu {
+        hdr: tcp::Header {
+            transaction_id: 0x1234,
+            unit_id: 0x12,
+        },
+        pdu: response,
+    };
+    let len = tcp::server::encode_response(adu, buf).expect("encode TCP response");
+    assert_eq!(
+        tcp::client::decode_response(&buf[..len]),
+        Ok(Some(adu)),
+        "TCP ADU"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_all_requests() {
+        for adu in rtu_request_adus(1) {
+            assert_roundtrip(adu.pdu.0);
+        }
+        assert_eq!(tcp_request_adus(1).count(), REQUESTS.len());
+        assert_eq!(
+            tcp_request_adus(1).last().unwrap().hdr.transaction_id,
+            REQUESTS.len() as u16
+        );
+    }
+
+    #[test]
+    fn roundtrip_all_responses() {
+        for adu in tcp_response_adus(1) {
+            assert_response_roundtrip(adu.pdu);
+        }
+        assert_eq!(
+            rtu_response_adus(1).count(),
+            RESPONSES.len() + REQUESTS.len()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "PDU")]
+    fn detect_lost_coils() {
+        let coils = Coils {
+            data: &[0x01],
+            quantity: 3,
+        };
+        assert_response_roundtrip(ResponsePdu(Ok(Response::ReadCoils(coils))));
+    }
+}